@@ -1,24 +1,53 @@
+use std::collections::VecDeque;
 use std::path::Path;
 
-use crate::exporter::{save_indexed_bmp, save_indexed_png, save_rgba_image};
-use crate::image_processor::ImageProcessor;
-use crate::settings_manager::SettingsBundle;
+use crate::color_processor::ColorProcessor;
+use crate::exporter::{
+    ContactSheetCell, add_cell_gutters, compose_contact_sheet, export_tile_flips, flip_pixels,
+    pad_indexed_pixels, palette_for_export, save_combined_palette, save_genesis_nametable,
+    save_index_csv, save_indexed_apng, save_indexed_bmp, save_indexed_png, save_indexed_tga,
+    save_padding_info, save_palette_cycle_gif, save_palette_strip_png, save_raw_palette,
+    save_rgba_image, save_rgba16_png, save_split_indexed_pngs, save_tiled_map,
+};
+use crate::image_processor::{ImageProcessor, TileFlip};
+use crate::settings_manager::{ExportMetadata, SettingsBundle, upsert_user_preset};
 use crate::types::ImageData;
-use crate::types::app_state::{AppStateRequest, AppearanceMode, QualetizeRequest};
-use crate::types::image::{ImageDataIndexed, SortMode, TileCountOptions};
-use crate::types::{AppState, ExportFormat};
+use crate::types::app_state::{
+    AnimationPreview, AppStateRequest, AppearanceMode, BatchExport, ExportResult, ExportSnapshot,
+    ImageLoadResult, PendingExport, QUEUE_THUMBNAIL_SIZE, QualetizeRequest, QueueThumbnailResult,
+    QueuedImage, Toast,
+};
+use crate::types::color_space_trial::ColorSpaceTrial;
+use crate::types::image::{
+    ImageDataIndexed, SortMode, TileCountOptions, load_reference_palette, preview_texture_options,
+    set_smooth_preview,
+};
+use crate::types::palette_diff::LoadedPalette;
+use crate::types::preferences::UserPreferences;
+use crate::types::snap_palette::load_snap_palette;
+use crate::types::{
+    AppState, BGRA8, ChannelOrder, ColorCorrection, ColorSpace, ExportFormat, ExportOptions,
+    PaletteCycle, QualetizePreset, QualetizeSettings, RowOrder,
+};
 use crate::ui::UI;
 use eframe::egui;
 use egui::{ColorImage, Margin};
+use rayon::prelude::*;
 use rfd::FileDialog;
 use std::sync::{
     Arc,
     atomic::{AtomicBool, Ordering},
+    mpsc::TryRecvError,
 };
 
+/// Quiet period after the last new output before a live export is written
+const LIVE_EXPORT_DEBOUNCE: std::time::Duration = std::time::Duration::from_millis(500);
+
 pub struct QualetizeApp {
     state: AppState,
     image_processor: ImageProcessor,
+    /// Requests taken off the channel but not handled yet, one is handled per frame
+    pending_requests: VecDeque<AppStateRequest>,
 }
 
 impl Default for QualetizeApp {
@@ -26,6 +55,7 @@ impl Default for QualetizeApp {
         Self {
             state: AppState::default(),
             image_processor: ImageProcessor::new(),
+            pending_requests: VecDeque::new(),
         }
     }
 }
@@ -41,21 +71,134 @@ impl QualetizeApp {
 
     fn handle_dropped_files(&mut self, ctx: &egui::Context) {
         let dropped_files = ctx.input(|i| i.raw.dropped_files.clone());
-
-        if !dropped_files.is_empty()
-            && let Some(dropped_file) = dropped_files.first()
-            && let Some(path) = &dropped_file.path
-        {
+        let paths: Vec<String> = dropped_files
+            .iter()
+            .filter_map(|file| file.path.as_ref())
+            .map(|path| path.display().to_string())
+            .collect();
+
+        if paths.len() > 1 {
+            self.queue_images(&paths, ctx);
+        }
+        if let Some(path) = paths.first() {
             _ = self
                 .state
                 .app_state_request_sender
-                .send(AppStateRequest::LoadImage {
-                    path: path.display().to_string(),
-                });
+                .send(AppStateRequest::LoadImage { path: path.clone() });
+        }
+    }
+
+    /// Replace the file queue and decode its thumbnails in the background
+    fn queue_images(&mut self, paths: &[String], ctx: &egui::Context) {
+        self.state.image_queue = paths
+            .iter()
+            .map(|path| QueuedImage {
+                path: path.clone(),
+                thumbnail: None,
+                error: None,
+            })
+            .collect();
+
+        let paths = paths.to_vec();
+        let sender = self.state.queue_thumbnail_sender.clone();
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            for path in paths {
+                let result = ImageData::load_thumbnail(&path, QUEUE_THUMBNAIL_SIZE, &ctx);
+                if sender.send(QueueThumbnailResult { path, result }).is_err() {
+                    break;
+                }
+                ctx.request_repaint();
+            }
+        });
+    }
+
+    fn check_queue_thumbnails(&mut self) {
+        while let Ok(loaded) = self.state.queue_thumbnail_receiver.try_recv() {
+            // Thumbnails from a queue that was since replaced find no entry
+            let Some(entry) = self
+                .state
+                .image_queue
+                .iter_mut()
+                .find(|entry| entry.path == loaded.path)
+            else {
+                continue;
+            };
+            match loaded.result {
+                Ok(thumbnail) => entry.thumbnail = Some(thumbnail),
+                Err(e) => {
+                    log::warn!("Failed to create thumbnail for {}: {e}", loaded.path);
+                    entry.error = Some(e);
+                }
+            }
         }
     }
 
+    /// Decode the image on a worker thread so large files don't freeze the UI
     fn load_image_file(&mut self, path: String, ctx: &egui::Context) {
+        self.state.image_load_generation_id = self.state.image_load_generation_id.wrapping_add(1);
+        self.state.image_loading = true;
+
+        self.state.apply_image_view(&path);
+
+        let generation_id = self.state.image_load_generation_id;
+        let sender = self.state.image_load_sender.clone();
+        let suggest_presets = self.state.preferences.suggest_presets;
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            let result = ImageData::load(&path, &ctx);
+            // Counting colors touches every pixel, so do it here rather than on the UI thread
+            let preset_suggestion = result
+                .as_ref()
+                .ok()
+                .filter(|_| suggest_presets)
+                .map(|image| {
+                    QualetizePreset::suggest(image.width, image.height, image.unique_color_count())
+                });
+            _ = sender.send(ImageLoadResult {
+                generation_id,
+                path,
+                result,
+                preset_suggestion,
+            });
+            ctx.request_repaint();
+        });
+    }
+
+    fn check_image_load_completion(&mut self, ctx: &egui::Context) {
+        while let Ok(loaded) = self.state.image_load_receiver.try_recv() {
+            if loaded.generation_id != self.state.image_load_generation_id {
+                log::debug!(
+                    "Ignoring superseded image load {} (current: {})",
+                    loaded.generation_id,
+                    self.state.image_load_generation_id
+                );
+                continue;
+            }
+            self.state.image_loading = false;
+            self.state.original_input_image = None;
+            self.state.preset_suggestion = loaded.preset_suggestion;
+            self.replace_input_image(loaded.path, loaded.result, ctx);
+        }
+    }
+
+    /// Swap in a new input image and run it through the pipeline as if freshly loaded
+    fn replace_input_image(
+        &mut self,
+        path: String,
+        result: Result<ImageData, String>,
+        ctx: &egui::Context,
+    ) {
+        self.apply_loaded_image(path, result);
+
+        self.apply_color_correct_image(ctx);
+        self.state.request_update_qualetized_image = Some(QualetizeRequest {
+            time: std::time::Instant::now(),
+        });
+        self.state.update_color_correction_tracking();
+    }
+
+    fn apply_loaded_image(&mut self, path: String, result: Result<ImageData, String>) {
         // Cancel any existing processing
         if self.image_processor.is_processing() {
             self.image_processor.cancel_current_processing();
@@ -64,10 +207,52 @@ impl QualetizeApp {
         self.image_processor.cancel_tile_reduce();
         self.state.tile_reduce_processing = false;
 
-        match ImageData::load(&path, ctx) {
+        match result {
             Ok(image_data) => {
+                // A batch applies the settings it started with to every file
+                let batch = self.state.batch_export.is_some();
+                if image_data.is_grayscale()
+                    && !batch
+                    && self.state.settings.color_space != ColorSpace::GRAYSCALE_DEFAULT
+                {
+                    let name = ColorSpace::GRAYSCALE_DEFAULT.display_name();
+                    if self.state.preferences.grayscale_source_defaults {
+                        self.state.settings.color_space = ColorSpace::GRAYSCALE_DEFAULT;
+                        log::info!("Grayscale source, using {name} color space");
+                    } else {
+                        self.state.push_toast(Toast::info(format!(
+                            "Grayscale source: try the {name} color space, which clusters on luma alone."
+                        )));
+                    }
+                }
+                if image_data.is_jpeg()
+                    && !batch
+                    && self.state.preferences.warn_on_jpeg_sources
+                    && !self.state.color_correction.reduce_jpeg_artifacts
+                {
+                    self.state.push_toast(Toast::info(
+                        "JPEG source: compression noise can end up in the palette.\nTry Reduce JPEG Artifacts under Color Correction.",
+                    ));
+                }
                 self.state.input_path = Some(path.clone());
+                self.state.exported_settings_hash = None;
+                self.state.export_snapshot = None;
+                self.state.pending_export_snapshots.clear();
+                self.state.export_diff_cache = None;
+                if let Some(animation) = image_data.animation() {
+                    self.state.animation_preview = AnimationPreview {
+                        show: true,
+                        ..AnimationPreview::default()
+                    };
+                    self.state.push_toast(Toast::info(format!(
+                        "Animated PNG: {} frames stacked top to bottom, sharing one set of palettes",
+                        animation.frame_count()
+                    )));
+                }
                 self.state.input_image = Some(image_data);
+                self.state.tile_grid_suggestions = None;
+                self.state.region_of_interest = None;
+                self.state.edit_mask = None;
                 self.state.color_corrected_image = None;
                 self.state.base_output_image = None;
                 self.state.output_image = None;
@@ -83,6 +268,8 @@ impl QualetizeApp {
             }
             Err(e) => {
                 log::error!("File load Error {e}");
+                self.state
+                    .push_toast(Toast::error(format!("Failed to open image: {e}")));
                 self.state.input_path = None;
                 self.state.input_image = Default::default();
                 self.state.color_corrected_image = None;
@@ -116,8 +303,12 @@ impl QualetizeApp {
         self.state.request_update_qualetized_image = None;
         self.image_processor.cancel_tile_reduce();
         self.state.tile_reduce_processing = false;
-        self.image_processor
-            .start_qualetize(color_corrected_image, self.state.settings.clone());
+        self.image_processor.start_qualetize(
+            color_corrected_image,
+            self.state.settings.clone(),
+            self.state.preferences.processing_thread_count(),
+            self.state.region_of_interest,
+        );
 
         // request tile reduce after qualetize finishes
         self.state.request_update_tile_reduce = self.state.settings.tile_reduce_post_enabled;
@@ -128,19 +319,20 @@ impl QualetizeApp {
             return true;
         };
 
-        let image_width = input_image.width as u16;
-        let image_height = input_image.height as u16;
-        let tile_width = self.state.settings.tile_width;
-        let tile_height = self.state.settings.tile_height;
-
-        let width_divisible = image_width.is_multiple_of(tile_width);
-        let height_divisible = image_height.is_multiple_of(tile_height);
+        let tile_size_error = self
+            .state
+            .settings
+            .tile_size_error(input_image.width, input_image.height);
 
         log::debug!(
-            "Tile size check: image {image_width}×{image_height}, tile {tile_width}×{tile_height}, divisible: width={width_divisible}, height={height_divisible}"
+            "Tile size check: image {}×{}, tile {}×{}: {tile_size_error:?}",
+            input_image.width,
+            input_image.height,
+            self.state.settings.tile_width,
+            self.state.settings.tile_height
         );
 
-        if !width_divisible || !height_divisible {
+        if tile_size_error.is_some() {
             self.state.tile_size_warning = true;
             self.state.output_image = None;
             self.state.output_palette_sorted_indexed_image = None;
@@ -155,20 +347,87 @@ impl QualetizeApp {
         }
     }
 
+    /// Correction applied to the source pixels. In palette-only mode the grading is
+    /// applied to the output palettes instead, leaving only the source filter.
+    fn source_color_correction(&self) -> ColorCorrection {
+        if self.state.palette_only_color_correction {
+            self.state.color_correction.source_filters()
+        } else {
+            self.state.color_correction.clone()
+        }
+    }
+
     fn update_color_corrected_image(&mut self, ctx: &egui::Context) {
-        if self.state.color_correction_changed() {
+        // A new mask needs the source corrected again, even when grading the palette only
+        let mode_changed =
+            self.state.palette_only_color_correction_changed() || self.state.edit_mask_changed();
+        if !mode_changed && !self.state.color_correction_changed() {
+            return;
+        }
+        if self.state.palette_only_color_correction
+            && !mode_changed
+            && !self.state.source_filter_changed()
+            && self.state.ungraded_output_palette.is_some()
+        {
+            self.apply_palette_grade(ctx);
+        } else {
             self.apply_color_correct_image(ctx);
             self.state.request_update_qualetized_image = Some(QualetizeRequest {
                 time: std::time::Instant::now(),
             });
-            self.state.update_color_correction_tracking();
         }
+        self.state.update_color_correction_tracking();
+    }
+
+    /// Re-grade the current output palettes with the color correction,
+    /// keeping the existing clustering and pixel indices
+    fn apply_palette_grade(&mut self, ctx: &egui::Context) {
+        let Some(ungraded) = &self.state.ungraded_output_palette else {
+            return;
+        };
+        let graded =
+            ColorProcessor::apply_palette_correction(ungraded, &self.state.color_correction);
+
+        for image in [
+            &mut self.state.base_output_image,
+            &mut self.state.output_image,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            if let Some(regraded) = image.with_palette_colors(&graded, ctx) {
+                *image = regraded;
+            }
+        }
+        self.state.output_palette_sorted_indexed_image = None;
+        self.state.live_export_request = Some(std::time::Instant::now());
     }
 
     fn check_preview_completion(&mut self, ctx: &egui::Context) {
-        if let Some(result) = self.image_processor.check_preview_complete(ctx) {
+        let palette_padding = self.state.preferences.palette_padding();
+        if let Some(result) = self
+            .image_processor
+            .check_preview_complete(palette_padding, ctx)
+        {
             match result {
                 Ok(image_data) => {
+                    self.state.output_rmse = self.image_processor.last_rmse();
+                    self.state.ungraded_output_palette = image_data
+                        .indexed
+                        .as_ref()
+                        .map(|indexed| indexed.palettes.clone());
+                    let image_data = match &self.state.ungraded_output_palette {
+                        Some(ungraded) if self.state.palette_only_color_correction => {
+                            let graded = ColorProcessor::apply_palette_correction(
+                                ungraded,
+                                &self.state.color_correction,
+                            );
+                            image_data
+                                .with_palette_colors(&graded, ctx)
+                                .unwrap_or(image_data)
+                        }
+                        _ => image_data,
+                    };
                     self.state.base_output_image = Some(image_data.clone());
                     self.state.base_tile_count = Self::count_tiles(
                         &image_data,
@@ -183,6 +442,7 @@ impl QualetizeApp {
                         self.state.output_palette_sorted_indexed_image = None;
                         self.state.reduced_tile_count = self.state.base_tile_count;
                         self.state.tile_reduce_processing = false;
+                        self.state.live_export_request = Some(std::time::Instant::now());
                     } else {
                         self.state.request_update_tile_reduce = true;
                         self.handle_tile_reduce_changes(ctx);
@@ -193,7 +453,14 @@ impl QualetizeApp {
                 }
                 Err(e) => {
                     log::error!("Failed to generate preview image: {e}");
+                    // Tile size problems already have their own warning in the panel
+                    if !self.state.tile_size_warning {
+                        self.state
+                            .push_toast(Toast::error(format!("Quantization failed: {e}")));
+                    }
                     self.state.output_image = None;
+                    self.state.ungraded_output_palette = None;
+                    self.state.output_rmse = None;
                     self.state.base_output_image = None;
                     self.state.base_tile_count = None;
                     self.state.reduced_tile_count = None;
@@ -206,6 +473,50 @@ impl QualetizeApp {
         }
     }
 
+    /// Switch to the best color space once the trials finish
+    fn check_color_space_trials(&mut self) {
+        let trials = &mut self.state.color_space_trials;
+        let Some(receiver) = &trials.receiver else {
+            return;
+        };
+        let results = match receiver.try_recv() {
+            Ok(results) => results,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => {
+                trials.receiver = None;
+                self.state
+                    .push_toast(Toast::error("Color space trials stopped unexpectedly"));
+                return;
+            }
+        };
+        let best = trials.finish(results);
+        let previous = self.state.settings.color_space.clone();
+        let previous_rmse = trials.rmse_of(&previous);
+        let Some(best) = best else {
+            self.state
+                .push_toast(Toast::error("Every color space trial failed"));
+            return;
+        };
+        let rmse = best.rmse.unwrap_or_default();
+        if best.color_space == previous {
+            self.state.push_toast(Toast::info(format!(
+                "{} is already the best color space (RMSE {rmse:.2})",
+                previous.display_name()
+            )));
+            return;
+        }
+        self.state.push_toast(Toast::info(format!(
+            "Switched to {} (RMSE {rmse:.2}, {} had {})",
+            best.color_space.display_name(),
+            previous.display_name(),
+            previous_rmse.map_or("failed".to_string(), |rmse| format!("{rmse:.2}"))
+        )));
+        self.state.settings.color_space = best.color_space;
+        self.state.request_update_qualetized_image = Some(QualetizeRequest {
+            time: std::time::Instant::now(),
+        });
+    }
+
     fn check_tile_reduce_completion(&mut self, ctx: &egui::Context) {
         if let Some(result) = self.image_processor.check_tile_reduce_complete() {
             match result {
@@ -224,7 +535,7 @@ impl QualetizeApp {
                     for &pixel_index in &res.indexed_pixels {
                         let palette_index = pixel_index as usize;
                         if let Some(color) = base_indexed.palettes.get(palette_index) {
-                            pixels.extend_from_slice(&[color.r, color.g, color.b, color.a]);
+                            pixels.extend_from_slice(&color.to_rgba());
                         } else {
                             pixels.extend_from_slice(&[0, 0, 0, 255]);
                         }
@@ -232,7 +543,7 @@ impl QualetizeApp {
                     let size = [base.width as usize, base.height as usize];
                     let color_image = ColorImage::from_rgba_unmultiplied(size, &pixels);
                     let texture =
-                        ctx.load_texture("output", color_image, egui::TextureOptions::NEAREST);
+                        ctx.load_texture("output", color_image, preview_texture_options(ctx));
 
                     let mut output = base.clone();
                     output.texture = texture;
@@ -241,6 +552,7 @@ impl QualetizeApp {
                         palettes_for_ui: base_indexed.palettes_for_ui.clone(),
                         palettes: base_indexed.palettes.clone(),
                         indexed_pixels: res.indexed_pixels,
+                        tile_flips: Some(res.tile_flips),
                     });
                     self.state.output_image = Some(output);
                     self.state.output_palette_sorted_indexed_image = None;
@@ -251,6 +563,7 @@ impl QualetizeApp {
                         self.state.tile_count.options(),
                     );
                     self.state.tile_reduce_processing = false;
+                    self.state.live_export_request = Some(std::time::Instant::now());
                     let diff = self
                         .state
                         .base_tile_count
@@ -274,14 +587,76 @@ impl QualetizeApp {
             }
         }
     }
+    /// Write the latest output to the live export path once processing has settled
+    /// Apply a change of the "Smooth When Zoomed Out" preference to the existing textures
+    fn update_preview_sampling(&mut self, ctx: &egui::Context) {
+        let smooth = self.state.preferences.smooth_when_zoomed_out;
+        let current = preview_texture_options(ctx).minification == egui::TextureFilter::Linear;
+        if smooth == current {
+            return;
+        }
+        set_smooth_preview(ctx, smooth);
+
+        for image in [
+            &mut self.state.input_image,
+            &mut self.state.color_corrected_image,
+            &mut self.state.base_output_image,
+            &mut self.state.output_image,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            image.refresh_texture_options(ctx);
+        }
+        // Derived textures are rebuilt on the next draw with the new sampling
+        self.state.display_filter_cache = None;
+        self.state.isolated_color_cache = None;
+    }
+
+    fn handle_live_export(&mut self) {
+        let Some(requested) = self.state.live_export_request else {
+            return;
+        };
+        if requested.elapsed() < LIVE_EXPORT_DEBOUNCE
+            || self.image_processor.is_processing()
+            || self.state.tile_reduce_processing
+            || self.state.request_update_qualetized_image.is_some()
+        {
+            return;
+        }
+        self.state.live_export_request = None;
+
+        if !self.state.preferences.live_export_enabled {
+            return;
+        }
+        let Some(output_path) = self.state.preferences.live_export_path.clone() else {
+            return;
+        };
+        let export_request = AppStateRequest::export(
+            self.state.preferences.selected_export_format.clone(),
+            output_path,
+        );
+        _ = self.state.app_state_request_sender.send(export_request);
+    }
+
     fn apply_theme(&self, ctx: &egui::Context) {
+        use crate::ui::styles;
+
+        let dark = || egui::Visuals {
+            warn_fg_color: styles::COLOR_WARNING,
+            ..egui::Visuals::dark()
+        };
+        let light = || egui::Visuals {
+            warn_fg_color: styles::COLOR_WARNING_LIGHT,
+            ..egui::Visuals::light()
+        };
         let visuals = match self.state.preferences.appearance_mode {
-            AppearanceMode::Dark => egui::Visuals::dark(),
-            AppearanceMode::Light => egui::Visuals::light(),
+            AppearanceMode::Dark => dark(),
+            AppearanceMode::Light => light(),
+            AppearanceMode::HighContrast => styles::high_contrast_visuals(),
             AppearanceMode::System => match ctx.system_theme() {
-                Some(egui::Theme::Dark) => egui::Visuals::dark(),
-                Some(egui::Theme::Light) => egui::Visuals::light(),
-                None => egui::Visuals::dark(),
+                Some(egui::Theme::Light) => light(),
+                Some(egui::Theme::Dark) | None => dark(),
             },
         };
         if ctx.style().visuals != visuals {
@@ -289,9 +664,49 @@ impl QualetizeApp {
         }
     }
 
+    /// Send the window level and minimum size of compact mode / always on top to the
+    /// viewport when they change, including once at startup
+    fn apply_window_mode(&mut self, ctx: &egui::Context) {
+        const COMPACT_MIN_SIZE: egui::Vec2 = egui::vec2(240.0, 160.0);
+        const FULL_MIN_SIZE: egui::Vec2 = egui::vec2(800.0, 600.0);
+
+        let mode = (
+            self.state.preferences.compact_mode,
+            self.state.preferences.always_on_top,
+        );
+        if self.state.applied_window_mode == Some(mode) {
+            return;
+        }
+        let (compact, always_on_top) = mode;
+        ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(if always_on_top {
+            egui::WindowLevel::AlwaysOnTop
+        } else {
+            egui::WindowLevel::Normal
+        }));
+        ctx.send_viewport_cmd(egui::ViewportCommand::MinInnerSize(if compact {
+            COMPACT_MIN_SIZE
+        } else {
+            FULL_MIN_SIZE
+        }));
+        self.state.applied_window_mode = Some(mode);
+    }
+
     fn apply_color_correct_image(&mut self, ctx: &egui::Context) {
         if let Some(image) = &self.state.input_image {
-            let color_corrected_image = image.color_corrected(&self.state.color_correction, ctx);
+            let mut color_corrected_image =
+                image.color_corrected(&self.source_color_correction(), ctx);
+            if let Some(mask) = &self.state.edit_mask {
+                color_corrected_image = color_corrected_image.with_rgba_data(
+                    image.width,
+                    image.height,
+                    mask.composite(
+                        &color_corrected_image.rgba_data,
+                        &image.rgba_data,
+                        image.width,
+                    ),
+                    ctx,
+                );
+            }
             self.state.color_corrected_image = Some(color_corrected_image);
         }
     }
@@ -353,108 +768,331 @@ impl QualetizeApp {
         ImageData::count_unique_tiles(indexed, image.width, image.height, tile_w, tile_h, options)
     }
 
+    /// Replace the current settings with a loaded bundle and re-process
+    fn apply_settings_bundle(&mut self, settings_bundle: SettingsBundle, ctx: &egui::Context) {
+        // Cancel any existing processing
+        if self.image_processor.is_processing() {
+            self.image_processor.cancel_current_processing();
+            self.image_processor = ImageProcessor::new();
+        }
+
+        self.state.settings = settings_bundle.qualetize_settings;
+        self.state.color_correction = settings_bundle.color_correction;
+        self.state.palette_sort_settings = settings_bundle.sort_settings;
+
+        self.state.request_update_qualetized_image = Some(QualetizeRequest {
+            time: std::time::Instant::now(),
+        });
+
+        if let Some(input_image) = &self.state.input_image {
+            self.state.color_corrected_image =
+                Some(input_image.color_corrected(&self.state.color_correction, ctx));
+        } else {
+            self.state.color_corrected_image = None;
+        }
+
+        // Update tracking
+        self.state.update_color_correction_tracking();
+    }
+
     fn handle_requests(&mut self, ctx: &egui::Context) {
-        // Check for file dialog results first
-        let Ok(app_state_request) = &self.state.app_state_request_receiver.try_recv() else {
+        // Check for file dialog results first. The channel is drained so the debug HUD
+        // can tell how many requests are waiting.
+        self.pending_requests
+            .extend(self.state.app_state_request_receiver.try_iter());
+        let Some(app_state_request) = self.pending_requests.pop_front() else {
             return;
         };
+        self.handle_request(&app_state_request, ctx);
+    }
+
+    fn handle_request(&mut self, app_state_request: &AppStateRequest, ctx: &egui::Context) {
         match app_state_request {
             AppStateRequest::LoadImage { path } => {
                 self.load_image_file(path.clone(), ctx);
-                self.apply_color_correct_image(ctx);
-                self.state.request_update_qualetized_image = Some(QualetizeRequest {
-                    time: std::time::Instant::now(),
-                });
-                self.state.update_color_correction_tracking();
             }
             AppStateRequest::ColorCorrectedPng { output_path } => {
-                // Use ImageData pixels directly
-                let Some(color_corrected_image) = &self.state.color_corrected_image else {
-                    log::error!("No color corrected image data available in memory");
+                self.export_or_toast(&ExportFormat::Png, output_path, ctx);
+            }
+            AppStateRequest::ColorCorrected16BitPng { output_path } => {
+                self.export_or_toast(&ExportFormat::Png16, output_path, ctx);
+            }
+            AppStateRequest::MaskedOutputPng { output_path } => {
+                self.export_or_toast(&ExportFormat::MaskedPng, output_path, ctx);
+            }
+            AppStateRequest::SplitExport { output_path } => {
+                self.export_or_toast(&ExportFormat::SplitPng, output_path, ctx);
+            }
+            AppStateRequest::QualetizedIndexed {
+                output_path,
+                format,
+            } => {
+                self.export_or_toast(format, output_path, ctx);
+            }
+            AppStateRequest::ReviewExport {
+                output_path,
+                format,
+            } => {
+                let request = AppStateRequest::export(format.clone(), output_path.clone());
+                if self.state.preferences.confirm_exports {
+                    self.state.pending_export = Some(PendingExport {
+                        summary: self.export_summary(format, output_path),
+                        request,
+                    });
+                } else {
+                    _ = self.state.app_state_request_sender.send(request);
+                }
+            }
+            AppStateRequest::ExportAllDialog => {
+                let sender = self.state.app_state_request_sender.clone();
+                let current = self.state.preferences.export_directory.clone();
+                let dialog_flag = self.state.file_dialog_open.clone();
+                std::thread::spawn(move || {
+                    let _guard = FileDialogGuard::new(dialog_flag);
+                    let mut dialog = FileDialog::new();
+                    if let Some(current) = current {
+                        dialog = dialog.set_directory(current);
+                    }
+                    let Some(folder) = dialog.pick_folder() else {
+                        return;
+                    };
+                    _ = sender.send(AppStateRequest::ExportAll {
+                        directory: folder.display().to_string(),
+                    });
+                });
+            }
+            AppStateRequest::ExportAll { directory } => {
+                let Some(input_path) = self.state.input_path.clone() else {
                     return;
                 };
-
-                let output_path = output_path.clone();
-                let rgba_data = color_corrected_image.rgba_data.clone();
-                let width = color_corrected_image.width;
-                let height = color_corrected_image.height;
+                let mut job = match self.indexed_export_job(&ExportFormat::PngIndexed) {
+                    Ok(job) => job,
+                    Err(e) => {
+                        log::error!("Export All failed: {e}");
+                        self.state.push_toast(Toast::error(e));
+                        return;
+                    }
+                };
+                self.hold_export_snapshot(directory, &mut job);
+                let targets: Vec<(ExportFormat, String)> = EXPORT_ALL_FORMATS
+                    .iter()
+                    .map(|(format, suffix)| {
+                        let default_path = get_export_path(
+                            input_path.clone(),
+                            format,
+                            Some(suffix.to_string()),
+                            &self.state.preferences,
+                            &self.state.settings,
+                        );
+                        let file_name = default_path.file_name().unwrap_or_default();
+                        let path = Path::new(directory).join(file_name);
+                        (format.clone(), path.display().to_string())
+                    })
+                    .collect();
+                self.spawn_export(ctx, directory.clone(), move |_| {
+                    let failures: Vec<String> = targets
+                        .iter()
+                        .filter_map(|(format, path)| {
+                            job.write(format, path)
+                                .err()
+                                .map(|e| format!("{}: {e}", format.display_name()))
+                        })
+                        .collect();
+                    if failures.is_empty() {
+                        Ok(())
+                    } else {
+                        Err(format!(
+                            "{} of {} files failed ({})",
+                            failures.len(),
+                            targets.len(),
+                            failures.join("; ")
+                        ))
+                    }
+                });
+            }
+            AppStateRequest::BatchExportDialog => {
+                let sender = self.state.app_state_request_sender.clone();
+                let current = self.state.preferences.export_directory.clone();
+                let dialog_flag = self.state.file_dialog_open.clone();
                 std::thread::spawn(move || {
-                    match save_rgba_image(
-                        &output_path,
-                        &rgba_data,
-                        width,
-                        height,
-                        crate::types::ExportFormat::Png,
-                    ) {
-                        Ok(()) => {
-                            log::info!(
-                                "Color corrected PNG export completed successfully (from memory)"
-                            );
-                        }
-                        Err(e) => {
-                            log::error!("Color corrected PNG export failed: {e}");
-                        }
+                    let _guard = FileDialogGuard::new(dialog_flag);
+                    let mut dialog = FileDialog::new();
+                    if let Some(current) = current {
+                        dialog = dialog.set_directory(current);
                     }
+                    let Some(folder) = dialog.pick_folder() else {
+                        return;
+                    };
+                    _ = sender.send(AppStateRequest::StartBatchExport {
+                        directory: folder.display().to_string(),
+                    });
                 });
             }
-            AppStateRequest::QualetizedIndexed {
-                output_path,
-                format,
-            } => {
-                let Some(output_image) = &self.state.output_image else {
-                    log::error!("Qualetized export failed: output image is None");
+            AppStateRequest::StartBatchExport { directory } => {
+                let paths: Vec<String> = self
+                    .state
+                    .image_queue
+                    .iter()
+                    .map(|entry| entry.path.clone())
+                    .collect();
+                let Some(first) = paths.first().cloned() else {
                     return;
                 };
-
-                let indexed = if self.state.output_palette_sorted_indexed_image.is_some() {
-                    &self.state.output_palette_sorted_indexed_image
-                } else if let Some(image) = &self.state.output_image {
-                    &image.indexed
-                } else {
-                    &None
+                log::info!("Batch export of {} files to {directory}", paths.len());
+                self.state.batch_export = Some(BatchExport::new(
+                    paths,
+                    directory.clone(),
+                    self.state.preferences.selected_export_format.clone(),
+                ));
+                self.load_image_file(first, ctx);
+            }
+            AppStateRequest::FindBestColorSpace => {
+                if self.state.color_space_trials.is_running() {
+                    return;
+                }
+                let Some(color_corrected_image) = &self.state.color_corrected_image else {
+                    self.state
+                        .push_toast(Toast::error("No image to compare color spaces on"));
+                    return;
                 };
-
-                let Some(indexed) = indexed else {
+                // The color space doesn't change anything the BGRA input depends on
+                let (bgra_data, width, height) = match self
+                    .image_processor
+                    .generate_bgra_data(color_corrected_image, &self.state.settings)
+                {
+                    Ok(data) => data,
+                    Err(e) => {
+                        self.state.push_toast(Toast::error(e));
+                        return;
+                    }
+                };
+                let settings = QualetizeSettings {
+                    // The error is measured on the rendered output
+                    palette_only: false,
+                    ..self.state.settings.clone()
+                };
+                let pool = self
+                    .image_processor
+                    .thread_pool(self.state.preferences.processing_thread_count());
+                let (sender, receiver) = std::sync::mpsc::channel();
+                self.state.color_space_trials.receiver = Some(receiver);
+                self.state.color_space_trials.show = true;
+                let ctx = ctx.clone();
+                std::thread::spawn(move || {
+                    let trials: Vec<ColorSpaceTrial> = pool.install(|| {
+                        ColorSpace::all()
+                            .par_iter()
+                            .map(|color_space| {
+                                std::panic::catch_unwind(|| {
+                                    color_space_trial(
+                                        &bgra_data,
+                                        width,
+                                        height,
+                                        &settings,
+                                        color_space,
+                                    )
+                                })
+                                .unwrap_or_else(|_| {
+                                    log::warn!(
+                                        "Color space trial {} panicked",
+                                        color_space.display_name()
+                                    );
+                                    ColorSpaceTrial {
+                                        color_space: color_space.clone(),
+                                        rmse: Err("panicked".to_string()),
+                                    }
+                                })
+                            })
+                            .collect()
+                    });
+                    _ = sender.send(trials);
+                    ctx.request_repaint();
+                });
+            }
+            AppStateRequest::ContactSheetDialog => {
+                let sender = self.state.app_state_request_sender.clone();
+                let Some(input_path) = self.state.input_path.clone() else {
                     return;
                 };
-
-                match format {
-                    crate::types::ExportFormat::Png => {
-                        log::error!("Qualetized export failed: Unexpected format");
+                let default_path = get_export_path(
+                    input_path,
+                    &ExportFormat::Png,
+                    Some("contact_sheet".to_string()),
+                    &self.state.preferences,
+                    &self.state.settings,
+                );
+                let dialog_flag = self.state.file_dialog_open.clone();
+                std::thread::spawn(move || {
+                    let _guard = FileDialogGuard::new(dialog_flag);
+                    let mut dialog = FileDialog::new().add_filter("PNG files", &["png"]);
+                    if let Some(filename) = default_path.file_name() {
+                        dialog = dialog.set_file_name(filename.to_string_lossy().to_string());
                     }
-                    crate::types::ExportFormat::Bmp => {
-                        match save_indexed_bmp(
-                            output_path,
-                            &indexed.indexed_pixels,
-                            &indexed.palettes,
-                            output_image.width,
-                            output_image.height,
-                        ) {
-                            Ok(()) => {
-                                log::info!("Qualetized indexed BMP export completed successfully");
-                            }
-                            Err(e) => {
-                                log::error!("Qualetized indexed export failed: {e}");
-                            }
-                        }
+                    if let Some(parent) = default_path.parent() {
+                        dialog = dialog.set_directory(parent);
                     }
-                    crate::types::ExportFormat::PngIndexed => {
-                        match save_indexed_png(
-                            output_path,
-                            &indexed.indexed_pixels,
-                            &indexed.palettes,
-                            output_image.width,
-                            output_image.height,
-                        ) {
-                            Ok(()) => {
-                                log::info!("Qualetized indexed PNG export completed successfully");
-                            }
-                            Err(e) => {
-                                log::error!("Qualetized indexed export failed: {e}");
-                            }
-                        }
+                    let Some(file) = dialog.save_file() else {
+                        return;
+                    };
+                    _ = sender.send(AppStateRequest::ExportContactSheet {
+                        output_path: file.display().to_string(),
+                    });
+                });
+            }
+            AppStateRequest::ExportContactSheet { output_path } => {
+                let Some(color_corrected_image) = &self.state.color_corrected_image else {
+                    self.state
+                        .push_toast(Toast::error("No image to build a contact sheet from"));
+                    return;
+                };
+                // The variations don't change anything the BGRA input depends on
+                let (bgra_data, width, height) = match self
+                    .image_processor
+                    .generate_bgra_data(color_corrected_image, &self.state.settings)
+                {
+                    Ok(data) => data,
+                    Err(e) => {
+                        self.state.push_toast(Toast::error(e));
+                        return;
                     }
-                }
+                };
+                let variations = self.state.contact_sheet.settings(&self.state.settings);
+                let options = self.state.preferences.export_options();
+                let pool = self
+                    .image_processor
+                    .thread_pool(self.state.preferences.processing_thread_count());
+                self.spawn_export(ctx, output_path.clone(), move |path| {
+                    // Collected in order, so every caption stays under its own cell
+                    let cells: Vec<ContactSheetCell> = pool.install(|| {
+                        variations
+                            .into_par_iter()
+                            .map(|(caption, settings)| {
+                                let bgra_data = bgra_data.clone();
+                                let cell = std::panic::catch_unwind(|| {
+                                    contact_sheet_cell(
+                                        bgra_data,
+                                        width,
+                                        height,
+                                        settings,
+                                        caption.clone(),
+                                    )
+                                });
+                                cell.unwrap_or_else(|_| {
+                                    failed_contact_sheet_cell(width, height, &caption, "panicked")
+                                })
+                            })
+                            .collect()
+                    });
+                    let (rgba, sheet_width, sheet_height) = compose_contact_sheet(&cells);
+                    save_rgba_image(
+                        path,
+                        &rgba,
+                        sheet_width,
+                        sheet_height,
+                        ExportFormat::Png,
+                        options,
+                    )
+                });
             }
             AppStateRequest::SaveSettings { path } => {
                 let settings_bundle = SettingsBundle::new(
@@ -466,45 +1104,59 @@ impl QualetizeApp {
                 match settings_bundle.save_to_file(path) {
                     Ok(()) => {
                         log::info!("Settings saved successfully to: {path}");
+                        self.state
+                            .push_toast(Toast::info(format!("Settings saved to {path}")));
                     }
                     Err(e) => {
                         log::error!("Failed to save settings: {e}");
+                        self.state.push_toast(Toast::error(e));
                     }
                 }
             }
-            AppStateRequest::LoadSettings { path } => {
-                match SettingsBundle::load_from_file(path) {
-                    Ok(settings_bundle) => {
-                        // Cancel any existing processing
-                        if self.image_processor.is_processing() {
-                            self.image_processor.cancel_current_processing();
-                            self.image_processor = ImageProcessor::new();
-                        }
-
-                        // Apply loaded settings
-                        self.state.settings = settings_bundle.qualetize_settings;
-                        self.state.color_correction = settings_bundle.color_correction;
-                        self.state.palette_sort_settings = settings_bundle.sort_settings;
-
-                        self.state.request_update_qualetized_image = Some(QualetizeRequest {
-                            time: std::time::Instant::now(),
-                        });
-
-                        if let Some(input_image) = &self.state.input_image {
-                            self.state.color_corrected_image = Some(
-                                input_image.color_corrected(&self.state.color_correction, ctx),
-                            );
-                        } else {
-                            self.state.color_corrected_image = None;
-                        }
-
-                        // Update tracking
-                        self.state.update_color_correction_tracking();
-
-                        log::info!("Settings loaded successfully from: {path}");
-                    }
+            AppStateRequest::LoadSettings { path } => match SettingsBundle::load_from_file(path) {
+                Ok(settings_bundle) => {
+                    self.apply_settings_bundle(settings_bundle, ctx);
+                    log::info!("Settings loaded successfully from: {path}");
+                    self.state
+                        .push_toast(Toast::info(format!("Settings loaded from {path}")));
+                }
+                Err(e) => {
+                    log::error!("Failed to load settings: {e}");
+                    self.state.push_toast(Toast::error(e));
+                }
+            },
+            AppStateRequest::SaveUserPreset { name } => {
+                let settings_bundle = SettingsBundle::new(
+                    self.state.settings.clone(),
+                    self.state.color_correction.clone(),
+                    self.state.palette_sort_settings.clone(),
+                );
+                upsert_user_preset(&mut self.state.user_presets, name, settings_bundle);
+                match SettingsBundle::save_user_presets(&self.state.user_presets) {
+                    Ok(()) => self
+                        .state
+                        .push_toast(Toast::info(format!("Preset \"{name}\" saved"))),
                     Err(e) => {
-                        log::error!("Failed to load settings: {e}");
+                        log::error!("Failed to save user presets: {e}");
+                        self.state.push_toast(Toast::error(e));
+                    }
+                }
+            }
+            AppStateRequest::LoadUserPreset { index } => {
+                if let Some(preset) = self.state.user_presets.get(*index).cloned() {
+                    self.apply_settings_bundle(preset.bundle, ctx);
+                    self.state
+                        .push_toast(Toast::info(format!("Preset \"{}\" loaded", preset.name)));
+                }
+            }
+            AppStateRequest::DeleteUserPreset { index } => {
+                if *index < self.state.user_presets.len() {
+                    let preset = self.state.user_presets.remove(*index);
+                    if let Err(e) = SettingsBundle::save_user_presets(&self.state.user_presets) {
+                        log::error!("Failed to save user presets: {e}");
+                        self.state.push_toast(Toast::error(e));
+                    } else {
+                        log::info!("User preset deleted: {}", preset.name);
                     }
                 }
             }
@@ -529,7 +1181,13 @@ impl QualetizeApp {
                 let Some(input_path) = self.state.input_path.clone() else {
                     return;
                 };
-                let default_path = get_export_path(input_path, format, suffix.clone());
+                let default_path = get_export_path(
+                    input_path,
+                    format,
+                    suffix.clone(),
+                    &self.state.preferences,
+                    &self.state.settings,
+                );
                 let format_clone = format.clone();
 
                 let dialog_flag = self.state.file_dialog_open.clone();
@@ -548,16 +1206,10 @@ impl QualetizeApp {
                     let Some(file) = dialog.save_file() else {
                         return;
                     };
-                    let export_request = match format_clone {
-                        crate::types::ExportFormat::Png => AppStateRequest::ColorCorrectedPng {
-                            output_path: file.display().to_string(),
-                        },
-                        _ => AppStateRequest::QualetizedIndexed {
-                            output_path: file.display().to_string(),
-                            format: format_clone,
-                        },
-                    };
-                    _ = sender.send(export_request);
+                    _ = sender.send(AppStateRequest::ReviewExport {
+                        output_path: file.display().to_string(),
+                        format: format_clone,
+                    });
                 });
             }
             AppStateRequest::SaveSettingsDialog => {
@@ -584,6 +1236,232 @@ impl QualetizeApp {
                     });
                 });
             }
+            AppStateRequest::ResizeInput {
+                width,
+                height,
+                filter,
+            } => {
+                // Always resample the original so repeated resizes don't compound
+                let Some(source) = self
+                    .state
+                    .original_input_image
+                    .as_ref()
+                    .or(self.state.input_image.as_ref())
+                else {
+                    return;
+                };
+                match source.resized(*width, *height, *filter, ctx) {
+                    Ok(resized) => {
+                        if self.state.original_input_image.is_none() {
+                            self.state.original_input_image = self.state.input_image.clone();
+                        }
+                        let path = self.state.input_path.clone().unwrap_or_default();
+                        self.replace_input_image(path, Ok(resized), ctx);
+                    }
+                    Err(e) => {
+                        log::error!("Resize failed: {e}");
+                        self.state
+                            .push_toast(Toast::error(format!("Resize failed: {e}")));
+                    }
+                }
+            }
+            AppStateRequest::SnapPaletteDialog => {
+                let sender = self.state.app_state_request_sender.clone();
+                let dialog_flag = self.state.file_dialog_open.clone();
+                std::thread::spawn(move || {
+                    let _guard = FileDialogGuard::new(dialog_flag);
+                    let Some(file) = FileDialog::new()
+                        .add_filter("Palette images", &["png", "bmp", "gif", "tga"])
+                        .pick_file()
+                    else {
+                        return;
+                    };
+                    _ = sender.send(AppStateRequest::LoadSnapPalette {
+                        path: file.display().to_string(),
+                    });
+                });
+            }
+            AppStateRequest::LoadSnapPalette { path } => match load_snap_palette(path) {
+                Ok(colors) => {
+                    log::info!("Loaded {} snap palette colors from {path}", colors.len());
+                    self.state.settings.snap_palette_colors = colors;
+                    self.state.request_update_qualetized_image = Some(QualetizeRequest {
+                        time: std::time::Instant::now(),
+                    });
+                }
+                Err(e) => {
+                    log::error!("Failed to load snap palette: {e}");
+                    self.state.push_toast(Toast::error(e));
+                }
+            },
+            AppStateRequest::ReferencePaletteDialog => {
+                let sender = self.state.app_state_request_sender.clone();
+                let dialog_flag = self.state.file_dialog_open.clone();
+                std::thread::spawn(move || {
+                    let _guard = FileDialogGuard::new(dialog_flag);
+                    let Some(file) = FileDialog::new()
+                        .add_filter("Reference images", &["png", "bmp", "gif", "tga"])
+                        .pick_file()
+                    else {
+                        return;
+                    };
+                    _ = sender.send(AppStateRequest::LoadReferencePalette {
+                        path: file.display().to_string(),
+                    });
+                });
+            }
+            AppStateRequest::LoadReferencePalette { path } => match load_reference_palette(path) {
+                Ok(colors) => {
+                    log::info!(
+                        "Loaded {} reference palette colors from {path}",
+                        colors.len()
+                    );
+                    self.state.palette_sort_settings.reference_palette = colors;
+                }
+                Err(e) => {
+                    log::error!("Failed to load reference palette: {e}");
+                    self.state.push_toast(Toast::error(e));
+                }
+            },
+            AppStateRequest::PaletteDiffDialog { side } => {
+                let sender = self.state.app_state_request_sender.clone();
+                let dialog_flag = self.state.file_dialog_open.clone();
+                let side = *side;
+                std::thread::spawn(move || {
+                    let _guard = FileDialogGuard::new(dialog_flag);
+                    let Some(file) = FileDialog::new()
+                        .add_filter("Palette images", &["png", "bmp", "gif", "tga"])
+                        .pick_file()
+                    else {
+                        return;
+                    };
+                    _ = sender.send(AppStateRequest::LoadPaletteDiff {
+                        side,
+                        path: file.display().to_string(),
+                    });
+                });
+            }
+            AppStateRequest::LoadPaletteDiff { side, path } => match load_reference_palette(path) {
+                Ok(colors) => {
+                    let name = Path::new(path)
+                        .file_name()
+                        .map_or(path.clone(), |name| name.to_string_lossy().to_string());
+                    self.state
+                        .palette_diff
+                        .set(*side, LoadedPalette { name, colors });
+                }
+                Err(e) => {
+                    log::error!("Failed to load palette for comparison: {e}");
+                    self.state.push_toast(Toast::error(e));
+                }
+            },
+            AppStateRequest::ResetInputSize => {
+                if let Some(original) = self.state.original_input_image.take() {
+                    let path = self.state.input_path.clone().unwrap_or_default();
+                    self.replace_input_image(path, Ok(original), ctx);
+                }
+            }
+            AppStateRequest::PreviewSeamFit => {
+                let Some(input_image) = &self.state.input_image else {
+                    return;
+                };
+                let Some((width, height)) = self
+                    .state
+                    .settings
+                    .seam_fit_size(input_image.width, input_image.height)
+                else {
+                    return;
+                };
+                match input_image.seam_carved(width, height, ctx) {
+                    Ok(carved) => self.state.seam_fit_preview = Some(carved),
+                    Err(e) => {
+                        log::error!("Seam fit failed: {e}");
+                        self.state
+                            .push_toast(Toast::error(format!("Seam fit failed: {e}")));
+                    }
+                }
+            }
+            AppStateRequest::ApplySeamFit => {
+                if let Some(carved) = self.state.seam_fit_preview.take() {
+                    if self.state.original_input_image.is_none() {
+                        self.state.original_input_image = self.state.input_image.clone();
+                    }
+                    let path = self.state.input_path.clone().unwrap_or_default();
+                    self.replace_input_image(path, Ok(carved), ctx);
+                }
+            }
+            AppStateRequest::Reprocess => {
+                // Drop any in-flight run and start a fresh one without waiting for the debounce
+                self.image_processor.cancel_current_processing();
+                self.image_processor.cancel_tile_reduce();
+                self.state.tile_reduce_processing = false;
+                let now = std::time::Instant::now();
+                self.state.request_update_qualetized_image = Some(QualetizeRequest {
+                    time: now.checked_sub(self.state.debounce_delay).unwrap_or(now),
+                });
+            }
+            AppStateRequest::ExportDirectoryDialog => {
+                let sender = self.state.app_state_request_sender.clone();
+                let current = self.state.preferences.export_directory.clone();
+                let dialog_flag = self.state.file_dialog_open.clone();
+                std::thread::spawn(move || {
+                    let _guard = FileDialogGuard::new(dialog_flag);
+                    let mut dialog = FileDialog::new();
+                    if let Some(current) = current {
+                        dialog = dialog.set_directory(current);
+                    }
+                    let Some(folder) = dialog.pick_folder() else {
+                        return;
+                    };
+                    _ = sender.send(AppStateRequest::SetExportDirectory {
+                        path: Some(folder.display().to_string()),
+                    });
+                });
+            }
+            AppStateRequest::SetExportDirectory { path } => {
+                self.state.preferences.export_directory = path.clone();
+            }
+            AppStateRequest::LiveExportPathDialog => {
+                let sender = self.state.app_state_request_sender.clone();
+                let format = self.state.preferences.selected_export_format.clone();
+                let default_path = self.state.input_path.clone().map(|input_path| {
+                    get_export_path(
+                        input_path,
+                        &format,
+                        Some("live".to_string()),
+                        &self.state.preferences,
+                        &self.state.settings,
+                    )
+                });
+                let dialog_flag = self.state.file_dialog_open.clone();
+                std::thread::spawn(move || {
+                    let _guard = FileDialogGuard::new(dialog_flag);
+                    let mut dialog = FileDialog::new().add_filter(
+                        format!("{} files", format.display_name()),
+                        &[format.extension()],
+                    );
+                    if let Some(default_path) = default_path {
+                        if let Some(filename) = default_path.file_name() {
+                            dialog = dialog.set_file_name(filename.to_string_lossy().to_string());
+                        }
+                        if let Some(parent) = default_path.parent() {
+                            dialog = dialog.set_directory(parent);
+                        }
+                    }
+                    let Some(file) = dialog.save_file() else {
+                        return;
+                    };
+                    _ = sender.send(AppStateRequest::SetLiveExportPath {
+                        path: file.display().to_string(),
+                    });
+                });
+            }
+            AppStateRequest::SetLiveExportPath { path } => {
+                self.state.preferences.live_export_path = Some(path.clone());
+                self.state.preferences.live_export_enabled = true;
+                // Write the current output right away
+                self.state.live_export_request = Some(std::time::Instant::now());
+            }
             AppStateRequest::LoadSettingsDialog => {
                 let sender = self.state.app_state_request_sender.clone();
                 let dialog_flag = self.state.file_dialog_open.clone();
@@ -607,6 +1485,596 @@ impl QualetizeApp {
         }
     }
 
+    /// Rows of the export confirmation window, from the current settings and output
+    fn export_summary(
+        &self,
+        format: &ExportFormat,
+        output_path: &str,
+    ) -> Vec<(&'static str, String)> {
+        let mut summary = vec![
+            ("Format", format.display_name().to_string()),
+            ("Contents", format.pixel_format().to_string()),
+        ];
+
+        let source = if format.is_color_corrected() {
+            self.state.color_corrected_image.as_ref()
+        } else {
+            self.state.output_image.as_ref()
+        };
+        if let Some(image) = source
+            && !format.is_palette_only()
+        {
+            let (width, height) = (image.width, image.height);
+            let mut dimensions = format!("{width}×{height}");
+            if !format.is_color_corrected()
+                && let Ok(padded) = self.state.settings.padded_export_size(width, height)
+                && padded != (width, height)
+            {
+                dimensions += &format!(" (padded to {}×{})", padded.0, padded.1);
+            }
+            summary.push(("Dimensions", dimensions));
+            let flip = match (
+                self.state.preferences.export_flip_x,
+                self.state.preferences.export_flip_y,
+            ) {
+                (false, false) => None,
+                (true, false) => Some("Horizontal"),
+                (false, true) => Some("Vertical"),
+                (true, true) => Some("Horizontal and vertical"),
+            };
+            if let Some(flip) = flip {
+                summary.push(("Flipped", flip.to_string()));
+            }
+        }
+
+        if !format.is_color_corrected()
+            && let Some(indexed) = self
+                .state
+                .output_palette_sorted_indexed_image
+                .as_ref()
+                .or_else(|| self.state.output_image.as_ref()?.indexed.as_ref())
+        {
+            let colors: usize = indexed.palettes_for_ui.iter().map(|p| p.len()).sum();
+            summary.push((
+                "Palette",
+                format!(
+                    "{colors} colors in {} palette(s)",
+                    indexed.palettes_for_ui.len()
+                ),
+            ));
+            summary.push((
+                "Transparent Index",
+                if self.state.settings.uses_transparent_index() {
+                    "0 of each palette".to_string()
+                } else {
+                    "None".to_string()
+                },
+            ));
+        }
+
+        if self.image_processor.is_processing()
+            || self.state.request_update_qualetized_image.is_some()
+        {
+            summary.push((
+                "⚠ Note",
+                "The preview is still updating; the last finished result will be written"
+                    .to_string(),
+            ));
+        }
+        if *format == ExportFormat::Png16 {
+            let skipped = self.state.color_correction.filters_skipped_at_16_bit();
+            if !skipped.is_empty() {
+                summary.push((
+                    "⚠ Note",
+                    format!("16-bit export doesn't apply {}", skipped.join(" or ")),
+                ));
+            }
+        }
+        summary.push(("Destination", output_path.to_string()));
+        summary
+    }
+
+    /// Start exporting `format` to `output_path` on a background thread. Problems found
+    /// before anything is spawned are returned; write errors arrive as an `ExportResult`.
+    fn start_export(
+        &mut self,
+        format: &ExportFormat,
+        output_path: &str,
+        ctx: &egui::Context,
+    ) -> Result<(), String> {
+        match format {
+            ExportFormat::Png => {
+                // Use ImageData pixels directly
+                let Some(color_corrected_image) = &self.state.color_corrected_image else {
+                    return Err("No color corrected image to export".to_string());
+                };
+
+                let mut rgba_data = color_corrected_image.rgba_data.clone();
+                let width = color_corrected_image.width;
+                let height = color_corrected_image.height;
+                flip_pixels(
+                    &mut rgba_data,
+                    width,
+                    4,
+                    self.state.preferences.export_flip_x,
+                    self.state.preferences.export_flip_y,
+                );
+                let options = self.state.preferences.export_options();
+                self.spawn_image_export(
+                    ctx,
+                    output_path.to_string(),
+                    &ExportFormat::Png,
+                    move |path| {
+                        save_rgba_image(
+                            path,
+                            &rgba_data,
+                            width,
+                            height,
+                            crate::types::ExportFormat::Png,
+                            options,
+                        )
+                    },
+                );
+            }
+            ExportFormat::Png16 => {
+                let (Some(input_path), Some(expected_size)) = (
+                    self.state.input_path.clone(),
+                    self.state
+                        .color_corrected_image
+                        .as_ref()
+                        .map(|image| (image.width, image.height)),
+                ) else {
+                    return Err("No color corrected image to export".to_string());
+                };
+                let color_correction = self.source_color_correction();
+                let skipped = color_correction.filters_skipped_at_16_bit();
+                if !skipped.is_empty() {
+                    self.state.push_toast(Toast::warning(format!(
+                        "16-bit export doesn't apply {}",
+                        skipped.join(" or ")
+                    )));
+                }
+                let edit_mask = self.state.edit_mask;
+                let options = self.state.preferences.export_options();
+                let (flip_x, flip_y) = (
+                    self.state.preferences.export_flip_x,
+                    self.state.preferences.export_flip_y,
+                );
+                self.spawn_image_export(ctx, output_path.to_string(), &ExportFormat::Png16, move |path| {
+                    // The 8-bit input was rounded on load, so go back to the file. It is read
+                    // again now, so edits made to it since loading end up in the export.
+                    let (pixels, width, height) = ImageData::load_rgba16(&input_path)?;
+                    if (width, height) != expected_size {
+                        return Err(format!(
+                            "The input was resized to {}×{}; 16-bit export needs the original {width}×{height}",
+                            expected_size.0, expected_size.1
+                        ));
+                    }
+                    let corrected =
+                        ColorProcessor::apply_pixels_correction16(&pixels, &color_correction);
+                    let mut pixels = match edit_mask {
+                        Some(mask) => mask.composite(&corrected, &pixels, width),
+                        None => corrected,
+                    };
+                    flip_pixels(&mut pixels, width, 4, flip_x, flip_y);
+                    save_rgba16_png(path, &pixels, width, height, options)
+                });
+            }
+            ExportFormat::MaskedPng => {
+                let Some((mut rgba_data, width, height)) =
+                    self.state.output_image.as_ref().and_then(|output| {
+                        let rgba_data = self.state.masked_output_rgba(output)?;
+                        Some((rgba_data, output.width, output.height))
+                    })
+                else {
+                    return Err(
+                        "Masked export needs an edit mask and a rendered qualetized image"
+                            .to_string(),
+                    );
+                };
+                flip_pixels(
+                    &mut rgba_data,
+                    width,
+                    4,
+                    self.state.preferences.export_flip_x,
+                    self.state.preferences.export_flip_y,
+                );
+                let options = self.state.preferences.export_options();
+                self.spawn_image_export(
+                    ctx,
+                    output_path.to_string(),
+                    &ExportFormat::MaskedPng,
+                    move |path| {
+                        save_rgba_image(
+                            path,
+                            &rgba_data,
+                            width,
+                            height,
+                            ExportFormat::MaskedPng,
+                            options,
+                        )
+                    },
+                );
+            }
+            ExportFormat::SplitPng => {
+                let Some((parts, width)) = self
+                    .state
+                    .output_image
+                    .as_ref()
+                    .filter(|image| !image.split_parts.is_empty())
+                    .map(|image| (image.split_parts.clone(), image.width))
+                else {
+                    return Err(
+                        "Split export needs more than 256 colors with Split Over 256 Colors enabled"
+                            .to_string(),
+                    );
+                };
+                let tile_size = (
+                    self.state.settings.tile_width as u32,
+                    self.state.settings.tile_height as u32,
+                );
+                let options = self.state.preferences.export_options();
+                self.spawn_image_export(
+                    ctx,
+                    output_path.to_string(),
+                    &ExportFormat::SplitPng,
+                    move |path| save_split_indexed_pngs(path, &parts, width, tile_size, options),
+                );
+            }
+            format => {
+                let mut job = self.indexed_export_job(format)?;
+                self.hold_export_snapshot(output_path, &mut job);
+                let job_format = format.clone();
+                self.spawn_image_export(ctx, output_path.to_string(), format, move |path| {
+                    job.write(&job_format, path)
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// `start_export`, with a problem found before spawning shown as an error toast
+    fn export_or_toast(&mut self, format: &ExportFormat, output_path: &str, ctx: &egui::Context) {
+        if let Err(e) = self.start_export(format, output_path, ctx) {
+            log::error!("Export to {output_path} failed: {e}");
+            self.state.push_toast(Toast::error(e));
+        }
+    }
+
+    /// Keep the job's snapshot until the export to `output_path` reports back;
+    /// only a successful export moves the "changes since last export" baseline
+    fn hold_export_snapshot(&mut self, output_path: &str, job: &mut IndexedExportJob) {
+        if let Some(snapshot) = job.snapshot.take() {
+            self.state
+                .pending_export_snapshots
+                .insert(output_path.to_string(), snapshot);
+        }
+    }
+
+    /// Snapshot of the displayed indexed output for exporting as `format`, with the
+    /// pixels for the "changes since last export" overlay unless it is palette-only.
+    fn indexed_export_job(&self, format: &ExportFormat) -> Result<IndexedExportJob, String> {
+        let Some(output_image) = &self.state.output_image else {
+            return Err("No qualetized image to export".to_string());
+        };
+        // Indices can't represent the original pixels outside the mask
+        if self.state.edit_mask.is_some() && !format.is_palette_only() {
+            return Err(
+                "An edit mask is active; export Masked PNG32 or clear the mask".to_string(),
+            );
+        }
+
+        let indexed = if self.state.output_palette_sorted_indexed_image.is_some() {
+            &self.state.output_palette_sorted_indexed_image
+        } else {
+            &output_image.indexed
+        };
+        let Some(indexed) = indexed.as_ref() else {
+            return Err(if output_image.split_parts.is_empty() {
+                "No indexed image to export".to_string()
+            } else {
+                "The output has more than 256 colors; use the Split Indexed PNGs export".to_string()
+            });
+        };
+
+        let indexed_pixels = indexed.indexed_pixels.clone();
+        let options = self.state.preferences.export_options();
+        let palettes = palette_for_export(&indexed.palettes, options.alpha_mode);
+        let palette_sizes: Vec<usize> = indexed
+            .palettes_for_ui
+            .iter()
+            .map(|palette| palette.len())
+            .collect();
+        let width = output_image.width;
+        let height = output_image.height;
+        let snapshot = (!format.is_palette_only()).then(|| ExportSnapshot {
+            width,
+            height,
+            indexed_pixels: indexed_pixels.clone(),
+        });
+
+        let (padded_width, padded_height) =
+            self.state.settings.padded_export_size(width, height)?;
+        let animation = self
+            .state
+            .input_image
+            .as_ref()
+            .and_then(|image| image.animation())
+            .and_then(|animation| {
+                Some((animation.frame_height(height)?, animation.delays_ms.clone()))
+            });
+        // Flip the content before padding so it stays at the top-left; stacked
+        // animation frames are flipped one by one so they keep their order
+        let mut indexed_pixels = indexed_pixels;
+        let flip_len = animation
+            .as_ref()
+            .map_or(indexed_pixels.len(), |(frame_height, _)| {
+                (width * frame_height) as usize
+            });
+        for part in indexed_pixels.chunks_mut(flip_len.max(1)) {
+            flip_pixels(
+                part,
+                width,
+                1,
+                self.state.preferences.export_flip_x,
+                self.state.preferences.export_flip_y,
+            );
+        }
+        let tile_size = (
+            self.state.settings.tile_width as u32,
+            self.state.settings.tile_height as u32,
+        );
+        let tile_flips = indexed.tile_flips.as_ref().and_then(|tile_flips| {
+            export_tile_flips(
+                tile_flips,
+                width,
+                animation
+                    .as_ref()
+                    .map_or(height, |(frame_height, _)| *frame_height),
+                tile_size,
+                (padded_width, padded_height),
+                self.state.preferences.export_flip_x,
+                self.state.preferences.export_flip_y,
+            )
+        });
+        let padding = (padded_width, padded_height) != (width, height);
+        let indexed_pixels = if padding {
+            pad_indexed_pixels(&indexed_pixels, width, height, padded_width, padded_height)
+        } else {
+            indexed_pixels
+        };
+
+        Ok(IndexedExportJob {
+            indexed_pixels,
+            palettes,
+            palette_sizes,
+            width: padded_width,
+            height: padded_height,
+            content_size: padding.then_some((width, height)),
+            snapshot,
+            options,
+            swatch_size: self.state.preferences.palette_swatch_size,
+            split_palette: self.state.preferences.index_csv_split_palette,
+            channel_order: self.state.preferences.raw_channel_order,
+            row_order: self.state.preferences.row_order,
+            palette_cycle: self.state.palette_cycle.clone(),
+            cell_size: if self.state.settings.sprite_sheet_enabled {
+                (
+                    self.state.settings.sprite_cell_width as u32,
+                    self.state.settings.sprite_cell_height as u32,
+                )
+            } else {
+                (
+                    self.state.settings.tile_width as u32,
+                    self.state.settings.tile_height as u32,
+                )
+            },
+            tile_size,
+            tile_flips,
+            gutter: self.state.preferences.export_cell_gutter,
+            animation,
+        })
+    }
+
+    fn update_debug_stats(&mut self, ctx: &egui::Context) {
+        let stats = &mut self.state.debug_stats;
+        stats.frame_time = std::time::Duration::from_secs_f32(ctx.input(|i| i.unstable_dt));
+        stats.quantization_in_flight = self.image_processor.is_processing();
+        stats.pending_requests = self.pending_requests.len();
+        stats.last_quantization_time = self.image_processor.last_quantization_time();
+    }
+
+    /// Run an export on a background thread and report the outcome through
+    /// `export_result_sender`, waking the UI so it is shown immediately.
+    fn spawn_export<F>(&mut self, ctx: &egui::Context, output_path: String, export: F)
+    where
+        F: FnOnce(&str) -> Result<(), String> + Send + 'static,
+    {
+        let sender = self.state.export_result_sender.clone();
+        let settings_hash = self.state.output_settings_hash();
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            let result = export(&output_path);
+            _ = sender.send(ExportResult {
+                output_path,
+                result,
+                settings_hash,
+            });
+            ctx.request_repaint();
+        });
+    }
+
+    /// Advance "Process & Export All": once the current file has been processed, export
+    /// it and load the next one. Files that failed to load, whose size doesn't fit
+    /// the tile size or whose export could not start are skipped and listed when the
+    /// batch ends.
+    fn handle_batch_export(&mut self, ctx: &egui::Context) {
+        let Some(batch) = &self.state.batch_export else {
+            return;
+        };
+        if batch.is_finished() || self.state.image_loading {
+            return;
+        }
+        let path = batch.paths[batch.current].clone();
+        let format = batch.format.clone();
+        let directory = batch.directory.clone();
+
+        let skip_reason = if self.state.input_path.as_deref() != Some(path.as_str()) {
+            Some("Could not be loaded".to_string())
+        } else if self.state.tile_size_warning {
+            Some(
+                self.state
+                    .input_image
+                    .as_ref()
+                    .and_then(|image| {
+                        self.state
+                            .settings
+                            .tile_size_error(image.width, image.height)
+                    })
+                    .unwrap_or_else(|| "Size doesn't fit the tile size".to_string()),
+            )
+        } else if self.image_processor.is_processing()
+            || self.state.request_update_qualetized_image.is_some()
+            || self.state.tile_reduce_processing
+            || self.state.request_update_tile_reduce
+        {
+            return;
+        } else if self.state.output_image.is_none() {
+            Some("Processing failed".to_string())
+        } else {
+            None
+        };
+
+        let skip_reason = skip_reason.or_else(|| {
+            let default_path = get_export_path(
+                path.clone(),
+                &format,
+                Some("qualetized".to_string()),
+                &self.state.preferences,
+                &self.state.settings,
+            );
+            let file_name = default_path.file_name().unwrap_or_default();
+            let output_path = Path::new(&directory).join(file_name).display().to_string();
+            self.start_export(&format, &output_path, ctx).err()
+        });
+
+        let Some(batch) = &mut self.state.batch_export else {
+            return;
+        };
+        match skip_reason {
+            Some(reason) => batch.skip(&path, reason),
+            None => batch.exported += 1,
+        }
+        batch.current += 1;
+        if let Some(next) = batch.paths.get(batch.current).cloned() {
+            self.load_image_file(next, ctx);
+        }
+    }
+
+    /// `spawn_export` followed by the metadata sidecar, when enabled and `format` is an image
+    fn spawn_image_export<F>(
+        &mut self,
+        ctx: &egui::Context,
+        output_path: String,
+        format: &ExportFormat,
+        export: F,
+    ) where
+        F: FnOnce(&str) -> Result<(), String> + Send + 'static,
+    {
+        let metadata = self.export_metadata(format);
+        self.spawn_export(ctx, output_path, move |path| {
+            export(path)?;
+            match metadata {
+                Some(metadata) => metadata.save_next_to(path),
+                None => Ok(()),
+            }
+        });
+    }
+
+    fn export_metadata(&self, format: &ExportFormat) -> Option<ExportMetadata> {
+        if !self.state.preferences.export_metadata_sidecar || format.is_palette_only() {
+            return None;
+        }
+        let bundle = SettingsBundle::new(
+            self.state.settings.clone(),
+            self.state.color_correction.clone(),
+            self.state.palette_sort_settings.clone(),
+        );
+        let (width, height, palette_sizes, rmse) = if format.is_color_corrected() {
+            let image = self.state.color_corrected_image.as_ref()?;
+            (image.width, image.height, Vec::new(), None)
+        } else {
+            let output = self.state.output_image.as_ref()?;
+            let palette_sizes = match self
+                .state
+                .output_palette_sorted_indexed_image
+                .as_ref()
+                .or(output.indexed.as_ref())
+            {
+                Some(indexed) => indexed.palettes_for_ui.iter().map(|p| p.len()).collect(),
+                // Split output: the palettes of every band, in band order
+                None if !output.split_parts.is_empty() => output
+                    .split_parts
+                    .iter()
+                    .flat_map(|part| part.palette_sizes.iter().copied())
+                    .collect(),
+                None => return None,
+            };
+            (
+                output.width,
+                output.height,
+                palette_sizes,
+                self.state.output_rmse,
+            )
+        };
+        Some(ExportMetadata {
+            bundle,
+            source_path: self.state.input_path.clone(),
+            format: format.display_name().to_string(),
+            width,
+            height,
+            palette_sizes,
+            rmse,
+        })
+    }
+
+    fn check_export_results(&mut self) {
+        while let Ok(export) = self.state.export_result_receiver.try_recv() {
+            match export.result {
+                Ok(()) => {
+                    log::info!("Export completed successfully: {}", export.output_path);
+                    self.state.exported_settings_hash = Some(export.settings_hash);
+                    if let Some(snapshot) = self
+                        .state
+                        .pending_export_snapshots
+                        .remove(&export.output_path)
+                    {
+                        self.state.export_snapshot = Some(snapshot);
+                        self.state.export_diff_cache = None;
+                    }
+                    // A batch reports its files in its own window
+                    if self.state.batch_export.is_none() {
+                        self.state
+                            .push_toast(Toast::info(format!("Exported to {}", export.output_path)));
+                    }
+                }
+                Err(e) => {
+                    log::error!("Export to {} failed: {e}", export.output_path);
+                    self.state
+                        .pending_export_snapshots
+                        .remove(&export.output_path);
+                    if let Some(batch) = &mut self.state.batch_export {
+                        batch.exported = batch.exported.saturating_sub(1);
+                        batch.skip(&export.output_path, e);
+                    } else {
+                        self.state
+                            .push_toast(Toast::error(format!("Export failed: {e}")));
+                    }
+                }
+            }
+        }
+    }
+
     fn update_palette_sort_settings(&mut self) {
         if self.state.output_palette_sorted_indexed_image.is_some()
             && !self.state.palette_sort_settings_changed()
@@ -628,15 +2096,34 @@ impl QualetizeApp {
         let palette_sort_settings = self.state.palette_sort_settings.clone();
 
         self.state.update_palette_sort_settings_tracking();
+        // Indices change with the order, so overlays built from them must be rebuilt
+        self.state.isolated_color_cache = None;
+        self.state.export_diff_cache = None;
+
+        let reordered = palette_sort_settings
+            .palette_order_for(indexed_image.palettes_for_ui.len())
+            .map(|order| indexed_image.with_palette_order(order));
 
         if palette_sort_settings.mode == SortMode::None {
-            self.state.output_palette_sorted_indexed_image = None;
+            self.state.output_palette_sorted_indexed_image = reordered;
+        } else if palette_sort_settings.mode == SortMode::Reference {
+            let reference = &palette_sort_settings.reference_palette;
+            self.state.output_palette_sorted_indexed_image =
+                if reference.is_empty() {
+                    reordered
+                } else {
+                    Some(reordered.unwrap_or(indexed_image).matched_to_reference(
+                        reference,
+                        self.state.settings.uses_transparent_index(),
+                    ))
+                };
         } else {
-            self.state.output_palette_sorted_indexed_image = Some(indexed_image.sorted(
-                palette_sort_settings.mode,
-                palette_sort_settings.order,
-                self.state.settings.col0_is_clear,
-            ));
+            self.state.output_palette_sorted_indexed_image =
+                Some(reordered.unwrap_or(indexed_image).sorted(
+                    palette_sort_settings.mode,
+                    palette_sort_settings.order,
+                    self.state.settings.uses_transparent_index(),
+                ));
         }
     }
 }
@@ -645,27 +2132,37 @@ impl Drop for QualetizeApp {
     fn drop(&mut self) {
         // Cancel any ongoing processing
         self.image_processor.cancel_current_processing();
+        // A panic unwinds through here too; keep the recovery file for the next start
+        if !std::thread::panicking() {
+            SettingsBundle::remove_recovery_file();
+        }
         log::debug!("QualetizeApp dropped, resources cleaned up");
     }
 }
 
 impl eframe::App for QualetizeApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        let update_start = std::time::Instant::now();
         let image_processing =
             self.image_processor.is_processing() || self.state.tile_reduce_processing;
 
         // apply theme
         self.apply_theme(ctx);
+        self.apply_window_mode(ctx);
 
         // Handle drag and drop first
         if !self.state.file_dialog_open.load(Ordering::Relaxed) {
             self.handle_dropped_files(ctx);
         }
 
+        self.check_image_load_completion(ctx);
+        self.check_queue_thumbnails();
+
         // Check preview completion
         self.check_preview_completion(ctx);
         // Check tile reduce completion
         self.check_tile_reduce_completion(ctx);
+        self.check_color_space_trials();
 
         // Update color corrected image if needed
         self.update_color_corrected_image(ctx);
@@ -680,9 +2177,20 @@ impl eframe::App for QualetizeApp {
 
         // Handle export requests
         self.handle_requests(ctx);
+        self.handle_batch_export(ctx);
+        self.update_debug_stats(ctx);
+
+        self.handle_live_export();
+
+        self.update_preview_sampling(ctx);
 
         // Save preferences
         self.state.check_and_save_preferences();
+        self.state.check_and_save_recovery();
+
+        // Report finished exports and expire old notifications
+        self.check_export_results();
+        self.state.prune_toasts();
 
         let mut settings_changed = false;
         let mut tile_reduce_changed = false;
@@ -695,11 +2203,13 @@ impl eframe::App for QualetizeApp {
                 });
         });
 
+        let compact = self.state.preferences.compact_mode;
+
         // Side（Settings）
         egui::SidePanel::left("settings_panel")
             .default_width(260.0)
             .resizable(true)
-            .show(ctx, |ui| {
+            .show_animated(ctx, !compact, |ui| {
                 egui::ScrollArea::vertical().show(ui, |ui| {
                     let (settings, tile_reduce) = UI::draw_settings_panel(ui, &mut self.state);
                     settings_changed |= settings;
@@ -717,13 +2227,13 @@ impl eframe::App for QualetizeApp {
             .show(ctx, |ui| {
                 // Main
                 if self.state.input_path.is_none() {
-                    UI::draw_main_content(ui);
+                    UI::draw_main_content(ui, self.state.image_loading);
                 } else {
                     UI::draw_image_view(ui, &mut self.state, image_processing);
                 }
 
                 // Footer
-                if self.state.input_image.is_some() {
+                if self.state.input_image.is_some() && !compact {
                     egui::TopBottomPanel::bottom("footer").show(ctx, |ui| {
                         egui::Frame::NONE
                             .inner_margin(Margin::symmetric(0, 4))
@@ -732,8 +2242,24 @@ impl eframe::App for QualetizeApp {
                             });
                     });
                 }
+
+                // Filmstrip of queued files
+                if self.state.image_queue.len() > 1 && !compact {
+                    egui::TopBottomPanel::bottom("queue_filmstrip").show(ctx, |ui| {
+                        UI::draw_queue_filmstrip(ui, &mut self.state);
+                    });
+                }
             });
 
+        UI::draw_palette_inspector(ctx, &mut self.state);
+        UI::draw_palette_diff(ctx, &mut self.state);
+        UI::draw_animation_preview(ctx, &mut self.state);
+        UI::draw_batch_export(ctx, &mut self.state);
+        UI::draw_toasts(ctx, &self.state);
+        if self.state.preferences.show_debug_info {
+            UI::draw_debug_hud(ctx, &self.state);
+        }
+
         if settings_changed {
             self.state.request_update_qualetized_image = Some(QualetizeRequest {
                 time: std::time::Instant::now(),
@@ -748,11 +2274,221 @@ impl eframe::App for QualetizeApp {
             || self.state.tile_reduce_processing
             || self.state.request_update_qualetized_image.is_some()
             || self.state.request_update_tile_reduce
+            || self.state.live_export_request.is_some()
+            || self.state.image_loading
+            || !self.pending_requests.is_empty()
         {
             ctx.request_repaint();
+        } else if !self.state.toasts.is_empty() {
+            // Keep repainting so toasts disappear when they expire
+            ctx.request_repaint_after(std::time::Duration::from_millis(250));
         }
 
         // enforce high quality always
+        self.state.debug_stats.update_time = update_start.elapsed();
+    }
+}
+
+/// Formats written by "Export All", with their file name suffixes
+const EXPORT_ALL_FORMATS: &[(ExportFormat, &str)] = &[
+    (ExportFormat::PngIndexed, "qualetized"),
+    (ExportFormat::Bmp, "qualetized"),
+    (ExportFormat::CombinedPalette, "palette"),
+];
+
+/// Everything an indexed export needs, copied out of the app state so it can be
+/// written from a background thread (possibly in several formats)
+struct IndexedExportJob {
+    indexed_pixels: Vec<u8>,
+    palettes: Vec<BGRA8>,
+    palette_sizes: Vec<usize>,
+    /// Size after padding
+    width: u32,
+    height: u32,
+    /// Unpadded size, if export padding changed it
+    content_size: Option<(u32, u32)>,
+    /// Taken before the job moves to its thread
+    snapshot: Option<ExportSnapshot>,
+    options: ExportOptions,
+    swatch_size: u32,
+    split_palette: bool,
+    channel_order: ChannelOrder,
+    row_order: RowOrder,
+    palette_cycle: PaletteCycle,
+    /// Sprite sheet cells, or tiles without a sprite sheet; laid out by the gutter export
+    cell_size: (u32, u32),
+    tile_size: (u32, u32),
+    /// Tile reduce's orientation of each `tile_size` tile of the exported pixels
+    tile_flips: Option<Vec<TileFlip>>,
+    gutter: u32,
+    /// Frame height and delays when the input is an animation
+    animation: Option<(u32, Vec<u32>)>,
+}
+
+impl IndexedExportJob {
+    fn write(&self, format: &ExportFormat, path: &str) -> Result<(), String> {
+        let (pixels, width, height) = (&self.indexed_pixels, self.width, self.height);
+        match format {
+            ExportFormat::Png
+            | ExportFormat::Png16
+            | ExportFormat::SplitPng
+            | ExportFormat::MaskedPng => Err("Unexpected format for indexed export".to_string()),
+            ExportFormat::Bmp => save_indexed_bmp(
+                path,
+                pixels,
+                &self.palettes,
+                width,
+                height,
+                self.row_order,
+                self.options,
+            ),
+            ExportFormat::Tga => {
+                save_indexed_tga(path, pixels, &self.palettes, width, height, self.row_order)
+            }
+            ExportFormat::PngIndexed => {
+                save_indexed_png(path, pixels, &self.palettes, width, height, self.options)
+            }
+            ExportFormat::GenesisNametable => {
+                // The name table is always made of 8×8 tiles
+                let tile_flips = self
+                    .tile_flips
+                    .as_deref()
+                    .filter(|_| self.tile_size == (8, 8));
+                save_genesis_nametable(path, pixels, &self.palette_sizes, width, height, tile_flips)
+            }
+            ExportFormat::Apng => match &self.animation {
+                Some((frame_height, delays_ms)) => save_indexed_apng(
+                    path,
+                    pixels,
+                    &self.palettes,
+                    width,
+                    *frame_height,
+                    delays_ms,
+                    self.options,
+                ),
+                None => Err("The input is not an animation".to_string()),
+            },
+            ExportFormat::TiledMap => save_tiled_map(
+                path,
+                pixels,
+                &self.palettes,
+                width,
+                height,
+                self.tile_size,
+                self.options.color_profile,
+            ),
+            ExportFormat::IndexCsv => save_index_csv(
+                path,
+                pixels,
+                &self.palette_sizes,
+                width,
+                height,
+                self.split_palette,
+            ),
+            ExportFormat::PaletteStrip => save_palette_strip_png(
+                path,
+                &self.palettes,
+                &self.palette_sizes,
+                self.swatch_size,
+                self.options.color_profile,
+            ),
+            ExportFormat::CombinedPalette => save_combined_palette(path, &self.palettes),
+            ExportFormat::RawPalette => save_raw_palette(path, &self.palettes, self.channel_order),
+            ExportFormat::PaletteCycleGif => save_palette_cycle_gif(
+                path,
+                pixels,
+                &self.palettes,
+                width,
+                height,
+                &self.palette_cycle,
+            ),
+            ExportFormat::GutteredPng => {
+                let (cell_width, cell_height) = self.cell_size;
+                let (pixels, width, height) =
+                    add_cell_gutters(pixels, width, height, cell_width, cell_height, self.gutter)?;
+                save_indexed_png(path, &pixels, &self.palettes, width, height, self.options)
+            }
+        }?;
+        if let Some((content_width, content_height)) = self.content_size
+            && !format.is_palette_only()
+        {
+            save_padding_info(path, content_width, content_height, width, height)?;
+        }
+        Ok(())
+    }
+}
+
+/// Quantize with `color_space` swapped into `settings` and measure the RGB error
+/// of the output against `bgra_data`
+fn color_space_trial(
+    bgra_data: &[BGRA8],
+    width: u32,
+    height: u32,
+    settings: &QualetizeSettings,
+    color_space: &ColorSpace,
+) -> ColorSpaceTrial {
+    let settings = QualetizeSettings {
+        color_space: color_space.clone(),
+        ..settings.clone()
+    };
+    let rmse =
+        ImageProcessor::perform_qualetize_processing(bgra_data.to_vec(), width, height, settings)
+            .map(|result| ImageProcessor::rgb_rmse(bgra_data, &result));
+    if let Err(e) = &rmse {
+        log::warn!(
+            "Color space trial {} failed: {e}",
+            color_space.display_name()
+        );
+    }
+    ColorSpaceTrial {
+        color_space: color_space.clone(),
+        rmse,
+    }
+}
+
+/// Quantize one contact sheet variation and render it, with its RMSE in the caption.
+/// A failed variation is left blank and says so in its caption.
+fn contact_sheet_cell(
+    bgra_data: Vec<BGRA8>,
+    width: u32,
+    height: u32,
+    settings: QualetizeSettings,
+    caption: String,
+) -> ContactSheetCell {
+    match ImageProcessor::perform_qualetize_processing(bgra_data, width, height, settings) {
+        Ok(result) => {
+            let mut rgba = Vec::with_capacity((width * height * 4) as usize);
+            for &pixel_index in &result.indexed_data {
+                match result.palette_data.get(pixel_index as usize) {
+                    Some(color) => rgba.extend_from_slice(&color.to_rgba()),
+                    None => rgba.extend_from_slice(&[0, 0, 0, 255]),
+                }
+            }
+            let rmse = (result.rmse.iter().map(|e| e * e).sum::<f32>() / 4.0).sqrt();
+            ContactSheetCell {
+                rgba,
+                width,
+                height,
+                caption: format!("{caption} RMSE {rmse:.3}"),
+            }
+        }
+        Err(e) => failed_contact_sheet_cell(width, height, &caption, &e),
+    }
+}
+
+/// Blank cell captioned as failed, so the sheet keeps its layout
+fn failed_contact_sheet_cell(
+    width: u32,
+    height: u32,
+    caption: &str,
+    reason: &str,
+) -> ContactSheetCell {
+    log::warn!("Contact sheet variation {caption} failed: {reason}");
+    ContactSheetCell {
+        rgba: vec![0; (width * height * 4) as usize],
+        width,
+        height,
+        caption: format!("{caption} failed"),
     }
 }
 
@@ -760,19 +2496,54 @@ fn get_export_path(
     input_path: String,
     format: &ExportFormat,
     suffix: Option<String>,
+    preferences: &UserPreferences,
+    settings: &QualetizeSettings,
 ) -> std::path::PathBuf {
     let path = Path::new(&input_path);
 
-    let parent = path.parent().unwrap_or(Path::new("."));
-    let stem = path.file_stem().unwrap_or(std::ffi::OsStr::new("output"));
-    let new_name = if let Some(suffix) = suffix {
-        format!("{}_{}", stem.to_string_lossy(), suffix)
-    } else {
-        stem.to_string_lossy().to_string()
+    let parent = match &preferences.export_directory {
+        Some(directory) => Path::new(directory),
+        None => path.parent().unwrap_or(Path::new(".")),
     };
+    let stem = path.file_stem().unwrap_or(std::ffi::OsStr::new("output"));
+    let new_name = export_file_name(
+        &preferences.export_filename_template,
+        &stem.to_string_lossy(),
+        suffix.as_deref(),
+        settings,
+    );
     parent.join(new_name).with_extension(format.extension())
 }
 
+/// Expand the file name template. Supported tokens are `{name}` (input file stem),
+/// `{suffix}` (export kind), `{palettes}`, `{colors}` and `{space}` (color space).
+/// An empty template gives `<name>_<suffix>`.
+fn export_file_name(
+    template: &str,
+    name: &str,
+    suffix: Option<&str>,
+    settings: &QualetizeSettings,
+) -> String {
+    if template.trim().is_empty() {
+        return match suffix {
+            Some(suffix) => format!("{name}_{suffix}"),
+            None => name.to_string(),
+        };
+    }
+    let space: String = settings
+        .color_space
+        .display_name()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect();
+    template
+        .replace("{name}", name)
+        .replace("{suffix}", suffix.unwrap_or(""))
+        .replace("{palettes}", &settings.n_palettes.to_string())
+        .replace("{colors}", &settings.n_colors.to_string())
+        .replace("{space}", &space)
+}
+
 pub struct FileDialogGuard {
     flag: Arc<AtomicBool>,
 }
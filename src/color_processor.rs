@@ -1,5 +1,7 @@
+use crate::types::BGRA8;
 use crate::types::color_correction::ColorCorrection;
 use image::{ImageBuffer, Rgba, RgbaImage};
+use rayon::prelude::*;
 
 pub struct ColorProcessor;
 
@@ -23,14 +25,171 @@ impl ColorProcessor {
         output
     }
 
+    /// 3×3 bilateral filter over RGBA pixels: neighbors are averaged in only when
+    /// their color is close, which smooths JPEG block noise but keeps edges. Alpha is kept.
+    pub fn reduce_jpeg_artifacts(pixels: &[u8], width: u32, height: u32) -> Vec<u8> {
+        // Squared color distance at which a neighbor's weight falls to 1/e,
+        // about 14 levels in every channel; JPEG noise is usually well below that
+        const RANGE_SIGMA_SQ: f32 = 3.0 * 14.0 * 14.0;
+
+        let (width, height) = (width as usize, height as usize);
+        let mut output = pixels.to_vec();
+        for y in 0..height {
+            for x in 0..width {
+                let center = (y * width + x) * 4;
+                let mut sum = [0.0f32; 3];
+                let mut total_weight = 0.0;
+                for ny in y.saturating_sub(1)..(y + 2).min(height) {
+                    for nx in x.saturating_sub(1)..(x + 2).min(width) {
+                        let neighbor = (ny * width + nx) * 4;
+                        let distance: f32 = (0..3)
+                            .map(|c| {
+                                let d = pixels[neighbor + c] as f32 - pixels[center + c] as f32;
+                                d * d
+                            })
+                            .sum();
+                        // Direct neighbors count more than diagonals
+                        let spatial = match (nx != x) as u8 + (ny != y) as u8 {
+                            0 => 1.0,
+                            1 => 0.5,
+                            _ => 0.25,
+                        };
+                        let weight = spatial * (-distance / RANGE_SIGMA_SQ).exp();
+                        for (c, channel_sum) in sum.iter_mut().enumerate() {
+                            *channel_sum += pixels[neighbor + c] as f32 * weight;
+                        }
+                        total_weight += weight;
+                    }
+                }
+                for (c, channel_sum) in sum.iter().enumerate() {
+                    output[center + c] = (channel_sum / total_weight).round() as u8;
+                }
+            }
+        }
+        output
+    }
+
+    /// Bilateral filter over a (2·radius+1)² window, rows in parallel. A neighbor is
+    /// averaged in by its distance and by how close its color is; `strength` (0-1)
+    /// widens the color tolerance, so noise is smoothed while sprite edges stay crisp.
+    /// Transparent neighbors are ignored and alpha is kept.
+    pub fn denoise(pixels: &[u8], width: u32, height: u32, strength: f32, radius: u32) -> Vec<u8> {
+        // Per-channel color difference at which a neighbor's weight falls to 1/e
+        let range_sigma = 4.0 + strength.clamp(0.0, 1.0) * 36.0;
+        let range_sigma_sq = 3.0 * range_sigma * range_sigma;
+        let radius = radius.max(1) as isize;
+        let spatial_sigma_sq = (radius * radius) as f32 / 2.0;
+
+        let (width, height) = (width as usize, height as usize);
+        let mut output = pixels.to_vec();
+        output
+            .par_chunks_mut(width * 4)
+            .enumerate()
+            .for_each(|(y, row)| {
+                for x in 0..width {
+                    let center = (y * width + x) * 4;
+                    if pixels[center + 3] == 0 {
+                        continue;
+                    }
+                    let mut sum = [0.0f32; 3];
+                    let mut total_weight = 0.0;
+                    for dy in -radius..=radius {
+                        let Some(ny) = y.checked_add_signed(dy).filter(|&ny| ny < height) else {
+                            continue;
+                        };
+                        for dx in -radius..=radius {
+                            let Some(nx) = x.checked_add_signed(dx).filter(|&nx| nx < width) else {
+                                continue;
+                            };
+                            let neighbor = (ny * width + nx) * 4;
+                            if pixels[neighbor + 3] == 0 {
+                                continue;
+                            }
+                            let distance: f32 = (0..3)
+                                .map(|c| {
+                                    let d = pixels[neighbor + c] as f32 - pixels[center + c] as f32;
+                                    d * d
+                                })
+                                .sum();
+                            let spatial = (dx * dx + dy * dy) as f32 / spatial_sigma_sq;
+                            let weight = (-spatial - distance / range_sigma_sq).exp();
+                            for (c, channel_sum) in sum.iter_mut().enumerate() {
+                                *channel_sum += pixels[neighbor + c] as f32 * weight;
+                            }
+                            total_weight += weight;
+                        }
+                    }
+                    for (c, channel_sum) in sum.iter().enumerate() {
+                        row[x * 4 + c] = (channel_sum / total_weight).round() as u8;
+                    }
+                }
+            });
+        output
+    }
+
+    /// Apply the corrections to palette entries instead of source pixels
+    pub fn apply_palette_correction(
+        palette: &[BGRA8],
+        corrections: &ColorCorrection,
+    ) -> Vec<BGRA8> {
+        palette
+            .iter()
+            .map(|color| {
+                let [r, g, b, a] =
+                    Self::apply_pixel_corrections(&Rgba(color.to_rgba()), corrections).0;
+                BGRA8 { b, g, r, a }
+            })
+            .collect()
+    }
+
+    /// Apply the corrections to 16 bits per channel RGBA pixels, keeping the precision
+    /// the 8-bit path rounds away. The JPEG artifact and denoise filters are 8-bit only
+    /// and are skipped.
+    pub fn apply_pixels_correction16(pixels: &[u16], corrections: &ColorCorrection) -> Vec<u16> {
+        let mut output = Vec::with_capacity(pixels.len());
+        for pixel in pixels.chunks_exact(4) {
+            let (rf, gf, bf) = Self::correct_rgb(
+                pixel[0] as f32 / 65535.0,
+                pixel[1] as f32 / 65535.0,
+                pixel[2] as f32 / 65535.0,
+                corrections,
+            );
+            output.extend_from_slice(&[
+                (rf * 65535.0).round() as u16,
+                (gf * 65535.0).round() as u16,
+                (bf * 65535.0).round() as u16,
+                pixel[3],
+            ]);
+        }
+        output
+    }
+
     fn apply_pixel_corrections(pixel: &Rgba<u8>, corrections: &ColorCorrection) -> Rgba<u8> {
         let [r, g, b, a] = pixel.0;
 
         // Convert to float 0.0-1.0 range
-        let mut rf = r as f32 / 255.0;
-        let mut gf = g as f32 / 255.0;
-        let mut bf = b as f32 / 255.0;
+        let (rf, gf, bf) = Self::correct_rgb(
+            r as f32 / 255.0,
+            g as f32 / 255.0,
+            b as f32 / 255.0,
+            corrections,
+        );
 
+        Rgba([
+            (rf * 255.0) as u8,
+            (gf * 255.0) as u8,
+            (bf * 255.0) as u8,
+            a, // Keep original alpha
+        ])
+    }
+
+    /// The corrections on 0.0-1.0 RGB, clamped to that range
+    fn correct_rgb(
+        mut rf: f32,
+        mut gf: f32,
+        mut bf: f32,
+        corrections: &ColorCorrection,
+    ) -> (f32, f32, f32) {
         // Apply gamma correction first
         rf = Self::apply_gamma(rf, corrections.gamma);
         gf = Self::apply_gamma(gf, corrections.gamma);
@@ -77,17 +236,7 @@ impl ColorProcessor {
             bf *= highlight_factor;
         }
 
-        // Clamp and convert back to u8
-        rf = rf.clamp(0.0, 1.0);
-        gf = gf.clamp(0.0, 1.0);
-        bf = bf.clamp(0.0, 1.0);
-
-        Rgba([
-            (rf * 255.0) as u8,
-            (gf * 255.0) as u8,
-            (bf * 255.0) as u8,
-            a, // Keep original alpha
-        ])
+        (rf.clamp(0.0, 1.0), gf.clamp(0.0, 1.0), bf.clamp(0.0, 1.0))
     }
 
     fn apply_gamma(value: f32, gamma: f32) -> f32 {
@@ -1,4 +1,1112 @@
-use crate::types::BGRA8;
+use crate::image_processor::{SplitPart, TileFlip};
+use crate::types::{
+    BGRA8, ChannelOrder, ExportAlphaMode, ExportColorProfile, ExportFormat, ExportOptions,
+    PaletteCycle, RowOrder,
+};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// A tile map cell pointing at a deduplicated tile
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TileMapEntry {
+    pub tile: usize,
+    pub palette: usize,
+    pub flip_x: bool,
+    pub flip_y: bool,
+}
+
+/// Unique tiles (as palette-local color indices) and the map that rebuilds the image
+pub struct TileSet {
+    pub tiles: Vec<Vec<u8>>,
+    pub map: Vec<TileMapEntry>,
+}
+
+fn flip_tile(tile: &[u8], tile_w: usize, tile_h: usize, flip_x: bool, flip_y: bool) -> Vec<u8> {
+    let mut flipped = Vec::with_capacity(tile.len());
+    for y in 0..tile_h {
+        let src_y = if flip_y { tile_h - 1 - y } else { y };
+        for x in 0..tile_w {
+            let src_x = if flip_x { tile_w - 1 - x } else { x };
+            flipped.push(tile[src_y * tile_w + src_x]);
+        }
+    }
+    flipped
+}
+
+/// Split an indexed image into unique tiles. With `tile_flips` from tile reduce each
+/// tile is stored in the orientation it was reduced in, so tiles merged into the same
+/// one share it; otherwise flipped copies of earlier tiles are matched exactly.
+/// Every tile must use colors from a single palette.
+pub fn build_tileset(
+    indexed_pixel_data: &[u8],
+    palette_sizes: &[usize],
+    width: u32,
+    height: u32,
+    tile_width: u32,
+    tile_height: u32,
+    tile_flips: Option<&[TileFlip]>,
+) -> Result<TileSet, String> {
+    if tile_width == 0
+        || tile_height == 0
+        || !width.is_multiple_of(tile_width)
+        || !height.is_multiple_of(tile_height)
+    {
+        return Err(format!(
+            "Image size ({width}×{height}) is not divisible by tile size ({tile_width}×{tile_height})"
+        ));
+    }
+
+    let stride = width as usize;
+    let tile_w = tile_width as usize;
+    let tile_h = tile_height as usize;
+    let palette_of = |index: u8| {
+        let mut start = 0;
+        for (palette, &size) in palette_sizes.iter().enumerate() {
+            if (start..start + size).contains(&(index as usize)) {
+                return Some((palette, index - start as u8));
+            }
+            start += size;
+        }
+        None
+    };
+
+    // Orientations from a different tile grid don't describe these tiles
+    let tile_count = ((width / tile_width) * (height / tile_height)) as usize;
+    let tile_flips = tile_flips.filter(|flips| flips.len() == tile_count);
+
+    let mut tiles: Vec<Vec<u8>> = Vec::new();
+    let mut lookup: HashMap<Vec<u8>, usize> = HashMap::new();
+    let mut map = Vec::new();
+
+    for ty in 0..(height / tile_height) as usize {
+        for tx in 0..(width / tile_width) as usize {
+            let mut tile = Vec::with_capacity(tile_w * tile_h);
+            let mut tile_palette = None;
+            for y in 0..tile_h {
+                let offset = (ty * tile_h + y) * stride + tx * tile_w;
+                for &index in &indexed_pixel_data[offset..offset + tile_w] {
+                    let (palette, local) = palette_of(index)
+                        .ok_or_else(|| format!("Pixel index {index} is outside every palette"))?;
+                    if *tile_palette.get_or_insert(palette) != palette {
+                        return Err(format!("Tile ({tx}, {ty}) uses more than one palette"));
+                    }
+                    tile.push(local);
+                }
+            }
+
+            let flips = match tile_flips {
+                Some(flips) => vec![(flips[map.len()].flip_x, flips[map.len()].flip_y)],
+                None => vec![(false, false), (true, false), (false, true), (true, true)],
+            };
+            let mut entry = None;
+            for &(flip_x, flip_y) in &flips {
+                let candidate = flip_tile(&tile, tile_w, tile_h, flip_x, flip_y);
+                if let Some(&existing) = lookup.get(&candidate) {
+                    entry = Some(TileMapEntry {
+                        tile: existing,
+                        palette: tile_palette.unwrap_or(0),
+                        flip_x,
+                        flip_y,
+                    });
+                    break;
+                }
+            }
+            let entry = entry.unwrap_or_else(|| {
+                let (flip_x, flip_y) = flips[0];
+                let stored = flip_tile(&tile, tile_w, tile_h, flip_x, flip_y);
+                lookup.insert(stored.clone(), tiles.len());
+                tiles.push(stored);
+                TileMapEntry {
+                    tile: tiles.len() - 1,
+                    palette: tile_palette.unwrap_or(0),
+                    flip_x,
+                    flip_y,
+                }
+            });
+            map.push(entry);
+        }
+    }
+
+    Ok(TileSet { tiles, map })
+}
+
+/// Pack a map entry into a Sega Genesis VDP name table word:
+/// priority (bit 15), palette (14-13), V flip (12), H flip (11), tile (10-0).
+/// The priority bit is left clear.
+fn genesis_name_table_word(entry: &TileMapEntry) -> u16 {
+    ((entry.palette as u16 & 0x3) << 13)
+        | ((entry.flip_y as u16) << 12)
+        | ((entry.flip_x as u16) << 11)
+        | (entry.tile as u16 & 0x7FF)
+}
+
+/// Write a big-endian Genesis name table to `output_path` and the matching
+/// 4bpp tile patterns next to it as `<name>.tiles.bin`. `tile_flips` are tile
+/// reduce's orientations of the 8×8 tiles, which set the H and V flip bits.
+pub fn save_genesis_nametable(
+    output_path: &str,
+    indexed_pixel_data: &[u8],
+    palette_sizes: &[usize],
+    width: u32,
+    height: u32,
+    tile_flips: Option<&[TileFlip]>,
+) -> Result<(), String> {
+    let tileset = build_tileset(
+        indexed_pixel_data,
+        palette_sizes,
+        width,
+        height,
+        8,
+        8,
+        tile_flips,
+    )?;
+
+    if tileset.tiles.len() > 2048 {
+        return Err(format!(
+            "{} unique tiles exceed the 2048 tiles addressable by the name table",
+            tileset.tiles.len()
+        ));
+    }
+    if let Some(entry) = tileset.map.iter().find(|entry| entry.palette > 3) {
+        return Err(format!(
+            "Palette {} is out of range; Genesis supports 4 palettes",
+            entry.palette
+        ));
+    }
+
+    let mut name_table = Vec::with_capacity(tileset.map.len() * 2);
+    for entry in &tileset.map {
+        name_table.extend_from_slice(&genesis_name_table_word(entry).to_be_bytes());
+    }
+
+    // 4bpp patterns: 4 bytes per row, left pixel in the high nibble
+    let mut patterns = Vec::with_capacity(tileset.tiles.len() * 32);
+    for tile in &tileset.tiles {
+        if let Some(&index) = tile.iter().find(|&&index| index > 15) {
+            return Err(format!(
+                "Color index {index} does not fit in 4bpp; use at most 16 colors per palette"
+            ));
+        }
+        for pair in tile.chunks_exact(2) {
+            patterns.push((pair[0] << 4) | pair[1]);
+        }
+    }
+
+    let tiles_path = Path::new(output_path).with_extension("tiles.bin");
+    std::fs::write(output_path, name_table).map_err(|e| format!("File write error: {e}"))?;
+    std::fs::write(&tiles_path, patterns).map_err(|e| format!("File write error: {e}"))?;
+
+    log::info!(
+        "Genesis name table exported to {output_path} with tiles at {}",
+        tiles_path.display()
+    );
+    Ok(())
+}
+
+/// Tiles per row of the tileset image written for Tiled
+const TILED_TILESET_COLUMNS: usize = 16;
+/// Tiled global tile ID flags for horizontally / vertically flipped tiles
+const TILED_FLIP_X: u32 = 0x8000_0000;
+const TILED_FLIP_Y: u32 = 0x4000_0000;
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Tiles laid out `TILED_TILESET_COLUMNS` per row, as one indexed image
+fn tiled_tileset_image(tiles: &[Vec<u8>], tile_w: usize, tile_h: usize) -> (Vec<u8>, usize, usize) {
+    let columns = tiles.len().clamp(1, TILED_TILESET_COLUMNS);
+    let rows = tiles.len().div_ceil(columns).max(1);
+    let (width, height) = (columns * tile_w, rows * tile_h);
+    let mut pixels = vec![0u8; width * height];
+    for (i, tile) in tiles.iter().enumerate() {
+        let (origin_x, origin_y) = (i % columns * tile_w, i / columns * tile_h);
+        for (y, row) in tile.chunks_exact(tile_w).enumerate() {
+            let offset = (origin_y + y) * width + origin_x;
+            pixels[offset..offset + tile_w].copy_from_slice(row);
+        }
+    }
+    (pixels, width, height)
+}
+
+fn format_tiled_tileset(
+    name: &str,
+    image_source: &str,
+    tile_count: usize,
+    tile_size: (usize, usize),
+    image_size: (usize, usize),
+) -> String {
+    let columns = tile_count.clamp(1, TILED_TILESET_COLUMNS);
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <tileset version=\"1.10\" name=\"{}\" tilewidth=\"{}\" tileheight=\"{}\" tilecount=\"{tile_count}\" columns=\"{columns}\">\n \
+         <image source=\"{}\" width=\"{}\" height=\"{}\"/>\n\
+         </tileset>\n",
+        xml_escape(name),
+        tile_size.0,
+        tile_size.1,
+        xml_escape(image_source),
+        image_size.0,
+        image_size.1,
+    )
+}
+
+/// Orthogonal map with one CSV tile layer; flips use Tiled's global ID flag bits
+fn format_tiled_map(
+    map: &[TileMapEntry],
+    map_width: usize,
+    map_height: usize,
+    tile_size: (usize, usize),
+    tileset_source: &str,
+) -> String {
+    let rows: Vec<String> = map
+        .chunks(map_width.max(1))
+        .map(|row| {
+            row.iter()
+                .map(|entry| {
+                    let mut gid = entry.tile as u32 + 1;
+                    if entry.flip_x {
+                        gid |= TILED_FLIP_X;
+                    }
+                    if entry.flip_y {
+                        gid |= TILED_FLIP_Y;
+                    }
+                    gid.to_string()
+                })
+                .collect::<Vec<_>>()
+                .join(",")
+        })
+        .collect();
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <map version=\"1.10\" orientation=\"orthogonal\" renderorder=\"right-down\" width=\"{map_width}\" height=\"{map_height}\" tilewidth=\"{}\" tileheight=\"{}\" infinite=\"0\" nextlayerid=\"2\" nextobjectid=\"1\">\n \
+         <tileset firstgid=\"1\" source=\"{}\"/>\n \
+         <layer id=\"1\" name=\"Tiles\" width=\"{map_width}\" height=\"{map_height}\">\n  \
+         <data encoding=\"csv\">\n{}\n</data>\n \
+         </layer>\n\
+         </map>\n",
+        tile_size.0,
+        tile_size.1,
+        xml_escape(tileset_source),
+        rows.join(",\n"),
+    )
+}
+
+/// Write a Tiled map to `output_path` (.tmx), plus the tileset it references next to it
+/// as `<name>.tsx` and `<name>.tiles.png`. Tiles are deduplicated with flips; since
+/// Tiled has no palettes, the same shape in two palettes becomes two tiles.
+pub fn save_tiled_map(
+    output_path: &str,
+    indexed_pixel_data: &[u8],
+    palette_data: &[BGRA8],
+    width: u32,
+    height: u32,
+    tile_size: (u32, u32),
+    color_profile: ExportColorProfile,
+) -> Result<(), String> {
+    let (tile_width, tile_height) = tile_size;
+    // One palette spanning every color keeps tiles in their global colors
+    let tileset = build_tileset(
+        indexed_pixel_data,
+        &[palette_data.len().max(256)],
+        width,
+        height,
+        tile_width,
+        tile_height,
+        None,
+    )?;
+    let (tile_w, tile_h) = (tile_width as usize, tile_height as usize);
+
+    let path = Path::new(output_path);
+    let name = path
+        .file_stem()
+        .map_or("tiles".into(), |stem| stem.to_string_lossy());
+    let tsx_path = path.with_extension("tsx");
+    let image_path = path.with_extension("tiles.png");
+    let file_name = |path: &Path| {
+        path.file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    };
+
+    let (tile_pixels, image_width, image_height) =
+        tiled_tileset_image(&tileset.tiles, tile_w, tile_h);
+    save_indexed_png(
+        &image_path.to_string_lossy(),
+        &tile_pixels,
+        palette_data,
+        image_width as u32,
+        image_height as u32,
+        // Tilesets are loaded by Tiled, not printed
+        ExportOptions {
+            color_profile,
+            ..Default::default()
+        },
+    )?;
+
+    let tsx = format_tiled_tileset(
+        &name,
+        &file_name(&image_path),
+        tileset.tiles.len(),
+        (tile_w, tile_h),
+        (image_width, image_height),
+    );
+    std::fs::write(&tsx_path, tsx).map_err(|e| format!("File write error: {e}"))?;
+
+    let tmx = format_tiled_map(
+        &tileset.map,
+        width as usize / tile_w,
+        height as usize / tile_h,
+        (tile_w, tile_h),
+        &file_name(&tsx_path),
+    );
+    std::fs::write(output_path, tmx).map_err(|e| format!("File write error: {e}"))?;
+
+    log::info!(
+        "Tiled map exported to {output_path} with {} tiles in {}",
+        tileset.tiles.len(),
+        tsx_path.display()
+    );
+    Ok(())
+}
+
+/// Manifest written next to the parts of a split export
+#[derive(serde::Serialize, Debug, PartialEq)]
+pub struct SplitManifest {
+    pub width: u32,
+    pub height: u32,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub parts: Vec<SplitManifestPart>,
+}
+
+/// One part file and the band of tile rows it covers
+#[derive(serde::Serialize, Debug, PartialEq)]
+pub struct SplitManifestPart {
+    pub file: String,
+    pub y: u32,
+    pub height: u32,
+    pub first_tile_row: u32,
+    pub tile_rows: u32,
+    pub palettes: usize,
+    pub colors: usize,
+}
+
+fn split_part_path(output_path: &Path, index: usize) -> std::path::PathBuf {
+    output_path.with_extension(format!("part{index}.png"))
+}
+
+fn build_split_manifest(
+    output_path: &Path,
+    parts: &[SplitPart],
+    width: u32,
+    tile_size: (u32, u32),
+) -> SplitManifest {
+    let (tile_width, tile_height) = tile_size;
+    let tile_height = tile_height.max(1);
+    let height = parts
+        .iter()
+        .map(|part| part.y + part.height)
+        .max()
+        .unwrap_or(0);
+    let parts = parts
+        .iter()
+        .enumerate()
+        .map(|(index, part)| SplitManifestPart {
+            file: split_part_path(output_path, index)
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            y: part.y,
+            height: part.height,
+            first_tile_row: part.y / tile_height,
+            tile_rows: part.height.div_ceil(tile_height),
+            palettes: part.palette_sizes.len(),
+            colors: part.palette_sizes.iter().sum(),
+        })
+        .collect();
+    SplitManifest {
+        width,
+        height,
+        tile_width,
+        tile_height,
+        parts,
+    }
+}
+
+/// Write each band of a split output as `<name>.part<N>.png`, an indexed PNG with at
+/// most 256 colors, and a JSON manifest to `output_path` listing which tile rows each
+/// file holds.
+pub fn save_split_indexed_pngs(
+    output_path: &str,
+    parts: &[SplitPart],
+    width: u32,
+    tile_size: (u32, u32),
+    options: ExportOptions,
+) -> Result<(), String> {
+    if parts.is_empty() {
+        return Err("Nothing to export: the output was not split".to_string());
+    }
+    let path = Path::new(output_path);
+    for (index, part) in parts.iter().enumerate() {
+        save_indexed_png(
+            &split_part_path(path, index).to_string_lossy(),
+            &part.indexed_pixels,
+            &palette_for_export(&part.palettes, options.alpha_mode),
+            width,
+            part.height,
+            options,
+        )?;
+    }
+
+    let manifest = build_split_manifest(path, parts, width, tile_size);
+    let json = serde_json::to_string_pretty(&manifest)
+        .map_err(|e| format!("Failed to serialize manifest: {e}"))?;
+    std::fs::write(output_path, json).map_err(|e| format!("File write error: {e}"))?;
+
+    log::info!(
+        "Split export wrote {} parts with manifest {output_path}",
+        parts.len()
+    );
+    Ok(())
+}
+
+/// Lay out the index of every pixel as comma-separated rows after a
+/// `# width=.. height=..` header. With `split_palette`, each cell reads `palette:color`.
+fn format_index_csv(
+    indexed_pixel_data: &[u8],
+    palette_sizes: &[usize],
+    width: u32,
+    height: u32,
+    split_palette: bool,
+) -> Result<String, String> {
+    let (width, height) = (width as usize, height as usize);
+    if indexed_pixel_data.len() != width * height {
+        return Err("Indexed data does not match the image size".to_string());
+    }
+
+    // Global index -> (palette, color within palette)
+    let components: Vec<(usize, usize)> = palette_sizes
+        .iter()
+        .enumerate()
+        .flat_map(|(palette, &size)| (0..size).map(move |color| (palette, color)))
+        .collect();
+
+    let mut csv = format!(
+        "# width={width} height={height} cells={}\n",
+        if split_palette {
+            "palette:color"
+        } else {
+            "index"
+        }
+    );
+    for row in indexed_pixel_data.chunks_exact(width.max(1)) {
+        let cells: Vec<String> = row
+            .iter()
+            .map(|&index| {
+                if !split_palette {
+                    return Ok(index.to_string());
+                }
+                components
+                    .get(index as usize)
+                    .map(|(palette, color)| format!("{palette}:{color}"))
+                    .ok_or_else(|| format!("Color index {index} is outside every palette"))
+            })
+            .collect::<Result<_, String>>()?;
+        csv.push_str(&cells.join(","));
+        csv.push('\n');
+    }
+    Ok(csv)
+}
+
+pub fn save_index_csv(
+    output_path: &str,
+    indexed_pixel_data: &[u8],
+    palette_sizes: &[usize],
+    width: u32,
+    height: u32,
+    split_palette: bool,
+) -> Result<(), String> {
+    let csv = format_index_csv(
+        indexed_pixel_data,
+        palette_sizes,
+        width,
+        height,
+        split_palette,
+    )?;
+    std::fs::write(output_path, csv).map_err(|e| format!("File write error: {e}"))?;
+
+    log::info!("Index CSV exported to: {output_path}");
+    Ok(())
+}
+
+/// Grow an indexed image to `padded_width`×`padded_height`, keeping the content at the
+/// top-left and filling the new area with index 0 (the clear color when the first color
+/// is transparent)
+pub fn pad_indexed_pixels(
+    indexed_pixel_data: &[u8],
+    width: u32,
+    height: u32,
+    padded_width: u32,
+    padded_height: u32,
+) -> Vec<u8> {
+    let (width, height) = (width as usize, height as usize);
+    let (padded_width, padded_height) = (padded_width as usize, padded_height as usize);
+    let mut padded = vec![0u8; padded_width * padded_height];
+    for (y, row) in indexed_pixel_data
+        .chunks_exact(width.max(1))
+        .take(height.min(padded_height))
+        .enumerate()
+    {
+        let len = width.min(padded_width);
+        padded[y * padded_width..y * padded_width + len].copy_from_slice(&row[..len]);
+    }
+    padded
+}
+
+/// One rendered variation of a contact sheet
+pub struct ContactSheetCell {
+    pub rgba: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+    pub caption: String,
+}
+
+const CONTACT_SHEET_GAP: u32 = 4;
+const CONTACT_SHEET_BACKGROUND: [u8; 4] = [32, 32, 32, 255];
+const CAPTION_COLOR: [u8; 4] = [255, 255, 255, 255];
+/// Caption glyphs are 5×7 pixels, advanced by one blank column
+const GLYPH_WIDTH: u32 = 5;
+const GLYPH_HEIGHT: u32 = 7;
+const GLYPH_ADVANCE: u32 = GLYPH_WIDTH + 1;
+
+/// Rows of a 5×7 caption glyph, most significant of the low 5 bits on the left.
+/// Letters are drawn uppercase; characters without a glyph become `?`.
+fn caption_glyph(c: char) -> [u8; 7] {
+    match c.to_ascii_uppercase() {
+        '0' => [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E],
+        '1' => [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        '2' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F],
+        '3' => [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E],
+        '4' => [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02],
+        '5' => [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E],
+        '6' => [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E],
+        '7' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E],
+        '9' => [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C],
+        'A' => [0x0E, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'B' => [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E],
+        'C' => [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E],
+        'D' => [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C],
+        'E' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F],
+        'F' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10],
+        'G' => [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F],
+        'H' => [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'I' => [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        'J' => [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C],
+        'K' => [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+        'L' => [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F],
+        'M' => [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11],
+        'N' => [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+        'O' => [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'P' => [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10],
+        'Q' => [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D],
+        'R' => [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11],
+        'S' => [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E],
+        'T' => [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+        'U' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'V' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04],
+        'W' => [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A],
+        'X' => [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11],
+        'Y' => [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04],
+        'Z' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F],
+        ' ' => [0x00; 7],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C],
+        ':' => [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00],
+        '-' => [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00],
+        '+' => [0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00],
+        '/' => [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00],
+        '×' => [0x00, 0x00, 0x11, 0x0A, 0x04, 0x0A, 0x11],
+        _ => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04],
+    }
+}
+
+fn caption_width(caption: &str) -> u32 {
+    (caption.chars().count() as u32 * GLYPH_ADVANCE).saturating_sub(1)
+}
+
+/// Draw `caption` into an RGBA image with its top-left corner at (`x`, `y`),
+/// clipping at the image edges
+fn draw_caption(rgba: &mut [u8], image_width: u32, x: u32, y: u32, caption: &str) {
+    let image_height = rgba.len() as u32 / 4 / image_width.max(1);
+    for (i, c) in caption.chars().enumerate() {
+        let glyph_x = x + i as u32 * GLYPH_ADVANCE;
+        for (row, bits) in caption_glyph(c).into_iter().enumerate() {
+            for column in 0..GLYPH_WIDTH {
+                let (px, py) = (glyph_x + column, y + row as u32);
+                if bits & (0x10 >> column) == 0 || px >= image_width || py >= image_height {
+                    continue;
+                }
+                let offset = ((py * image_width + px) * 4) as usize;
+                rgba[offset..offset + 4].copy_from_slice(&CAPTION_COLOR);
+            }
+        }
+    }
+}
+
+/// Tile the cells into a near-square grid with each caption under its image.
+/// Returns the RGBA pixels and size of the sheet.
+pub fn compose_contact_sheet(cells: &[ContactSheetCell]) -> (Vec<u8>, u32, u32) {
+    let columns = (cells.len() as f64).sqrt().ceil().max(1.0) as u32;
+    let rows = (cells.len() as u32).div_ceil(columns).max(1);
+    let cell_width = cells
+        .iter()
+        .map(|cell| cell.width.max(caption_width(&cell.caption)))
+        .max()
+        .unwrap_or(0);
+    let image_height = cells.iter().map(|cell| cell.height).max().unwrap_or(0);
+    let cell_height = image_height + CONTACT_SHEET_GAP + GLYPH_HEIGHT;
+
+    let width = columns * (cell_width + CONTACT_SHEET_GAP) + CONTACT_SHEET_GAP;
+    let height = rows * (cell_height + CONTACT_SHEET_GAP) + CONTACT_SHEET_GAP;
+    let mut rgba = CONTACT_SHEET_BACKGROUND.repeat((width * height) as usize);
+
+    for (i, cell) in cells.iter().enumerate() {
+        let cell_x = CONTACT_SHEET_GAP + (i as u32 % columns) * (cell_width + CONTACT_SHEET_GAP);
+        let cell_y = CONTACT_SHEET_GAP + (i as u32 / columns) * (cell_height + CONTACT_SHEET_GAP);
+        let row_len = (cell.width * 4) as usize;
+        for (y, row) in cell.rgba.chunks_exact(row_len.max(1)).enumerate() {
+            let offset = (((cell_y + y as u32) * width + cell_x) * 4) as usize;
+            rgba[offset..offset + row_len].copy_from_slice(row);
+        }
+        draw_caption(
+            &mut rgba,
+            width,
+            cell_x,
+            cell_y + image_height + CONTACT_SHEET_GAP,
+            &cell.caption,
+        );
+    }
+    (rgba, width, height)
+}
+
+/// Mirror an image in place: `flip_x` reverses each row, `flip_y` the row order.
+/// Works on indexed (`channels` 1) and RGBA (4) data of any sample type.
+pub fn flip_pixels<T>(pixels: &mut [T], width: u32, channels: usize, flip_x: bool, flip_y: bool) {
+    let row_len = width as usize * channels;
+    if row_len == 0 {
+        return;
+    }
+    if flip_x {
+        for row in pixels.chunks_exact_mut(row_len) {
+            row.reverse();
+            // Reversing the row also reversed the channels inside each pixel
+            for pixel in row.chunks_exact_mut(channels) {
+                pixel.reverse();
+            }
+        }
+    }
+    if flip_y {
+        let rows = pixels.len() / row_len;
+        for y in 0..rows / 2 {
+            let (top, bottom) = pixels.split_at_mut((rows - 1 - y) * row_len);
+            top[y * row_len..(y + 1) * row_len].swap_with_slice(&mut bottom[..row_len]);
+        }
+    }
+}
+
+/// Tile reduce's per-tile orientations after the same export flip (of each
+/// `part_height`-pixel part) and padding as the pixels, so they still describe the
+/// exported tiles. `None` when the parts or the padded size don't split into whole tiles.
+pub fn export_tile_flips(
+    tile_flips: &[TileFlip],
+    width: u32,
+    part_height: u32,
+    tile_size: (u32, u32),
+    padded_size: (u32, u32),
+    flip_x: bool,
+    flip_y: bool,
+) -> Option<Vec<TileFlip>> {
+    let (tile_width, tile_height) = tile_size;
+    let (padded_width, padded_height) = padded_size;
+    if tile_width == 0
+        || tile_height == 0
+        || !width.is_multiple_of(tile_width)
+        || !part_height.is_multiple_of(tile_height)
+        || !padded_width.is_multiple_of(tile_width)
+        || !padded_height.is_multiple_of(tile_height)
+    {
+        return None;
+    }
+    let tiles_x = (width / tile_width) as usize;
+    let part_len = tiles_x * (part_height / tile_height) as usize;
+    if part_len == 0 || !tile_flips.len().is_multiple_of(part_len) {
+        return None;
+    }
+
+    let mut flips = tile_flips.to_vec();
+    for part in flips.chunks_mut(part_len) {
+        flip_pixels(part, tiles_x as u32, 1, flip_x, flip_y);
+    }
+    // Mirroring the image also mirrors every tile
+    for flip in &mut flips {
+        flip.flip_x ^= flip_x;
+        flip.flip_y ^= flip_y;
+    }
+
+    // Padding tiles are blank, so their orientation doesn't matter
+    let padded_tiles_x = (padded_width / tile_width) as usize;
+    let padded_tiles_y = (padded_height / tile_height) as usize;
+    let mut padded = vec![TileFlip::default(); padded_tiles_x * padded_tiles_y];
+    let len = tiles_x.min(padded_tiles_x);
+    for (y, row) in flips.chunks_exact(tiles_x).take(padded_tiles_y).enumerate() {
+        padded[y * padded_tiles_x..y * padded_tiles_x + len].copy_from_slice(&row[..len]);
+    }
+    Some(padded)
+}
+
+/// Re-lay the `cell_width`×`cell_height` cells of an indexed image with `gutter` pixels
+/// of index 0 around every cell, so filtered GPU sampling of one cell never picks up its
+/// neighbours. Returns the new pixels and size.
+pub fn add_cell_gutters(
+    indexed_pixel_data: &[u8],
+    width: u32,
+    height: u32,
+    cell_width: u32,
+    cell_height: u32,
+    gutter: u32,
+) -> Result<(Vec<u8>, u32, u32), String> {
+    if cell_width == 0
+        || cell_height == 0
+        || !width.is_multiple_of(cell_width)
+        || !height.is_multiple_of(cell_height)
+    {
+        return Err(format!(
+            "Image size ({width}×{height}) is not divisible by cell size ({cell_width}×{cell_height})"
+        ));
+    }
+    let (cells_x, cells_y) = (width / cell_width, height / cell_height);
+    let out_width = cells_x * cell_width + (cells_x + 1) * gutter;
+    let out_height = cells_y * cell_height + (cells_y + 1) * gutter;
+
+    let (stride, out_stride) = (width as usize, out_width as usize);
+    let mut out = vec![0u8; out_stride * out_height as usize];
+    for (y, row) in indexed_pixel_data
+        .chunks_exact(stride)
+        .take(height as usize)
+        .enumerate()
+    {
+        let out_y = (y as u32 + (y as u32 / cell_height + 1) * gutter) as usize;
+        for (cell_x, cell_row) in row.chunks_exact(cell_width as usize).enumerate() {
+            let out_x = (cell_x as u32 * (cell_width + gutter) + gutter) as usize;
+            let start = out_y * out_stride + out_x;
+            out[start..start + cell_row.len()].copy_from_slice(cell_row);
+        }
+    }
+    Ok((out, out_width, out_height))
+}
+
+/// Write `<output>.padding.txt` next to a padded export so the content can be cropped back
+pub fn save_padding_info(
+    output_path: &str,
+    width: u32,
+    height: u32,
+    padded_width: u32,
+    padded_height: u32,
+) -> Result<(), String> {
+    let info_path = format!("{output_path}.padding.txt");
+    let info = format!(
+        "# content is anchored at the top-left\nwidth={width}\nheight={height}\npadded_width={padded_width}\npadded_height={padded_height}\n"
+    );
+    std::fs::write(&info_path, info).map_err(|e| format!("File write error: {e}"))?;
+
+    log::info!("Padding info written to: {info_path}");
+    Ok(())
+}
+
+/// Every palette block concatenated into one 256-entry JASC-PAL color table.
+///
+/// Blocks are packed back-to-back in palette order, so palette `p` color `c` sits at
+/// index `sum(sizes[..p]) + c`: the same global index the exported images store.
+/// Entries after the last block are black. JASC-PAL has no alpha, so it is dropped.
+fn format_combined_palette(palette_data: &[BGRA8]) -> String {
+    let mut pal = String::from("JASC-PAL\n0100\n256\n");
+    for index in 0..256 {
+        let color = palette_data.get(index).copied().unwrap_or(BGRA8 {
+            b: 0,
+            g: 0,
+            r: 0,
+            a: 0,
+        });
+        pal.push_str(&format!("{} {} {}\n", color.r, color.g, color.b));
+    }
+    pal
+}
+
+pub fn save_combined_palette(output_path: &str, palette_data: &[BGRA8]) -> Result<(), String> {
+    if palette_data.len() > 256 {
+        return Err(format!(
+            "Palettes hold {} colors, more than the 256 a color table can store",
+            palette_data.len()
+        ));
+    }
+    std::fs::write(output_path, format_combined_palette(palette_data))
+        .map_err(|e| format!("File write error: {e}"))?;
+
+    log::info!("Combined palette exported to: {output_path}");
+    Ok(())
+}
+
+/// The bytes of `color` in `order`
+pub fn order_channels(color: BGRA8, order: ChannelOrder) -> [u8; 4] {
+    let BGRA8 { b, g, r, a } = color;
+    match order {
+        ChannelOrder::Rgba => [r, g, b, a],
+        ChannelOrder::Bgra => [b, g, r, a],
+        ChannelOrder::Argb => [a, r, g, b],
+    }
+}
+
+fn encode_raw_palette(palette_data: &[BGRA8], order: ChannelOrder) -> Vec<u8> {
+    palette_data
+        .iter()
+        .flat_map(|&color| order_channels(color, order))
+        .collect()
+}
+
+/// Every palette color as 4 bytes in `order`, back to back with no header
+pub fn save_raw_palette(
+    output_path: &str,
+    palette_data: &[BGRA8],
+    order: ChannelOrder,
+) -> Result<(), String> {
+    std::fs::write(output_path, encode_raw_palette(palette_data, order))
+        .map_err(|e| format!("File write error: {e}"))?;
+
+    log::info!(
+        "Raw {} palette exported to: {output_path}",
+        order.display_name()
+    );
+    Ok(())
+}
+
+/// Encode one full palette cycle as a looping GIF, one frame per step
+fn encode_palette_cycle_gif(
+    indexed_pixel_data: &[u8],
+    palette_data: &[BGRA8],
+    width: u32,
+    height: u32,
+    cycle: &PaletteCycle,
+) -> Result<Vec<u8>, String> {
+    use image::codecs::gif::{GifEncoder, Repeat};
+    use image::{Delay, Frame, RgbaImage};
+
+    let frame_count = cycle.frame_count(palette_data.len());
+    let delay = Delay::from_numer_denom_ms(cycle.frame_delay_ms(), 1);
+    let mut frames = Vec::with_capacity(frame_count);
+    for step in 0..frame_count {
+        let palette = cycle.cycled_palette(palette_data, step);
+        let rgba: Vec<u8> = indexed_pixel_data
+            .iter()
+            .flat_map(|&index| {
+                palette
+                    .get(index as usize)
+                    .map_or([0, 0, 0, 255], |color| color.to_rgba())
+            })
+            .collect();
+        let image = RgbaImage::from_raw(width, height, rgba)
+            .ok_or_else(|| "Failed to create image buffer from indexed data".to_string())?;
+        frames.push(Frame::from_parts(image, 0, 0, delay));
+    }
+
+    let mut gif_data = Vec::new();
+    {
+        let mut encoder = GifEncoder::new(&mut gif_data);
+        encoder
+            .set_repeat(Repeat::Infinite)
+            .map_err(|e| format!("GIF encode error: {e}"))?;
+        encoder
+            .encode_frames(frames)
+            .map_err(|e| format!("GIF encode error: {e}"))?;
+    }
+    Ok(gif_data)
+}
+
+pub fn save_palette_cycle_gif(
+    output_path: &str,
+    indexed_pixel_data: &[u8],
+    palette_data: &[BGRA8],
+    width: u32,
+    height: u32,
+    cycle: &PaletteCycle,
+) -> Result<(), String> {
+    if cycle.range(palette_data.len()).is_none() {
+        return Err(format!(
+            "Palette cycle range {}-{} is not within the {} palette colors",
+            cycle.first_index,
+            cycle.last_index,
+            palette_data.len()
+        ));
+    }
+    let gif_data =
+        encode_palette_cycle_gif(indexed_pixel_data, palette_data, width, height, cycle)?;
+    std::fs::write(output_path, gif_data).map_err(|e| format!("File write error: {e}"))?;
+
+    log::info!("Palette cycle GIF exported to: {output_path}");
+    Ok(())
+}
+
+fn premultiply_channel(value: u8, alpha: u8) -> u8 {
+    ((value as u32 * alpha as u32 + 127) / 255) as u8
+}
+
+pub(crate) fn unpremultiply_channel(value: u8, alpha: u8) -> u8 {
+    if alpha == 0 {
+        return value;
+    }
+    ((value as u32 * 255 + alpha as u32 / 2) / alpha as u32).min(255) as u8
+}
+
+/// Multiply straight-alpha colors by their alpha
+pub fn premultiply_palette(palette_data: &[BGRA8]) -> Vec<BGRA8> {
+    palette_data
+        .iter()
+        .map(|c| BGRA8 {
+            b: premultiply_channel(c.b, c.a),
+            g: premultiply_channel(c.g, c.a),
+            r: premultiply_channel(c.r, c.a),
+            a: c.a,
+        })
+        .collect()
+}
+
+/// Convert the app's straight-alpha palette to the convention stored in exports
+pub fn palette_for_export(palette_data: &[BGRA8], alpha_mode: ExportAlphaMode) -> Vec<BGRA8> {
+    match alpha_mode {
+        ExportAlphaMode::Straight => palette_data.to_vec(),
+        ExportAlphaMode::Premultiplied => premultiply_palette(palette_data),
+    }
+}
+
+/// Tag the PNG as sRGB when requested. Pixel values are written unchanged either way.
+fn apply_color_profile<W: std::io::Write>(
+    encoder: &mut png::Encoder<W>,
+    color_profile: ExportColorProfile,
+) {
+    match color_profile {
+        ExportColorProfile::Untagged => {}
+        ExportColorProfile::Srgb => {
+            encoder.set_source_srgb(png::SrgbRenderingIntent::Perceptual);
+        }
+    }
+}
+
+/// Pixels per meter for `dpi`, the unit both PNG `pHYs` and BMP headers store
+fn dots_per_meter(dpi: u32) -> u32 {
+    (dpi as f64 / 0.0254).round() as u32
+}
+
+/// Write a `pHYs` chunk when a DPI is set; without one, viewers assume their default
+fn apply_dpi<W: std::io::Write>(encoder: &mut png::Encoder<W>, dpi: Option<u32>) {
+    encoder.set_pixel_dims(dpi.map(|dpi| png::PixelDimensions {
+        xppu: dots_per_meter(dpi),
+        yppu: dots_per_meter(dpi),
+        unit: png::Unit::Meter,
+    }));
+}
+
+/// 16 bits per channel RGBA PNG, for handing the graded image to other tools
+/// without rounding it to 8 bits
+pub fn save_rgba16_png(
+    output_path: &str,
+    rgba16_data: &[u16],
+    width: u32,
+    height: u32,
+    options: ExportOptions,
+) -> Result<(), String> {
+    let file = std::fs::File::create(output_path)
+        .map_err(|e| format!("Failed to create output file: {e}"))?;
+    let mut encoder = png::Encoder::new(std::io::BufWriter::new(file), width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Sixteen);
+    apply_color_profile(&mut encoder, options.color_profile);
+    apply_dpi(&mut encoder, options.dpi);
+
+    // PNG stores 16-bit samples big-endian
+    let bytes: Vec<u8> = rgba16_data
+        .iter()
+        .flat_map(|sample| sample.to_be_bytes())
+        .collect();
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(&bytes))
+        .map_err(|e| format!("PNG save error: {e}"))?;
+
+    log::info!("16-bit PNG exported to: {output_path}");
+    Ok(())
+}
+
+/// Render palettes as RGBA swatches, one row per palette.
+/// Shorter palettes are padded with transparent pixels.
+fn render_palette_strip(
+    palette_data: &[BGRA8],
+    palette_sizes: &[usize],
+    swatch_size: u32,
+) -> (Vec<u8>, u32, u32) {
+    let swatch = swatch_size.max(1) as usize;
+    let columns = palette_sizes.iter().copied().max().unwrap_or(0);
+    let width = columns * swatch;
+    let height = palette_sizes.len() * swatch;
+    let mut rgba = vec![0u8; width * height * 4];
+
+    let mut palette_start = 0;
+    for (row, &size) in palette_sizes.iter().enumerate() {
+        for column in 0..size {
+            let Some(color) = palette_data.get(palette_start + column) else {
+                break;
+            };
+            for y in row * swatch..(row + 1) * swatch {
+                for x in column * swatch..(column + 1) * swatch {
+                    let i = (y * width + x) * 4;
+                    rgba[i..i + 4].copy_from_slice(&color.to_rgba());
+                }
+            }
+        }
+        palette_start += size;
+    }
+
+    (rgba, width as u32, height as u32)
+}
+
+pub fn save_palette_strip_png(
+    output_path: &str,
+    palette_data: &[BGRA8],
+    palette_sizes: &[usize],
+    swatch_size: u32,
+    color_profile: ExportColorProfile,
+) -> Result<(), String> {
+    let (rgba, width, height) = render_palette_strip(palette_data, palette_sizes, swatch_size);
+    if width == 0 || height == 0 {
+        return Err("No palette colors to export".to_string());
+    }
+
+    let file = std::fs::File::create(output_path)
+        .map_err(|e| format!("Failed to create output file: {e}"))?;
+    let mut encoder = png::Encoder::new(std::io::BufWriter::new(file), width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    apply_color_profile(&mut encoder, color_profile);
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(&rgba))
+        .map_err(|e| format!("PNG save error: {e}"))?;
+
+    log::info!("Palette strip exported to: {output_path}");
+    Ok(())
+}
 
 pub fn save_indexed_png(
     output_path: &str,
@@ -6,18 +1114,56 @@ pub fn save_indexed_png(
     palette_data: &[BGRA8],
     width: u32,
     height: u32,
+    options: ExportOptions,
 ) -> Result<(), String> {
     use std::fs::File;
     use std::io::BufWriter;
 
-    // Create PNG encoder
     let file =
         File::create(output_path).map_err(|e| format!("Failed to create output file: {e}"))?;
-    let w = BufWriter::new(file);
+    write_indexed_png(
+        BufWriter::new(file),
+        indexed_pixel_data,
+        palette_data,
+        width,
+        height,
+        options,
+    )
+}
 
+fn write_indexed_png<W: std::io::Write>(
+    w: W,
+    indexed_pixel_data: &[u8],
+    palette_data: &[BGRA8],
+    width: u32,
+    height: u32,
+    options: ExportOptions,
+) -> Result<(), String> {
+    let encoder = indexed_png_encoder(w, palette_data, width, height, options);
+    let mut writer = encoder
+        .write_header()
+        .map_err(|e| format!("Failed to write PNG header: {e}"))?;
+
+    writer
+        .write_image_data(indexed_pixel_data)
+        .map_err(|e| format!("Failed to write PNG image data: {e}"))?;
+
+    Ok(())
+}
+
+/// 8-bit indexed PNG encoder with the palette and its alpha as tRNS
+fn indexed_png_encoder<'a, W: std::io::Write>(
+    w: W,
+    palette_data: &[BGRA8],
+    width: u32,
+    height: u32,
+    options: ExportOptions,
+) -> png::Encoder<'a, W> {
     let mut encoder = png::Encoder::new(w, width, height);
     encoder.set_color(png::ColorType::Indexed);
     encoder.set_depth(png::BitDepth::Eight);
+    apply_color_profile(&mut encoder, options.color_profile);
+    apply_dpi(&mut encoder, options.dpi);
 
     // Convert palette to PNG format (RGB)
     let png_palette: Vec<u8> = palette_data
@@ -31,24 +1177,246 @@ pub fn save_indexed_png(
 
     encoder.set_palette(png_palette);
     encoder.set_trns(transparency);
+    encoder
+}
+
+/// Indexed APNG of the frames stacked in `indexed_pixel_data`, `frame_height` rows each,
+/// all using the one palette. Frames replace each other (dispose none, blend source);
+/// rows below the last whole frame, e.g. export padding, are dropped.
+pub fn save_indexed_apng(
+    output_path: &str,
+    indexed_pixel_data: &[u8],
+    palette_data: &[BGRA8],
+    width: u32,
+    frame_height: u32,
+    delays_ms: &[u32],
+    options: ExportOptions,
+) -> Result<(), String> {
+    let file = std::fs::File::create(output_path)
+        .map_err(|e| format!("Failed to create output file: {e}"))?;
+    write_indexed_apng(
+        std::io::BufWriter::new(file),
+        indexed_pixel_data,
+        palette_data,
+        width,
+        frame_height,
+        delays_ms,
+        options,
+    )?;
 
+    log::info!(
+        "Indexed APNG exported to {output_path} with {} frames",
+        delays_ms.len()
+    );
+    Ok(())
+}
+
+fn write_indexed_apng<W: std::io::Write>(
+    w: W,
+    indexed_pixel_data: &[u8],
+    palette_data: &[BGRA8],
+    width: u32,
+    frame_height: u32,
+    delays_ms: &[u32],
+    options: ExportOptions,
+) -> Result<(), String> {
+    let frame_len = (width * frame_height) as usize;
+    if delays_ms.is_empty() || indexed_pixel_data.len() < frame_len * delays_ms.len() {
+        return Err("Image does not hold every animation frame".to_string());
+    }
+
+    let mut encoder = indexed_png_encoder(w, palette_data, width, frame_height, options);
+    encoder
+        .set_animated(delays_ms.len() as u32, 0)
+        .map_err(|e| format!("Failed to set up APNG animation: {e}"))?;
     let mut writer = encoder
         .write_header()
         .map_err(|e| format!("Failed to write PNG header: {e}"))?;
 
+    for (frame, &delay_ms) in indexed_pixel_data.chunks_exact(frame_len).zip(delays_ms) {
+        writer
+            .set_frame_delay(delay_ms.min(u16::MAX as u32) as u16, 1000)
+            .map_err(|e| format!("Failed to set APNG frame delay: {e}"))?;
+        writer
+            .write_image_data(frame)
+            .map_err(|e| format!("Failed to write APNG frame: {e}"))?;
+    }
     writer
-        .write_image_data(indexed_pixel_data)
-        .map_err(|e| format!("Failed to write PNG image data: {e}"))?;
+        .finish()
+        .map_err(|e| format!("Failed to finish APNG: {e}"))
+}
 
-    Ok(())
+/// Counts the bytes written to it, to size an encoding without keeping it
+#[derive(Default)]
+struct ByteCounter(u64);
+
+impl std::io::Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0 += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Bytes `encode_indexed_bmp` writes: headers, a 256-entry palette and 4-byte aligned rows
+pub fn indexed_bmp_file_size(width: u32, height: u32) -> u64 {
+    54 + 256 * 4 + width.div_ceil(4) as u64 * 4 * height as u64
+}
+
+/// Bytes `encode_indexed_tga` writes
+pub fn indexed_tga_file_size(width: u32, height: u32, palette_len: usize) -> u64 {
+    18 + palette_len.clamp(1, 256) as u64 * 4 + width as u64 * height as u64
 }
+
+/// Size of the file an indexed export would write: exact for BMP and TGA, and for PNG
+/// the length of an encode into a byte counter. `None` for the other formats.
+pub fn estimate_indexed_export_size(
+    format: &ExportFormat,
+    indexed_pixel_data: &[u8],
+    palette_data: &[BGRA8],
+    width: u32,
+    height: u32,
+    options: ExportOptions,
+) -> Option<u64> {
+    match format {
+        ExportFormat::Bmp => Some(indexed_bmp_file_size(width, height)),
+        ExportFormat::Tga => Some(indexed_tga_file_size(width, height, palette_data.len())),
+        ExportFormat::PngIndexed => {
+            let mut counter = ByteCounter::default();
+            write_indexed_png(
+                &mut counter,
+                indexed_pixel_data,
+                palette_data,
+                width,
+                height,
+                options,
+            )
+            .ok()?;
+            Some(counter.0)
+        }
+        _ => None,
+    }
+}
+
+/// "1.2 KB"-style size for display
+pub fn format_file_size(bytes: u64) -> String {
+    match bytes {
+        0..1024 => format!("{bytes} B"),
+        1024..1_048_576 => format!("{:.1} KB", bytes as f64 / 1024.0),
+        _ => format!("{:.2} MB", bytes as f64 / 1_048_576.0),
+    }
+}
+
 pub fn save_indexed_bmp(
     output_path: &str,
     indexed_pixel_data: &[u8],
     palette_data: &[BGRA8],
     width: u32,
     height: u32,
+    row_order: RowOrder,
+    options: ExportOptions,
 ) -> Result<(), String> {
+    let bmp_data = encode_indexed_bmp(
+        indexed_pixel_data,
+        palette_data,
+        width,
+        height,
+        row_order,
+        options,
+    );
+    std::fs::write(output_path, bmp_data).map_err(|e| format!("File write error: {e}"))?;
+
+    Ok(())
+}
+
+pub fn save_indexed_tga(
+    output_path: &str,
+    indexed_pixel_data: &[u8],
+    palette_data: &[BGRA8],
+    width: u32,
+    height: u32,
+    row_order: RowOrder,
+) -> Result<(), String> {
+    let tga_data = encode_indexed_tga(indexed_pixel_data, palette_data, width, height, row_order)?;
+    std::fs::write(output_path, tga_data).map_err(|e| format!("File write error: {e}"))?;
+
+    log::info!("Indexed TGA exported to: {output_path}");
+    Ok(())
+}
+
+/// 8-bit color-mapped TGA (image type 1) with a 32-bit BGRA color map.
+/// Bit 5 of the image descriptor marks a top-left origin for top-down rows.
+fn encode_indexed_tga(
+    indexed_pixel_data: &[u8],
+    palette_data: &[BGRA8],
+    width: u32,
+    height: u32,
+    row_order: RowOrder,
+) -> Result<Vec<u8>, String> {
+    const TOP_LEFT_ORIGIN: u8 = 0x20;
+    const ALPHA_BITS: u8 = 8;
+
+    let (Ok(tga_width), Ok(tga_height)) = (u16::try_from(width), u16::try_from(height)) else {
+        return Err(format!(
+            "TGA images are at most 65535×65535, got {width}×{height}"
+        ));
+    };
+    let palette_size = palette_data.len().clamp(1, 256) as u16;
+
+    let mut tga_data =
+        Vec::with_capacity(18 + palette_size as usize * 4 + (width * height) as usize);
+    tga_data.push(0); // ID length
+    tga_data.push(1); // Color map present
+    tga_data.push(1); // Uncompressed color-mapped image
+    tga_data.extend_from_slice(&0u16.to_le_bytes()); // First color map entry
+    tga_data.extend_from_slice(&palette_size.to_le_bytes());
+    tga_data.push(32); // Bits per color map entry
+    tga_data.extend_from_slice(&0u16.to_le_bytes()); // X origin
+    tga_data.extend_from_slice(&0u16.to_le_bytes()); // Y origin
+    tga_data.extend_from_slice(&tga_width.to_le_bytes());
+    tga_data.extend_from_slice(&tga_height.to_le_bytes());
+    tga_data.push(8); // Bits per pixel
+    tga_data.push(match row_order {
+        RowOrder::BottomUp => ALPHA_BITS,
+        RowOrder::TopDown => ALPHA_BITS | TOP_LEFT_ORIGIN,
+    });
+
+    for i in 0..palette_size as usize {
+        let color = palette_data.get(i).copied().unwrap_or(BGRA8 {
+            b: 0,
+            g: 0,
+            r: 0,
+            a: 255,
+        });
+        tga_data.extend_from_slice(&[color.b, color.g, color.r, color.a]);
+    }
+
+    let width = width as usize;
+    let height = height as usize;
+    for row in 0..height {
+        let y = match row_order {
+            RowOrder::BottomUp => height - 1 - row,
+            RowOrder::TopDown => row,
+        };
+        for x in 0..width {
+            tga_data.push(indexed_pixel_data.get(y * width + x).copied().unwrap_or(0));
+        }
+    }
+
+    Ok(tga_data)
+}
+
+fn encode_indexed_bmp(
+    indexed_pixel_data: &[u8],
+    palette_data: &[BGRA8],
+    width: u32,
+    height: u32,
+    row_order: RowOrder,
+    options: ExportOptions,
+) -> Vec<u8> {
     // Create 8-bit indexed BMP with palette (always 256 entries)
     let palette_size = palette_data.len().min(256); // Max 256 colors for 8-bit
     let row_size = width.div_ceil(4) * 4; // 4-byte aligned for 8-bit data
@@ -68,13 +1436,19 @@ pub fn save_indexed_bmp(
     // BMP Info Header (40 bytes)
     bmp_data.extend_from_slice(&40u32.to_le_bytes()); // Header size
     bmp_data.extend_from_slice(&(width as i32).to_le_bytes()); // Width
-    bmp_data.extend_from_slice(&(height as i32).to_le_bytes()); // Height
+    // Height (negative for top-down rows)
+    let signed_height = match row_order {
+        RowOrder::BottomUp => height as i32,
+        RowOrder::TopDown => -(height as i32),
+    };
+    bmp_data.extend_from_slice(&signed_height.to_le_bytes());
     bmp_data.extend_from_slice(&1u16.to_le_bytes()); // Planes
     bmp_data.extend_from_slice(&8u16.to_le_bytes()); // Bits per pixel (8-bit indexed)
     bmp_data.extend_from_slice(&0u32.to_le_bytes()); // Compression
     bmp_data.extend_from_slice(&image_size.to_le_bytes()); // Image size
-    bmp_data.extend_from_slice(&0u32.to_le_bytes()); // X pixels per meter
-    bmp_data.extend_from_slice(&0u32.to_le_bytes()); // Y pixels per meter
+    let pixels_per_meter = options.dpi.map_or(0, dots_per_meter);
+    bmp_data.extend_from_slice(&pixels_per_meter.to_le_bytes()); // X pixels per meter
+    bmp_data.extend_from_slice(&pixels_per_meter.to_le_bytes()); // Y pixels per meter
     bmp_data.extend_from_slice(&256u32.to_le_bytes()); // Colors used (always 256 for 8-bit)
     bmp_data.extend_from_slice(&0u32.to_le_bytes()); // Important colors
 
@@ -91,8 +1465,12 @@ pub fn save_indexed_bmp(
         bmp_data.extend_from_slice(&[0, 0, 0, 0]);
     }
 
-    // Image data (bottom-up, 8-bit indexed)
-    for y in (0..height).rev() {
+    // Image data (8-bit indexed)
+    for row in 0..height {
+        let y = match row_order {
+            RowOrder::BottomUp => height - 1 - row,
+            RowOrder::TopDown => row,
+        };
         for x in 0..width {
             let pixel_idx = (y * width + x) as usize;
             if pixel_idx < indexed_pixel_data.len() {
@@ -106,9 +1484,7 @@ pub fn save_indexed_bmp(
         bmp_data.extend(std::iter::repeat_n(0, padding));
     }
 
-    std::fs::write(output_path, bmp_data).map_err(|e| format!("File write error: {e}"))?;
-
-    Ok(())
+    bmp_data
 }
 
 pub fn save_rgba_image(
@@ -117,6 +1493,7 @@ pub fn save_rgba_image(
     width: u32,
     height: u32,
     export_format: crate::types::ExportFormat,
+    options: ExportOptions,
 ) -> Result<(), String> {
     use image::{ImageBuffer, Rgba};
 
@@ -132,9 +1509,17 @@ pub fn save_rgba_image(
                     .to_string(),
             );
         }
-        crate::types::ExportFormat::Png => {
-            dynamic_img
-                .save_with_format(output_path, image::ImageFormat::Png)
+        crate::types::ExportFormat::Png | crate::types::ExportFormat::MaskedPng => {
+            let file = std::fs::File::create(output_path)
+                .map_err(|e| format!("Failed to create output file: {e}"))?;
+            let mut encoder = png::Encoder::new(std::io::BufWriter::new(file), width, height);
+            encoder.set_color(png::ColorType::Rgba);
+            encoder.set_depth(png::BitDepth::Eight);
+            apply_color_profile(&mut encoder, options.color_profile);
+            apply_dpi(&mut encoder, options.dpi);
+            encoder
+                .write_header()
+                .and_then(|mut writer| writer.write_image_data(rgba_data))
                 .map_err(|e| format!("PNG save error: {e}"))?;
         }
         crate::types::ExportFormat::Bmp => {
@@ -142,8 +1527,649 @@ pub fn save_rgba_image(
                 .save_with_format(output_path, image::ImageFormat::Bmp)
                 .map_err(|e| format!("BMP save error: {e}"))?;
         }
+        crate::types::ExportFormat::GenesisNametable => {
+            return Err("Genesis name tables require indexed data".to_string());
+        }
+        crate::types::ExportFormat::PaletteStrip => {
+            return Err("Palette strips require palette data".to_string());
+        }
+        crate::types::ExportFormat::IndexCsv => {
+            return Err("Index CSV export requires indexed data".to_string());
+        }
+        crate::types::ExportFormat::CombinedPalette => {
+            return Err("Palette export requires palette data".to_string());
+        }
+        crate::types::ExportFormat::PaletteCycleGif => {
+            return Err("Palette cycle GIFs require indexed data".to_string());
+        }
+        crate::types::ExportFormat::Tga => {
+            return Err("TGA export requires palette data".to_string());
+        }
+        crate::types::ExportFormat::GutteredPng => {
+            return Err("Gutter export requires indexed data".to_string());
+        }
+        crate::types::ExportFormat::TiledMap => {
+            return Err("Tiled export requires indexed data".to_string());
+        }
+        crate::types::ExportFormat::Apng => {
+            return Err("APNG export requires indexed data".to_string());
+        }
+        crate::types::ExportFormat::RawPalette => {
+            return Err("Palette export requires palette data".to_string());
+        }
+        crate::types::ExportFormat::Png16 => {
+            return Err("16-bit PNG export requires 16-bit data".to_string());
+        }
+        crate::types::ExportFormat::SplitPng => {
+            return Err("Split export requires split output".to_string());
+        }
     }
 
     log::info!("RGBA image exported successfully to: {output_path}");
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::image_processor::ImageProcessor;
+    use crate::types::QualetizeSettings;
+
+    #[test]
+    fn test_build_tileset_matches_flipped_tiles() {
+        // Two 2x2 tiles side by side; the second is the first mirrored horizontally
+        // and uses the second palette.
+        let indexed = [0, 1, 5, 4, 2, 3, 7, 6];
+        let tileset = build_tileset(&indexed, &[4, 4], 4, 2, 2, 2, None).unwrap();
+
+        assert_eq!(tileset.tiles, vec![vec![0, 1, 2, 3]]);
+        assert_eq!(
+            tileset.map[1],
+            TileMapEntry {
+                tile: 0,
+                palette: 1,
+                flip_x: true,
+                flip_y: false,
+            }
+        );
+        assert_eq!(genesis_name_table_word(&tileset.map[1]), 0x2800);
+    }
+
+    #[test]
+    fn test_build_tileset_stores_tiles_in_their_tile_reduce_orientation() {
+        // The second tile is the first mirrored horizontally, and tile reduce wrote the
+        // first one mirrored
+        let indexed = [0, 1, 1, 0, 2, 3, 3, 2];
+        let mirrored = TileFlip {
+            flip_x: true,
+            flip_y: false,
+        };
+        let tile_flips = [mirrored, TileFlip::default()];
+        let tileset = build_tileset(&indexed, &[4], 4, 2, 2, 2, Some(&tile_flips)).unwrap();
+
+        assert_eq!(tileset.tiles, vec![vec![1, 0, 3, 2]]);
+        assert_eq!(
+            tileset
+                .map
+                .iter()
+                .map(|entry| (entry.tile, entry.flip_x))
+                .collect::<Vec<_>>(),
+            vec![(0, true), (0, false)]
+        );
+    }
+
+    #[test]
+    fn test_export_tile_flips_follow_the_export_flip_and_padding() {
+        let mirrored = TileFlip {
+            flip_x: true,
+            flip_y: false,
+        };
+        let flips = export_tile_flips(
+            &[mirrored, TileFlip::default()],
+            16,
+            8,
+            (8, 8),
+            (24, 16),
+            true,
+            false,
+        )
+        .unwrap();
+
+        // Swapped and mirrored, then padded to 3×2 tiles
+        let mut expected = vec![TileFlip::default(); 6];
+        expected[0] = mirrored;
+        assert_eq!(flips, expected);
+        assert!(export_tile_flips(&[mirrored], 8, 8, (8, 8), (12, 8), false, false).is_none());
+    }
+
+    #[test]
+    fn test_tiled_map_uses_flip_flags_and_one_based_ids() {
+        // Two 2x2 tiles in a row; the second is the first mirrored vertically
+        let indexed = [0, 1, 2, 3, 2, 3, 0, 1];
+        let tileset = build_tileset(&indexed, &[256], 4, 2, 2, 2, None).unwrap();
+        assert_eq!(tileset.tiles.len(), 1);
+
+        let tmx = format_tiled_map(&tileset.map, 2, 1, (2, 2), "level.tsx");
+        assert!(tmx.contains("<tileset firstgid=\"1\" source=\"level.tsx\"/>"));
+        assert!(tmx.contains(&format!("\n1,{}\n</data>", 1 | TILED_FLIP_Y)));
+
+        let (pixels, width, height) = tiled_tileset_image(&tileset.tiles, 2, 2);
+        assert_eq!((width, height), (2, 2));
+        assert_eq!(pixels, vec![0, 1, 2, 3]);
+        let tsx = format_tiled_tileset("level", "level.tiles.png", 1, (2, 2), (2, 2));
+        assert!(tsx.contains("tilecount=\"1\" columns=\"1\""));
+    }
+
+    #[test]
+    fn test_index_csv_has_header_and_rows() {
+        let indexed = [0, 1, 4, 5];
+
+        let plain = format_index_csv(&indexed, &[4, 4], 2, 2, false).unwrap();
+        assert_eq!(plain, "# width=2 height=2 cells=index\n0,1\n4,5\n");
+
+        let split = format_index_csv(&indexed, &[4, 4], 2, 2, true).unwrap();
+        assert_eq!(
+            split,
+            "# width=2 height=2 cells=palette:color\n0:0,0:1\n1:0,1:1\n"
+        );
+
+        assert!(format_index_csv(&indexed, &[4], 2, 2, true).is_err());
+    }
+
+    #[test]
+    fn test_palette_alpha_conversion_round_trips() {
+        let straight = [
+            BGRA8 {
+                b: 0,
+                g: 64,
+                r: 128,
+                a: 128,
+            },
+            BGRA8 {
+                b: 10,
+                g: 20,
+                r: 30,
+                a: 255,
+            },
+        ];
+
+        let premultiplied = palette_for_export(&straight, ExportAlphaMode::Premultiplied);
+        assert_eq!(
+            premultiplied[0],
+            BGRA8 {
+                b: 0,
+                g: 32,
+                r: 64,
+                a: 128
+            }
+        );
+        assert_eq!(premultiplied[1], straight[1]);
+        let mut unpremultiplied = premultiplied.clone();
+        let settings = QualetizeSettings {
+            rgba_depth: "8888".to_string(),
+            use_custom_levels: false,
+            ..QualetizeSettings::default()
+        };
+        ImageProcessor::unpremultiply_palette(&mut unpremultiplied, &settings);
+        assert_eq!(unpremultiplied, straight);
+        assert_eq!(
+            palette_for_export(&straight, ExportAlphaMode::Straight),
+            straight
+        );
+    }
+
+    #[test]
+    fn test_bmp_row_orders_decode_to_the_same_image() {
+        let palette = [
+            BGRA8 {
+                b: 0,
+                g: 0,
+                r: 255,
+                a: 0,
+            },
+            BGRA8 {
+                b: 255,
+                g: 0,
+                r: 0,
+                a: 0,
+            },
+        ];
+        // Top row red, bottom row blue
+        let indexed = [0, 0, 0, 1, 1, 1];
+
+        for row_order in RowOrder::all() {
+            let bmp = encode_indexed_bmp(
+                &indexed,
+                &palette,
+                3,
+                2,
+                *row_order,
+                ExportOptions::default(),
+            );
+            let decoded = image::load_from_memory_with_format(&bmp, image::ImageFormat::Bmp)
+                .unwrap()
+                .to_rgb8();
+
+            assert_eq!(decoded.dimensions(), (3, 2), "{row_order:?}");
+            assert_eq!(decoded.get_pixel(0, 0).0, [255, 0, 0], "{row_order:?}");
+            assert_eq!(decoded.get_pixel(2, 1).0, [0, 0, 255], "{row_order:?}");
+        }
+    }
+
+    #[test]
+    fn test_export_size_estimates_match_the_encoders() {
+        let palette = [BGRA8 {
+            b: 10,
+            g: 20,
+            r: 30,
+            a: 255,
+        }; 5];
+        let indexed: Vec<u8> = (0..7 * 3).map(|i| (i % 5) as u8).collect();
+
+        let bmp = encode_indexed_bmp(
+            &indexed,
+            &palette,
+            7,
+            3,
+            RowOrder::BottomUp,
+            ExportOptions::default(),
+        );
+        assert_eq!(indexed_bmp_file_size(7, 3), bmp.len() as u64);
+        let tga = encode_indexed_tga(&indexed, &palette, 7, 3, RowOrder::BottomUp).unwrap();
+        assert_eq!(indexed_tga_file_size(7, 3, palette.len()), tga.len() as u64);
+
+        let mut png = Vec::new();
+        write_indexed_png(&mut png, &indexed, &palette, 7, 3, ExportOptions::default()).unwrap();
+        let estimate = estimate_indexed_export_size(
+            &ExportFormat::PngIndexed,
+            &indexed,
+            &palette,
+            7,
+            3,
+            ExportOptions::default(),
+        );
+        assert_eq!(estimate, Some(png.len() as u64));
+        assert_eq!(format_file_size(2048), "2.0 KB");
+    }
+
+    #[test]
+    fn test_dpi_is_written_as_pixels_per_meter() {
+        let palette = [BGRA8 {
+            b: 0,
+            g: 0,
+            r: 0,
+            a: 255,
+        }];
+        let indexed = [0; 4];
+
+        let bmp = encode_indexed_bmp(
+            &indexed,
+            &palette,
+            2,
+            2,
+            RowOrder::BottomUp,
+            ExportOptions {
+                dpi: Some(72),
+                ..Default::default()
+            },
+        );
+        // 72 DPI is 2834.6 pixels per meter
+        assert_eq!(bmp[38..42], 2835u32.to_le_bytes());
+        assert_eq!(bmp[42..46], 2835u32.to_le_bytes());
+        let bmp = encode_indexed_bmp(
+            &indexed,
+            &palette,
+            2,
+            2,
+            RowOrder::BottomUp,
+            ExportOptions::default(),
+        );
+        assert_eq!(bmp[38..46], [0; 8]);
+
+        let mut png = Vec::new();
+        write_indexed_png(
+            &mut png,
+            &indexed,
+            &palette,
+            2,
+            2,
+            ExportOptions {
+                dpi: Some(300),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let reader = png::Decoder::new(std::io::Cursor::new(png))
+            .read_info()
+            .unwrap();
+        let pixel_dims = reader.info().pixel_dims.unwrap();
+        assert_eq!((pixel_dims.xppu, pixel_dims.yppu), (11811, 11811));
+        assert_eq!(pixel_dims.unit, png::Unit::Meter);
+    }
+
+    #[test]
+    fn test_tga_row_orders_set_origin_and_decode_to_the_same_image() {
+        let palette = [
+            BGRA8 {
+                b: 0,
+                g: 0,
+                r: 255,
+                a: 255,
+            },
+            BGRA8 {
+                b: 255,
+                g: 0,
+                r: 0,
+                a: 128,
+            },
+        ];
+        let indexed = [0, 1, 1, 1, 0, 0];
+        // 18-byte header, then two 4-byte color map entries
+        let pixels_start = 18 + 2 * 4;
+
+        let bottom_up = encode_indexed_tga(&indexed, &palette, 3, 2, RowOrder::BottomUp).unwrap();
+        let top_down = encode_indexed_tga(&indexed, &palette, 3, 2, RowOrder::TopDown).unwrap();
+
+        // Image descriptor: bit 5 set for a top-left origin
+        assert_eq!(bottom_up[17] & 0x20, 0);
+        assert_eq!(top_down[17] & 0x20, 0x20);
+        assert_eq!(bottom_up[12..16], [3, 0, 2, 0]);
+        assert_eq!(
+            bottom_up[18..pixels_start],
+            [0, 0, 255, 255, 255, 0, 0, 128]
+        );
+        assert_eq!(bottom_up[pixels_start..], [1, 0, 0, 0, 1, 1]);
+        assert_eq!(top_down[pixels_start..], indexed);
+
+        for row_order in RowOrder::all() {
+            let tga = encode_indexed_tga(&indexed, &palette, 3, 2, *row_order).unwrap();
+            let decoded = image::load_from_memory_with_format(&tga, image::ImageFormat::Tga)
+                .unwrap()
+                .to_rgba8();
+
+            assert_eq!(decoded.dimensions(), (3, 2), "{row_order:?}");
+            assert_eq!(decoded.get_pixel(0, 0).0, [255, 0, 0, 255], "{row_order:?}");
+            assert_eq!(decoded.get_pixel(1, 0).0, [0, 0, 255, 128], "{row_order:?}");
+            assert_eq!(decoded.get_pixel(0, 1).0, [0, 0, 255, 128], "{row_order:?}");
+            assert_eq!(decoded.get_pixel(2, 1).0, [255, 0, 0, 255], "{row_order:?}");
+        }
+    }
+
+    #[test]
+    fn test_combined_palette_is_padded_to_256_entries() {
+        let red = BGRA8 {
+            b: 0,
+            g: 0,
+            r: 255,
+            a: 255,
+        };
+        let blue = BGRA8 {
+            b: 255,
+            g: 0,
+            r: 0,
+            a: 255,
+        };
+        let pal = format_combined_palette(&[red, blue]);
+        let lines: Vec<&str> = pal.lines().collect();
+
+        assert_eq!(&lines[..3], &["JASC-PAL", "0100", "256"]);
+        assert_eq!(lines.len(), 3 + 256);
+        assert_eq!(lines[3], "255 0 0");
+        assert_eq!(lines[4], "0 0 255");
+        assert_eq!(lines[258], "0 0 0");
+    }
+
+    #[test]
+    fn test_palette_cycle_gif_has_one_frame_per_step() {
+        use image::AnimationDecoder;
+        use image::codecs::gif::GifDecoder;
+
+        let gray = |v: u8| BGRA8 {
+            b: v,
+            g: v,
+            r: v,
+            a: 255,
+        };
+        let palette = [gray(0), gray(80), gray(160), gray(240)];
+        let cycle = PaletteCycle {
+            enabled: true,
+            first_index: 1,
+            last_index: 3,
+            steps_per_second: 10.0,
+        };
+
+        let gif_data = encode_palette_cycle_gif(&[0, 1], &palette, 2, 1, &cycle).unwrap();
+        let frames = GifDecoder::new(std::io::Cursor::new(gif_data))
+            .unwrap()
+            .into_frames()
+            .collect_frames()
+            .unwrap();
+
+        assert_eq!(frames.len(), 3);
+        // Index 0 is outside the range and never changes; index 1 shows 1, 3, 2
+        let pixels: Vec<[u8; 2]> = frames
+            .iter()
+            .map(|frame| {
+                [
+                    frame.buffer().get_pixel(0, 0)[0],
+                    frame.buffer().get_pixel(1, 0)[0],
+                ]
+            })
+            .collect();
+        assert_eq!(pixels, vec![[0, 80], [0, 240], [0, 160]]);
+    }
+
+    #[test]
+    fn test_cell_gutters_surround_every_cell() {
+        // Two 2x2 cells side by side
+        let indexed = [1, 2, 5, 6, 3, 4, 7, 8];
+
+        let (guttered, width, height) = add_cell_gutters(&indexed, 4, 2, 2, 2, 1).unwrap();
+
+        assert_eq!((width, height), (7, 4));
+        #[rustfmt::skip]
+        let expected = vec![
+            0, 0, 0, 0, 0, 0, 0,
+            0, 1, 2, 0, 5, 6, 0,
+            0, 3, 4, 0, 7, 8, 0,
+            0, 0, 0, 0, 0, 0, 0,
+        ];
+        assert_eq!(guttered, expected);
+        assert!(add_cell_gutters(&indexed, 4, 2, 3, 2, 1).is_err());
+    }
+
+    #[test]
+    fn test_apng_splits_stacked_frames_with_one_palette() {
+        use image::AnimationDecoder;
+        use image::codecs::png::PngDecoder;
+
+        let palette = [
+            BGRA8 {
+                b: 0,
+                g: 0,
+                r: 255,
+                a: 255,
+            },
+            BGRA8 {
+                b: 255,
+                g: 0,
+                r: 0,
+                a: 255,
+            },
+        ];
+        // Two 2x1 frames plus a padding row that must be dropped
+        let indexed = [0, 1, 1, 0, 0, 0];
+        let mut apng = Vec::new();
+        write_indexed_apng(
+            &mut apng,
+            &indexed,
+            &palette,
+            2,
+            1,
+            &[100, 250],
+            ExportOptions::default(),
+        )
+        .unwrap();
+
+        let frames = PngDecoder::new(std::io::Cursor::new(apng))
+            .unwrap()
+            .apng()
+            .unwrap()
+            .into_frames()
+            .collect_frames()
+            .unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].buffer().get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(frames[1].buffer().get_pixel(0, 0).0, [0, 0, 255, 255]);
+        let (numerator, denominator) = frames[1].delay().numer_denom_ms();
+        assert_eq!(numerator / denominator, 250);
+    }
+
+    #[test]
+    fn test_raw_palette_channel_orders() {
+        let palette = [
+            BGRA8 {
+                b: 3,
+                g: 2,
+                r: 1,
+                a: 4,
+            },
+            BGRA8 {
+                b: 7,
+                g: 6,
+                r: 5,
+                a: 8,
+            },
+        ];
+
+        assert_eq!(
+            encode_raw_palette(&palette, ChannelOrder::Rgba),
+            [1, 2, 3, 4, 5, 6, 7, 8]
+        );
+        assert_eq!(
+            encode_raw_palette(&palette, ChannelOrder::Bgra),
+            [3, 2, 1, 4, 7, 6, 5, 8]
+        );
+        assert_eq!(
+            encode_raw_palette(&palette, ChannelOrder::Argb),
+            [4, 1, 2, 3, 8, 5, 6, 7]
+        );
+        assert_eq!(BGRA8::from_rgba(palette[0].to_rgba()), palette[0]);
+    }
+
+    #[test]
+    fn test_pad_indexed_pixels_anchors_top_left() {
+        let indexed = [1, 2, 3, 4, 5, 6];
+        let padded = pad_indexed_pixels(&indexed, 3, 2, 4, 4);
+
+        assert_eq!(padded, vec![1, 2, 3, 0, 4, 5, 6, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_contact_sheet_lays_out_cells_with_captions() {
+        let cells = [
+            ContactSheetCell {
+                rgba: [255, 0, 0, 255].repeat(4),
+                width: 2,
+                height: 2,
+                caption: "A".to_string(),
+            },
+            ContactSheetCell {
+                rgba: [0, 0, 255, 255].repeat(4),
+                width: 2,
+                height: 2,
+                caption: "1".to_string(),
+            },
+        ];
+        let (rgba, width, height) = compose_contact_sheet(&cells);
+
+        // Two columns, each as wide as a 5 pixel caption, with gaps around them
+        assert_eq!((width, height), (4 + 5 + 4 + 5 + 4, 4 + 2 + 4 + 7 + 4));
+        let pixel = |x: u32, y: u32| {
+            let offset = ((y * width + x) * 4) as usize;
+            &rgba[offset..offset + 4]
+        };
+        assert_eq!(pixel(4, 4), &[255, 0, 0, 255]);
+        assert_eq!(pixel(13, 5), &[0, 0, 255, 255]);
+        assert_eq!(pixel(0, 0), &CONTACT_SHEET_BACKGROUND);
+        // Top row of "A" starts one pixel in, under the first image
+        assert_eq!(pixel(4, 10), &CONTACT_SHEET_BACKGROUND);
+        assert_eq!(pixel(5, 10), &CAPTION_COLOR);
+    }
+
+    #[test]
+    fn test_flip_pixels_mirrors_indexed_and_rgba() {
+        let mut indexed = [1, 2, 3, 4, 5, 6];
+        flip_pixels(&mut indexed, 3, 1, true, true);
+        assert_eq!(indexed, [6, 5, 4, 3, 2, 1]);
+
+        // Two RGBA pixels in one row keep their channel order when swapped
+        let mut rgba = [1, 2, 3, 4, 5, 6, 7, 8];
+        flip_pixels(&mut rgba, 2, 4, true, false);
+        assert_eq!(rgba, [5, 6, 7, 8, 1, 2, 3, 4]);
+
+        let mut rows = [1, 2, 3, 4, 5, 6];
+        flip_pixels(&mut rows, 2, 1, false, true);
+        assert_eq!(rows, [5, 6, 3, 4, 1, 2]);
+    }
+
+    #[test]
+    fn test_palette_strip_pads_short_palettes() {
+        let red = BGRA8 {
+            b: 0,
+            g: 0,
+            r: 255,
+            a: 255,
+        };
+        let green = BGRA8 {
+            b: 0,
+            g: 255,
+            r: 0,
+            a: 255,
+        };
+        let blue = BGRA8 {
+            b: 255,
+            g: 0,
+            r: 0,
+            a: 255,
+        };
+        let (rgba, width, height) = render_palette_strip(&[red, green, blue], &[2, 1], 2);
+
+        assert_eq!((width, height), (4, 4));
+        let pixel = |x: usize, y: usize| &rgba[(y * 4 + x) * 4..(y * 4 + x) * 4 + 4];
+        assert_eq!(pixel(1, 1), &[255, 0, 0, 255]);
+        assert_eq!(pixel(2, 0), &[0, 255, 0, 255]);
+        assert_eq!(pixel(0, 3), &[0, 0, 255, 255]);
+        assert_eq!(pixel(3, 3), &[0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_split_manifest_covers_every_tile_row() {
+        // 5 rows of 8px tiles, 36px tall: the last tile row is cut short
+        let bands = crate::image_processor::ImageProcessor::split_bands(36, 8, 2);
+        assert_eq!(bands, vec![(0, 24), (24, 12)]);
+
+        let parts: Vec<SplitPart> = bands
+            .iter()
+            .map(|&(y, height)| SplitPart {
+                y,
+                height,
+                indexed_pixels: vec![0; 16 * height as usize],
+                palettes: Vec::new(),
+                palette_sizes: vec![16, 12],
+            })
+            .collect();
+        let manifest = build_split_manifest(Path::new("out/level.json"), &parts, 16, (8, 8));
+
+        assert_eq!((manifest.width, manifest.height), (16, 36));
+        assert_eq!(manifest.parts[0].file, "level.part0.png");
+        assert_eq!(
+            (
+                manifest.parts[1].first_tile_row,
+                manifest.parts[1].tile_rows
+            ),
+            (3, 2)
+        );
+        assert_eq!(manifest.parts[1].colors, 28);
+    }
+}
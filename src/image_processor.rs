@@ -1,9 +1,16 @@
+use crate::color_processor::ColorProcessor;
+use crate::exporter::unpremultiply_channel;
 use crate::types::qualetize::{Qualetize, QualetizePlanOwned, Vec4f};
-use crate::types::{BGRA8, ImageData, QualetizeSettings};
+use crate::types::{BGRA8, DepthRounding, DitherMode, ImageData, QualetizeSettings};
 use egui::Context;
-use std::sync::mpsc;
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::sync::{Arc, mpsc};
 
-#[derive(Debug)]
+/// 4x4 Bayer threshold matrix, values 0-15
+const BAYER_4X4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+#[derive(Debug, Clone)]
 pub struct QualetizeResult {
     pub indexed_data: Vec<u8>,
     pub palette_data: Vec<BGRA8>,
@@ -11,6 +18,22 @@ pub struct QualetizeResult {
     pub width: u32,
     pub height: u32,
     pub generation_id: u64,
+    pub rmse: [f32; 4],
+    /// Bands of an output with more than 256 colors; empty otherwise
+    pub split_parts: Vec<SplitPart>,
+}
+
+/// One band of whole tile rows of a split output, quantized on its own with at most
+/// 256 colors so it can be written as a separate indexed file
+#[derive(Debug, Clone)]
+pub struct SplitPart {
+    /// First pixel row of the band and its height
+    pub y: u32,
+    pub height: u32,
+    /// Indices into this part's `palettes`
+    pub indexed_pixels: Vec<u8>,
+    pub palettes: Vec<BGRA8>,
+    pub palette_sizes: Vec<usize>,
 }
 
 #[derive(Default)]
@@ -24,6 +47,14 @@ pub struct ImageProcessor {
     tile_reduce_receiver: Option<mpsc::Receiver<Result<TileReduceResult, String>>>,
     tile_reduce_generation_id: u64,
     tile_reduce_cancel: Option<std::sync::Arc<std::sync::atomic::AtomicBool>>,
+    last_rmse: Option<[f32; 4]>,
+    /// Most recently accepted result, the base that region-limited runs composite into
+    last_result: Option<QualetizeResult>,
+    /// When the running quantization was started, for the debug HUD
+    quantization_started: Option<std::time::Instant>,
+    last_quantization_time: Option<std::time::Duration>,
+    /// Pool that sprite sheet cells and split bands run on, with its thread count
+    thread_pool: Option<(usize, Arc<rayon::ThreadPool>)>,
 }
 
 struct ClusterMember {
@@ -49,11 +80,264 @@ pub struct TileReduceOptions {
 
 pub struct TileReduceResult {
     pub indexed_pixels: Vec<u8>,
+    /// Orientation each tile was written in, row-major over the tile grid
+    pub tile_flips: Vec<TileFlip>,
     pub merged: usize,
     pub generation_id: u64,
 }
 
+/// Axes a reduced tile mirrors its representative on; both false for tiles
+/// that were kept as they are
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TileFlip {
+    pub flip_x: bool,
+    pub flip_y: bool,
+}
+
+/// Rectangle of the input to re-quantize, in input pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RegionOfInterest {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl RegionOfInterest {
+    /// The region grown outward to whole tiles and clipped to the image, as
+    /// `(x0, y0, x1, y1)` with exclusive ends; `None` if nothing is left
+    pub fn tile_bounds(
+        &self,
+        tile_width: u32,
+        tile_height: u32,
+        image_width: u32,
+        image_height: u32,
+    ) -> Option<(u32, u32, u32, u32)> {
+        let (tile_width, tile_height) = (tile_width.max(1), tile_height.max(1));
+        let x1 = self.x.saturating_add(self.width).min(image_width);
+        let y1 = self.y.saturating_add(self.height).min(image_height);
+        if self.x >= x1 || self.y >= y1 {
+            return None;
+        }
+        Some((
+            self.x / tile_width * tile_width,
+            self.y / tile_height * tile_height,
+            x1.next_multiple_of(tile_width).min(image_width),
+            y1.next_multiple_of(tile_height).min(image_height),
+        ))
+    }
+
+    /// Whether the pixel at (`x`, `y`) lies inside the rectangle
+    pub fn contains(&self, x: u32, y: u32) -> bool {
+        (self.x..self.x.saturating_add(self.width)).contains(&x)
+            && (self.y..self.y.saturating_add(self.height)).contains(&y)
+    }
+
+    /// Used as an edit mask: `inside` pixels within the rectangle and `outside` pixels
+    /// everywhere else. Both are `width`×`height` with 4 channels.
+    pub fn composite<T: Copy>(&self, inside: &[T], outside: &[T], width: u32) -> Vec<T> {
+        let mut pixels = outside.to_vec();
+        for (i, (pixel, source)) in pixels
+            .chunks_exact_mut(4)
+            .zip(inside.chunks_exact(4))
+            .enumerate()
+        {
+            let (x, y) = (i as u32 % width.max(1), i as u32 / width.max(1));
+            if self.contains(x, y) {
+                pixel.copy_from_slice(source);
+            }
+        }
+        pixels
+    }
+}
+
+/// Tile size whose grid lines coincide with edges that repeat across the image
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TileGridSuggestion {
+    pub tile_width: u16,
+    pub tile_height: u16,
+    /// Average edge strength on the grid lines relative to the whole image (1.0 = no grid)
+    pub strength: f32,
+}
+
+/// Grid lines must have at least this much more edge energy than average
+const GRID_MIN_STRENGTH: f32 = 1.5;
+/// A divisor of the best period scoring within this fraction of it is preferred,
+/// since every multiple of the real cell size lines up with the same edges
+const GRID_HARMONIC_RATIO: f32 = 0.75;
+const GRID_MAX_PERIOD: usize = 64;
+const GRID_MAX_SUGGESTIONS: usize = 3;
+
+/// Change the width of a `width`×`height` image to `target_width` by removing or
+/// duplicating vertical seams. Seams are found by repeatedly removing the cheapest one
+/// from a working copy, remembering which original column each removed pixel was.
+fn carve_columns(
+    pixels: &[[u8; 4]],
+    width: usize,
+    height: usize,
+    target_width: usize,
+) -> Vec<[u8; 4]> {
+    let seam_count = width.abs_diff(target_width);
+    let mut columns: Vec<Vec<usize>> = (0..height).map(|_| (0..width).collect()).collect();
+    let mut in_seam = vec![false; width * height];
+    let difference =
+        |a: [u8; 4], b: [u8; 4]| -> u32 { (0..4).map(|c| a[c].abs_diff(b[c]) as u32).sum() };
+
+    for _ in 0..seam_count {
+        let current_width = columns[0].len();
+        let at = |x: usize, y: usize| pixels[y * width + columns[y][x]];
+        // Forward differences, so fine patterns like checkerboards count as detail
+        let mut cost = vec![0u32; current_width * height];
+        for y in 0..height {
+            for x in 0..current_width {
+                let pixel = at(x, y);
+                let energy = difference(pixel, at((x + 1).min(current_width - 1), y))
+                    + difference(pixel, at(x, (y + 1).min(height - 1)));
+                let above = if y == 0 {
+                    0
+                } else {
+                    (x.saturating_sub(1)..=(x + 1).min(current_width - 1))
+                        .map(|above_x| cost[(y - 1) * current_width + above_x])
+                        .min()
+                        .unwrap_or(0)
+                };
+                cost[y * current_width + x] = above + energy;
+            }
+        }
+
+        let cheapest = |y: usize, range: std::ops::RangeInclusive<usize>| {
+            range
+                .min_by_key(|&x| cost[y * current_width + x])
+                .unwrap_or(0)
+        };
+        let mut x = cheapest(height - 1, 0..=current_width - 1);
+        for y in (0..height).rev() {
+            if y < height - 1 {
+                x = cheapest(y, x.saturating_sub(1)..=(x + 1).min(current_width - 1));
+            }
+            let original_x = columns[y].remove(x);
+            in_seam[y * width + original_x] = true;
+        }
+    }
+
+    let mut carved = Vec::with_capacity(target_width * height);
+    for (y, row_columns) in columns.iter().enumerate() {
+        let row = &pixels[y * width..(y + 1) * width];
+        if target_width < width {
+            carved.extend(row_columns.iter().map(|&x| row[x]));
+            continue;
+        }
+        // Widening duplicates each seam pixel as the average with its right neighbour
+        for (x, &pixel) in row.iter().enumerate() {
+            carved.push(pixel);
+            if in_seam[y * width + x] {
+                let next = row[(x + 1).min(width - 1)];
+                carved.push(std::array::from_fn(|c| {
+                    (pixel[c] as u16 + next[c] as u16).div_ceil(2) as u8
+                }));
+            }
+        }
+    }
+    carved
+}
+
+/// Swap rows and columns; the result is `height` wide and `width` tall
+fn transpose_pixels(pixels: &[[u8; 4]], width: usize, height: usize) -> Vec<[u8; 4]> {
+    let mut transposed = Vec::with_capacity(pixels.len());
+    for x in 0..width {
+        for y in 0..height {
+            transposed.push(pixels[y * width + x]);
+        }
+    }
+    transposed
+}
+
 impl ImageProcessor {
+    /// Look for a repeating grid of edges (e.g. sprite sheet cells) and suggest the
+    /// tile sizes that match it, best first. Empty when no clear grid is found.
+    pub fn detect_tile_grid(rgba: &[u8], width: u32, height: u32) -> Vec<TileGridSuggestion> {
+        let (width, height) = (width as usize, height as usize);
+        if rgba.len() < width * height * 4 {
+            return Vec::new();
+        }
+        let columns = grid_periods(&edge_profile(rgba, width, height, true));
+        let rows = grid_periods(&edge_profile(rgba, width, height, false));
+
+        let mut suggestions: Vec<TileGridSuggestion> = match (columns.is_empty(), rows.is_empty()) {
+            (true, true) => Vec::new(),
+            (false, false) => columns
+                .iter()
+                .flat_map(|&(tile_width, x_strength)| {
+                    rows.iter()
+                        .map(move |&(tile_height, y_strength)| TileGridSuggestion {
+                            tile_width: tile_width as u16,
+                            tile_height: tile_height as u16,
+                            strength: (x_strength * y_strength).sqrt(),
+                        })
+                })
+                .collect(),
+            // A single row or column of cells only shows the grid along one axis;
+            // assume square cells if they fit the other axis
+            _ => columns
+                .iter()
+                .map(|&period| (period, height))
+                .chain(rows.iter().map(|&period| (period, width)))
+                .filter(|&((period, _), other_len)| {
+                    period <= other_len && other_len.is_multiple_of(period)
+                })
+                .map(|((period, strength), _)| TileGridSuggestion {
+                    tile_width: period as u16,
+                    tile_height: period as u16,
+                    strength,
+                })
+                .collect(),
+        };
+        suggestions.sort_by(|a, b| b.strength.total_cmp(&a.strength));
+        suggestions.truncate(GRID_MAX_SUGGESTIONS);
+        suggestions
+    }
+
+    /// Content-aware resize by a few pixels: remove or duplicate the lowest-energy
+    /// seams (connected paths through flat areas) until the image has the target size.
+    /// At most doubles or shrinks to one pixel per axis.
+    pub fn seam_carve(
+        rgba: &[u8],
+        width: u32,
+        height: u32,
+        target_width: u32,
+        target_height: u32,
+    ) -> Result<Vec<u8>, String> {
+        if rgba.len() != (width * height * 4) as usize {
+            return Err("Image buffer does not match its size".to_string());
+        }
+        if target_width == 0
+            || target_height == 0
+            || target_width > width * 2
+            || target_height > height * 2
+        {
+            return Err(format!(
+                "Cannot reach {target_width}×{target_height} from {width}×{height} with seams"
+            ));
+        }
+        let (width, height) = (width as usize, height as usize);
+        let mut pixels: Vec<[u8; 4]> = rgba
+            .chunks_exact(4)
+            .map(|c| [c[0], c[1], c[2], c[3]])
+            .collect();
+        let mut current_width = width;
+        if target_width as usize != width {
+            pixels = carve_columns(&pixels, width, height, target_width as usize);
+            current_width = target_width as usize;
+        }
+        if target_height as usize != height {
+            // Rows are carved as the columns of the transposed image
+            let transposed = transpose_pixels(&pixels, current_width, height);
+            let carved = carve_columns(&transposed, height, current_width, target_height as usize);
+            pixels = transpose_pixels(&carved, target_height as usize, current_width);
+        }
+        Ok(pixels.into_iter().flatten().collect())
+    }
+
     pub fn new() -> Self {
         Self {
             tile_reduce_thread: None,
@@ -64,16 +348,22 @@ impl ImageProcessor {
         }
     }
 
+    /// With a `region`, only tiles overlapping it are re-quantized against the palettes
+    /// of the last result, as long as that result came from the same settings and size.
+    /// Anything else runs on the whole image.
     pub fn start_qualetize(
         &mut self,
         color_corrected_image: &ImageData,
         settings: QualetizeSettings,
+        thread_count: usize,
+        region: Option<RegionOfInterest>,
     ) {
         // Cancel any existing processing
         self.cancel_current_processing();
+        self.quantization_started = Some(std::time::Instant::now());
 
         // Pre-generate BGRA data to improve responsiveness and avoid redundancy
-        let bgra_result = self.generate_bgra_data(color_corrected_image);
+        let bgra_result = self.generate_bgra_data(color_corrected_image, &settings);
         let (bgra_data, width, height) = match bgra_result {
             Ok(data) => data,
             Err(e) => {
@@ -82,6 +372,20 @@ impl ImageProcessor {
             }
         };
 
+        let region_base = region.and_then(|region| {
+            let base = self.last_result.as_ref()?;
+            // Premultiplied or weighted input can't be compared against the output palettes,
+            // split bands don't share one index space, and sprite cells can't change palette
+            (base.settings == settings
+                && base.split_parts.is_empty()
+                && !settings.sprite_sheet_enabled
+                && !settings.premul_alpha
+                && settings.active_channel_weights().is_none()
+                && base.width == width
+                && base.height == height)
+                .then(|| (region, base.clone()))
+        });
+
         let (result_sender, result_receiver) = mpsc::channel();
         let (cancel_sender, cancel_receiver) = mpsc::channel();
         let generation_id = self.current_generation_id;
@@ -89,23 +393,53 @@ impl ImageProcessor {
         self.preview_receiver = Some(result_receiver);
         self.cancel_sender = Some(cancel_sender);
 
+        let thread_pool = self.thread_pool(thread_count);
         let thread = std::thread::spawn(move || {
-            let result = Self::generate_preview(
-                bgra_data,
-                width,
-                height,
-                settings,
-                cancel_receiver,
-                generation_id,
-            );
+            let result = thread_pool.install(|| {
+                Self::generate_preview(
+                    bgra_data,
+                    width,
+                    height,
+                    settings,
+                    region_base,
+                    cancel_receiver,
+                    generation_id,
+                )
+            });
             let _ = result_sender.send(result);
         });
         self.preview_thread = Some(thread);
     }
 
+    /// Rayon pool with `thread_count` threads, rebuilt only when the count changes
+    pub fn thread_pool(&mut self, thread_count: usize) -> Arc<rayon::ThreadPool> {
+        let thread_count = thread_count.max(1);
+        if let Some((count, pool)) = &self.thread_pool
+            && *count == thread_count
+        {
+            return pool.clone();
+        }
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(thread_count)
+            .build()
+            .map(Arc::new)
+            .unwrap_or_else(|e| {
+                log::warn!("Failed to build a {thread_count}-thread pool: {e}");
+                Arc::new(
+                    rayon::ThreadPoolBuilder::new()
+                        .num_threads(1)
+                        .build()
+                        .expect("single-thread pool"),
+                )
+            });
+        self.thread_pool = Some((thread_count, pool.clone()));
+        pool
+    }
+
     pub fn generate_bgra_data(
         &mut self,
         color_corrected_image: &ImageData,
+        settings: &QualetizeSettings,
     ) -> Result<(Vec<BGRA8>, u32, u32), String> {
         // Convert to BGRA
         let width = color_corrected_image.width;
@@ -114,19 +448,354 @@ impl ImageProcessor {
         let input_data = &color_corrected_image.rgba_data;
 
         // Convert RGBA to BGRA for qualetize
-        let mut bgra_data: Vec<BGRA8> = Vec::with_capacity((width * height) as usize);
-        for chunk in input_data.chunks_exact(4) {
-            bgra_data.push(BGRA8 {
-                b: chunk[2],
-                g: chunk[1],
-                r: chunk[0],
-                a: chunk[3],
-            });
+        let mut bgra_data: Vec<BGRA8> = input_data
+            .chunks_exact(4)
+            .map(|chunk| BGRA8::from_rgba([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect();
+        if settings.source_alpha_transparency {
+            for pixel in bgra_data
+                .iter_mut()
+                .filter(|pixel| pixel.a <= settings.alpha_threshold)
+            {
+                pixel.a = 0;
+            }
+        }
+        if settings.input_merge_tolerance > 0 {
+            Self::merge_near_duplicate_colors(&mut bgra_data, settings.input_merge_tolerance);
+        }
+        if let Some(max_hues) = settings.max_hues {
+            Self::limit_hues(&mut bgra_data, max_hues);
+        }
+        if settings.depth_rounding != DepthRounding::Round {
+            let levels = [0, 1, 2].map(|channel| settings.channel_levels(channel));
+            Self::apply_depth_rounding(&mut bgra_data, width, &levels, settings.depth_rounding);
+        }
+        if settings.dither_mode == DitherMode::Hybrid {
+            Self::apply_bayer_pattern(&mut bgra_data, width, settings.hybrid_pattern_level);
+        }
+        if let Some(weights) = settings.active_channel_weights() {
+            Self::apply_channel_weights(&mut bgra_data, weights);
+        }
+        if settings.ignore_transparent_in_clustering {
+            Self::mask_transparent_pixels(
+                &mut bgra_data,
+                width,
+                height,
+                settings.tile_width as u32,
+                settings.tile_height as u32,
+            );
         }
         Ok((bgra_data, width, height))
     }
 
-    pub fn check_preview_complete(&mut self, ctx: &Context) -> Option<Result<ImageData, String>> {
+    /// Move the R, G and B of opaque pixels onto the given levels (`None` = full depth)
+    /// with `rounding`, so the library's own nearest-level rounding finds them in place
+    pub fn apply_depth_rounding(
+        bgra_data: &mut [BGRA8],
+        width: u32,
+        levels: &[Option<Vec<u8>>; 3],
+        rounding: DepthRounding,
+    ) {
+        if width == 0 {
+            return;
+        }
+        for (i, pixel) in bgra_data.iter_mut().enumerate() {
+            if pixel.a == 0 {
+                continue;
+            }
+            let (x, y) = (i % width as usize, i / width as usize);
+            let threshold = (BAYER_4X4[y % 4][x % 4] as f32 + 0.5) / 16.0;
+            for (channel, value) in [&mut pixel.r, &mut pixel.g, &mut pixel.b]
+                .into_iter()
+                .enumerate()
+            {
+                let Some(levels) = &levels[channel] else {
+                    continue;
+                };
+                let above = levels.partition_point(|&level| level <= *value);
+                let lower = above.checked_sub(1).map_or(levels[0], |i| levels[i]);
+                let upper = levels.get(above).copied().unwrap_or(lower);
+                *value = match rounding {
+                    DepthRounding::Truncate => lower,
+                    DepthRounding::Round => {
+                        if value.saturating_sub(lower) <= upper.saturating_sub(*value) {
+                            lower
+                        } else {
+                            upper
+                        }
+                    }
+                    DepthRounding::Dither if upper > lower => {
+                        let position = (*value - lower) as f32 / (upper - lower) as f32;
+                        if position > threshold { upper } else { lower }
+                    }
+                    DepthRounding::Dither => lower,
+                };
+            }
+        }
+    }
+
+    /// Add a 4x4 Bayer threshold pattern to the opaque pixels, for the ordered half of
+    /// `DitherMode::Hybrid`. At level 1.0 the offsets span ±32 of 255; the library's
+    /// error diffusion then runs on the patterned input.
+    pub fn apply_bayer_pattern(bgra_data: &mut [BGRA8], width: u32, level: f32) {
+        const FULL_SPAN: f32 = 64.0;
+        if level <= 0.0 || width == 0 {
+            return;
+        }
+        for (i, pixel) in bgra_data.iter_mut().enumerate() {
+            if pixel.a == 0 {
+                continue;
+            }
+            let (x, y) = (i % width as usize, i / width as usize);
+            let threshold = (BAYER_4X4[y % 4][x % 4] as f32 + 0.5) / 16.0 - 0.5;
+            let offset = threshold * FULL_SPAN * level;
+            for channel in [&mut pixel.r, &mut pixel.g, &mut pixel.b] {
+                *channel = (*channel as f32 + offset).round().clamp(0.0, 255.0) as u8;
+            }
+        }
+    }
+
+    /// Replace each input color with a far more common color (same alpha) within
+    /// `tolerance` on every channel, removing noise before clustering. Requiring the
+    /// target to be `MERGE_DOMINANCE` times as frequent keeps smooth gradients intact,
+    /// since their steps are all about equally common.
+    pub fn merge_near_duplicate_colors(bgra_data: &mut [BGRA8], tolerance: u8) {
+        const MERGE_DOMINANCE: u32 = 4;
+        if tolerance == 0 {
+            return;
+        }
+
+        let mut counts: HashMap<[u8; 4], u32> = HashMap::new();
+        for pixel in bgra_data.iter().filter(|pixel| pixel.a != 0) {
+            *counts
+                .entry([pixel.r, pixel.g, pixel.b, pixel.a])
+                .or_default() += 1;
+        }
+        let mut colors: Vec<([u8; 4], u32)> = counts.into_iter().collect();
+        colors.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        // Colors that stay are bucketed by cells of the tolerance, so candidates
+        // are always in the same or a neighbouring cell
+        let cell_size = tolerance as i32 + 1;
+        let cell_of = |color: [u8; 4]| {
+            [
+                color[0] as i32 / cell_size,
+                color[1] as i32 / cell_size,
+                color[2] as i32 / cell_size,
+            ]
+        };
+        let mut kept: HashMap<[i32; 3], Vec<([u8; 4], u32)>> = HashMap::new();
+        let mut replacements: HashMap<[u8; 4], [u8; 4]> = HashMap::new();
+        for (color, count) in colors {
+            let [x, y, z] = cell_of(color);
+            let mut target: Option<([u8; 4], u32)> = None;
+            for dx in -1..=1 {
+                for dy in -1..=1 {
+                    for dz in -1..=1 {
+                        let Some(candidates) = kept.get(&[x + dx, y + dy, z + dz]) else {
+                            continue;
+                        };
+                        for &(candidate, candidate_count) in candidates {
+                            let close = candidate[3] == color[3]
+                                && (0..3).all(|c| candidate[c].abs_diff(color[c]) <= tolerance);
+                            if close
+                                && candidate_count >= count.saturating_mul(MERGE_DOMINANCE)
+                                && target.is_none_or(|(_, best)| candidate_count > best)
+                            {
+                                target = Some((candidate, candidate_count));
+                            }
+                        }
+                    }
+                }
+            }
+            match target {
+                Some((candidate, _)) => {
+                    replacements.insert(color, candidate);
+                }
+                None => kept.entry([x, y, z]).or_default().push((color, count)),
+            }
+        }
+
+        for pixel in bgra_data.iter_mut() {
+            if let Some(&[r, g, b, _]) = replacements.get(&[pixel.r, pixel.g, pixel.b, pixel.a]) {
+                pixel.r = r;
+                pixel.g = g;
+                pixel.b = b;
+            }
+        }
+    }
+
+    /// Snap every pixel's hue to the nearest of `max_hues` dominant hues, keeping its
+    /// saturation and value, so the palette can only hold shades of those hue families.
+    /// The hues are found by weighted k-means on the hue circle; grays are left alone.
+    pub fn limit_hues(bgra_data: &mut [BGRA8], max_hues: u8) {
+        const BINS: usize = 360;
+        const ITERATIONS: usize = 8;
+
+        let hsv = |pixel: &BGRA8| {
+            ColorProcessor::rgb_to_hsv(
+                pixel.r as f32 / 255.0,
+                pixel.g as f32 / 255.0,
+                pixel.b as f32 / 255.0,
+            )
+        };
+        // Saturated pixels say more about the hues in use than near-grays
+        let mut histogram = [0.0f32; BINS];
+        for pixel in bgra_data.iter() {
+            let (h, s, _) = hsv(pixel);
+            histogram[(h as usize).min(BINS - 1)] += s;
+        }
+        let hue_distance = |a: f32, b: f32| {
+            let d = (a - b).abs() % 360.0;
+            d.min(360.0 - d)
+        };
+
+        // Seed with the strongest peaks, kept apart so one broad peak doesn't take them all
+        let max_hues = (max_hues.max(1) as usize).min(BINS);
+        let spacing = 180.0 / max_hues as f32;
+        let mut centers: Vec<f32> = Vec::with_capacity(max_hues);
+        let mut by_weight: Vec<usize> = (0..BINS).filter(|&bin| histogram[bin] > 0.0).collect();
+        by_weight.sort_by(|&a, &b| histogram[b].total_cmp(&histogram[a]));
+        for bin in by_weight {
+            if centers.len() == max_hues {
+                break;
+            }
+            if centers
+                .iter()
+                .all(|&center| hue_distance(center, bin as f32) >= spacing)
+            {
+                centers.push(bin as f32);
+            }
+        }
+        if centers.is_empty() {
+            return;
+        }
+
+        let nearest = |centers: &[f32], hue: f32| {
+            centers
+                .iter()
+                .copied()
+                .min_by(|&a, &b| hue_distance(a, hue).total_cmp(&hue_distance(b, hue)))
+                .unwrap_or(hue)
+        };
+        for _ in 0..ITERATIONS {
+            // Circular weighted mean of the bins closest to each center
+            let mut sums = vec![(0.0f32, 0.0f32); centers.len()];
+            for (bin, &weight) in histogram.iter().enumerate() {
+                if weight == 0.0 {
+                    continue;
+                }
+                let hue = bin as f32;
+                let closest = nearest(&centers, hue);
+                let index = centers.iter().position(|&c| c == closest).unwrap_or(0);
+                let radians = hue.to_radians();
+                sums[index].0 += weight * radians.cos();
+                sums[index].1 += weight * radians.sin();
+            }
+            for (center, (x, y)) in centers.iter_mut().zip(sums) {
+                if x != 0.0 || y != 0.0 {
+                    *center = y.atan2(x).to_degrees().rem_euclid(360.0);
+                }
+            }
+        }
+
+        for pixel in bgra_data.iter_mut() {
+            let (h, s, v) = hsv(pixel);
+            if s == 0.0 {
+                continue;
+            }
+            let (r, g, b) = ColorProcessor::hsv_to_rgb(nearest(&centers, h) % 360.0, s, v);
+            pixel.r = (r * 255.0).round() as u8;
+            pixel.g = (g * 255.0).round() as u8;
+            pixel.b = (b * 255.0).round() as u8;
+        }
+    }
+
+    /// Scale R, G and B down by their weights so the quantizer sees smaller differences
+    /// in the less important channels. The output palette is scaled back afterwards by
+    /// `remove_channel_weights`.
+    pub fn apply_channel_weights(bgra_data: &mut [BGRA8], [r, g, b]: [f32; 3]) {
+        let scale = |value: u8, weight: f32| (value as f32 * weight).round() as u8;
+        for pixel in bgra_data.iter_mut() {
+            pixel.r = scale(pixel.r, r);
+            pixel.g = scale(pixel.g, g);
+            pixel.b = scale(pixel.b, b);
+        }
+    }
+
+    /// Undo `apply_channel_weights` on a straight-alpha palette. The quantizer put the
+    /// scaled colors on the depth's levels, so the restored values are snapped back onto them.
+    pub fn remove_channel_weights(
+        palette: &mut [BGRA8],
+        weights: [f32; 3],
+        settings: &QualetizeSettings,
+    ) {
+        let levels: [Option<Vec<u8>>; 3] = std::array::from_fn(|c| settings.channel_levels(c));
+        let restore = |value: u8, channel: usize| {
+            let value = (value as f32 / weights[channel]).round().min(255.0) as u8;
+            nearest_level(value, levels[channel].as_deref())
+        };
+        for color in palette.iter_mut() {
+            color.r = restore(color.r, 0);
+            color.g = restore(color.g, 1);
+            color.b = restore(color.b, 2);
+        }
+    }
+
+    /// Give fully transparent pixels the average color of the opaque pixels in their
+    /// tile (or of the whole image for empty tiles), so their hidden RGB values
+    /// neither pull clusters toward unused colors nor show up as dither noise.
+    pub fn mask_transparent_pixels(
+        bgra_data: &mut [BGRA8],
+        width: u32,
+        height: u32,
+        tile_width: u32,
+        tile_height: u32,
+    ) {
+        fn opaque_average<'a>(pixels: impl Iterator<Item = &'a BGRA8>) -> Option<[u8; 3]> {
+            let mut sum = [0u64; 3];
+            let mut count = 0u64;
+            for pixel in pixels.filter(|pixel| pixel.a != 0) {
+                sum[0] += pixel.r as u64;
+                sum[1] += pixel.g as u64;
+                sum[2] += pixel.b as u64;
+                count += 1;
+            }
+            (count > 0).then(|| sum.map(|channel| (channel / count) as u8))
+        }
+
+        let (width, height) = (width as usize, height as usize);
+        let (tile_width, tile_height) = (tile_width.max(1) as usize, tile_height.max(1) as usize);
+        let Some(image_average) = opaque_average(bgra_data.iter()) else {
+            return;
+        };
+
+        for tile_y in (0..height).step_by(tile_height) {
+            for tile_x in (0..width).step_by(tile_width) {
+                let tile_indices = || {
+                    (tile_y..(tile_y + tile_height).min(height)).flat_map(move |y| {
+                        (tile_x..(tile_x + tile_width).min(width)).map(move |x| y * width + x)
+                    })
+                };
+                let [r, g, b] =
+                    opaque_average(tile_indices().map(|i| &bgra_data[i])).unwrap_or(image_average);
+                for i in tile_indices() {
+                    let pixel = &mut bgra_data[i];
+                    if pixel.a == 0 {
+                        pixel.r = r;
+                        pixel.g = g;
+                        pixel.b = b;
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn check_preview_complete(
+        &mut self,
+        palette_padding: egui::Color32,
+        ctx: &Context,
+    ) -> Option<Result<ImageData, String>> {
         self.cleanup_finished_threads();
 
         if let Some(receiver) = &mut self.preview_receiver
@@ -142,7 +811,17 @@ impl ImageProcessor {
                             "Accepting result from generation {}",
                             qualetize_result.generation_id
                         );
-                        match ImageData::create_from_qualetize_result(qualetize_result, ctx) {
+                        self.last_rmse = Some(qualetize_result.rmse);
+                        self.last_result = Some(qualetize_result.clone());
+                        self.last_quantization_time = self
+                            .quantization_started
+                            .take()
+                            .map(|started| started.elapsed());
+                        match ImageData::create_from_qualetize_result(
+                            qualetize_result,
+                            palette_padding,
+                            ctx,
+                        ) {
                             Ok(image_data) => Ok(image_data),
                             Err(e) => Err(e),
                         }
@@ -167,10 +846,21 @@ impl ImageProcessor {
         None
     }
 
+    /// RMSE reported by Qualetize for the most recently accepted preview
+    pub fn last_rmse(&self) -> Option<[f32; 4]> {
+        self.last_rmse
+    }
+
     pub fn is_processing(&self) -> bool {
         self.preview_thread.is_some()
     }
 
+    /// Time from `start_qualetize` to the accepted result of the last preview, including
+    /// BGRA conversion and building the output image
+    pub fn last_quantization_time(&self) -> Option<std::time::Duration> {
+        self.last_quantization_time
+    }
+
     pub fn cancel_tile_reduce(&mut self) {
         if let Some(cancel) = &self.tile_reduce_cancel {
             cancel.store(true, std::sync::atomic::Ordering::Relaxed);
@@ -196,6 +886,7 @@ impl ImageProcessor {
         self.preview_thread = None;
         self.preview_receiver = None;
         self.cancel_sender = None;
+        self.quantization_started = None;
 
         // 世代IDを更新（古い結果を無視するため）
         self.current_generation_id += 1;
@@ -228,8 +919,10 @@ impl ImageProcessor {
         let generation_id = self.tile_reduce_generation_id;
         let thread = std::thread::spawn(move || {
             let mut indexed_pixels = indexed;
+            let mut tile_flips = Vec::new();
             let merged = Self::reduce_tiles_indexed(
                 &mut indexed_pixels,
+                &mut tile_flips,
                 &palettes,
                 width,
                 height,
@@ -241,6 +934,7 @@ impl ImageProcessor {
             }
             let result = TileReduceResult {
                 indexed_pixels,
+                tile_flips,
                 merged,
                 generation_id,
             };
@@ -277,6 +971,7 @@ impl ImageProcessor {
         width: u32,
         height: u32,
         settings: QualetizeSettings,
+        region_base: Option<(RegionOfInterest, QualetizeResult)>,
         cancel_receiver: mpsc::Receiver<()>,
         generation_id: u64,
     ) -> Result<QualetizeResult, String> {
@@ -289,8 +984,12 @@ impl ImageProcessor {
         }
 
         // Use the common qualetize processing function
-        let mut qualetize_result =
-            Self::perform_qualetize_processing(bgra_data, width, height, settings)?;
+        let mut qualetize_result = match region_base {
+            Some((region, base)) => {
+                Self::perform_qualetize_processing_in_region(&bgra_data, &region, base)
+            }
+            None => Self::perform_qualetize_processing(bgra_data, width, height, settings),
+        }?;
 
         // Set the generation ID for preview tracking
         qualetize_result.generation_id = generation_id;
@@ -298,12 +997,149 @@ impl ImageProcessor {
         Ok(qualetize_result)
     }
 
+    /// Catch the inputs the C library would reject, so the user gets the reason
+    /// instead of a bare failure code.
+    pub fn validate_qualetize_input(
+        bgra_data: &[BGRA8],
+        width: u32,
+        height: u32,
+        settings: &QualetizeSettings,
+    ) -> Result<(), String> {
+        let total_colors = settings.n_palettes as u32 * settings.n_colors as u32;
+        if settings.n_palettes == 0 || settings.n_colors == 0 || total_colors > 256 {
+            return Err(format!(
+                "{} palettes × {} colors = {total_colors} colors (must be 1-256)",
+                settings.n_palettes, settings.n_colors
+            ));
+        }
+        let Some(pixel_count) = width.checked_mul(height) else {
+            return Err(format!("Image is too large to process ({width}×{height})"));
+        };
+        if let Some(error) = settings.tile_size_error(width, height) {
+            return Err(error);
+        }
+        if bgra_data.len() != pixel_count as usize {
+            return Err(format!(
+                "Input has {} pixels, but {width}×{height} needs {pixel_count}",
+                bgra_data.len()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Re-quantize only the tiles overlapping `region`, compositing them into `base`.
+    ///
+    /// The palettes of `base` are kept as they are: each tile picks the palette that
+    /// fits its new pixels best and every pixel takes that palette's nearest color,
+    /// so the untouched tiles stay valid. Sprite sheets have one palette per cell,
+    /// which tiles can't choose freely, so they return `base` unchanged.
+    pub fn perform_qualetize_processing_in_region(
+        bgra_data: &[BGRA8],
+        region: &RegionOfInterest,
+        base: QualetizeResult,
+    ) -> Result<QualetizeResult, String> {
+        let mut result = base;
+        let (width, height) = (result.width, result.height);
+        if bgra_data.len() != (width * height) as usize {
+            return Err("Region input does not match the previous result".to_string());
+        }
+        let settings = &result.settings;
+        if settings.sprite_sheet_enabled {
+            return Ok(result);
+        }
+        let (tile_width, tile_height) = (settings.tile_width as u32, settings.tile_height as u32);
+        let Some((x0, y0, x1, y1)) = region.tile_bounds(tile_width, tile_height, width, height)
+        else {
+            return Ok(result);
+        };
+
+        let transparent_index = settings.uses_transparent_index();
+        let mut palette_ranges = Vec::new();
+        let mut start = 0;
+        for &size in &settings.palette_sizes() {
+            let end = (start + size as usize).min(result.palette_data.len());
+            if start < end {
+                palette_ranges.push(start..end);
+            }
+            start = end;
+        }
+        if palette_ranges.is_empty() {
+            return Err("Previous result has no palettes".to_string());
+        }
+
+        let palette_data = &result.palette_data;
+        let nearest = |range: &std::ops::Range<usize>, pixel: &BGRA8| -> (usize, u64) {
+            if transparent_index && pixel.a == 0 {
+                return (range.start, 0);
+            }
+            let first = range.start + transparent_index as usize;
+            (first..range.end)
+                .map(|i| (i, color_distance_sq(&palette_data[i], pixel)))
+                .min_by_key(|&(_, distance)| distance)
+                .unwrap_or((range.start, 0))
+        };
+
+        let stride = width as usize;
+        for tile_y in (y0..y1).step_by(tile_height.max(1) as usize) {
+            for tile_x in (x0..x1).step_by(tile_width.max(1) as usize) {
+                let pixels: Vec<usize> = (tile_y..(tile_y + tile_height).min(height))
+                    .flat_map(|y| {
+                        (tile_x..(tile_x + tile_width).min(width))
+                            .map(move |x| y as usize * stride + x as usize)
+                    })
+                    .collect();
+                let Some(best) = palette_ranges.iter().min_by_key(|range| {
+                    pixels
+                        .iter()
+                        .map(|&i| nearest(range, &bgra_data[i]).1)
+                        .sum::<u64>()
+                }) else {
+                    continue;
+                };
+                for &i in &pixels {
+                    result.indexed_data[i] = nearest(best, &bgra_data[i]).0 as u8;
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Run the C quantizer on a BGRA buffer.
+    ///
+    /// The plan exposes no seed, so repeatable output relies on the C side being
+    /// deterministic and on every buffer handed to it here being zero-initialised.
+    /// Identical inputs and settings must yield byte-identical results.
+    ///
+    /// Sprite sheet cells and split bands run in parallel on the current rayon pool;
+    /// a whole-image run is a single call into the C library on the calling thread.
     pub fn perform_qualetize_processing(
         bgra_data: Vec<BGRA8>,
         width: u32,
         height: u32,
-        settings: QualetizeSettings,
+        mut settings: QualetizeSettings,
     ) -> Result<QualetizeResult, String> {
+        // The UI ranges can lag behind quick edits, so never trust the palette budget here
+        let (requested_palettes, requested_colors) = (settings.n_palettes, settings.n_colors);
+        if settings.clamp_palette_budget() {
+            log::warn!(
+                "Clamped {requested_palettes} palettes x {requested_colors} colors to {} x {} (max {} colors)",
+                settings.n_palettes,
+                settings.n_colors,
+                settings.max_total_colors()
+            );
+        }
+
+        if settings.is_split() {
+            return Self::perform_split_processing(bgra_data, width, height, settings);
+        }
+
+        Self::validate_qualetize_input(&bgra_data, width, height, &settings)?;
+
+        if settings.sprite_sheet_enabled {
+            return Self::perform_sprite_sheet_processing(bgra_data, width, height, settings);
+        }
+
         // Create qualetize plan
         let plan = QualetizePlanOwned::from(settings.clone());
 
@@ -337,23 +1173,483 @@ impl ImageProcessor {
         };
 
         if result == 0 {
-            return Err("Qualetize processing failed".to_string());
+            return Err(format!(
+                "Qualetize processing failed in the C library ({width}×{height}, {} palettes × {} colors)",
+                settings.n_palettes, settings.n_colors
+            ));
+        }
+
+        log::debug!("Qualetize succeeded, RMSE: {:?}", rmse.f32);
+
+        // The preview texture and every exporter expect straight alpha
+        if settings.premul_alpha {
+            Self::unpremultiply_palette(&mut output_palette, &settings);
+        }
+        if let Some(weights) = settings.active_channel_weights() {
+            Self::remove_channel_weights(&mut output_palette, weights, &settings);
+        }
+
+        if let Some(clear) = settings.reserved_clear_color() {
+            Self::reserve_clear_color_slot(
+                &mut output_data,
+                &mut output_palette,
+                &bgra_data,
+                settings.n_colors as usize,
+                clear,
+            );
+        }
+
+        if settings.source_alpha_transparency {
+            Self::map_transparent_pixels_to_clear_index(
+                &mut output_data,
+                &bgra_data,
+                width,
+                height,
+                settings.tile_width as u32,
+                settings.tile_height as u32,
+                settings.n_colors as usize,
+            );
+        }
+
+        if settings.use_custom_palette_sizes {
+            output_palette = Self::shrink_palettes(
+                &mut output_data,
+                &output_palette,
+                settings.n_colors as usize,
+                &settings.palette_sizes(),
+                settings.uses_transparent_index(),
+            );
+        }
+
+        Ok(QualetizeResult {
+            indexed_data: output_data,
+            palette_data: output_palette,
+            settings,
+            width,
+            height,
+            generation_id: 0, // Not needed for export
+            rmse: rmse.f32,
+            split_parts: Vec::new(),
+        })
+    }
+
+    /// Quantize every cell of a sprite sheet on its own with a single palette,
+    /// then reassemble the cells into one image whose palettes are the
+    /// per-cell palettes in row-major cell order.
+    ///
+    /// Cells are independent, so they are quantized in parallel on the current rayon
+    /// pool. Results are reassembled in cell order, making the output identical for
+    /// any thread count.
+    fn perform_sprite_sheet_processing(
+        bgra_data: Vec<BGRA8>,
+        width: u32,
+        height: u32,
+        settings: QualetizeSettings,
+    ) -> Result<QualetizeResult, String> {
+        if let Some(error) = settings.sprite_sheet_error(width, height) {
+            return Err(error);
+        }
+
+        let cell_w = settings.sprite_cell_width as u32;
+        let cell_h = settings.sprite_cell_height as u32;
+        let cells_x = width / cell_w;
+        let cells_y = height / cell_h;
+        let cell_count = (cells_x * cells_y) as usize;
+
+        let cell_settings = QualetizeSettings {
+            n_palettes: 1,
+            use_custom_palette_sizes: false,
+            sprite_sheet_enabled: false,
+            ..settings.clone()
+        };
+
+        let stride = width as usize;
+        let mut indexed_data = vec![0u8; (width * height) as usize];
+        let mut palette_data: Vec<BGRA8> =
+            Vec::with_capacity(cell_count * settings.n_colors as usize);
+        let mut squared_error = [0.0f32; 4];
+
+        let cell_origin = |cell_idx: usize| {
+            let cx = cell_idx % cells_x as usize;
+            let cy = cell_idx / cells_x as usize;
+            (cy * cell_h as usize) * stride + cx * cell_w as usize
+        };
+        let cells: Vec<Vec<BGRA8>> = (0..cell_count)
+            .map(|cell_idx| {
+                let origin = cell_origin(cell_idx);
+                let mut cell = Vec::with_capacity((cell_w * cell_h) as usize);
+                for y in 0..cell_h as usize {
+                    let offset = origin + y * stride;
+                    cell.extend_from_slice(&bgra_data[offset..offset + cell_w as usize]);
+                }
+                cell
+            })
+            .collect();
+
+        let results: Vec<Result<QualetizeResult, String>> = cells
+            .into_par_iter()
+            .map(|cell| {
+                Self::perform_qualetize_processing(cell, cell_w, cell_h, cell_settings.clone())
+            })
+            .collect();
+
+        for (cell_idx, result) in results.into_iter().enumerate() {
+            let result = result?;
+            let origin = cell_origin(cell_idx);
+
+            let palette_offset = palette_data.len();
+            palette_data.extend_from_slice(&result.palette_data);
+            for y in 0..cell_h as usize {
+                let offset = origin + y * stride;
+                let row = &result.indexed_data[y * cell_w as usize..(y + 1) * cell_w as usize];
+                for (dst, &src) in indexed_data[offset..offset + cell_w as usize]
+                    .iter_mut()
+                    .zip(row)
+                {
+                    *dst = (palette_offset + src as usize) as u8;
+                }
+            }
+            for (acc, value) in squared_error.iter_mut().zip(result.rmse) {
+                *acc += value * value;
+            }
+        }
+
+        Ok(QualetizeResult {
+            indexed_data,
+            palette_data,
+            settings: QualetizeSettings {
+                n_palettes: cell_count as u16,
+                use_custom_palette_sizes: false,
+                ..settings
+            },
+            width,
+            height,
+            generation_id: 0,
+            rmse: squared_error.map(|acc| (acc / cell_count as f32).sqrt()),
+            split_parts: Vec::new(),
+        })
+    }
+
+    /// RMSE over the RGB channels of the rendered `result` against `bgra_data`, the
+    /// input it was quantized from. Unlike the quantizer's own RMSE this doesn't depend
+    /// on the color space, so runs in different spaces can be compared. Transparent
+    /// input pixels are skipped.
+    pub fn rgb_rmse(bgra_data: &[BGRA8], result: &QualetizeResult) -> f32 {
+        let parts: Vec<(&[u8], &[BGRA8])> = if result.split_parts.is_empty() {
+            vec![(&result.indexed_data, &result.palette_data)]
+        } else {
+            result
+                .split_parts
+                .iter()
+                .map(|part| (part.indexed_pixels.as_slice(), part.palettes.as_slice()))
+                .collect()
+        };
+        let mut squared_error = 0.0f64;
+        let mut count = 0usize;
+        let outputs = parts.iter().flat_map(|&(indices, palette)| {
+            indices
+                .iter()
+                .map(move |&index| palette.get(index as usize).copied())
+        });
+        for (input, output) in bgra_data.iter().zip(outputs) {
+            if input.a == 0 {
+                continue;
+            }
+            let [r, g, b, _] = output.map_or([0; 4], BGRA8::to_rgba);
+            for (expected, actual) in [(input.r, r), (input.g, g), (input.b, b)] {
+                squared_error += (expected as f64 - actual as f64).powi(2);
+            }
+            count += 3;
+        }
+        if count == 0 {
+            return 0.0;
+        }
+        (squared_error / count as f64).sqrt() as f32
+    }
+
+    /// Bands of whole tile rows, as (first row, height), for `part_count` split parts.
+    /// Tile rows are shared out as evenly as possible; there are never more bands
+    /// than tile rows.
+    pub fn split_bands(height: u32, tile_height: u32, part_count: usize) -> Vec<(u32, u32)> {
+        let tile_height = tile_height.max(1);
+        let tile_rows = height.div_ceil(tile_height) as usize;
+        let part_count = part_count.clamp(1, tile_rows.max(1));
+        let mut bands = Vec::with_capacity(part_count);
+        let mut row = 0;
+        for part in 0..part_count {
+            let rows = tile_rows / part_count + usize::from(part < tile_rows % part_count);
+            let y = row as u32 * tile_height;
+            let band_height = (rows as u32 * tile_height).min(height - y);
+            bands.push((y, band_height));
+            row += rows;
+        }
+        bands
+    }
+
+    /// More than 256 colors in total: quantize bands of tile rows on their own, each
+    /// with as many palettes as fit in 256 colors, so every band can be exported as its
+    /// own indexed file. Bands run in parallel on the current rayon pool.
+    ///
+    /// The combined result keeps each band's indices relative to its own palettes, so
+    /// it is only rendered and split-exported, never used as one indexed image.
+    fn perform_split_processing(
+        bgra_data: Vec<BGRA8>,
+        width: u32,
+        height: u32,
+        settings: QualetizeSettings,
+    ) -> Result<QualetizeResult, String> {
+        let palettes_per_part = (256 / settings.n_colors.max(1)).max(1) as usize;
+        let part_count = (settings.n_palettes as usize).div_ceil(palettes_per_part);
+        let bands = Self::split_bands(height, settings.tile_height as u32, part_count);
+        if bands.len() < part_count {
+            log::warn!(
+                "Only {} tile rows for {part_count} split parts; some palettes go unused",
+                bands.len()
+            );
         }
 
-        log::debug!("Qualetize succeeded, RMSE: {:?}", rmse.f32);
+        let stride = width as usize;
+        let mut remaining_palettes = settings.n_palettes as usize;
+        let jobs: Vec<(u32, u32, QualetizeSettings)> = bands
+            .iter()
+            .enumerate()
+            .map(|(index, &(y, band_height))| {
+                // Spread the palettes so later bands are not left with too few
+                let bands_left = bands.len() - index;
+                let n_palettes = remaining_palettes
+                    .div_ceil(bands_left)
+                    .clamp(1, palettes_per_part);
+                remaining_palettes = remaining_palettes.saturating_sub(n_palettes);
+                let part_settings = QualetizeSettings {
+                    n_palettes: n_palettes as u16,
+                    use_custom_palette_sizes: false,
+                    split_over_256: false,
+                    ..settings.clone()
+                };
+                (y, band_height, part_settings)
+            })
+            .collect();
+
+        let results: Vec<Result<QualetizeResult, String>> = jobs
+            .par_iter()
+            .map(|(y, band_height, part_settings)| {
+                let start = *y as usize * stride;
+                let band = bgra_data[start..start + *band_height as usize * stride].to_vec();
+                Self::perform_qualetize_processing(band, width, *band_height, part_settings.clone())
+            })
+            .collect();
+
+        let mut indexed_data = Vec::with_capacity((width * height) as usize);
+        let mut palette_data = Vec::new();
+        let mut split_parts = Vec::with_capacity(results.len());
+        let mut squared_error = [0.0f32; 4];
+        for ((y, band_height, part_settings), result) in jobs.into_iter().zip(results) {
+            let result = result?;
+            indexed_data.extend_from_slice(&result.indexed_data);
+            palette_data.extend_from_slice(&result.palette_data);
+            for (acc, value) in squared_error.iter_mut().zip(result.rmse) {
+                *acc += value * value * band_height as f32;
+            }
+            split_parts.push(SplitPart {
+                y,
+                height: band_height,
+                indexed_pixels: result.indexed_data,
+                palettes: result.palette_data,
+                palette_sizes: vec![
+                    part_settings.n_colors as usize;
+                    part_settings.n_palettes as usize
+                ],
+            });
+        }
 
         Ok(QualetizeResult {
-            indexed_data: output_data,
-            palette_data: output_palette,
+            indexed_data,
+            palette_data,
             settings,
             width,
             height,
-            generation_id: 0, // Not needed for export
+            generation_id: 0,
+            rmse: squared_error.map(|acc| (acc / height as f32).sqrt()),
+            split_parts,
         })
     }
 
+    /// Point every transparent source pixel at color 0 of its tile's palette.
+    /// The tile's palette is taken from its first opaque pixel; fully transparent
+    /// tiles use palette 0. Palettes must still be `n_colors` apart (before shrinking).
+    pub fn map_transparent_pixels_to_clear_index(
+        indexed: &mut [u8],
+        bgra_data: &[BGRA8],
+        width: u32,
+        height: u32,
+        tile_width: u32,
+        tile_height: u32,
+        n_colors: usize,
+    ) {
+        let (width, height) = (width as usize, height as usize);
+        let (tile_width, tile_height) = (tile_width.max(1) as usize, tile_height.max(1) as usize);
+        let n_colors = n_colors.max(1);
+
+        for tile_y in (0..height).step_by(tile_height) {
+            for tile_x in (0..width).step_by(tile_width) {
+                let tile_indices = || {
+                    (tile_y..(tile_y + tile_height).min(height)).flat_map(move |y| {
+                        (tile_x..(tile_x + tile_width).min(width)).map(move |x| y * width + x)
+                    })
+                };
+                let palette = tile_indices()
+                    .find(|&i| bgra_data[i].a != 0)
+                    .map_or(0, |i| indexed[i] as usize / n_colors);
+                for i in tile_indices().filter(|&i| bgra_data[i].a == 0) {
+                    indexed[i] = (palette * n_colors) as u8;
+                }
+            }
+        }
+    }
+
+    /// Keep slot 0 of every palette for the clear color alone. The plan already sets
+    /// `first_color_is_transparent` and `transparent_color`, so the library keys clear
+    /// pixels onto slot 0 and clusters visible pixels into the other `n_colors - 1`.
+    /// This pass only enforces that result: opaque pixels still found on slot 0 (e.g.
+    /// from dithering) move to the other color of their palette closest to it, and the
+    /// slot is set to the exact clear color (fully transparent) instead of whatever
+    /// value the library left there.
+    pub fn reserve_clear_color_slot(
+        indexed: &mut [u8],
+        palette: &mut [BGRA8],
+        bgra_data: &[BGRA8],
+        n_colors: usize,
+        clear: BGRA8,
+    ) {
+        if n_colors < 2 {
+            return;
+        }
+        let is_clear = |pixel: &BGRA8| {
+            pixel.a == 0 || (pixel.r, pixel.g, pixel.b) == (clear.r, clear.g, clear.b)
+        };
+        for (index, pixel) in indexed.iter_mut().zip(bgra_data) {
+            let slot_start = *index as usize / n_colors * n_colors;
+            if *index as usize != slot_start || is_clear(pixel) {
+                continue;
+            }
+            let Some(clustered) = palette.get(slot_start) else {
+                continue;
+            };
+            if let Some(nearest) = (slot_start + 1..(slot_start + n_colors).min(palette.len()))
+                .min_by_key(|&i| color_distance_sq(&palette[i], clustered))
+            {
+                *index = nearest as u8;
+            }
+        }
+        for slot in palette.iter_mut().step_by(n_colors) {
+            *slot = clear;
+        }
+    }
+
+    /// Convert premultiplied palette colors back to straight alpha, snapping each
+    /// channel back onto the RGB depth levels.
+    /// Fully transparent entries carry no color information and are left as they are.
+    pub fn unpremultiply_palette(palette: &mut [BGRA8], settings: &QualetizeSettings) {
+        let levels: [Option<Vec<u8>>; 3] = std::array::from_fn(|c| settings.channel_levels(c));
+        let restore = |value: u8, alpha: u8, channel: usize| {
+            nearest_level(
+                unpremultiply_channel(value, alpha),
+                levels[channel].as_deref(),
+            )
+        };
+        for color in palette.iter_mut() {
+            color.r = restore(color.r, color.a, 0);
+            color.g = restore(color.g, color.a, 1);
+            color.b = restore(color.b, color.a, 2);
+        }
+    }
+
+    /// Shrink each `n_colors`-sized palette down to its requested size by merging
+    /// the least-used similar colors, then pack the palettes back-to-back and
+    /// remap the pixel indices accordingly.
+    pub fn shrink_palettes(
+        indexed: &mut [u8],
+        palette: &[BGRA8],
+        n_colors: usize,
+        sizes: &[u16],
+        keep_first_color: bool,
+    ) -> Vec<BGRA8> {
+        let mut remap: Vec<u8> = (0..=255u8).collect();
+        let mut packed: Vec<BGRA8> = Vec::with_capacity(palette.len());
+
+        let mut usage = vec![0usize; palette.len()];
+        for &pixel_index in indexed.iter() {
+            if let Some(count) = usage.get_mut(pixel_index as usize) {
+                *count += 1;
+            }
+        }
+
+        for (palette_idx, &size) in sizes.iter().enumerate() {
+            let start = palette_idx * n_colors;
+            let end = (start + n_colors).min(palette.len());
+            if start >= end {
+                break;
+            }
+            let colors = &palette[start..end];
+            let mut counts = usage[start..end].to_vec();
+            let target = (size as usize).clamp(1, colors.len());
+
+            // merged_into[i] is the slot whose color replaces slot i
+            let mut merged_into: Vec<usize> = (0..colors.len()).collect();
+            let mut alive: Vec<usize> = (0..colors.len()).collect();
+
+            while alive.len() > target {
+                let mut best: Option<(u64, usize, usize)> = None;
+                for (pos, &a) in alive.iter().enumerate() {
+                    for &b in &alive[pos + 1..] {
+                        let weight = counts[a].min(counts[b]) as u64;
+                        let mut cost = color_distance_sq(&colors[a], &colors[b]) * weight;
+                        // Never fold visible colors into the transparent slot unless forced to
+                        if keep_first_color && a == 0 && counts[b] > 0 {
+                            cost = u64::MAX;
+                        }
+                        if best.is_none_or(|(best_cost, _, _)| cost < best_cost) {
+                            let keep_a = (keep_first_color && a == 0) || counts[a] >= counts[b];
+                            best = Some(if keep_a { (cost, a, b) } else { (cost, b, a) });
+                        }
+                    }
+                }
+                let Some((_, keep, drop)) = best else {
+                    break;
+                };
+                counts[keep] += counts[drop];
+                for slot in merged_into.iter_mut() {
+                    if *slot == drop {
+                        *slot = keep;
+                    }
+                }
+                alive.retain(|&i| i != drop);
+            }
+
+            let offset = packed.len();
+            let mut new_slot = vec![0usize; colors.len()];
+            for (new_idx, &old_idx) in alive.iter().enumerate() {
+                packed.push(colors[old_idx]);
+                new_slot[old_idx] = offset + new_idx;
+            }
+            for (old_idx, &target_idx) in merged_into.iter().enumerate() {
+                remap[start + old_idx] = new_slot[target_idx] as u8;
+            }
+        }
+
+        for pixel_index in indexed.iter_mut() {
+            *pixel_index = remap[*pixel_index as usize];
+        }
+
+        packed
+    }
+
+    /// Merge tiles within `opts.threshold` of an earlier one into copies of it,
+    /// recording in `tile_flips` how each tile was oriented. Returns the number of
+    /// merged tiles, or `usize::MAX` when cancelled.
     pub fn reduce_tiles_indexed(
         indexed: &mut [u8],
+        tile_flips: &mut Vec<TileFlip>,
         palette: &[BGRA8],
         width: u32,
         height: u32,
@@ -381,6 +1677,8 @@ impl ImageProcessor {
         let stride = width as usize;
         let orientation_maps =
             Orientation::maps(tile_w, tile_h, opts.allow_flip_x, opts.allow_flip_y);
+        tile_flips.clear();
+        tile_flips.resize((tiles_x * tiles_y) as usize, TileFlip::default());
 
         let mut tile_indices_buf = vec![0u8; tile_area];
         let mut tile_colors_buf = vec![[0u8; 4]; tile_area];
@@ -443,6 +1741,7 @@ impl ImageProcessor {
             }
 
             if let Some((rep_idx, orientation)) = matched {
+                tile_flips[(ty * tiles_x + tx) as usize] = orientation.tile_flip();
                 let rep = &representatives[rep_idx];
                 let map = orientation_maps
                     .iter()
@@ -498,7 +1797,7 @@ impl ImageProcessor {
     fn expand_indices_to_colors_into(indices: &[u8], palette: &[BGRA8], out: &mut [[u8; 4]]) {
         for (dst, &idx) in out.iter_mut().zip(indices.iter()) {
             if let Some(color) = palette.get(idx as usize) {
-                *dst = [color.r, color.g, color.b, color.a];
+                *dst = color.to_rgba();
             } else {
                 *dst = [0, 0, 0, 0];
             }
@@ -663,6 +1962,97 @@ impl ImageProcessor {
     }
 }
 
+/// Edge energy between each column (or row) and the previous one; index 0 is unused
+fn edge_profile(rgba: &[u8], width: usize, height: usize, columns: bool) -> Vec<f32> {
+    let pixel = |x: usize, y: usize| &rgba[(y * width + x) * 4..][..4];
+    let difference = |a: &[u8], b: &[u8]| -> f32 {
+        a.iter()
+            .zip(b)
+            .map(|(&a, &b)| (a as f32 - b as f32).abs())
+            .sum()
+    };
+    let (len, across) = if columns {
+        (width, height)
+    } else {
+        (height, width)
+    };
+    let mut profile = vec![0.0; len];
+    for (i, energy) in profile.iter_mut().enumerate().skip(1) {
+        *energy = (0..across)
+            .map(|j| {
+                if columns {
+                    difference(pixel(i, j), pixel(i - 1, j))
+                } else {
+                    difference(pixel(j, i), pixel(j, i - 1))
+                }
+            })
+            .sum();
+    }
+    profile
+}
+
+/// Periods that divide the axis and whose grid lines carry clearly more edge energy
+/// than average, strongest first, with harmonics of the same grid removed
+fn grid_periods(profile: &[f32]) -> Vec<(usize, f32)> {
+    let len = profile.len();
+    if len < 4 {
+        return Vec::new();
+    }
+    let mean = profile[1..].iter().sum::<f32>() / (len - 1) as f32;
+    if mean <= 0.0 {
+        return Vec::new();
+    }
+
+    let mut scores: Vec<(usize, f32)> = (2..=GRID_MAX_PERIOD.min(len / 2))
+        .filter(|period| len.is_multiple_of(*period))
+        .map(|period| {
+            let lines: Vec<f32> = (period..len).step_by(period).map(|i| profile[i]).collect();
+            let on_grid = lines.iter().sum::<f32>() / lines.len() as f32;
+            (period, on_grid / mean)
+        })
+        .filter(|&(_, strength)| strength >= GRID_MIN_STRENGTH)
+        .collect();
+    scores.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    let mut periods: Vec<(usize, f32)> = Vec::new();
+    for &(period, strength) in &scores {
+        let related = |other: usize| period.is_multiple_of(other) || other.is_multiple_of(period);
+        if periods.iter().any(|&(accepted, _)| related(accepted)) {
+            continue;
+        }
+        let fundamental = scores
+            .iter()
+            .filter(|&&(divisor, score)| {
+                period.is_multiple_of(divisor) && score >= strength * GRID_HARMONIC_RATIO
+            })
+            .min_by_key(|&&(divisor, _)| divisor)
+            .copied()
+            .unwrap_or((period, strength));
+        periods.push(fundamental);
+    }
+    periods
+}
+
+fn color_distance_sq(a: &BGRA8, b: &BGRA8) -> u64 {
+    let dr = a.r as i64 - b.r as i64;
+    let dg = a.g as i64 - b.g as i64;
+    let db = a.b as i64 - b.b as i64;
+    let da = a.a as i64 - b.a as i64;
+    (dr * dr + dg * dg + db * db + da * da) as u64
+}
+
+/// Closest of `levels` to `value`, or `value` itself when the channel is unquantized
+fn nearest_level(value: u8, levels: Option<&[u8]>) -> u8 {
+    levels
+        .and_then(|levels| {
+            levels
+                .iter()
+                .min_by_key(|&&level| (level as i16 - value as i16).abs())
+        })
+        .copied()
+        .unwrap_or(value)
+}
+
 #[derive(Clone, Copy, PartialEq)]
 enum Orientation {
     None,
@@ -672,6 +2062,13 @@ enum Orientation {
 }
 
 impl Orientation {
+    fn tile_flip(self) -> TileFlip {
+        TileFlip {
+            flip_x: matches!(self, Orientation::FlipX | Orientation::FlipXY),
+            flip_y: matches!(self, Orientation::FlipY | Orientation::FlipXY),
+        }
+    }
+
     fn available(allow_flip_x: bool, allow_flip_y: bool) -> Vec<Orientation> {
         let mut v = vec![Orientation::None];
         if allow_flip_x {
@@ -718,3 +2115,729 @@ struct OrientationMap {
     orientation: Orientation,
     map: Vec<usize>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ClearColor;
+
+    fn gradient_image(width: u32, height: u32) -> Vec<BGRA8> {
+        (0..width * height)
+            .map(|i| {
+                let x = i % width;
+                let y = i / width;
+                BGRA8 {
+                    b: (x * 255 / (width - 1)) as u8,
+                    g: (y * 255 / (height - 1)) as u8,
+                    r: ((x + y) * 255 / (width + height - 2)) as u8,
+                    a: 255,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_qualetize_is_deterministic() {
+        let settings = QualetizeSettings::genesis_full_palettes();
+
+        let first = ImageProcessor::perform_qualetize_processing(
+            gradient_image(32, 32),
+            32,
+            32,
+            settings.clone(),
+        )
+        .unwrap();
+        let second =
+            ImageProcessor::perform_qualetize_processing(gradient_image(32, 32), 32, 32, settings)
+                .unwrap();
+
+        assert_eq!(first.indexed_data, second.indexed_data);
+        assert_eq!(first.palette_data, second.palette_data);
+    }
+
+    #[test]
+    fn test_over_budget_settings_are_clamped() {
+        let settings = QualetizeSettings {
+            n_palettes: 16,
+            n_colors: 32,
+            ..QualetizeSettings::default()
+        };
+
+        let result =
+            ImageProcessor::perform_qualetize_processing(gradient_image(32, 32), 32, 32, settings)
+                .unwrap();
+
+        assert_eq!(result.settings.n_colors, 32);
+        assert_eq!(result.settings.n_palettes, 8);
+        assert_eq!(result.palette_data.len(), 256);
+    }
+
+    #[test]
+    fn test_transparent_pixels_map_to_their_tile_palette_clear_index() {
+        let opaque = BGRA8 {
+            b: 0,
+            g: 0,
+            r: 0,
+            a: 255,
+        };
+        let clear = BGRA8 { a: 0, ..opaque };
+        // Two 1x2 tiles: the left one uses palette 1, the right one is fully transparent
+        let pixels = vec![opaque, clear, clear, clear];
+        let mut indexed = vec![5, 3, 7, 6];
+
+        ImageProcessor::map_transparent_pixels_to_clear_index(&mut indexed, &pixels, 2, 2, 1, 2, 4);
+
+        assert_eq!(indexed, vec![5, 0, 4, 0]);
+    }
+
+    #[test]
+    fn test_region_requantize_only_touches_overlapping_tiles() {
+        let color = |r: u8, g: u8, b: u8| BGRA8 { b, g, r, a: 255 };
+        let (black, white) = (color(0, 0, 0), color(255, 255, 255));
+        let (red, blue) = (color(255, 0, 0), color(0, 0, 255));
+        let settings = QualetizeSettings {
+            tile_width: 2,
+            tile_height: 2,
+            n_palettes: 2,
+            n_colors: 2,
+            col0_is_clear: false,
+            ..QualetizeSettings::default()
+        };
+        let base = QualetizeResult {
+            indexed_data: vec![0; 8],
+            palette_data: vec![black, white, red, blue],
+            settings,
+            width: 4,
+            height: 2,
+            generation_id: 0,
+            rmse: [0.0; 4],
+            split_parts: Vec::new(),
+        };
+        // Both 2x2 tiles changed, but the region only covers one pixel of the right one
+        #[rustfmt::skip]
+        let pixels = vec![
+            white, white, red, blue,
+            white, white, blue, red,
+        ];
+        let region = RegionOfInterest {
+            x: 3,
+            y: 0,
+            width: 1,
+            height: 1,
+        };
+
+        let result =
+            ImageProcessor::perform_qualetize_processing_in_region(&pixels, &region, base).unwrap();
+
+        assert_eq!(result.indexed_data, vec![0, 0, 2, 3, 0, 0, 3, 2]);
+    }
+
+    #[test]
+    fn test_rgb_rmse_skips_transparent_pixels() {
+        let color = |r: u8, g: u8, b: u8, a: u8| BGRA8 { b, g, r, a };
+        let result = QualetizeResult {
+            indexed_data: vec![0, 0, 1],
+            palette_data: vec![color(10, 10, 10, 255), color(0, 0, 0, 0)],
+            settings: QualetizeSettings::default(),
+            width: 3,
+            height: 1,
+            generation_id: 0,
+            rmse: [0.0; 4],
+            split_parts: Vec::new(),
+        };
+        let input = [
+            color(10, 10, 10, 255),
+            color(13, 14, 10, 255),
+            color(200, 0, 0, 0),
+        ];
+
+        let rmse = ImageProcessor::rgb_rmse(&input, &result);
+
+        // (3² + 4²) over the 6 channels of the two opaque pixels
+        assert!((rmse - (25.0f32 / 6.0).sqrt()).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_mask_composite_keeps_outside_pixels() {
+        let mask = RegionOfInterest {
+            x: 1,
+            y: 0,
+            width: 1,
+            height: 2,
+        };
+        let inside = [1u8; 3 * 2 * 4];
+        let outside = [9u8; 3 * 2 * 4];
+
+        let pixels = mask.composite(&inside, &outside, 3);
+
+        let first_channel: Vec<u8> = pixels.chunks_exact(4).map(|pixel| pixel[0]).collect();
+        assert_eq!(first_channel, vec![9, 1, 9, 9, 1, 9]);
+    }
+
+    #[test]
+    fn test_clear_color_owns_slot_zero_of_every_palette() {
+        let magenta = BGRA8 {
+            b: 255,
+            g: 0,
+            r: 255,
+            a: 255,
+        };
+        let settings = QualetizeSettings {
+            col0_is_clear: true,
+            clear_color: ClearColor::Rgb(255, 0, 255),
+            ..QualetizeSettings::genesis()
+        };
+        // Left quarter keyed out, the rest a gradient
+        let pixels: Vec<BGRA8> = gradient_image(32, 32)
+            .into_iter()
+            .enumerate()
+            .map(|(i, pixel)| if i % 32 < 8 { magenta } else { pixel })
+            .collect();
+        let n_colors = settings.n_colors as usize;
+
+        let result =
+            ImageProcessor::perform_qualetize_processing(pixels.clone(), 32, 32, settings).unwrap();
+
+        for slot in result.palette_data.iter().step_by(n_colors) {
+            assert_eq!((slot.r, slot.g, slot.b, slot.a), (255, 0, 255, 0));
+        }
+        for (pixel, &index) in pixels.iter().zip(&result.indexed_data) {
+            assert_eq!(
+                *pixel == magenta,
+                (index as usize).is_multiple_of(n_colors),
+                "only keyed pixels may use slot 0"
+            );
+        }
+    }
+
+    #[test]
+    fn test_reserve_clear_color_slot_moves_opaque_pixels_off_slot_zero() {
+        let color = |r: u8, g: u8, b: u8, a: u8| BGRA8 { b, g, r, a };
+        let clear = color(255, 0, 255, 0);
+        // Two palettes of three colors; slot 0 of each holds a clustered value
+        let mut palette = vec![
+            color(10, 10, 10, 255),
+            color(12, 12, 12, 255),
+            color(200, 200, 200, 255),
+            color(90, 90, 90, 255),
+            color(250, 250, 250, 255),
+            color(80, 80, 80, 255),
+        ];
+        let pixels = [
+            color(255, 0, 255, 255),
+            color(10, 10, 10, 255),
+            color(0, 0, 0, 0),
+            color(90, 90, 90, 255),
+        ];
+        let mut indexed = vec![0, 0, 3, 3];
+
+        ImageProcessor::reserve_clear_color_slot(&mut indexed, &mut palette, &pixels, 3, clear);
+
+        assert_eq!(palette[0], clear);
+        assert_eq!(palette[3], clear);
+        // Keyed and transparent pixels stay on slot 0, opaque ones move to the nearest
+        // other color of the same palette
+        assert_eq!(indexed, vec![0, 1, 3, 5]);
+    }
+
+    #[test]
+    fn test_1bit_alpha_target_has_only_opaque_or_clear_colors() {
+        let mut settings = QualetizeSettings::gba_nds();
+        settings.premul_alpha = true;
+        let pixels: Vec<BGRA8> = gradient_image(16, 16)
+            .into_iter()
+            .enumerate()
+            .map(|(i, pixel)| BGRA8 {
+                a: (i % 16 * 17) as u8,
+                ..pixel
+            })
+            .collect();
+        let result =
+            ImageProcessor::perform_qualetize_processing(pixels, 16, 16, settings).unwrap();
+
+        let output = ImageData::create_from_qualetize_result(
+            result,
+            egui::Color32::BLACK,
+            &egui::Context::default(),
+        )
+        .unwrap();
+
+        let indexed = output.indexed.unwrap();
+        assert!(
+            indexed
+                .palettes
+                .iter()
+                .all(|color| matches!(color.a, 0 | 255))
+        );
+        assert!(
+            output
+                .rgba_data
+                .chunks_exact(4)
+                .all(|px| matches!(px[3], 0 | 255))
+        );
+    }
+
+    #[test]
+    fn test_channel_weights_round_trip_onto_depth_levels() {
+        let settings = QualetizeSettings {
+            rgba_depth: "3338".to_string(),
+            use_custom_levels: false,
+            channel_weights: [1.0, 0.5, 0.25],
+            ..QualetizeSettings::default()
+        };
+        let weights = settings.active_channel_weights().unwrap();
+        let original = BGRA8 {
+            b: 182,
+            g: 109,
+            r: 255,
+            a: 255,
+        };
+        let mut palette = vec![original];
+
+        ImageProcessor::apply_channel_weights(&mut palette, weights);
+        assert_eq!((palette[0].r, palette[0].g, palette[0].b), (255, 55, 46));
+        ImageProcessor::remove_channel_weights(&mut palette, weights, &settings);
+
+        // Restored values land back on the 3-bit levels (multiples of 255/7)
+        assert_eq!(palette[0], original);
+    }
+
+    #[test]
+    fn test_seam_carve_keeps_detailed_columns() {
+        // Checkerboard on the left five columns, flat gray on the right five
+        let (width, height) = (10u32, 6u32);
+        let pixel = |x: u32, y: u32| {
+            if x < 5 {
+                if (x + y).is_multiple_of(2) {
+                    [0, 0, 0, 255]
+                } else {
+                    [255, 255, 255, 255]
+                }
+            } else {
+                [128, 128, 128, 255]
+            }
+        };
+        let rgba: Vec<u8> = (0..height)
+            .flat_map(|y| (0..width).flat_map(move |x| pixel(x, y)))
+            .collect();
+
+        for target_width in [8, 12] {
+            let carved =
+                ImageProcessor::seam_carve(&rgba, width, height, target_width, height).unwrap();
+            assert_eq!(carved.len(), (target_width * height * 4) as usize);
+            for y in 0..height {
+                for x in 0..5 {
+                    let i = ((y * target_width + x) * 4) as usize;
+                    assert_eq!(
+                        carved[i..i + 4],
+                        pixel(x, y),
+                        "({x}, {y}) at width {target_width}"
+                    );
+                }
+            }
+        }
+
+        let taller = ImageProcessor::seam_carve(&rgba, width, height, width, 8).unwrap();
+        assert_eq!(taller.len(), (width * 8 * 4) as usize);
+        assert!(ImageProcessor::seam_carve(&rgba, width, height, 30, height).is_err());
+    }
+
+    #[test]
+    fn test_depth_rounding_modes() {
+        // 2 bits: levels 0, 85, 170, 255
+        let levels = [0, 1, 2].map(|_| Some(vec![0, 85, 170, 255]));
+        let gray = |v| BGRA8 {
+            b: v,
+            g: v,
+            r: v,
+            a: 255,
+        };
+
+        let mut pixels = vec![gray(160), gray(255), gray(10)];
+        ImageProcessor::apply_depth_rounding(&mut pixels, 3, &levels, DepthRounding::Truncate);
+        assert_eq!(pixels.iter().map(|p| p.r).collect::<Vec<_>>(), [85, 255, 0]);
+
+        let mut pixels = vec![gray(160), gray(255), gray(10)];
+        ImageProcessor::apply_depth_rounding(&mut pixels, 3, &levels, DepthRounding::Round);
+        assert_eq!(
+            pixels.iter().map(|p| p.r).collect::<Vec<_>>(),
+            [170, 255, 0]
+        );
+
+        // A quarter of the way from 85 to 170 picks the upper level in 4 of 16 cells,
+        // one of which is transparent and left alone
+        let mut pixels = vec![gray(106); 16];
+        pixels[0].a = 0;
+        ImageProcessor::apply_depth_rounding(&mut pixels, 4, &levels, DepthRounding::Dither);
+        assert_eq!(pixels[0].r, 106);
+        let upper = pixels[1..].iter().filter(|p| p.r == 170).count();
+        assert!(pixels[1..].iter().all(|p| p.r == 85 || p.r == 170));
+        assert_eq!(upper, 3);
+    }
+
+    #[test]
+    fn test_bayer_pattern_is_balanced_and_skips_transparent_pixels() {
+        let gray = BGRA8 {
+            b: 128,
+            g: 128,
+            r: 128,
+            a: 255,
+        };
+        let mut pixels = vec![gray; 16];
+        pixels[5].a = 0;
+
+        ImageProcessor::apply_bayer_pattern(&mut pixels, 4, 1.0);
+
+        assert_eq!(pixels[5].r, 128);
+        let values: Vec<i32> = pixels.iter().map(|p| p.r as i32).collect();
+        assert!(values.iter().any(|&v| v < 128) && values.iter().any(|&v| v > 128));
+        // Offsets are symmetric around zero, so the mean stays put
+        let mean = values.iter().sum::<i32>() as f32 / values.len() as f32;
+        assert!((mean - 128.0).abs() < 3.0);
+        assert!(pixels.iter().all(|p| p.r == p.g && p.g == p.b));
+    }
+
+    #[test]
+    fn test_merge_near_duplicate_colors_removes_noise_but_keeps_gradients() {
+        let pixel = |r, g, b| BGRA8 { b, g, r, a: 255 };
+        let mut pixels = vec![pixel(100, 100, 100); 40];
+        pixels.extend([pixel(102, 99, 101), pixel(98, 100, 103)]);
+        // One pixel per step, like a smooth ramp
+        pixels.extend((0..20).map(|i| pixel(i * 2, 0, 200)));
+        let gradient = pixels[42..].to_vec();
+
+        ImageProcessor::merge_near_duplicate_colors(&mut pixels, 4);
+
+        assert!(pixels[..42].iter().all(|&p| p == pixel(100, 100, 100)));
+        assert_eq!(&pixels[42..], &gradient[..]);
+    }
+
+    #[test]
+    fn test_limit_hues_merges_nearby_hues_and_keeps_grays() {
+        let pixel = |r, g, b| BGRA8 { b, g, r, a: 255 };
+        // Two reds about 9° apart, two greens about 9° apart and a gray
+        let mut pixels = vec![
+            pixel(255, 0, 0),
+            pixel(255, 40, 0),
+            pixel(0, 255, 0),
+            pixel(40, 255, 0),
+            pixel(128, 128, 128),
+        ];
+
+        ImageProcessor::limit_hues(&mut pixels, 2);
+
+        let hue = |p: &BGRA8| {
+            ColorProcessor::rgb_to_hsv(p.r as f32 / 255.0, p.g as f32 / 255.0, p.b as f32 / 255.0).0
+        };
+        assert!((hue(&pixels[0]) - hue(&pixels[1])).abs() < 1.0);
+        assert!((hue(&pixels[2]) - hue(&pixels[3])).abs() < 1.0);
+        assert!((hue(&pixels[0]) - hue(&pixels[2])).abs() > 90.0);
+        assert_eq!(pixels[4], pixel(128, 128, 128));
+    }
+
+    #[test]
+    fn test_detect_tile_grid_finds_cell_size() {
+        // 48x32 image of solid 8x8 cells with distinct colors
+        let (width, height) = (48u32, 32u32);
+        let rgba: Vec<u8> = (0..width * height)
+            .flat_map(|i| {
+                let cell = (i % width) / 8 + (i / width) / 8 * 6;
+                [
+                    (cell * 37 % 256) as u8,
+                    (cell * 91 % 256) as u8,
+                    (cell * 13) as u8,
+                    255,
+                ]
+            })
+            .collect();
+
+        let suggestions = ImageProcessor::detect_tile_grid(&rgba, width, height);
+
+        let best = suggestions.first().expect("grid should be detected");
+        assert_eq!((best.tile_width, best.tile_height), (8, 8));
+    }
+
+    #[test]
+    fn test_detect_tile_grid_ignores_flat_images() {
+        let rgba = [128u8; 32 * 32 * 4];
+        assert!(ImageProcessor::detect_tile_grid(&rgba, 32, 32).is_empty());
+    }
+
+    #[test]
+    fn test_denoise_smooths_noise_and_keeps_edges() {
+        // Noisy dark left half, noisy bright right half
+        let noise = [3i16, -4, 2, -3, 4, -2, 3, -4];
+        let (width, height) = (8u32, 4u32);
+        let pixels: Vec<u8> = (0..width * height)
+            .flat_map(|i| {
+                let base = if i % width < width / 2 { 60 } else { 190 };
+                let value = (base + noise[(i as usize * 3) % noise.len()]) as u8;
+                [value, value, value, 255]
+            })
+            .collect();
+
+        let denoised = ColorProcessor::denoise(&pixels, width, height, 0.3, 2);
+
+        let spread = |data: &[u8], left: bool| {
+            let values: Vec<u8> = data
+                .chunks_exact(4)
+                .enumerate()
+                .filter(|(i, _)| (*i as u32 % width < width / 2) == left)
+                .map(|(_, pixel)| pixel[0])
+                .collect();
+            values.iter().max().unwrap() - values.iter().min().unwrap()
+        };
+        assert!(spread(&denoised, true) < spread(&pixels, true));
+        assert!(spread(&denoised, false) < spread(&pixels, false));
+        // The edge between the halves is not blurred
+        for (i, pixel) in denoised.chunks_exact(4).enumerate() {
+            if i as u32 % width < width / 2 {
+                assert!(pixel[0] < 70, "left pixel {i} = {}", pixel[0]);
+            } else {
+                assert!(pixel[0] > 180, "right pixel {i} = {}", pixel[0]);
+            }
+            assert_eq!(pixel[3], 255);
+        }
+    }
+
+    #[test]
+    fn test_mask_transparent_pixels_uses_tile_average() {
+        let clear = BGRA8 {
+            b: 255,
+            g: 0,
+            r: 255,
+            a: 0,
+        };
+        let mut pixels = vec![
+            BGRA8 {
+                b: 10,
+                g: 20,
+                r: 30,
+                a: 255,
+            },
+            clear,
+            clear,
+            clear,
+        ];
+
+        ImageProcessor::mask_transparent_pixels(&mut pixels, 2, 2, 1, 2);
+
+        assert_eq!(
+            pixels[2],
+            BGRA8 {
+                b: 10,
+                g: 20,
+                r: 30,
+                a: 0
+            }
+        );
+        // The right column tile has no opaque pixels and falls back to the image average
+        assert_eq!(
+            pixels[1],
+            BGRA8 {
+                b: 10,
+                g: 20,
+                r: 30,
+                a: 0
+            }
+        );
+        assert_eq!(
+            pixels[3],
+            BGRA8 {
+                b: 10,
+                g: 20,
+                r: 30,
+                a: 0
+            }
+        );
+    }
+
+    /// Quantize a `size`x`size` gradient as a sprite sheet of 16x16 cells on a
+    /// pool of `thread_count` threads
+    fn run_sprite_sheet(thread_count: usize, size: u32) -> QualetizeResult {
+        let settings = QualetizeSettings {
+            sprite_sheet_enabled: true,
+            sprite_cell_width: 16,
+            sprite_cell_height: 16,
+            ..QualetizeSettings::default()
+        };
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(thread_count)
+            .build()
+            .unwrap();
+        pool.install(|| {
+            ImageProcessor::perform_qualetize_processing(
+                gradient_image(size, size),
+                size,
+                size,
+                settings,
+            )
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_sprite_sheet_threads_match_single_thread() {
+        let single = run_sprite_sheet(1, 64);
+        let threaded = run_sprite_sheet(4, 64);
+
+        assert_eq!(single.indexed_data, threaded.indexed_data);
+        assert_eq!(single.palette_data, threaded.palette_data);
+        assert_eq!(single.rmse, threaded.rmse);
+    }
+
+    /// Run with `cargo test --release -- --ignored --nocapture bench_sprite_sheet`
+    #[test]
+    #[ignore]
+    fn bench_sprite_sheet_threads() {
+        _ = env_logger::builder()
+            .is_test(true)
+            .filter_level(log::LevelFilter::Info)
+            .try_init();
+        let threads = std::thread::available_parallelism().map_or(4, |n| n.get());
+
+        let start = std::time::Instant::now();
+        let single = run_sprite_sheet(1, 256);
+        let single_time = start.elapsed();
+        let start = std::time::Instant::now();
+        let threaded = run_sprite_sheet(threads, 256);
+        let threaded_time = start.elapsed();
+
+        log::info!(
+            "Sprite sheet 256x256: 1 thread {single_time:?}, {threads} threads {threaded_time:?} ({:.2}x)",
+            single_time.as_secs_f64() / threaded_time.as_secs_f64()
+        );
+        assert_eq!(single.indexed_data, threaded.indexed_data);
+    }
+
+    #[test]
+    fn test_unpremultiply_palette_restores_translucent_colors() {
+        let mut palette = vec![
+            BGRA8 {
+                b: 0,
+                g: 32,
+                r: 64,
+                a: 128,
+            },
+            BGRA8 {
+                b: 10,
+                g: 20,
+                r: 30,
+                a: 255,
+            },
+            BGRA8 {
+                b: 0,
+                g: 0,
+                r: 0,
+                a: 0,
+            },
+        ];
+
+        let settings = QualetizeSettings {
+            rgba_depth: "8888".to_string(),
+            use_custom_levels: false,
+            ..QualetizeSettings::default()
+        };
+        ImageProcessor::unpremultiply_palette(&mut palette, &settings);
+
+        assert_eq!(
+            palette[0],
+            BGRA8 {
+                b: 0,
+                g: 64,
+                r: 128,
+                a: 128
+            }
+        );
+        assert_eq!(palette[1].r, 30);
+        assert_eq!(palette[2].a, 0);
+    }
+
+    #[test]
+    fn test_unpremultiply_palette_snaps_to_channel_levels() {
+        let settings = QualetizeSettings {
+            rgba_depth: "5551".to_string(),
+            use_custom_levels: false,
+            ..QualetizeSettings::default()
+        };
+        // 66 at half alpha unpremultiplies to 131, which sits between 5-bit levels
+        let mut palette = vec![BGRA8 {
+            b: 66,
+            g: 66,
+            r: 66,
+            a: 128,
+        }];
+
+        ImageProcessor::unpremultiply_palette(&mut palette, &settings);
+
+        let levels = settings.channel_levels(0).unwrap();
+        for value in [palette[0].r, palette[0].g, palette[0].b] {
+            assert!(levels.contains(&value), "{value} is not on {levels:?}");
+        }
+    }
+
+    #[test]
+    fn test_palette_budget_is_validated() {
+        let settings = QualetizeSettings {
+            n_palettes: 16,
+            n_colors: 32,
+            ..QualetizeSettings::default()
+        };
+        let error =
+            ImageProcessor::validate_qualetize_input(&gradient_image(8, 8), 8, 8, &settings)
+                .unwrap_err();
+        assert!(error.contains("512 colors"));
+    }
+
+    #[test]
+    fn test_oversized_image_is_validated() {
+        let settings = QualetizeSettings::default();
+        let error =
+            ImageProcessor::validate_qualetize_input(&[], 1 << 16, 1 << 16, &settings).unwrap_err();
+        assert!(error.contains("too large"));
+    }
+
+    #[test]
+    fn test_invalid_tile_size_is_validated() {
+        let settings = QualetizeSettings::default();
+        let error =
+            ImageProcessor::validate_qualetize_input(&gradient_image(12, 8), 12, 8, &settings)
+                .unwrap_err();
+        assert!(error.contains("not divisible by tile size"));
+    }
+
+    #[test]
+    fn test_pixel_buffer_size_is_validated() {
+        let settings = QualetizeSettings::default();
+        let error =
+            ImageProcessor::validate_qualetize_input(&gradient_image(8, 8), 16, 8, &settings)
+                .unwrap_err();
+        assert!(error.contains("Input has 64 pixels"));
+        assert!(
+            ImageProcessor::validate_qualetize_input(&gradient_image(16, 8), 16, 8, &settings)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_degenerate_images_are_rejected() {
+        let settings = QualetizeSettings::default();
+
+        let empty =
+            ImageProcessor::perform_qualetize_processing(Vec::new(), 0, 0, settings.clone());
+        assert!(empty.unwrap_err().contains("no pixels"));
+
+        let single_pixel = ImageProcessor::perform_qualetize_processing(
+            vec![BGRA8 {
+                b: 0,
+                g: 0,
+                r: 0,
+                a: 255,
+            }],
+            1,
+            1,
+            settings,
+        );
+        assert!(single_pixel.unwrap_err().contains("smaller than one tile"));
+    }
+}
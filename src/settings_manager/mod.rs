@@ -14,6 +14,13 @@ pub struct SettingsBundle {
     pub version: String,
 }
 
+/// Settings saved under a name as a user preset
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NamedBundle {
+    pub name: String,
+    pub bundle: SettingsBundle,
+}
+
 impl SettingsBundle {
     pub fn new(
         qualetize_settings: QualetizeSettings,
@@ -65,6 +72,90 @@ impl SettingsBundle {
     pub fn get_settings_file_extension() -> &'static str {
         "qset"
     }
+
+    /// Settings autosaved during the session; removed again on a clean exit
+    pub fn recovery_file_path() -> Result<std::path::PathBuf, String> {
+        Ok(Self::get_default_settings_dir()?
+            .join(format!("recovery.{}", Self::get_settings_file_extension())))
+    }
+
+    /// User presets live in one JSON file next to the preferences
+    pub fn user_presets_path() -> Result<std::path::PathBuf, String> {
+        Ok(Self::get_default_settings_dir()?.join("user_presets.json"))
+    }
+
+    pub fn load_user_presets() -> Vec<NamedBundle> {
+        let Ok(path) = Self::user_presets_path() else {
+            return Vec::new();
+        };
+        let Ok(json_data) = fs::read_to_string(&path) else {
+            return Vec::new();
+        };
+        serde_json::from_str(&json_data).unwrap_or_else(|e| {
+            log::warn!("Failed to parse user presets {}: {e}", path.display());
+            Vec::new()
+        })
+    }
+
+    pub fn save_user_presets(presets: &[NamedBundle]) -> Result<(), String> {
+        let json_data = serde_json::to_string_pretty(presets)
+            .map_err(|e| format!("Failed to serialize user presets: {e}"))?;
+        fs::write(Self::user_presets_path()?, json_data)
+            .map_err(|e| format!("Failed to write user presets: {e}"))
+    }
+
+    pub fn remove_recovery_file() {
+        if let Ok(path) = Self::recovery_file_path()
+            && path.exists()
+            && let Err(e) = fs::remove_file(&path)
+        {
+            log::warn!("Failed to remove recovery file {}: {e}", path.display());
+        }
+    }
+}
+
+/// How an exported image was produced, written next to it as `<file>.json`.
+/// The bundle is flattened in, so the sidecar also loads as a settings file.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ExportMetadata {
+    #[serde(flatten)]
+    pub bundle: SettingsBundle,
+    pub source_path: Option<String>,
+    pub format: String,
+    pub width: u32,
+    pub height: u32,
+    /// Colors in each palette, in export order; empty for color-corrected exports
+    pub palette_sizes: Vec<usize>,
+    /// RMSE of R, G, B and A against the source; `None` for color-corrected exports
+    pub rmse: Option<[f32; 4]>,
+}
+
+impl ExportMetadata {
+    pub fn sidecar_path(output_path: &str) -> String {
+        format!("{output_path}.json")
+    }
+
+    pub fn save_next_to(&self, output_path: &str) -> Result<(), String> {
+        let json_data = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize export metadata: {e}"))?;
+        let path = Self::sidecar_path(output_path);
+        fs::write(&path, json_data).map_err(|e| format!("Failed to write export metadata: {e}"))?;
+
+        log::info!("Export metadata written to: {path}");
+        Ok(())
+    }
+}
+
+/// Add `bundle` as `name`, replacing a preset of the same name in place
+pub fn upsert_user_preset(presets: &mut Vec<NamedBundle>, name: &str, bundle: SettingsBundle) {
+    if let Some(preset) = presets.iter_mut().find(|preset| preset.name == name) {
+        preset.bundle = bundle;
+    } else {
+        presets.push(NamedBundle {
+            name: name.to_string(),
+            bundle,
+        });
+    }
 }
 
 #[cfg(test)]
@@ -92,4 +183,63 @@ mod tests {
             deserialized.color_correction.brightness
         );
     }
+
+    #[test]
+    fn test_export_metadata_loads_as_settings_bundle() {
+        let settings = QualetizeSettings {
+            n_colors: 4,
+            ..Default::default()
+        };
+        let metadata = ExportMetadata {
+            bundle: SettingsBundle::new(
+                settings,
+                ColorCorrection::default(),
+                PaletteSortSettings::default(),
+            ),
+            source_path: Some("input.png".to_string()),
+            format: "PNG (Indexed)".to_string(),
+            width: 16,
+            height: 8,
+            palette_sizes: vec![4, 4],
+            rmse: Some([1.0, 2.0, 3.0, 0.0]),
+        };
+
+        let json = serde_json::to_string(&metadata).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert!(value.get("qualetize_settings").is_some());
+        assert_eq!(value["palette_sizes"], serde_json::json!([4, 4]));
+
+        let bundle: SettingsBundle = serde_json::from_str(&json).unwrap();
+        assert_eq!(bundle.qualetize_settings.n_colors, 4);
+        let roundtrip: ExportMetadata = serde_json::from_str(&json).unwrap();
+        assert_eq!(roundtrip.rmse, metadata.rmse);
+        assert_eq!(ExportMetadata::sidecar_path("out.png"), "out.png.json");
+    }
+
+    #[test]
+    fn test_upsert_user_preset_replaces_same_name() {
+        let bundle = |n_colors| {
+            let settings = QualetizeSettings {
+                n_colors,
+                ..Default::default()
+            };
+            SettingsBundle::new(
+                settings,
+                ColorCorrection::default(),
+                PaletteSortSettings::default(),
+            )
+        };
+        let mut presets = Vec::new();
+        upsert_user_preset(&mut presets, "Sprites", bundle(16));
+        upsert_user_preset(&mut presets, "Backgrounds", bundle(8));
+        upsert_user_preset(&mut presets, "Sprites", bundle(4));
+
+        assert_eq!(presets.len(), 2);
+        assert_eq!(presets[0].name, "Sprites");
+        assert_eq!(presets[0].bundle.qualetize_settings.n_colors, 4);
+
+        let json = serde_json::to_string(&presets).unwrap();
+        let deserialized: Vec<NamedBundle> = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized[1].name, "Backgrounds");
+    }
 }
@@ -3,11 +3,22 @@ use std::sync::{Arc, atomic::AtomicBool, mpsc};
 
 use super::{
     color_correction::ColorCorrection,
-    export::ExportFormat,
-    image::{ImageData, ImageDataIndexed, PaletteSortSettings},
-    preferences::UserPreferences,
-    qualetize::QualetizeSettings,
+    color_space_trial::ColorSpaceTrials,
+    contact_sheet::ContactSheetVariations,
+    display_filter::DisplayFilter,
+    export::{ExportFormat, ExportOptions},
+    image::{
+        ImageData, ImageDataIndexed, ImageDifference, PaletteErrorStats, PaletteSortSettings,
+        ResizeFilter,
+    },
+    palette_cycle::PaletteCycle,
+    palette_diff::{PaletteDiffSide, PaletteDiffState},
+    preferences::{ImageViewOverride, UserPreferences},
+    qualetize::{BGRA8, PresetSuggestion, QualetizeSettings},
 };
+use crate::exporter::{estimate_indexed_export_size, pad_indexed_pixels};
+use crate::image_processor::{RegionOfInterest, TileGridSuggestion};
+use crate::settings_manager::{NamedBundle, SettingsBundle};
 use crate::types::image::TileCountOptions;
 use std::time::Instant;
 
@@ -19,6 +30,8 @@ pub enum AppearanceMode {
     System,
     Light,
     Dark,
+    /// Black panels, white text and strong outlines
+    HighContrast,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -72,12 +85,41 @@ pub enum AppStateRequest {
         output_path: String,
         format: ExportFormat,
     },
+    /// Re-decode the input at full precision and write it color corrected as 16-bit PNG.
+    /// Only 8-bit copies are kept in memory, so this reads the input file from disk again
+    /// and fails if it was changed or the input was resized since loading.
+    ColorCorrected16BitPng {
+        output_path: String,
+    },
+    /// Write each band of a split output as its own indexed PNG, plus the manifest
+    SplitExport {
+        output_path: String,
+    },
+    /// Write the output composited over the original outside the edit mask
+    MaskedOutputPng {
+        output_path: String,
+    },
+    /// Destination picked; confirm with a summary before exporting if enabled
+    ReviewExport {
+        output_path: String,
+        format: ExportFormat,
+    },
     SaveSettings {
         path: String,
     },
     LoadSettings {
         path: String,
     },
+    /// Save the current settings as a user preset, replacing one of the same name
+    SaveUserPreset {
+        name: String,
+    },
+    LoadUserPreset {
+        index: usize,
+    },
+    DeleteUserPreset {
+        index: usize,
+    },
 
     OpenImageDialog,
     ExportImageDialog {
@@ -86,6 +128,213 @@ pub enum AppStateRequest {
     },
     SaveSettingsDialog,
     LoadSettingsDialog,
+    LiveExportPathDialog,
+    ExportDirectoryDialog,
+    /// Pick a folder, then write every format of `ExportAll` into it
+    ExportAllDialog,
+    ExportAll {
+        directory: String,
+    },
+    /// Pick a folder, then run `BatchExport` over the queued files into it
+    BatchExportDialog,
+    StartBatchExport {
+        directory: String,
+    },
+    /// Quantize with every color space and switch to the one with the lowest error
+    FindBestColorSpace,
+    /// Pick a file, then write a contact sheet of the variations to it
+    ContactSheetDialog,
+    ExportContactSheet {
+        output_path: String,
+    },
+    SetExportDirectory {
+        path: Option<String>,
+    },
+    Reprocess,
+    ResizeInput {
+        width: u32,
+        height: u32,
+        filter: ResizeFilter,
+    },
+    ResetInputSize,
+    /// Seam-carve the input to the nearest tile-divisible size for preview
+    PreviewSeamFit,
+    /// Replace the input with the previewed seam-carved image
+    ApplySeamFit,
+    SnapPaletteDialog,
+    LoadSnapPalette {
+        path: String,
+    },
+    ReferencePaletteDialog,
+    LoadReferencePalette {
+        path: String,
+    },
+    /// Pick a palette file for one side of the palette comparison
+    PaletteDiffDialog {
+        side: PaletteDiffSide,
+    },
+    LoadPaletteDiff {
+        side: PaletteDiffSide,
+        path: String,
+    },
+    SetLiveExportPath {
+        path: String,
+    },
+}
+
+impl AppStateRequest {
+    /// Request that writes `format` to `output_path`
+    pub fn export(format: ExportFormat, output_path: String) -> Self {
+        match format {
+            ExportFormat::Png => AppStateRequest::ColorCorrectedPng { output_path },
+            ExportFormat::Png16 => AppStateRequest::ColorCorrected16BitPng { output_path },
+            ExportFormat::SplitPng => AppStateRequest::SplitExport { output_path },
+            ExportFormat::MaskedPng => AppStateRequest::MaskedOutputPng { output_path },
+            format => AppStateRequest::QualetizedIndexed {
+                output_path,
+                format,
+            },
+        }
+    }
+}
+
+/// "Process & Export All" over the file queue. Files are loaded one at a time,
+/// processed with the current settings and exported before the next is loaded.
+pub struct BatchExport {
+    pub paths: Vec<String>,
+    /// Index into `paths` of the file being processed
+    pub current: usize,
+    pub directory: String,
+    pub format: ExportFormat,
+    pub exported: usize,
+    /// File name and reason of each file that was not exported
+    pub skipped: Vec<(String, String)>,
+    pub cancelled: bool,
+}
+
+impl BatchExport {
+    pub fn new(paths: Vec<String>, directory: String, format: ExportFormat) -> Self {
+        Self {
+            paths,
+            current: 0,
+            directory,
+            format,
+            exported: 0,
+            skipped: Vec::new(),
+            cancelled: false,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.cancelled || self.current >= self.paths.len()
+    }
+
+    pub fn skip(&mut self, path: &str, reason: String) {
+        log::warn!("Batch export skipped {path}: {reason}");
+        self.skipped.push((file_name(path), reason));
+    }
+}
+
+fn file_name(path: &str) -> String {
+    std::path::Path::new(path)
+        .file_name()
+        .map_or(path.to_string(), |name| name.to_string_lossy().to_string())
+}
+
+/// Export waiting for the user to confirm its summary
+pub struct PendingExport {
+    pub request: AppStateRequest,
+    /// (label, value) rows shown in the confirmation window
+    pub summary: Vec<(&'static str, String)>,
+}
+
+/// Settings and view kept by "Reset Everything to Defaults" for its undo
+pub struct ResetSnapshot {
+    settings: QualetizeSettings,
+    color_correction: ColorCorrection,
+    palette_sort_settings: PaletteSortSettings,
+    zoom: f32,
+    pan_offset: Vec2,
+}
+
+/// Everything the export size estimate depends on
+#[derive(PartialEq)]
+struct ExportSizeKey {
+    texture: egui::TextureId,
+    format: ExportFormat,
+    padded_size: (u32, u32),
+    options: ExportOptions,
+}
+
+#[derive(Debug)]
+pub struct ExportResult {
+    pub output_path: String,
+    pub result: Result<(), String>,
+    /// `AppState::output_settings_hash` when the export started
+    pub settings_hash: u64,
+}
+
+/// Values being edited in the input resize window
+#[derive(Debug, Clone)]
+pub struct ResizeDialog {
+    pub width: u32,
+    pub height: u32,
+    pub lock_aspect: bool,
+    pub filter: ResizeFilter,
+}
+
+/// A decoded image sent back by the loader thread
+pub struct ImageLoadResult {
+    pub generation_id: u64,
+    pub path: String,
+    pub result: Result<ImageData, String>,
+    /// Set when preset suggestions are enabled and the image loaded
+    pub preset_suggestion: Option<PresetSuggestion>,
+}
+
+/// How often changed settings are autosaved for crash recovery
+const RECOVERY_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Longest edge of the thumbnails in the file queue filmstrip
+pub const QUEUE_THUMBNAIL_SIZE: u32 = 64;
+
+/// A file dropped together with others, waiting to be switched to
+pub struct QueuedImage {
+    pub path: String,
+    /// None until the thumbnail worker has decoded the file
+    pub thumbnail: Option<egui::TextureHandle>,
+    pub error: Option<String>,
+}
+
+pub struct QueueThumbnailResult {
+    pub path: String,
+    pub result: Result<egui::TextureHandle, String>,
+}
+
+/// Indexed pixels of an exported output, kept to show what changed since
+pub struct ExportSnapshot {
+    pub width: u32,
+    pub height: u32,
+    pub indexed_pixels: Vec<u8>,
+}
+
+/// "Extract Palette" mode: only palettes are built and exported. Keeps the settings it
+/// replaced so leaving the mode returns to the full pipeline.
+#[derive(Debug, Clone)]
+pub struct PaletteExtraction {
+    previous_palette_only: bool,
+    previous_n_palettes: u16,
+    pub format: ExportFormat,
+}
+
+/// Frame scrubber for animated sources
+#[derive(Debug, Clone, Default)]
+pub struct AnimationPreview {
+    pub show: bool,
+    pub frame: usize,
+    pub playing: bool,
+    /// `ctx` time the shown frame started playing
+    pub frame_started: f64,
 }
 
 #[derive(Debug, Clone)]
@@ -97,6 +346,11 @@ pub struct AppState {
     // Image management
     pub input_path: Option<String>,
     pub input_image: Option<ImageData>,
+    /// Input as loaded from disk while a resized copy is in use
+    pub original_input_image: Option<ImageData>,
+    pub resize_dialog: Option<ResizeDialog>,
+    /// Seam-carved input shown before/after until applied or cancelled
+    pub seam_fit_preview: Option<ImageData>,
     pub color_corrected_image: Option<ImageData>,
     pub base_output_image: Option<ImageData>,
     pub output_image: Option<ImageData>,
@@ -106,12 +360,80 @@ pub struct AppState {
     pub tile_reduce_processing: bool,
     pub tile_reduce_generation_id: u64,
     pub tile_reduce_toast: Option<TileReduceToast>,
+    pub output_rmse: Option<[f32; 4]>,
+    pub live_export_request: Option<Instant>,
+    output_difference_cache: Option<((egui::TextureId, egui::TextureId), ImageDifference)>,
+    /// Keyed on the source and output textures and the tile size
+    palette_error_cache: Option<(
+        (egui::TextureId, egui::TextureId, u16, u16),
+        PaletteErrorStats,
+    )>,
+    export_size_cache: Option<(ExportSizeKey, Option<u64>)>,
+    /// Outline the tile with the highest error in the output preview
+    pub show_worst_tile: bool,
+    /// Filtered preview texture, keyed on the output texture and filter it was built from
+    pub display_filter_cache: Option<(egui::TextureId, DisplayFilter, egui::TextureHandle)>,
+    /// Palette chip focused for keyboard navigation, as (palette, index)
+    pub palette_focus: Option<(usize, usize)>,
+    /// Palette chip whose pixels are shown on their own in the output preview
+    pub isolated_color: Option<(usize, usize)>,
+    /// Isolated preview texture, keyed on the output texture and chip it was built from
+    pub isolated_color_cache: Option<(egui::TextureId, (usize, usize), egui::TextureHandle)>,
+    /// Pixel entered in the footer's go-to field
+    pub go_to_pixel: [u32; 2],
+    /// Palette cycling animation of the output preview
+    pub palette_cycle: PaletteCycle,
+    /// Cycled preview texture, keyed on the output texture, cycle and step it was built from
+    pub palette_cycle_cache: Option<(egui::TextureId, PaletteCycle, usize, egui::TextureHandle)>,
+    /// Tile sizes suggested by grid detection on the input; `None` until detection runs
+    pub tile_grid_suggestions: Option<Vec<TileGridSuggestion>>,
+    /// When set, re-quantization only touches tiles overlapping this input rectangle
+    pub region_of_interest: Option<RegionOfInterest>,
+    /// Preset recommended for the loaded image until applied or dismissed
+    pub preset_suggestion: Option<PresetSuggestion>,
+    /// Palette comparison window
+    pub palette_diff: PaletteDiffState,
+    pub animation_preview: AnimationPreview,
+    pub palette_extraction: Option<PaletteExtraction>,
+    pub show_contact_sheet: bool,
+    /// Setting variations tiled by the contact sheet export
+    pub contact_sheet: ContactSheetVariations,
+    /// "Find Best Color Space" runs and their results window
+    pub color_space_trials: ColorSpaceTrials,
+    /// Last qualetized export, cleared when a new image is loaded
+    pub export_snapshot: Option<ExportSnapshot>,
+    /// Snapshots of running exports by output path, kept once the export succeeds
+    pub pending_export_snapshots: std::collections::HashMap<String, ExportSnapshot>,
+    /// Mask of pixels differing from `export_snapshot`, keyed on the output texture
+    pub export_diff_cache: Option<(egui::TextureId, egui::TextureHandle)>,
+    /// Output composited over the input outside `edit_mask`, keyed on the output
+    /// texture and mask it was built from
+    pub masked_output_cache: Option<(egui::TextureId, RegionOfInterest, egui::TextureHandle)>,
+    /// Export shown in the confirmation window
+    pub pending_export: Option<PendingExport>,
+    pub batch_export: Option<BatchExport>,
+    /// State replaced by the last "Reset Everything to Defaults"
+    pub reset_undo: Option<ResetSnapshot>,
+
+    // Notifications
+    pub toasts: Vec<Toast>,
+    pub debug_stats: DebugStats,
 
     // View Settings
     pub zoom: f32,
     pub pan_offset: Vec2,
+    /// A Shift-drag on the image is adjusting brightness/contrast instead of panning
+    pub grading_drag: bool,
+    /// The blink comparison is held on its current side
+    pub blink_paused: bool,
+    /// Side the blink comparison is on; kept while paused
+    pub blink_showing_original: bool,
+    /// (compact mode, always on top) last sent to the viewport
+    pub applied_window_mode: Option<(bool, bool)>,
     pub preferences: UserPreferences,
     last_preferences: UserPreferences,
+    /// Global view preferences set aside while an image's saved view is applied
+    global_view: Option<ImageViewOverride>,
 
     // Qualetize Settings
     pub settings: QualetizeSettings,
@@ -119,9 +441,27 @@ pub struct AppState {
     pub request_update_tile_reduce: bool,
     pub debounce_delay: std::time::Duration,
 
+    /// A recovery file from a session that didn't exit cleanly was found at startup
+    pub recovery_available: bool,
+    last_recovery_save: Instant,
+    last_recovery_hash: u64,
+    /// Named settings saved in the config dir, listed under Edit > Presets > User
+    pub user_presets: Vec<NamedBundle>,
+    /// Name being entered for a new user preset; `Some` while the prompt is open
+    pub user_preset_name: Option<String>,
+
     // Color Correction Settings
     pub color_correction: ColorCorrection,
     last_color_correction: ColorCorrection,
+    /// Grade the output palettes instead of the source image, skipping re-quantization
+    pub palette_only_color_correction: bool,
+    last_palette_only_color_correction: bool,
+    /// Rectangle of the input that color correction and quantization apply to; the
+    /// rest of the image is shown and exported as the original
+    pub edit_mask: Option<RegionOfInterest>,
+    last_edit_mask: Option<RegionOfInterest>,
+    /// Output palette as quantized, before palette-only grading
+    pub ungraded_output_palette: Option<Vec<BGRA8>>,
 
     // Palette Sort Settings
     pub palette_sort_settings: PaletteSortSettings,
@@ -132,10 +472,31 @@ pub struct AppState {
     // warning
     pub tile_size_warning: bool,
 
+    /// Level count and gamma of the custom level generator
+    pub level_ramp_count: usize,
+    pub level_ramp_gamma: f32,
+
     // Export requests
     pub app_state_request_receiver: mpsc::Receiver<AppStateRequest>,
     pub app_state_request_sender: mpsc::Sender<AppStateRequest>,
 
+    // Results reported back by export threads
+    pub export_result_receiver: mpsc::Receiver<ExportResult>,
+    pub export_result_sender: mpsc::Sender<ExportResult>,
+    /// Settings hash of the last successful export, cleared when a new image is loaded
+    pub exported_settings_hash: Option<u64>,
+
+    // Images decoded on a worker thread; results from older loads are ignored
+    pub image_load_receiver: mpsc::Receiver<ImageLoadResult>,
+    pub image_load_sender: mpsc::Sender<ImageLoadResult>,
+    pub image_load_generation_id: u64,
+    pub image_loading: bool,
+
+    /// Files from the last multi-file drop, shown as a filmstrip
+    pub image_queue: Vec<QueuedImage>,
+    pub queue_thumbnail_receiver: mpsc::Receiver<QueueThumbnailResult>,
+    pub queue_thumbnail_sender: mpsc::Sender<QueueThumbnailResult>,
+
     pub file_dialog_open: Arc<AtomicBool>,
 }
 
@@ -145,14 +506,85 @@ pub struct TileReduceToast {
     pub time: Instant,
 }
 
+/// Timings shown in the debug HUD, refreshed every frame
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DebugStats {
+    /// Time since the previous frame
+    pub frame_time: std::time::Duration,
+    /// Time spent in the app's own update of the previous frame
+    pub update_time: std::time::Duration,
+    pub quantization_in_flight: bool,
+    /// Requests still queued after this frame's one was handled
+    pub pending_requests: usize,
+    pub last_quantization_time: Option<std::time::Duration>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToastKind {
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub struct Toast {
+    pub message: String,
+    pub kind: ToastKind,
+    pub time: Instant,
+}
+
+impl Toast {
+    pub fn info(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            kind: ToastKind::Info,
+            time: Instant::now(),
+        }
+    }
+
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            kind: ToastKind::Warning,
+            time: Instant::now(),
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            kind: ToastKind::Error,
+            time: Instant::now(),
+        }
+    }
+
+    /// Warnings and errors stay on screen longer so they can be read
+    pub fn duration(&self) -> std::time::Duration {
+        match self.kind {
+            ToastKind::Info => std::time::Duration::from_secs(3),
+            ToastKind::Warning | ToastKind::Error => std::time::Duration::from_secs(6),
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.time.elapsed() >= self.duration()
+    }
+}
+
 impl Default for AppState {
     fn default() -> Self {
         let preferences = UserPreferences::load();
         let (sender, receiver) = mpsc::channel();
+        let (export_result_sender, export_result_receiver) = mpsc::channel();
+        let (image_load_sender, image_load_receiver) = mpsc::channel();
+        let (queue_thumbnail_sender, queue_thumbnail_receiver) = mpsc::channel();
 
         Self {
             input_path: None,
             input_image: None,
+            original_input_image: None,
+            resize_dialog: None,
+            seam_fit_preview: None,
             color_corrected_image: None,
             base_output_image: None,
             output_image: None,
@@ -162,18 +594,66 @@ impl Default for AppState {
             tile_reduce_processing: false,
             tile_reduce_generation_id: 0,
             tile_reduce_toast: None,
+            output_rmse: None,
+            live_export_request: None,
+            output_difference_cache: None,
+            palette_error_cache: None,
+            export_size_cache: None,
+            show_worst_tile: false,
+            display_filter_cache: None,
+            palette_focus: None,
+            isolated_color: None,
+            isolated_color_cache: None,
+            go_to_pixel: [0, 0],
+            palette_cycle: PaletteCycle::default(),
+            palette_cycle_cache: None,
+            tile_grid_suggestions: None,
+            region_of_interest: None,
+            preset_suggestion: None,
+            palette_diff: PaletteDiffState::default(),
+            animation_preview: AnimationPreview::default(),
+            palette_extraction: None,
+            show_contact_sheet: false,
+            contact_sheet: ContactSheetVariations::default(),
+            color_space_trials: ColorSpaceTrials::default(),
+            export_snapshot: None,
+            pending_export_snapshots: std::collections::HashMap::new(),
+            export_diff_cache: None,
+            masked_output_cache: None,
+            pending_export: None,
+            batch_export: None,
+            reset_undo: None,
+
+            toasts: Vec::new(),
+            debug_stats: DebugStats::default(),
 
             zoom: 1.0,
             pan_offset: Vec2::ZERO,
+            grading_drag: false,
+            blink_paused: false,
+            blink_showing_original: false,
+            applied_window_mode: None,
             preferences: preferences.clone(),
             last_preferences: preferences.clone(),
+            global_view: None,
 
             settings: QualetizeSettings::default(),
             request_update_qualetized_image: None,
             request_update_tile_reduce: false,
-            debounce_delay: std::time::Duration::from_millis(100),
+            debounce_delay: preferences.preview_delay(),
+            recovery_available: SettingsBundle::recovery_file_path()
+                .is_ok_and(|path| path.exists()),
+            last_recovery_save: Instant::now(),
+            last_recovery_hash: 0,
+            user_presets: SettingsBundle::load_user_presets(),
+            user_preset_name: None,
 
             last_color_correction: ColorCorrection::default(),
+            palette_only_color_correction: false,
+            last_palette_only_color_correction: false,
+            edit_mask: None,
+            last_edit_mask: None,
+            ungraded_output_palette: None,
             color_correction: ColorCorrection::default(),
 
             palette_sort_settings: PaletteSortSettings::default(),
@@ -182,10 +662,24 @@ impl Default for AppState {
             tile_count: TileCountState::default(),
 
             tile_size_warning: false,
+            level_ramp_count: 8,
+            level_ramp_gamma: 1.0,
 
             app_state_request_receiver: receiver,
             app_state_request_sender: sender,
 
+            export_result_receiver,
+            export_result_sender,
+            exported_settings_hash: None,
+            image_load_receiver,
+            image_load_sender,
+            image_load_generation_id: 0,
+            image_loading: false,
+
+            image_queue: Vec::new(),
+            queue_thumbnail_receiver,
+            queue_thumbnail_sender,
+
             file_dialog_open: Arc::new(AtomicBool::new(false)),
         }
     }
@@ -196,24 +690,145 @@ impl AppState {
         let Some(input_image) = &self.input_image else {
             return String::new();
         };
-        format!(
-            "Image size ({}×{}) is not divisible by tile size ({}×{}). Qualetize processing cannot proceed.",
-            input_image.width,
-            input_image.height,
+        self.settings
+            .tile_size_error(input_image.width, input_image.height)
+            .unwrap_or_default()
+    }
+
+    /// Difference between the color corrected input and the output,
+    /// recomputed only when either image changes
+    pub fn output_difference(&mut self) -> Option<ImageDifference> {
+        let (Some(source), Some(output)) = (&self.color_corrected_image, &self.output_image) else {
+            return None;
+        };
+        let key = (source.texture.id(), output.texture.id());
+        if let Some((cached_key, difference)) = &self.output_difference_cache
+            && *cached_key == key
+        {
+            return Some(*difference);
+        }
+
+        let difference = source.difference(output)?;
+        self.output_difference_cache = Some((key, difference));
+        Some(difference)
+    }
+
+    /// Per-palette error and worst tile of the output against the color corrected input,
+    /// recomputed only when either image or the tile size changes
+    pub fn palette_error_stats(&mut self) -> Option<&PaletteErrorStats> {
+        let (Some(source), Some(output)) = (&self.color_corrected_image, &self.output_image) else {
+            return None;
+        };
+        let key = (
+            source.texture.id(),
+            output.texture.id(),
             self.settings.tile_width,
             self.settings.tile_height,
-        )
+        );
+        if self
+            .palette_error_cache
+            .as_ref()
+            .is_none_or(|(cached_key, _)| *cached_key != key)
+        {
+            let stats = output.palette_error_stats(
+                source,
+                self.settings.tile_width as u32,
+                self.settings.tile_height as u32,
+            )?;
+            self.palette_error_cache = Some((key, stats));
+        }
+        self.palette_error_cache.as_ref().map(|(_, stats)| stats)
+    }
+
+    /// Estimated file size of exporting the output in the selected format,
+    /// recomputed only when the output or export options change
+    pub fn export_size_estimate(&mut self) -> Option<u64> {
+        let output = self.output_image.as_ref()?;
+        let indexed = self
+            .output_palette_sorted_indexed_image
+            .as_ref()
+            .or(output.indexed.as_ref())?;
+        let format = &self.preferences.selected_export_format;
+        let padded_size = self
+            .settings
+            .padded_export_size(output.width, output.height)
+            .ok()?;
+        let key = ExportSizeKey {
+            texture: output.texture.id(),
+            format: format.clone(),
+            padded_size,
+            options: self.preferences.export_options(),
+        };
+        if let Some((cached_key, estimate)) = &self.export_size_cache
+            && *cached_key == key
+        {
+            return *estimate;
+        }
+
+        let pixels = pad_indexed_pixels(
+            &indexed.indexed_pixels,
+            output.width,
+            output.height,
+            padded_size.0,
+            padded_size.1,
+        );
+        let estimate = estimate_indexed_export_size(
+            format,
+            &pixels,
+            &indexed.palettes,
+            padded_size.0,
+            padded_size.1,
+            key.options,
+        );
+        self.export_size_cache = Some((key, estimate));
+        estimate
     }
 
     pub fn check_and_save_preferences(&mut self) {
         if self.preferences != self.last_preferences {
             self.last_preferences = self.preferences.clone();
-            if let Err(e) = self.preferences.save() {
+            self.debounce_delay = self.preferences.preview_delay();
+            // A per-image view must not become the global default
+            let result = match &self.global_view {
+                Some(global_view) => {
+                    let mut preferences = self.preferences.clone();
+                    global_view.apply_to(&mut preferences);
+                    preferences.save()
+                }
+                None => self.preferences.save(),
+            };
+            if let Err(e) = result {
                 eprintln!("Failed to save preferences: {e}");
             }
         }
     }
 
+    /// Autosave the settings every `RECOVERY_INTERVAL` while they differ from the last
+    /// autosave, so a crash doesn't lose the session's tuning
+    pub fn check_and_save_recovery(&mut self) {
+        if self.recovery_available || self.last_recovery_save.elapsed() < RECOVERY_INTERVAL {
+            return;
+        }
+        self.last_recovery_save = Instant::now();
+
+        let hash = self.output_settings_hash();
+        if hash == self.last_recovery_hash {
+            return;
+        }
+        self.last_recovery_hash = hash;
+
+        let bundle = SettingsBundle::new(
+            self.settings.clone(),
+            self.color_correction.clone(),
+            self.palette_sort_settings.clone(),
+        );
+        if let Err(e) =
+            SettingsBundle::recovery_file_path().and_then(|path| bundle.save_to_file(path))
+        {
+            log::warn!("Failed to autosave settings: {e}");
+        }
+    }
+
     pub fn palette_sort_settings_changed(&self) -> bool {
         self.palette_sort_settings != self.last_palette_sort_settings
     }
@@ -231,9 +846,184 @@ impl AppState {
     /// Update the tracked color correction settings
     pub fn update_color_correction_tracking(&mut self) {
         self.last_color_correction = self.color_correction.clone();
+        self.last_palette_only_color_correction = self.palette_only_color_correction;
+        self.last_edit_mask = self.edit_mask;
+    }
+
+    /// Whether the source pixel filters changed; they have to run on the source even
+    /// when grading the palette only
+    pub fn source_filter_changed(&self) -> bool {
+        self.color_correction.source_filters() != self.last_color_correction.source_filters()
+    }
+
+    pub fn palette_only_color_correction_changed(&self) -> bool {
+        self.palette_only_color_correction != self.last_palette_only_color_correction
+    }
+
+    pub fn edit_mask_changed(&self) -> bool {
+        self.edit_mask != self.last_edit_mask
+    }
+
+    /// Apply a new palette padding color to the current output without re-quantizing.
+    /// The sorted view is rebuilt from it on the next frame; exports don't use padding.
+    pub fn repad_output_palettes(&mut self) {
+        let padding = self.preferences.palette_padding();
+        if let Some(indexed) = self.output_image.as_mut().and_then(|o| o.indexed.as_mut()) {
+            indexed.repad(padding);
+            self.output_palette_sorted_indexed_image = None;
+        }
+    }
+
+    /// `output` pixels inside the edit mask over the input outside it, or `None`
+    /// without a mask
+    pub fn masked_output_rgba(&self, output: &ImageData) -> Option<Vec<u8>> {
+        let mask = self.edit_mask.as_ref()?;
+        let input = self.input_image.as_ref()?;
+        // Palette-only output has no rendered pixels to composite
+        if output.is_palette_only() || (input.width, input.height) != (output.width, output.height)
+        {
+            return None;
+        }
+        Some(mask.composite(&output.rgba_data, &input.rgba_data, output.width))
+    }
+
+    /// Hash of everything that shapes the exported output
+    pub fn output_settings_hash(&self) -> u64 {
+        use std::hash::{Hash, Hasher};
+        // The settings contain floats, so hash their serialized form
+        let serialized = serde_json::to_string(&(
+            &self.settings,
+            &self.color_correction,
+            self.palette_only_color_correction,
+            &self.palette_sort_settings,
+        ))
+        .unwrap_or_default();
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        serialized.hash(&mut hasher);
+        self.edit_mask.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Whether settings changed since the last successful export
+    pub fn export_is_stale(&self) -> bool {
+        self.exported_settings_hash
+            .is_some_and(|hash| hash != self.output_settings_hash())
+    }
+
+    pub fn push_toast(&mut self, toast: Toast) {
+        self.toasts.push(toast);
+    }
+
+    pub fn prune_toasts(&mut self) {
+        self.toasts.retain(|toast| !toast.is_expired());
     }
 
     pub fn reset_view_settings(&mut self) {
-        self.preferences = UserPreferences::default();
+        let image_view_overrides = std::mem::take(&mut self.preferences.image_view_overrides);
+        self.preferences = UserPreferences {
+            image_view_overrides,
+            ..UserPreferences::default()
+        };
+        self.global_view = None;
+    }
+
+    /// Switch to the view saved for `path`, or back to the global view if it has none
+    pub fn apply_image_view(&mut self, path: &str) {
+        if let Some(global_view) = self.global_view.take() {
+            global_view.apply_to(&mut self.preferences);
+        }
+        if let Some(image_view) = self.preferences.image_view_overrides.get(path).cloned() {
+            self.global_view = Some(ImageViewOverride::from_preferences(&self.preferences));
+            image_view.apply_to(&mut self.preferences);
+        }
+    }
+
+    pub fn has_image_view(&self) -> bool {
+        self.input_path
+            .as_ref()
+            .is_some_and(|path| self.preferences.image_view_overrides.contains_key(path))
+    }
+
+    /// Remember the current view for the open image
+    pub fn save_image_view(&mut self) {
+        let Some(path) = self.input_path.clone() else {
+            return;
+        };
+        let image_view = ImageViewOverride::from_preferences(&self.preferences);
+        if self.global_view.is_none() {
+            self.global_view = Some(image_view.clone());
+        }
+        self.preferences
+            .image_view_overrides
+            .insert(path, image_view);
+    }
+
+    /// Drop the open image's saved view and return to the global one
+    pub fn forget_image_view(&mut self) {
+        if let Some(path) = &self.input_path {
+            self.preferences.image_view_overrides.remove(path);
+        }
+        if let Some(global_view) = self.global_view.take() {
+            global_view.apply_to(&mut self.preferences);
+        }
+    }
+
+    /// Restore every settings group and the view to defaults, keeping what was
+    /// replaced so `undo_reset_everything` can bring it back
+    pub fn reset_everything(&mut self) {
+        self.reset_undo = Some(ResetSnapshot {
+            settings: self.settings.clone(),
+            color_correction: self.color_correction.clone(),
+            palette_sort_settings: self.palette_sort_settings.clone(),
+            zoom: self.zoom,
+            pan_offset: self.pan_offset,
+        });
+        self.settings = QualetizeSettings::default();
+        self.palette_extraction = None;
+        self.color_correction = ColorCorrection::default();
+        self.palette_sort_settings = PaletteSortSettings::default();
+        self.zoom = 1.0;
+        self.pan_offset = Vec2::ZERO;
+        self.palette_focus = None;
+        self.isolated_color = None;
+        self.region_of_interest = None;
+        self.edit_mask = None;
+    }
+
+    /// Switch to building a single palette without rendering the image
+    pub fn enter_palette_extraction(&mut self) {
+        if self.palette_extraction.is_some() {
+            return;
+        }
+        self.palette_extraction = Some(PaletteExtraction {
+            previous_palette_only: self.settings.palette_only,
+            previous_n_palettes: self.settings.n_palettes,
+            format: ExportFormat::PaletteStrip,
+        });
+        self.settings.palette_only = true;
+        self.settings.n_palettes = 1;
+    }
+
+    /// Back to the full pipeline with the settings extraction replaced
+    pub fn leave_palette_extraction(&mut self) {
+        let Some(extraction) = self.palette_extraction.take() else {
+            return;
+        };
+        self.settings.palette_only = extraction.previous_palette_only;
+        self.settings.n_palettes = extraction.previous_n_palettes;
+        self.settings.clamp_palette_budget();
+    }
+
+    /// Returns false when there is no reset to undo
+    pub fn undo_reset_everything(&mut self) -> bool {
+        let Some(snapshot) = self.reset_undo.take() else {
+            return false;
+        };
+        self.settings = snapshot.settings;
+        self.color_correction = snapshot.color_correction;
+        self.palette_sort_settings = snapshot.palette_sort_settings;
+        self.zoom = snapshot.zoom;
+        self.pan_offset = snapshot.pan_offset;
+        true
     }
 }
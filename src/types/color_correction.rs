@@ -9,6 +9,29 @@ pub struct ColorCorrection {
     pub hue_shift: f32,  // -180.0 to 180.0 degrees
     pub shadows: f32,    // -1.0 to 1.0
     pub highlights: f32, // -1.0 to 1.0
+    /// Edge-preserving smoothing of JPEG block noise before quantizing
+    #[serde(default)]
+    pub reduce_jpeg_artifacts: bool,
+    /// Edge-preserving bilateral smoothing of the source, see `ColorProcessor::denoise`
+    #[serde(default)]
+    pub denoise_enabled: bool,
+    #[serde(default = "default_denoise_strength")]
+    pub denoise_strength: f32,
+    #[serde(default = "default_denoise_radius")]
+    pub denoise_radius: u32,
+}
+
+pub const BRIGHTNESS_RANGE: std::ops::RangeInclusive<f32> = -1.0..=1.0;
+pub const CONTRAST_RANGE: std::ops::RangeInclusive<f32> = 0.0..=2.0;
+pub const DENOISE_STRENGTH_RANGE: std::ops::RangeInclusive<f32> = 0.0..=1.0;
+pub const DENOISE_RADIUS_RANGE: std::ops::RangeInclusive<u32> = 1..=4;
+
+fn default_denoise_strength() -> f32 {
+    0.3
+}
+
+fn default_denoise_radius() -> u32 {
+    2
 }
 
 pub enum ColorCorrectionPreset {
@@ -61,11 +84,39 @@ impl Default for ColorCorrection {
             hue_shift: 0.0,
             shadows: 0.0,
             highlights: 0.0,
+            reduce_jpeg_artifacts: false,
+            denoise_enabled: false,
+            denoise_strength: default_denoise_strength(),
+            denoise_radius: default_denoise_radius(),
         }
     }
 }
 
 impl ColorCorrection {
+    /// Only the filters that run on the source pixels, with the grading left neutral
+    pub fn source_filters(&self) -> ColorCorrection {
+        ColorCorrection {
+            reduce_jpeg_artifacts: self.reduce_jpeg_artifacts,
+            denoise_enabled: self.denoise_enabled,
+            denoise_strength: self.denoise_strength,
+            denoise_radius: self.denoise_radius,
+            ..ColorCorrection::default()
+        }
+    }
+
+    /// Names of the enabled source filters the 16-bit PNG export doesn't apply,
+    /// since it re-reads the file and only grades it
+    pub fn filters_skipped_at_16_bit(&self) -> Vec<&'static str> {
+        let mut skipped = Vec::new();
+        if self.reduce_jpeg_artifacts {
+            skipped.push("JPEG artifact reduction");
+        }
+        if self.denoise_enabled {
+            skipped.push("denoise");
+        }
+        skipped
+    }
+
     pub fn preset_dark() -> ColorCorrection {
         ColorCorrection {
             contrast: 1.75,
@@ -14,6 +14,9 @@ pub enum ColorSpace {
 }
 
 impl ColorSpace {
+    /// Picked for grayscale sources: chroma is zero, so clustering works on luma alone
+    pub const GRAYSCALE_DEFAULT: ColorSpace = ColorSpace::Ycbcr;
+
     pub fn display_name(&self) -> &'static str {
         match self {
             ColorSpace::Srgb => "sRGB",
@@ -50,6 +53,84 @@ impl ColorSpace {
         }
     }
 
+    /// When to pick this color space, shown in the help window
+    pub fn recommended_use(&self) -> &'static str {
+        match self {
+            ColorSpace::Srgb => "Pixel art and flat colors that should match exactly",
+            ColorSpace::RgbLinear => "Good general-purpose default",
+            ColorSpace::Ycbcr | ColorSpace::Ycocg => {
+                "Photos and gradients where brightness detail matters most"
+            }
+            ColorSpace::Cielab => "Rarely; try OkLab first",
+            ColorSpace::Ictcp | ColorSpace::Oklab => {
+                "Perceptually even results on photographic art"
+            }
+            ColorSpace::RgbPsy | ColorSpace::YcbcrPsy | ColorSpace::YcocgPsy => {
+                "Detailed or noisy images where perceived quality beats exact colors"
+            }
+        }
+    }
+
+    /// Approximate coordinates of an sRGB color in this space, scaled so the main axis
+    /// spans roughly 0-255. Used for distances on the Rust side, e.g. merging palette colors.
+    /// ICtCp has no cheap sRGB path and is approximated by OkLab; psyopt weighting is ignored.
+    pub fn components(&self, rgb: [u8; 3]) -> [f32; 3] {
+        let srgb = rgb.map(|c| c as f32 / 255.0);
+        let linear = srgb.map(|c| {
+            if c <= 0.04045 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        });
+        let [r, g, b] = srgb;
+        let [lr, lg, lb] = linear;
+        let components = match self {
+            ColorSpace::Srgb | ColorSpace::RgbPsy => srgb,
+            ColorSpace::RgbLinear => linear,
+            ColorSpace::Ycbcr | ColorSpace::YcbcrPsy => [
+                0.299 * r + 0.587 * g + 0.114 * b,
+                -0.168_736 * r - 0.331_264 * g + 0.5 * b,
+                0.5 * r - 0.418_688 * g - 0.081_312 * b,
+            ],
+            ColorSpace::Ycocg | ColorSpace::YcocgPsy => [
+                0.25 * r + 0.5 * g + 0.25 * b,
+                0.5 * r - 0.5 * b,
+                -0.25 * r + 0.5 * g - 0.25 * b,
+            ],
+            ColorSpace::Cielab => {
+                // D65 white point
+                let x = (0.412_456 * lr + 0.357_576 * lg + 0.180_438 * lb) / 0.950_47;
+                let y = 0.212_673 * lr + 0.715_152 * lg + 0.072_175 * lb;
+                let z = (0.019_334 * lr + 0.119_192 * lg + 0.950_304 * lb) / 1.088_83;
+                let f = |t: f32| {
+                    if t > 0.008_856 {
+                        t.cbrt()
+                    } else {
+                        7.787 * t + 16.0 / 116.0
+                    }
+                };
+                let (fx, fy, fz) = (f(x), f(y), f(z));
+                [
+                    (116.0 * fy - 16.0) / 100.0,
+                    5.0 * (fx - fy) / 100.0,
+                    2.0 * (fy - fz) / 100.0,
+                ]
+            }
+            ColorSpace::Ictcp | ColorSpace::Oklab => {
+                let l = (0.412_221_47 * lr + 0.536_332_55 * lg + 0.051_445_995 * lb).cbrt();
+                let m = (0.211_903_5 * lr + 0.680_699_5 * lg + 0.107_396_96 * lb).cbrt();
+                let s = (0.088_302_46 * lr + 0.281_718_85 * lg + 0.629_978_7 * lb).cbrt();
+                [
+                    0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s,
+                    1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s,
+                    0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s,
+                ]
+            }
+        };
+        components.map(|c| c * 255.0)
+    }
+
     pub fn to_id(&self) -> u8 {
         match self {
             ColorSpace::Srgb => 0,
@@ -80,3 +161,19 @@ impl ColorSpace {
         ]
     }
 }
+
+/// CIE76 difference of two sRGB colors; around 2.3 is just noticeable
+pub fn delta_e(a: [u8; 3], b: [u8; 3]) -> f32 {
+    // `components` scales L*a*b* by 255/100
+    let (a, b) = (
+        ColorSpace::Cielab.components(a),
+        ColorSpace::Cielab.components(b),
+    );
+    a.iter()
+        .zip(&b)
+        .map(|(x, y)| (x - y) * (x - y))
+        .sum::<f32>()
+        .sqrt()
+        * 100.0
+        / 255.0
+}
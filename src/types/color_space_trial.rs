@@ -0,0 +1,51 @@
+use super::color_space::ColorSpace;
+use std::sync::mpsc;
+
+/// Outcome of quantizing with one color space in "Find Best Color Space"
+#[derive(Clone, Debug)]
+pub struct ColorSpaceTrial {
+    pub color_space: ColorSpace,
+    /// RGB error of the output against the quantizer input; `Err` if the run failed
+    pub rmse: Result<f32, String>,
+}
+
+/// State of the color space trials and their results window
+#[derive(Default)]
+pub struct ColorSpaceTrials {
+    pub show: bool,
+    /// Set while the trials run on worker threads
+    pub receiver: Option<mpsc::Receiver<Vec<ColorSpaceTrial>>>,
+    /// Sorted from lowest to highest error, failed runs last
+    pub results: Vec<ColorSpaceTrial>,
+}
+
+impl ColorSpaceTrials {
+    pub fn is_running(&self) -> bool {
+        self.receiver.is_some()
+    }
+
+    /// Sort the results and return the color space with the lowest error
+    pub fn finish(&mut self, mut results: Vec<ColorSpaceTrial>) -> Option<ColorSpaceTrial> {
+        results.sort_by(|a, b| match (&a.rmse, &b.rmse) {
+            (Ok(a), Ok(b)) => a.total_cmp(b),
+            (Ok(_), Err(_)) => std::cmp::Ordering::Less,
+            (Err(_), Ok(_)) => std::cmp::Ordering::Greater,
+            (Err(_), Err(_)) => std::cmp::Ordering::Equal,
+        });
+        self.receiver = None;
+        self.results = results;
+        self.results
+            .first()
+            .filter(|trial| trial.rmse.is_ok())
+            .cloned()
+    }
+
+    pub fn rmse_of(&self, color_space: &ColorSpace) -> Option<f32> {
+        self.results
+            .iter()
+            .find(|trial| trial.color_space == *color_space)?
+            .rmse
+            .clone()
+            .ok()
+    }
+}
@@ -0,0 +1,76 @@
+use super::color_space::ColorSpace;
+use super::qualetize::QualetizeSettings;
+
+/// Colors-per-palette choices offered for contact sheet variations
+pub const CONTACT_SHEET_COLOR_COUNTS: &[u16] = &[2, 4, 8, 16, 32, 64, 128, 256];
+
+/// Cells beyond this are dropped, so a careless selection can't start dozens of runs
+pub const CONTACT_SHEET_MAX_VARIATIONS: usize = 16;
+
+/// Setting variations tiled into one contact sheet image. Every selected color space
+/// is combined with every selected colors-per-palette count; an empty selection keeps
+/// the current value.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ContactSheetVariations {
+    pub color_spaces: Vec<ColorSpace>,
+    pub colors_per_palette: Vec<u16>,
+}
+
+impl ContactSheetVariations {
+    pub fn variation_count(&self) -> usize {
+        (self.color_spaces.len().max(1) * self.colors_per_palette.len().max(1))
+            .min(CONTACT_SHEET_MAX_VARIATIONS)
+    }
+
+    /// `base` with each variation applied, captioned with what it uses
+    pub fn settings(&self, base: &QualetizeSettings) -> Vec<(String, QualetizeSettings)> {
+        let color_spaces = if self.color_spaces.is_empty() {
+            vec![base.color_space.clone()]
+        } else {
+            self.color_spaces.clone()
+        };
+        let color_counts = if self.colors_per_palette.is_empty() {
+            vec![base.n_colors]
+        } else {
+            self.colors_per_palette.clone()
+        };
+
+        let mut variations = Vec::new();
+        for color_space in &color_spaces {
+            for &n_colors in &color_counts {
+                let mut settings = base.clone();
+                settings.color_space = color_space.clone();
+                if !self.colors_per_palette.is_empty() {
+                    settings.n_colors = n_colors;
+                    // Custom sizes would override the varied count
+                    settings.use_custom_palette_sizes = false;
+                }
+                let caption = format!(
+                    "{} {}×{}",
+                    color_space.display_name(),
+                    settings.n_palettes,
+                    settings.n_colors
+                );
+                variations.push((caption, settings));
+            }
+        }
+        variations.truncate(CONTACT_SHEET_MAX_VARIATIONS);
+        variations
+    }
+
+    pub fn toggle_color_space(&mut self, color_space: &ColorSpace) {
+        toggle(&mut self.color_spaces, color_space.clone());
+    }
+
+    pub fn toggle_colors_per_palette(&mut self, n_colors: u16) {
+        toggle(&mut self.colors_per_palette, n_colors);
+    }
+}
+
+fn toggle<T: PartialEq>(values: &mut Vec<T>, value: T) {
+    if let Some(position) = values.iter().position(|v| *v == value) {
+        values.remove(position);
+    } else {
+        values.push(value);
+    }
+}
@@ -0,0 +1,47 @@
+/// Preview-only filters that approximate how the output looks on the target display.
+/// Exported data is never filtered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize, Default)]
+pub enum DisplayFilter {
+    #[default]
+    None,
+    CrtScanlines,
+    NtscBlur,
+    LcdGrid,
+    AlphaChannel,
+}
+
+impl DisplayFilter {
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            DisplayFilter::None => "None",
+            DisplayFilter::CrtScanlines => "CRT Scanlines",
+            DisplayFilter::NtscBlur => "NTSC Blur",
+            DisplayFilter::LcdGrid => "LCD Grid",
+            DisplayFilter::AlphaChannel => "Alpha Channel",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            DisplayFilter::None => "Show the output pixels as they are",
+            DisplayFilter::CrtScanlines => "Darken the gap between lines like a CRT",
+            DisplayFilter::NtscBlur => {
+                "Blur horizontally with extra chroma bleed,\nroughly like a composite NTSC signal"
+            }
+            DisplayFilter::LcdGrid => "Dark grid between pixels like a handheld LCD",
+            DisplayFilter::AlphaChannel => {
+                "Show output alpha as grayscale (black = transparent)\nto check how alpha was quantized"
+            }
+        }
+    }
+
+    pub fn all() -> &'static [DisplayFilter] {
+        &[
+            DisplayFilter::None,
+            DisplayFilter::CrtScanlines,
+            DisplayFilter::NtscBlur,
+            DisplayFilter::LcdGrid,
+            DisplayFilter::AlphaChannel,
+        ]
+    }
+}
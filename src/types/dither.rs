@@ -4,6 +4,8 @@ pub enum DitherMode {
     #[default]
     Floyd,
     Atkinson,
+    /// 4x4 Bayer pattern added to the input before Floyd-Steinberg diffusion
+    Hybrid,
     Checker,
     Ord2,
     Ord4,
@@ -19,6 +21,7 @@ impl DitherMode {
             DitherMode::None => "None",
             DitherMode::Floyd => "Floyd-Steinberg",
             DitherMode::Atkinson => "Atkinson",
+            DitherMode::Hybrid => "Pattern + Diffusion",
             DitherMode::Checker => "Checkerboard",
             DitherMode::Ord2 => "2x2 Ordered",
             DitherMode::Ord4 => "4x4 Ordered",
@@ -34,6 +37,9 @@ impl DitherMode {
             DitherMode::None => "No dithering",
             DitherMode::Floyd => "Floyd-Steinberg error diffusion (default level: 0.5)",
             DitherMode::Atkinson => "Atkinson error diffusion (default level: 0.5)",
+            DitherMode::Hybrid => {
+                "4x4 Bayer pattern layered under Floyd-Steinberg (default level: 0.5, pattern: 0.25).\nThe pattern is added to the input before clustering, so it also nudges the palette\nand makes flat areas less tile-reducible; diffusion still handles the detail."
+            }
             DitherMode::Checker => "Checkerboard dithering (default level: 1.0)",
             DitherMode::Ord2 => "2x2 ordered dithering (default level: 1.0)",
             DitherMode::Ord4 => "4x4 ordered dithering (default level: 1.0)",
@@ -44,10 +50,41 @@ impl DitherMode {
         }
     }
 
+    /// When to pick this dither mode, shown in the help window
+    pub fn recommended_use(&self) -> &'static str {
+        match self {
+            DitherMode::None => "Clean pixel art and tiles that must stay reducible",
+            DitherMode::Floyd => "Smooth gradients on photos; noisier tiles",
+            DitherMode::Atkinson => "Like Floyd-Steinberg with more contrast and less noise",
+            DitherMode::Hybrid => "Regular crosshatch in flat areas with diffused detail",
+            DitherMode::Checker => "Retro 50% mixes that blend on CRTs",
+            DitherMode::Ord2 | DitherMode::Ord4 => "Small regular patterns that tile well",
+            DitherMode::Ord8 | DitherMode::Ord16 | DitherMode::Ord32 | DitherMode::Ord64 => {
+                "Finer gradients with a stable, repeatable pattern"
+            }
+        }
+    }
+
+    /// Checkerboard and Bayer modes, which add a fixed threshold pattern
+    /// instead of diffusing error
+    pub fn is_ordered(&self) -> bool {
+        matches!(
+            self,
+            DitherMode::Checker
+                | DitherMode::Ord2
+                | DitherMode::Ord4
+                | DitherMode::Ord8
+                | DitherMode::Ord16
+                | DitherMode::Ord32
+                | DitherMode::Ord64
+        )
+    }
+
     pub fn to_id(&self) -> u8 {
         match self {
             DitherMode::None => 0,
-            DitherMode::Floyd => 0xFE,
+            // The pattern is a Rust pre-pass; the library only diffuses
+            DitherMode::Floyd | DitherMode::Hybrid => 0xFE,
             DitherMode::Atkinson => 0xFD,
             DitherMode::Checker => 0xFF,
             DitherMode::Ord2 => 2,
@@ -64,6 +101,7 @@ impl DitherMode {
             DitherMode::None,
             DitherMode::Floyd,
             DitherMode::Atkinson,
+            DitherMode::Hybrid,
             DitherMode::Checker,
             DitherMode::Ord2,
             DitherMode::Ord4,
@@ -4,6 +4,25 @@ pub enum ExportFormat {
     PngIndexed,
     Png,
     Bmp,
+    GenesisNametable,
+    PaletteStrip,
+    IndexCsv,
+    CombinedPalette,
+    PaletteCycleGif,
+    Tga,
+    GutteredPng,
+    /// Color-corrected source at 16 bits per channel
+    Png16,
+    /// Tiled map (.tmx) with its tileset (.tsx + tile PNG)
+    TiledMap,
+    /// Every frame of an animated source, sharing the output palette
+    Apng,
+    /// Palette colors as 4 bytes each in a chosen channel order
+    RawPalette,
+    /// One indexed PNG per band of a split (over 256 colors) output, plus a manifest
+    SplitPng,
+    /// Output inside the edit mask over the original image, as RGBA
+    MaskedPng,
 }
 
 impl ExportFormat {
@@ -12,6 +31,19 @@ impl ExportFormat {
             ExportFormat::PngIndexed => "PNG",
             ExportFormat::Png => "PNG32",
             ExportFormat::Bmp => "BMP",
+            ExportFormat::GenesisNametable => "Genesis Nametable",
+            ExportFormat::PaletteStrip => "Palette Strip",
+            ExportFormat::IndexCsv => "Index CSV",
+            ExportFormat::CombinedPalette => "256-Color Palette",
+            ExportFormat::PaletteCycleGif => "Palette Cycle GIF",
+            ExportFormat::Tga => "TGA",
+            ExportFormat::GutteredPng => "PNG with Gutters",
+            ExportFormat::Png16 => "PNG64",
+            ExportFormat::TiledMap => "Tiled Map",
+            ExportFormat::Apng => "APNG",
+            ExportFormat::RawPalette => "Raw Palette",
+            ExportFormat::SplitPng => "Split Indexed PNGs",
+            ExportFormat::MaskedPng => "Masked PNG32",
         }
     }
 
@@ -20,11 +52,73 @@ impl ExportFormat {
             ExportFormat::PngIndexed => "png",
             ExportFormat::Png => "png",
             ExportFormat::Bmp => "bmp",
+            ExportFormat::GenesisNametable => "bin",
+            ExportFormat::PaletteStrip => "png",
+            ExportFormat::IndexCsv => "csv",
+            ExportFormat::CombinedPalette => "pal",
+            ExportFormat::PaletteCycleGif => "gif",
+            ExportFormat::Tga => "tga",
+            ExportFormat::GutteredPng => "png",
+            ExportFormat::Png16 => "png",
+            ExportFormat::TiledMap => "tmx",
+            ExportFormat::Apng => "png",
+            ExportFormat::RawPalette => "bin",
+            ExportFormat::SplitPng => "json",
+            ExportFormat::MaskedPng => "png",
         }
     }
 
+    /// What ends up in the file, for the export summary
+    pub fn pixel_format(&self) -> &'static str {
+        match self {
+            ExportFormat::PngIndexed | ExportFormat::GutteredPng => "8-bit indexed PNG",
+            ExportFormat::Png => "32-bit RGBA PNG",
+            ExportFormat::Png16 => {
+                "64-bit RGBA PNG (16 bits per channel), re-read from the source file"
+            }
+            ExportFormat::Bmp => "8-bit indexed BMP",
+            ExportFormat::GenesisNametable => "16-bit name table words + 4bpp tiles",
+            ExportFormat::PaletteStrip => "32-bit RGBA swatches",
+            ExportFormat::IndexCsv => "Text, one index per pixel",
+            ExportFormat::CombinedPalette => "JASC-PAL text, 256 entries",
+            ExportFormat::PaletteCycleGif => "8-bit indexed GIF animation",
+            ExportFormat::Tga => "8-bit color-mapped TGA",
+            ExportFormat::TiledMap => "TMX map + TSX tileset + indexed tile PNG",
+            ExportFormat::Apng => "8-bit indexed APNG animation, one palette",
+            ExportFormat::RawPalette => "Binary, 4 bytes per color",
+            ExportFormat::SplitPng => "JSON manifest + one 8-bit indexed PNG per band",
+            ExportFormat::MaskedPng => "32-bit RGBA PNG, original outside the mask",
+        }
+    }
+
+    /// Formats written from the color-corrected source rather than the indexed output
+    pub fn is_color_corrected(&self) -> bool {
+        matches!(self, ExportFormat::Png | ExportFormat::Png16)
+    }
+
+    /// Formats that write only the palette, not the image
+    pub fn is_palette_only(&self) -> bool {
+        matches!(
+            self,
+            ExportFormat::PaletteStrip | ExportFormat::CombinedPalette | ExportFormat::RawPalette
+        )
+    }
+
+    /// Formats offered by "Extract Palette" mode
+    pub fn palette_list() -> &'static [ExportFormat] {
+        &[
+            ExportFormat::PaletteStrip,
+            ExportFormat::CombinedPalette,
+            ExportFormat::RawPalette,
+        ]
+    }
+
     pub fn indexed_list() -> &'static [ExportFormat] {
-        &[ExportFormat::Bmp, ExportFormat::PngIndexed]
+        &[
+            ExportFormat::Bmp,
+            ExportFormat::PngIndexed,
+            ExportFormat::Tga,
+        ]
     }
 
     // pub fn all() -> &'static [ExportFormat] {
@@ -35,3 +129,181 @@ impl ExportFormat {
     //     ]
     // }
 }
+
+/// Color space tagging written into exported files.
+///
+/// Pixel values are always exported exactly as previewed, which is sRGB: the
+/// preview is not display-managed, so there is no separate display-converted
+/// data to write. This only controls whether the file says so.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize, Default)]
+pub enum ExportColorProfile {
+    /// No color space chunk; most tools assume sRGB
+    #[default]
+    Untagged,
+    /// Embed an sRGB chunk (PNG only) so color-managed apps don't reinterpret the colors
+    Srgb,
+}
+
+impl ExportColorProfile {
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            ExportColorProfile::Untagged => "Untagged",
+            ExportColorProfile::Srgb => "Embed sRGB",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            ExportColorProfile::Untagged => {
+                "Write raw sRGB values without a color profile.\nMost tools and converters assume sRGB."
+            }
+            ExportColorProfile::Srgb => {
+                "Write raw sRGB values and mark the PNG as sRGB,\nso color-managed apps display it without shifts.\nBMP and binary exports are never tagged."
+            }
+        }
+    }
+
+    pub fn all() -> &'static [ExportColorProfile] {
+        &[ExportColorProfile::Untagged, ExportColorProfile::Srgb]
+    }
+}
+
+/// Byte order of each color in raw binary exports; hardware palette and DMA
+/// formats disagree on it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize, Default)]
+pub enum ChannelOrder {
+    #[default]
+    Rgba,
+    Bgra,
+    Argb,
+}
+
+impl ChannelOrder {
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            ChannelOrder::Rgba => "RGBA",
+            ChannelOrder::Bgra => "BGRA",
+            ChannelOrder::Argb => "ARGB",
+        }
+    }
+
+    pub fn all() -> &'static [ChannelOrder] {
+        &[ChannelOrder::Rgba, ChannelOrder::Bgra, ChannelOrder::Argb]
+    }
+}
+
+/// Alpha convention of the palette colors written into exported files.
+///
+/// Output palettes are always kept as straight alpha inside the app, independent
+/// of the premultiplied clustering option; this only changes what is stored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize, Default)]
+pub enum ExportAlphaMode {
+    #[default]
+    Straight,
+    Premultiplied,
+}
+
+impl ExportAlphaMode {
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            ExportAlphaMode::Straight => "Straight Alpha",
+            ExportAlphaMode::Premultiplied => "Premultiplied Alpha",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            ExportAlphaMode::Straight => {
+                "Store palette colors unmultiplied, as most image tools expect."
+            }
+            ExportAlphaMode::Premultiplied => {
+                "Store palette colors multiplied by their alpha,\nfor engines that blend premultiplied colors."
+            }
+        }
+    }
+
+    pub fn all() -> &'static [ExportAlphaMode] {
+        &[ExportAlphaMode::Straight, ExportAlphaMode::Premultiplied]
+    }
+}
+
+/// The export preferences every writer shares, taken together from `UserPreferences`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct ExportOptions {
+    pub color_profile: ExportColorProfile,
+    /// Resolution written to PNG `pHYs` and BMP headers; `None` leaves it unspecified
+    pub dpi: Option<u32>,
+    pub alpha_mode: ExportAlphaMode,
+}
+
+/// Canvas growth applied to indexed exports, e.g. for GPU textures that must be
+/// power-of-two sized. Content stays at the top-left; the rest is filled with index 0.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize, Default)]
+pub enum ExportPadding {
+    #[default]
+    None,
+    NextPowerOfTwo,
+    Specific,
+}
+
+impl ExportPadding {
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            ExportPadding::None => "No Padding",
+            ExportPadding::NextPowerOfTwo => "Next Power of Two",
+            ExportPadding::Specific => "Specific Size",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            ExportPadding::None => "Export the image at its own size.",
+            ExportPadding::NextPowerOfTwo => {
+                "Grow each side to the next power of two (e.g. 200×96 → 256×128)."
+            }
+            ExportPadding::Specific => "Grow the canvas to a fixed size, e.g. a texture page.",
+        }
+    }
+
+    pub fn all() -> &'static [ExportPadding] {
+        &[
+            ExportPadding::None,
+            ExportPadding::NextPowerOfTwo,
+            ExportPadding::Specific,
+        ]
+    }
+}
+
+/// Row order of exported BMP and TGA files. Bottom-up is the classic layout; some
+/// homebrew toolchains only read top-down files, marked by a negative BMP height or
+/// a top-left TGA origin.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize, Default)]
+pub enum RowOrder {
+    #[default]
+    BottomUp,
+    TopDown,
+}
+
+impl RowOrder {
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            RowOrder::BottomUp => "Bottom-Up",
+            RowOrder::TopDown => "Top-Down",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            RowOrder::BottomUp => {
+                "Last row first (positive BMP height, bottom-left TGA origin).\nRead by every loader."
+            }
+            RowOrder::TopDown => {
+                "First row first (negative BMP height, top-left TGA origin).\nFor tools that expect it."
+            }
+        }
+    }
+
+    pub fn all() -> &'static [RowOrder] {
+        &[RowOrder::BottomUp, RowOrder::TopDown]
+    }
+}
@@ -1,8 +1,12 @@
 use super::BGRA8;
 use super::ColorCorrection;
+use super::ColorSpace;
+use super::qualetize::quantize_palette_alpha;
+use super::snap_palette::snap_palette_colors;
 use crate::color_processor::ColorProcessor;
-use crate::image_processor::QualetizeResult;
+use crate::image_processor::{ImageProcessor, QualetizeResult, SplitPart, TileFlip};
 use egui::{Color32, ColorImage, TextureHandle};
+use image::{ExtendedColorType, ImageDecoder};
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
@@ -14,6 +18,134 @@ pub struct ImageData {
     pub rgba_data: Vec<u8>,
     // indexed data
     pub indexed: Option<ImageDataIndexed>,
+    /// Details of the file this image was loaded from; `None` for derived images
+    pub source_info: Option<SourceInfo>,
+    /// Error added by snapping the palette to a fixed palette, if snapping was applied
+    pub snap_rmse: Option<f32>,
+    /// Palette entries collapsed by the merge step, if merging was applied
+    pub merged_colors: Option<usize>,
+    /// Bands of an output with more than 256 colors, which has no `indexed` data
+    pub split_parts: Vec<SplitPart>,
+}
+
+/// egui memory key holding the "Smooth When Zoomed Out" preference, so texture creation
+/// on worker threads can read it from the context it already has
+const SMOOTH_PREVIEW_KEY: &str = "smooth_preview_when_zoomed_out";
+
+pub fn set_smooth_preview(ctx: &egui::Context, smooth: bool) {
+    ctx.data_mut(|data| data.insert_temp(egui::Id::new(SMOOTH_PREVIEW_KEY), smooth));
+}
+
+/// Sampling for preview textures: magnified pixels always stay sharp, and with smoothing
+/// on, textures drawn smaller than 1:1 are filtered linearly instead of dropping pixels
+pub fn preview_texture_options(ctx: &egui::Context) -> egui::TextureOptions {
+    let smooth = ctx.data(|data| {
+        data.get_temp::<bool>(egui::Id::new(SMOOTH_PREVIEW_KEY))
+            .unwrap_or(false)
+    });
+    if smooth {
+        egui::TextureOptions {
+            minification: egui::TextureFilter::Linear,
+            ..egui::TextureOptions::NEAREST
+        }
+    } else {
+        egui::TextureOptions::NEAREST
+    }
+}
+
+/// What the loader found in the source file before converting it to RGBA8
+#[derive(Clone, Debug)]
+pub struct SourceInfo {
+    pub format: String,
+    pub color_type: String,
+    pub bits_per_pixel: u16,
+    pub has_icc_profile: bool,
+    /// Decoded as luma only (with or without alpha)
+    pub is_grayscale: bool,
+    /// Lossy JPEG source, whose block noise quantization tends to amplify
+    pub is_jpeg: bool,
+    /// More than 8 bits per channel, which `ImageData` rounds away
+    pub is_high_bit_depth: bool,
+    /// Frames of an animated PNG, stacked top to bottom in the image
+    pub animation: Option<AnimationInfo>,
+}
+
+impl SourceInfo {
+    pub fn summary(&self) -> String {
+        format!(
+            "{} · {} ({} bpp){}{}",
+            self.format,
+            self.color_type,
+            self.bits_per_pixel,
+            if self.has_icc_profile { " · ICC" } else { "" },
+            self.animation
+                .as_ref()
+                .map_or(String::new(), |animation| format!(
+                    " · {} frames",
+                    animation.frame_count()
+                ))
+        )
+    }
+}
+
+/// Timing of the frames of an animated source. The frames are stacked into one image,
+/// so quantizing it gives every frame the same palettes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnimationInfo {
+    /// Display time of each frame in milliseconds
+    pub delays_ms: Vec<u32>,
+}
+
+impl AnimationInfo {
+    pub fn frame_count(&self) -> usize {
+        self.delays_ms.len()
+    }
+
+    /// Height of one frame of the stacked image; `None` once resizing has left a
+    /// height the frames don't divide evenly
+    pub fn frame_height(&self, height: u32) -> Option<u32> {
+        let count = self.frame_count() as u32;
+        (count > 0 && height.is_multiple_of(count)).then(|| height / count)
+    }
+}
+
+/// Every frame of an animated PNG, composited onto the canvas with its dispose and
+/// blend ops and stacked top to bottom; `None` for a still PNG
+fn load_apng_frames(path: &str) -> Result<Option<(image::RgbaImage, AnimationInfo)>, String> {
+    use image::AnimationDecoder;
+    use image::codecs::png::PngDecoder;
+
+    let file = std::fs::File::open(path).map_err(|e| format!("Image loading error: {e}"))?;
+    let decoder = PngDecoder::new(std::io::BufReader::new(file))
+        .map_err(|e| format!("Image loading error: {e}"))?;
+    if !decoder
+        .is_apng()
+        .map_err(|e| format!("Image loading error: {e}"))?
+    {
+        return Ok(None);
+    }
+    let frames = decoder
+        .apng()
+        .and_then(|apng| apng.into_frames().collect_frames())
+        .map_err(|e| format!("APNG decoding error: {e}"))?;
+    if frames.len() < 2 {
+        return Ok(None);
+    }
+
+    let (width, frame_height) = frames[0].buffer().dimensions();
+    let mut stacked = image::RgbaImage::new(width, frame_height * frames.len() as u32);
+    let mut delays_ms = Vec::with_capacity(frames.len());
+    for (i, frame) in frames.iter().enumerate() {
+        image::imageops::replace(
+            &mut stacked,
+            frame.buffer(),
+            0,
+            i as i64 * frame_height as i64,
+        );
+        let (numerator, denominator) = frame.delay().numer_denom_ms();
+        delays_ms.push(numerator / denominator.max(1));
+    }
+    Ok(Some((stacked, AnimationInfo { delays_ms })))
 }
 
 #[derive(Clone)]
@@ -21,6 +153,43 @@ pub struct ImageDataIndexed {
     pub palettes_for_ui: Vec<Vec<egui::Color32>>,
     pub palettes: Vec<BGRA8>,
     pub indexed_pixels: Vec<u8>,
+    /// How tile reduce oriented each tile, when it ran on these pixels
+    pub tile_flips: Option<Vec<TileFlip>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ImageDifference {
+    pub differing_pixels: usize,
+    pub total_pixels: usize,
+    /// Largest absolute difference of any channel of any pixel
+    pub max_error: u8,
+}
+
+impl ImageDifference {
+    pub fn differing_percentage(&self) -> f32 {
+        if self.total_pixels == 0 {
+            0.0
+        } else {
+            self.differing_pixels as f32 * 100.0 / self.total_pixels as f32
+        }
+    }
+}
+
+/// The tile that matches its source worst
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WorstTile {
+    pub tile_x: u32,
+    pub tile_y: u32,
+    pub palette: usize,
+    pub rmse: f32,
+}
+
+/// Output error against the source, broken down by palette
+#[derive(Clone, Debug, PartialEq)]
+pub struct PaletteErrorStats {
+    /// RMSE (0-255 over RGBA) of the pixels drawn from each palette; `None` if unused
+    pub palette_rmse: Vec<Option<f32>>,
+    pub worst_tile: Option<WorstTile>,
 }
 
 #[derive(Clone, Copy)]
@@ -34,6 +203,116 @@ pub struct TileCountOptions {
 pub struct PaletteSortSettings {
     pub mode: SortMode,
     pub order: SortOrder,
+    /// Palette block order: position `i` holds original palette `palette_order[i]`
+    #[serde(default)]
+    pub palette_order: Vec<usize>,
+    /// RGBA colors in the index order `SortMode::Reference` matches against
+    #[serde(default)]
+    pub reference_palette: Vec<[u8; 4]>,
+}
+
+impl PaletteSortSettings {
+    /// The palette order to apply, if it is a non-identity permutation of `palette_count` palettes
+    pub fn palette_order_for(&self, palette_count: usize) -> Option<&[usize]> {
+        if self.palette_order.len() != palette_count
+            || self.palette_order.iter().enumerate().all(|(i, &p)| i == p)
+        {
+            return None;
+        }
+        let mut seen = vec![false; palette_count];
+        for &palette in &self.palette_order {
+            if palette >= palette_count || std::mem::replace(&mut seen[palette], true) {
+                return None;
+            }
+        }
+        Some(&self.palette_order)
+    }
+
+    /// Swap the palettes at two positions, starting from the original order if needed
+    pub fn swap_palettes(&mut self, palette_count: usize, a: usize, b: usize) {
+        if self.palette_order_for(palette_count).is_none() {
+            self.palette_order = (0..palette_count).collect();
+        }
+        self.palette_order.swap(a, b);
+    }
+}
+
+/// Colors of a reference image in index order: the PLTE order of an indexed PNG,
+/// otherwise the order in which colors first appear.
+pub fn load_reference_palette(path: &str) -> Result<Vec<[u8; 4]>, String> {
+    if let Some(colors) = load_png_palette(path) {
+        return Ok(colors);
+    }
+    let img = image::open(path).map_err(|e| format!("Reference loading error: {e}"))?;
+    let mut colors: Vec<[u8; 4]> = Vec::new();
+    for pixel in img.to_rgba8().pixels() {
+        if !colors.contains(&pixel.0) {
+            if colors.len() == 256 {
+                return Err("Reference image has more than 256 colors".to_string());
+            }
+            colors.push(pixel.0);
+        }
+    }
+    if colors.is_empty() {
+        return Err("Reference image has no pixels".to_string());
+    }
+    Ok(colors)
+}
+
+/// PLTE entries (with tRNS alpha) of an indexed PNG; `None` for anything else
+fn load_png_palette(path: &str) -> Option<Vec<[u8; 4]>> {
+    let file = std::fs::File::open(path).ok()?;
+    let reader = png::Decoder::new(std::io::BufReader::new(file))
+        .read_info()
+        .ok()?;
+    let info = reader.info();
+    if info.color_type != png::ColorType::Indexed {
+        return None;
+    }
+    let palette = info.palette.as_ref()?;
+    let alpha = info.trns.as_deref().unwrap_or(&[]);
+    Some(
+        palette
+            .chunks_exact(3)
+            .enumerate()
+            .map(|(i, rgb)| [rgb[0], rgb[1], rgb[2], alpha.get(i).copied().unwrap_or(255)])
+            .collect(),
+    )
+}
+
+/// Sampling used when resizing the input image
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ResizeFilter {
+    #[default]
+    Nearest,
+    Bilinear,
+}
+
+impl ResizeFilter {
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Self::Nearest => "Nearest",
+            Self::Bilinear => "Bilinear",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::Nearest => "Keep hard pixel edges; best for pixel art and integer scales",
+            Self::Bilinear => "Smooth interpolation; best for photos and non-integer scales",
+        }
+    }
+
+    pub fn all() -> &'static [Self] {
+        &[Self::Nearest, Self::Bilinear]
+    }
+
+    fn filter_type(&self) -> image::imageops::FilterType {
+        match self {
+            Self::Nearest => image::imageops::FilterType::Nearest,
+            Self::Bilinear => image::imageops::FilterType::Triangle,
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default)]
@@ -63,6 +342,8 @@ pub enum SortMode {
     Hue,
     Brightness,
     Saturation,
+    /// Follow the index order of `PaletteSortSettings::reference_palette`
+    Reference,
 }
 
 impl SortMode {
@@ -73,6 +354,7 @@ impl SortMode {
             Self::Hue => "Hue",
             Self::Brightness => "Brightness",
             Self::Saturation => "Saturation",
+            Self::Reference => "Match Reference",
         }
     }
     pub fn all() -> &'static [Self] {
@@ -82,38 +364,47 @@ impl SortMode {
             Self::Hue,
             Self::Brightness,
             Self::Saturation,
+            Self::Reference,
         ]
     }
 }
 
 impl ImageDataIndexed {
+    /// Refill the slots past the quantizer's palette data with `padding`
+    pub fn repad(&mut self, padding: egui::Color32) {
+        let mut start = 0;
+        for palette in &mut self.palettes_for_ui {
+            let filled = self.palettes.len().saturating_sub(start).min(palette.len());
+            palette[filled..].fill(padding);
+            start += palette.len();
+        }
+    }
+
     pub fn sorted(
         &self,
         mode: SortMode,
         order: SortOrder,
         first_color_is_transparent: bool,
     ) -> Self {
-        // Get the number of colors per palette from palettes_for_ui
         if self.palettes_for_ui.is_empty() {
             return self.clone();
         }
 
-        let colors_per_palette = self.palettes_for_ui[0].len();
-        let num_palettes = self.palettes_for_ui.len();
-
         // Create a new copy to work with
         let mut new_palettes_for_ui = self.palettes_for_ui.clone();
         let mut new_palettes = self.palettes.clone();
         let mut new_indexed_pixels = self.indexed_pixels.clone();
 
+        // Palettes may differ in size, so track where each one starts
+        let mut palette_start = 0;
+
         // Process each palette
-        for palette_idx in 0..num_palettes.min(new_palettes_for_ui.len()) {
-            // Get colors for this palette
-            let palette_start = palette_idx * colors_per_palette;
+        for (palette_idx, ui_palette) in self.palettes_for_ui.iter().enumerate() {
+            let colors_per_palette = ui_palette.len();
             let palette_end = palette_start + colors_per_palette;
 
             if palette_end > self.palettes.len() {
-                continue;
+                break;
             }
 
             // Create index mapping for sorting
@@ -128,8 +419,8 @@ impl ImageDataIndexed {
                         return std::cmp::Ordering::Greater;
                     }
                 }
-                let color_a = &self.palettes_for_ui[palette_idx][a];
-                let color_b = &self.palettes_for_ui[palette_idx][b];
+                let color_a = &ui_palette[a];
+                let color_b = &ui_palette[b];
 
                 let sort_key_a = Self::get_sort_key(color_a, &mode);
                 let sort_key_b = Self::get_sort_key(color_b, &mode);
@@ -153,46 +444,318 @@ impl ImageDataIndexed {
             // Update palettes_for_ui for this palette
             let mut sorted_ui_palette = vec![egui::Color32::BLACK; colors_per_palette];
             for (new_idx, &old_idx) in indices.iter().enumerate() {
-                sorted_ui_palette[new_idx] = self.palettes_for_ui[palette_idx][old_idx];
+                sorted_ui_palette[new_idx] = ui_palette[old_idx];
             }
             new_palettes_for_ui[palette_idx] = sorted_ui_palette;
 
             // Update palettes for this palette
-            let mut sorted_palette = vec![
-                BGRA8 {
-                    b: 0,
-                    g: 0,
-                    r: 0,
-                    a: 255
-                };
-                colors_per_palette
-            ];
             for (new_idx, &old_idx) in indices.iter().enumerate() {
-                sorted_palette[new_idx] = self.palettes[palette_start + old_idx];
-            }
-            for (i, color) in sorted_palette.iter().enumerate() {
-                new_palettes[palette_start + i] = *color;
+                new_palettes[palette_start + new_idx] = self.palettes[palette_start + old_idx];
             }
 
             // Update indexed_pixels that reference this palette
             for pixel in new_indexed_pixels.iter_mut() {
-                let pixel_palette_idx = (*pixel as usize) / colors_per_palette;
-                let pixel_color_idx = (*pixel as usize) % colors_per_palette;
+                let pixel_index = *pixel as usize;
+                if (palette_start..palette_end).contains(&pixel_index) {
+                    let new_color_idx = index_mapping[pixel_index - palette_start];
+                    *pixel = (palette_start + new_color_idx) as u8;
+                }
+            }
+
+            palette_start = palette_end;
+        }
+
+        ImageDataIndexed {
+            palettes_for_ui: new_palettes_for_ui,
+            palettes: new_palettes,
+            indexed_pixels: new_indexed_pixels,
+            tile_flips: self.tile_flips.clone(),
+        }
+    }
+
+    /// Reorder each palette's colors onto the slots of their closest reference colors,
+    /// remapping pixels so the image stays the same.
+    ///
+    /// Pairs are assigned greedily, closest first; colors left without a reference slot
+    /// fill the remaining slots in their original order. Palette `p` matches the reference
+    /// entries at its own global indices, or the start of the reference if that is too short.
+    pub fn matched_to_reference(
+        &self,
+        reference: &[[u8; 4]],
+        first_color_is_transparent: bool,
+    ) -> Self {
+        let mut new_palettes_for_ui = self.palettes_for_ui.clone();
+        let mut new_palettes = self.palettes.clone();
+        let mut new_indexed_pixels = self.indexed_pixels.clone();
 
-                if pixel_palette_idx == palette_idx {
-                    let new_color_idx = index_mapping[pixel_color_idx];
-                    *pixel = (palette_idx * colors_per_palette + new_color_idx) as u8;
+        let mut palette_start = 0;
+        for (palette_idx, palette) in self.palettes_for_ui.iter().enumerate() {
+            let len = palette.len();
+            let palette_end = palette_start + len;
+            if palette_end > self.palettes.len() {
+                break;
+            }
+            let reference = reference
+                .get(palette_start..palette_end)
+                .unwrap_or(&reference[..len.min(reference.len())]);
+
+            let fixed = (first_color_is_transparent && len > 0) as usize;
+            let distance = |old: usize, slot: usize| -> u32 {
+                let color = &self.palettes[palette_start + old];
+                let [r, g, b, a] = reference[slot];
+                [(color.r, r), (color.g, g), (color.b, b), (color.a, a)]
+                    .iter()
+                    .map(|&(x, y)| (x as i32 - y as i32).pow(2) as u32)
+                    .sum()
+            };
+            let mut pairs: Vec<(u32, usize, usize)> = (fixed..len)
+                .flat_map(|old| (fixed..reference.len()).map(move |slot| (old, slot)))
+                .map(|(old, slot)| (distance(old, slot), old, slot))
+                .collect();
+            pairs.sort();
+
+            // indices[new_idx] = old_idx
+            let mut indices: Vec<Option<usize>> = vec![None; len];
+            let mut placed = vec![false; len];
+            if fixed == 1 {
+                // The transparent color keeps index 0
+                indices[0] = Some(0);
+                placed[0] = true;
+            }
+            for (_, old, slot) in pairs {
+                if !placed[old] && indices[slot].is_none() {
+                    indices[slot] = Some(old);
+                    placed[old] = true;
                 }
             }
+            let mut leftovers = (0..len).filter(|&old| !placed[old]);
+            let indices: Vec<usize> = indices
+                .into_iter()
+                .map(|old| old.or_else(|| leftovers.next()).unwrap_or(0))
+                .collect();
+
+            let mut index_mapping = vec![0; len];
+            for (new_idx, &old_idx) in indices.iter().enumerate() {
+                index_mapping[old_idx] = new_idx;
+                new_palettes_for_ui[palette_idx][new_idx] = palette[old_idx];
+                new_palettes[palette_start + new_idx] = self.palettes[palette_start + old_idx];
+            }
+            for pixel in new_indexed_pixels.iter_mut() {
+                let pixel_index = *pixel as usize;
+                if (palette_start..palette_end).contains(&pixel_index) {
+                    *pixel = (palette_start + index_mapping[pixel_index - palette_start]) as u8;
+                }
+            }
+
+            palette_start = palette_end;
         }
 
         ImageDataIndexed {
             palettes_for_ui: new_palettes_for_ui,
             palettes: new_palettes,
             indexed_pixels: new_indexed_pixels,
+            tile_flips: self.tile_flips.clone(),
         }
     }
 
+    /// Global index of the first color of each palette
+    pub fn palette_starts(&self) -> Vec<usize> {
+        self.palettes_for_ui
+            .iter()
+            .scan(0, |start, palette| {
+                let palette_start = *start;
+                *start += palette.len();
+                Some(palette_start)
+            })
+            .collect()
+    }
+
+    /// Palette a global color index belongs to
+    pub fn palette_of_index(palette_starts: &[usize], index: u8) -> usize {
+        palette_starts
+            .iter()
+            .rposition(|&start| start <= index as usize)
+            .unwrap_or(0)
+    }
+
+    /// Replace the palette colors, keeping every pixel's index and each palette's size
+    pub fn with_palette_colors(&self, colors: &[BGRA8]) -> Self {
+        let mut palettes = self.palettes.clone();
+        for (dst, src) in palettes.iter_mut().zip(colors) {
+            *dst = *src;
+        }
+
+        let mut start = 0;
+        let palettes_for_ui = self
+            .palettes_for_ui
+            .iter()
+            .map(|palette| {
+                let ui_palette = (start..start + palette.len())
+                    .map(|i| {
+                        // Slots past the palette data keep their padding color
+                        palettes.get(i).map_or(palette[i - start], |c| {
+                            egui::Color32::from_rgba_unmultiplied(c.r, c.g, c.b, c.a)
+                        })
+                    })
+                    .collect();
+                start += palette.len();
+                ui_palette
+            })
+            .collect();
+
+        ImageDataIndexed {
+            palettes_for_ui,
+            palettes,
+            indexed_pixels: self.indexed_pixels.clone(),
+            tile_flips: self.tile_flips.clone(),
+        }
+    }
+
+    /// Reorder whole palettes so position `i` holds palette `order[i]`.
+    /// Pixel indices are remapped, so the image looks the same.
+    pub fn with_palette_order(&self, order: &[usize]) -> Self {
+        let palette_starts: Vec<usize> = self
+            .palettes_for_ui
+            .iter()
+            .scan(0, |start, palette| {
+                let palette_start = *start;
+                *start += palette.len();
+                Some(palette_start)
+            })
+            .collect();
+        let total_colors: usize = self.palettes_for_ui.iter().map(|p| p.len()).sum();
+        if order.len() != self.palettes_for_ui.len() || total_colors > self.palettes.len() {
+            return self.clone();
+        }
+
+        let mut palettes_for_ui = Vec::with_capacity(order.len());
+        let mut palettes = Vec::with_capacity(self.palettes.len());
+        let mut index_mapping: Vec<u8> = (0..=255).collect();
+        for &old_palette in order {
+            let old_start = palette_starts[old_palette];
+            let len = self.palettes_for_ui[old_palette].len();
+            let new_start = palettes.len();
+            for offset in 0..len {
+                if old_start + offset < index_mapping.len() {
+                    index_mapping[old_start + offset] = (new_start + offset) as u8;
+                }
+            }
+            palettes.extend_from_slice(&self.palettes[old_start..old_start + len]);
+            palettes_for_ui.push(self.palettes_for_ui[old_palette].clone());
+        }
+        palettes.extend_from_slice(&self.palettes[total_colors..]);
+
+        ImageDataIndexed {
+            palettes_for_ui,
+            palettes,
+            indexed_pixels: self
+                .indexed_pixels
+                .iter()
+                .map(|&index| index_mapping[index as usize])
+                .collect(),
+            tile_flips: self.tile_flips.clone(),
+        }
+    }
+
+    /// Collapse entries of each palette that lie within `tolerance` of each other in
+    /// `color_space` into the more used one, remapping pixels. Survivors move to the front
+    /// of their palette so the freed slots end up at the back. Colors are only merged
+    /// within a palette, since a tile can't reach the entries of another one.
+    /// Returns the number of entries merged away.
+    pub fn merge_similar_colors(
+        &mut self,
+        tolerance: f32,
+        color_space: &ColorSpace,
+        keep_first_color: bool,
+    ) -> usize {
+        let mut usage = [0usize; 256];
+        for &index in &self.indexed_pixels {
+            usage[index as usize] += 1;
+        }
+
+        let mut index_mapping: Vec<u8> = (0..=255).collect();
+        let mut merged_total = 0;
+        let mut palette_start = 0;
+        for palette_ui in &mut self.palettes_for_ui {
+            let len = palette_ui.len();
+            let range = palette_start..(palette_start + len).min(self.palettes.len());
+            palette_start += len;
+            let fixed = if keep_first_color { 1 } else { 0 };
+            if range.len() <= fixed {
+                continue;
+            }
+
+            let colors: Vec<BGRA8> = self.palettes[range.clone()].to_vec();
+            let components: Vec<[f32; 3]> = colors
+                .iter()
+                .map(|c| color_space.components([c.r, c.g, c.b]))
+                .collect();
+
+            // Each entry points at the entry it was merged into
+            let mut target: Vec<usize> = (0..colors.len()).collect();
+            // Most used first, so frequent colors survive
+            let mut order: Vec<usize> = (fixed..colors.len()).collect();
+            order.sort_by_key(|&i| std::cmp::Reverse(usage[range.start + i]));
+            for (n, &keep) in order.iter().enumerate() {
+                if target[keep] != keep {
+                    continue;
+                }
+                for &other in &order[n + 1..] {
+                    if target[other] != other || colors[other].a != colors[keep].a {
+                        continue;
+                    }
+                    let distance = components[keep]
+                        .iter()
+                        .zip(&components[other])
+                        .map(|(a, b)| (a - b) * (a - b))
+                        .sum::<f32>()
+                        .sqrt();
+                    if distance <= tolerance {
+                        target[other] = keep;
+                    }
+                }
+            }
+
+            let survivors: Vec<usize> = (0..colors.len()).filter(|&i| target[i] == i).collect();
+            let merged = colors.len() - survivors.len();
+            if merged == 0 {
+                continue;
+            }
+            merged_total += merged;
+
+            let mut new_position = vec![0usize; colors.len()];
+            for (position, &survivor) in survivors.iter().enumerate() {
+                new_position[survivor] = position;
+                self.palettes[range.start + position] = colors[survivor];
+                palette_ui[position] = Color32::from_rgba_unmultiplied(
+                    colors[survivor].r,
+                    colors[survivor].g,
+                    colors[survivor].b,
+                    colors[survivor].a,
+                );
+            }
+            // Freed slots repeat the last survivor so they stay harmless if referenced
+            let filler = colors[*survivors.last().unwrap_or(&0)];
+            let freed = range.start + survivors.len()..range.end;
+            self.palettes[freed].fill(filler);
+            palette_ui[survivors.len()..colors.len()].fill(Color32::from_rgba_unmultiplied(
+                filler.r, filler.g, filler.b, filler.a,
+            ));
+            for (i, &merged_into) in target.iter().enumerate() {
+                if let Some(mapping) = index_mapping.get_mut(range.start + i) {
+                    *mapping = (range.start + new_position[merged_into]) as u8;
+                }
+            }
+        }
+
+        if merged_total > 0 {
+            for index in &mut self.indexed_pixels {
+                *index = index_mapping[*index as usize];
+            }
+        }
+        merged_total
+    }
+
     fn get_sort_key(color: &egui::Color32, mode: &SortMode) -> f32 {
         if mode == &SortMode::None {
             return 0.0;
@@ -206,7 +769,7 @@ impl ImageDataIndexed {
         let l = ColorProcessor::rgb_f32_to_luminance(r, g, b);
 
         match mode {
-            SortMode::None => 0.0,
+            SortMode::None | SortMode::Reference => 0.0,
             SortMode::Luminance => l * 10000.0 + a + v,
             SortMode::Hue => h * 10000.0 + a + l,
             SortMode::Saturation => s * 10000.0 + a + l,
@@ -228,13 +791,141 @@ impl ImageData {
         }
     }
 
+    /// Per-palette RMSE of this indexed output against `source`, and the tile with the
+    /// highest error. A tile is attributed to the palette of its first pixel.
+    pub fn palette_error_stats(
+        &self,
+        source: &ImageData,
+        tile_width: u32,
+        tile_height: u32,
+    ) -> Option<PaletteErrorStats> {
+        let indexed = self.indexed.as_ref()?;
+        if self.width != source.width
+            || self.height != source.height
+            || self.rgba_data.len() != source.rgba_data.len()
+            || indexed.indexed_pixels.len() != (self.width * self.height) as usize
+            || tile_width == 0
+            || tile_height == 0
+        {
+            return None;
+        }
+
+        let palette_starts = indexed.palette_starts();
+        let pixel_error = |i: usize| -> f64 {
+            self.rgba_data[i * 4..i * 4 + 4]
+                .iter()
+                .zip(&source.rgba_data[i * 4..i * 4 + 4])
+                .map(|(&a, &b)| {
+                    let d = a as f64 - b as f64;
+                    d * d
+                })
+                .sum()
+        };
+
+        let mut palette_sums = vec![(0.0f64, 0usize); palette_starts.len()];
+        for (i, &index) in indexed.indexed_pixels.iter().enumerate() {
+            let palette = ImageDataIndexed::palette_of_index(&palette_starts, index);
+            if let Some(sum) = palette_sums.get_mut(palette) {
+                sum.0 += pixel_error(i);
+                sum.1 += 1;
+            }
+        }
+
+        let mut worst_tile: Option<WorstTile> = None;
+        for tile_y in 0..self.height.div_ceil(tile_height) {
+            for tile_x in 0..self.width.div_ceil(tile_width) {
+                let (mut sum, mut count) = (0.0f64, 0usize);
+                for y in tile_y * tile_height..((tile_y + 1) * tile_height).min(self.height) {
+                    for x in tile_x * tile_width..((tile_x + 1) * tile_width).min(self.width) {
+                        sum += pixel_error((y * self.width + x) as usize);
+                        count += 1;
+                    }
+                }
+                let rmse = (sum / (count.max(1) * 4) as f64).sqrt() as f32;
+                if worst_tile.is_none_or(|worst| rmse > worst.rmse) {
+                    let first = (tile_y * tile_height * self.width + tile_x * tile_width) as usize;
+                    worst_tile = Some(WorstTile {
+                        tile_x,
+                        tile_y,
+                        palette: ImageDataIndexed::palette_of_index(
+                            &palette_starts,
+                            indexed.indexed_pixels[first],
+                        ),
+                        rmse,
+                    });
+                }
+            }
+        }
+
+        Some(PaletteErrorStats {
+            palette_rmse: palette_sums
+                .iter()
+                .map(|&(sum, count)| (count > 0).then(|| (sum / (count * 4) as f64).sqrt() as f32))
+                .collect(),
+            worst_tile,
+        })
+    }
+
+    /// Compare two images of the same size pixel by pixel
+    pub fn difference(&self, other: &ImageData) -> Option<ImageDifference> {
+        if self.width != other.width
+            || self.height != other.height
+            || self.rgba_data.len() != other.rgba_data.len()
+        {
+            return None;
+        }
+
+        let mut differing_pixels = 0;
+        let mut max_error = 0u8;
+        for (a, b) in self
+            .rgba_data
+            .chunks_exact(4)
+            .zip(other.rgba_data.chunks_exact(4))
+        {
+            let pixel_error = a
+                .iter()
+                .zip(b.iter())
+                .map(|(&ca, &cb)| ca.abs_diff(cb))
+                .max()
+                .unwrap_or(0);
+            if pixel_error > 0 {
+                differing_pixels += 1;
+                max_error = max_error.max(pixel_error);
+            }
+        }
+
+        Some(ImageDifference {
+            differing_pixels,
+            total_pixels: (self.width * self.height) as usize,
+            max_error,
+        })
+    }
+
     pub fn color_corrected(
         &self,
         color_correction: &ColorCorrection,
         ctx: &egui::Context,
     ) -> ImageData {
+        let mut filtered = None;
+        if color_correction.reduce_jpeg_artifacts {
+            filtered = Some(ColorProcessor::reduce_jpeg_artifacts(
+                &self.rgba_data,
+                self.width,
+                self.height,
+            ));
+        }
+        if color_correction.denoise_enabled {
+            filtered = Some(ColorProcessor::denoise(
+                filtered.as_deref().unwrap_or(&self.rgba_data),
+                self.width,
+                self.height,
+                color_correction.denoise_strength,
+                color_correction.denoise_radius,
+            ));
+        }
+        let source = filtered.as_deref().unwrap_or(&self.rgba_data);
         let rgba_img = ColorProcessor::apply_pixels_correction(
-            &self.rgba_data,
+            source,
             self.width,
             self.height,
             color_correction,
@@ -243,11 +934,8 @@ impl ImageData {
         let rgba_data = rgba_img.into_raw();
 
         let color_image = ColorImage::from_rgba_unmultiplied(size, &rgba_data);
-        let texture = ctx.load_texture(
-            "color_corrected",
-            color_image,
-            egui::TextureOptions::NEAREST,
-        );
+        let texture =
+            ctx.load_texture("color_corrected", color_image, preview_texture_options(ctx));
 
         ImageData {
             texture,
@@ -255,77 +943,182 @@ impl ImageData {
             height: size[1] as u32,
             rgba_data,
             indexed: None,
+            source_info: None,
+            snap_rmse: None,
+            merged_colors: None,
+            split_parts: Vec::new(),
         }
     }
 
+    /// Copy of an indexed image with new palette colors, re-rendering its texture
+    pub fn with_palette_colors(&self, colors: &[BGRA8], ctx: &egui::Context) -> Option<ImageData> {
+        let indexed = self.indexed.as_ref()?.with_palette_colors(colors);
+
+        let mut pixels = Vec::with_capacity((self.width * self.height * 4) as usize);
+        for &pixel_index in &indexed.indexed_pixels {
+            match indexed.palettes.get(pixel_index as usize) {
+                Some(color) => pixels.extend_from_slice(&color.to_rgba()),
+                None => pixels.extend_from_slice(&[0, 0, 0, 255]),
+            }
+        }
+
+        let size = [self.width as usize, self.height as usize];
+        let color_image = ColorImage::from_rgba_unmultiplied(size, &pixels);
+        let texture = ctx.load_texture("output", color_image, preview_texture_options(ctx));
+
+        Some(ImageData {
+            texture,
+            width: self.width,
+            height: self.height,
+            rgba_data: pixels,
+            indexed: Some(indexed),
+            source_info: None,
+            snap_rmse: self.snap_rmse,
+            merged_colors: self.merged_colors,
+            split_parts: Vec::new(),
+        })
+    }
+
+    /// `palette_padding` fills palette slots beyond the quantizer's palette data.
+    ///
+    /// The preview texture is not color managed: it holds the same raw sRGB palette
+    /// values the exporters write, and egui shows them untransformed. Differences from
+    /// an exported file come from the viewer (e.g. a PNG tagged with
+    /// `ExportColorProfile::Srgb` in a color-managed app), not from this conversion.
     pub fn create_from_qualetize_result(
         result: QualetizeResult,
+        palette_padding: egui::Color32,
         ctx: &egui::Context,
     ) -> Result<ImageData, String> {
+        if !result.split_parts.is_empty() {
+            return Ok(Self::from_split_parts(result, ctx));
+        }
         let QualetizeResult {
             indexed_data,
-            palette_data,
+            mut palette_data,
             settings,
             width,
             height,
             generation_id: _,
+            rmse: _,
+            split_parts: _,
         } = result;
 
-        let mut pixels = Vec::with_capacity((width * height * 4) as usize);
-        for &pixel_index in &indexed_data {
-            let palette_index = pixel_index as usize;
-            if palette_index < palette_data.len() {
-                let color = &palette_data[palette_index];
-                pixels.extend_from_slice(&[color.r, color.g, color.b, color.a]);
-            } else {
-                pixels.extend_from_slice(&[0, 0, 0, 255]);
-            }
+        // The quantizer does not put output alpha on the A-depth grid, so snap it here
+        if let Some(levels) = settings.alpha_levels() {
+            quantize_palette_alpha(&mut palette_data, &levels);
         }
 
-        let size = [width as usize, height as usize];
-        let color_image = ColorImage::from_rgba_unmultiplied(size, &pixels);
-        let texture = ctx.load_texture("output", color_image, egui::TextureOptions::NEAREST);
+        // Clustering ran freely; now pull the palette onto the fixed palette if requested
+        let snap_rmse = settings
+            .snap_palette_targets()
+            .map(|targets| snap_palette_colors(&mut palette_data, &targets, &indexed_data));
 
-        // パレット情報を直接変換
-        let palettes_for_ui = Self::convert_palette_data(
-            &palette_data,
-            settings.n_palettes as usize,
-            settings.n_colors as usize,
-        );
+        let palettes_for_ui =
+            Self::convert_palette_data(&palette_data, &settings.palette_sizes(), palette_padding);
+        let mut indexed = ImageDataIndexed {
+            palettes_for_ui,
+            palettes: palette_data,
+            indexed_pixels: indexed_data,
+            tile_flips: None,
+        };
+        // After snapping, so colors snapped onto the same target are merged too
+        let merged_colors = settings.merge_colors_enabled.then(|| {
+            indexed.merge_similar_colors(
+                settings.merge_colors_tolerance,
+                &settings.color_space,
+                settings.uses_transparent_index(),
+            )
+        });
+        let palette_data = &indexed.palettes;
+
+        // Palette-only output keeps the indices for exports but skips rendering them
+        let (pixels, texture) = if settings.palette_only {
+            let color_image = ColorImage::from_rgba_unmultiplied([1, 1], &[0, 0, 0, 0]);
+            let texture = ctx.load_texture("output", color_image, preview_texture_options(ctx));
+            (Vec::new(), texture)
+        } else {
+            let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+            for &pixel_index in &indexed.indexed_pixels {
+                let palette_index = pixel_index as usize;
+                if palette_index < palette_data.len() {
+                    let color = &palette_data[palette_index];
+                    pixels.extend_from_slice(&color.to_rgba());
+                } else {
+                    pixels.extend_from_slice(&[0, 0, 0, 255]);
+                }
+            }
+
+            let size = [width as usize, height as usize];
+            let color_image = ColorImage::from_rgba_unmultiplied(size, &pixels);
+            let texture = ctx.load_texture("output", color_image, preview_texture_options(ctx));
+            (pixels, texture)
+        };
 
         Ok(ImageData {
             texture,
             width,
             height,
             rgba_data: pixels,
-            indexed: Some(ImageDataIndexed {
-                palettes_for_ui,
-                palettes: palette_data,
-                indexed_pixels: indexed_data,
-            }),
+            indexed: Some(indexed),
+            source_info: None,
+            snap_rmse,
+            merged_colors,
+            split_parts: Vec::new(),
         })
     }
+
+    /// Render a split output band by band. Its bands don't share one index space, so
+    /// `indexed` stays `None` and the palette post-processing steps don't apply.
+    fn from_split_parts(result: QualetizeResult, ctx: &egui::Context) -> ImageData {
+        let (width, height) = (result.width, result.height);
+        let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+        for part in &result.split_parts {
+            for &index in &part.indexed_pixels {
+                let color = part
+                    .palettes
+                    .get(index as usize)
+                    .map_or([0, 0, 0, 255], |color| color.to_rgba());
+                pixels.extend_from_slice(&color);
+            }
+        }
+        let size = [width as usize, height as usize];
+        let color_image = ColorImage::from_rgba_unmultiplied(size, &pixels);
+        let texture = ctx.load_texture("output", color_image, preview_texture_options(ctx));
+        ImageData {
+            texture,
+            width,
+            height,
+            rgba_data: pixels,
+            indexed: None,
+            source_info: None,
+            snap_rmse: None,
+            merged_colors: None,
+            split_parts: result.split_parts,
+        }
+    }
+
     fn convert_palette_data(
         palette_data: &[BGRA8],
-        n_palettes: usize,
-        n_colors: usize,
+        sizes: &[u16],
+        padding: egui::Color32,
     ) -> Vec<Vec<egui::Color32>> {
-        let colors_per_palette = n_colors;
-        let mut palettes = Vec::new();
-
         let egui_colors: Vec<egui::Color32> = palette_data
             .iter()
             .map(|bgra| egui::Color32::from_rgba_unmultiplied(bgra.r, bgra.g, bgra.b, bgra.a))
             .collect();
 
-        for chunk in egui_colors.chunks(colors_per_palette) {
-            palettes.push(chunk.to_vec());
-        }
-
-        while palettes.len() < n_palettes {
-            palettes.push(vec![egui::Color32::BLACK; colors_per_palette]);
+        // Palettes are packed back-to-back, each with its own size
+        let mut palettes = Vec::with_capacity(sizes.len());
+        let mut start = 0;
+        for &size in sizes {
+            let size = size as usize;
+            let mut palette: Vec<egui::Color32> =
+                egui_colors.iter().skip(start).take(size).copied().collect();
+            palette.resize(size, padding);
+            palettes.push(palette);
+            start += size;
         }
-        palettes.truncate(n_palettes);
 
         palettes
     }
@@ -423,20 +1216,213 @@ impl ImageData {
         Some(unique_tiles.len())
     }
 
+    /// Resample the RGBA pixels to a new size. The result has no indexed data.
+    pub fn resized(
+        &self,
+        width: u32,
+        height: u32,
+        filter: ResizeFilter,
+        ctx: &egui::Context,
+    ) -> Result<ImageData, String> {
+        if width == 0 || height == 0 {
+            return Err(format!("Invalid target size ({width}×{height})"));
+        }
+        let source = image::RgbaImage::from_raw(self.width, self.height, self.rgba_data.clone())
+            .ok_or_else(|| "Image buffer does not match its size".to_string())?;
+        let rgba_data =
+            image::imageops::resize(&source, width, height, filter.filter_type()).into_raw();
+        Ok(self.with_rgba_data(width, height, rgba_data, ctx))
+    }
+
+    /// Copy brought to `width`×`height` by adding or removing low-detail seams,
+    /// for sizes a few pixels off
+    pub fn seam_carved(
+        &self,
+        width: u32,
+        height: u32,
+        ctx: &egui::Context,
+    ) -> Result<ImageData, String> {
+        let rgba_data =
+            ImageProcessor::seam_carve(&self.rgba_data, self.width, self.height, width, height)?;
+        Ok(self.with_rgba_data(width, height, rgba_data, ctx))
+    }
+
+    /// Image with new pixels and the same source information, without indexed data
+    pub fn with_rgba_data(
+        &self,
+        width: u32,
+        height: u32,
+        rgba_data: Vec<u8>,
+        ctx: &egui::Context,
+    ) -> ImageData {
+        let size = [width as usize, height as usize];
+        let color_image = ColorImage::from_rgba_unmultiplied(size, &rgba_data);
+        let texture = ctx.load_texture("input", color_image, preview_texture_options(ctx));
+        ImageData {
+            texture,
+            width,
+            height,
+            rgba_data,
+            indexed: None,
+            source_info: self.source_info.clone(),
+            snap_rmse: None,
+            merged_colors: None,
+            split_parts: Vec::new(),
+        }
+    }
+
     pub fn load(path: &str, ctx: &egui::Context) -> Result<ImageData, String> {
-        let img = image::open(path).map_err(|e| format!("Image loading error: {e}"))?;
-        let rgba_img = img.to_rgba8();
+        let reader = image::ImageReader::open(path)
+            .and_then(|reader| reader.with_guessed_format())
+            .map_err(|e| format!("Image loading error: {e}"))?;
+        let format = reader.format();
+        let mut decoder = reader
+            .into_decoder()
+            .map_err(|e| format!("Image loading error: {e}"))?;
+        let color_type = decoder.original_color_type();
+        let has_icc_profile = decoder.icc_profile().ok().flatten().is_some();
+        let mut source_info = SourceInfo {
+            format: format
+                .and_then(|format| format.extensions_str().first())
+                .map_or("Unknown".to_string(), |ext| ext.to_uppercase()),
+            color_type: format!("{color_type:?}"),
+            bits_per_pixel: color_type.bits_per_pixel(),
+            has_icc_profile,
+            is_grayscale: matches!(
+                color_type,
+                ExtendedColorType::L1
+                    | ExtendedColorType::La1
+                    | ExtendedColorType::L2
+                    | ExtendedColorType::La2
+                    | ExtendedColorType::L4
+                    | ExtendedColorType::La4
+                    | ExtendedColorType::L8
+                    | ExtendedColorType::La8
+                    | ExtendedColorType::L16
+                    | ExtendedColorType::La16
+            ),
+            is_jpeg: format == Some(image::ImageFormat::Jpeg),
+            is_high_bit_depth: color_type.bits_per_pixel() / u16::from(color_type.channel_count())
+                > 8,
+            animation: None,
+        };
+        let animated = if format == Some(image::ImageFormat::Png) {
+            load_apng_frames(path)?
+        } else {
+            None
+        };
+        let rgba_img = match animated {
+            Some((frames, animation)) => {
+                source_info.animation = Some(animation);
+                frames
+            }
+            None => image::DynamicImage::from_decoder(decoder)
+                .map_err(|e| format!("Image loading error: {e}"))?
+                .to_rgba8(),
+        };
+        if rgba_img.width() == 0 || rgba_img.height() == 0 {
+            return Err(format!(
+                "Image has no pixels ({}×{})",
+                rgba_img.width(),
+                rgba_img.height()
+            ));
+        }
         let size = [rgba_img.width() as usize, rgba_img.height() as usize];
         let rgba_data = rgba_img.into_raw();
 
         let color_image = ColorImage::from_rgba_unmultiplied(size, &rgba_data);
-        let texture = ctx.load_texture("input", color_image, egui::TextureOptions::NEAREST);
+        let texture = ctx.load_texture("input", color_image, preview_texture_options(ctx));
         Ok(ImageData {
             texture,
             width: size[0] as u32,
             height: size[1] as u32,
             rgba_data,
             indexed: None,
+            source_info: Some(source_info),
+            snap_rmse: None,
+            merged_colors: None,
+            split_parts: Vec::new(),
         })
     }
+
+    /// Distinct RGBA values among the visible pixels
+    pub fn unique_color_count(&self) -> usize {
+        self.rgba_data
+            .chunks_exact(4)
+            .filter(|pixel| pixel[3] > 0)
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    /// Loaded from a luma-only file
+    pub fn is_grayscale(&self) -> bool {
+        self.source_info
+            .as_ref()
+            .is_some_and(|info| info.is_grayscale)
+    }
+
+    /// Decode `path` at 16 bits per channel, for exports that keep the source precision.
+    /// Returns the RGBA samples and size.
+    pub fn load_rgba16(path: &str) -> Result<(Vec<u16>, u32, u32), String> {
+        let img = image::ImageReader::open(path)
+            .and_then(|reader| reader.with_guessed_format())
+            .map_err(|e| format!("Image loading error: {e}"))?
+            .decode()
+            .map_err(|e| format!("Image loading error: {e}"))?
+            .to_rgba16();
+        let (width, height) = img.dimensions();
+        Ok((img.into_raw(), width, height))
+    }
+
+    /// Loaded from a file with more than 8 bits per channel
+    pub fn is_high_bit_depth(&self) -> bool {
+        self.source_info
+            .as_ref()
+            .is_some_and(|info| info.is_high_bit_depth)
+    }
+
+    /// Frames of an animated source, if this image still holds all of them evenly
+    pub fn animation(&self) -> Option<&AnimationInfo> {
+        self.source_info
+            .as_ref()
+            .and_then(|info| info.animation.as_ref())
+            .filter(|animation| animation.frame_height(self.height).is_some())
+    }
+
+    /// Loaded from a JPEG file
+    pub fn is_jpeg(&self) -> bool {
+        self.source_info.as_ref().is_some_and(|info| info.is_jpeg)
+    }
+
+    /// Indexed output whose pixels were never rendered (palette-only mode)
+    pub fn is_palette_only(&self) -> bool {
+        self.indexed.is_some() && self.rgba_data.is_empty()
+    }
+
+    /// Re-upload the texture so it picks up the current preview sampling
+    pub fn refresh_texture_options(&mut self, ctx: &egui::Context) {
+        if self.is_palette_only() {
+            return;
+        }
+        let size = [self.width as usize, self.height as usize];
+        let color_image = ColorImage::from_rgba_unmultiplied(size, &self.rgba_data);
+        self.texture.set(color_image, preview_texture_options(ctx));
+    }
+
+    /// Decode a small preview for the file queue, scaled to fit `max_size`
+    pub fn load_thumbnail(
+        path: &str,
+        max_size: u32,
+        ctx: &egui::Context,
+    ) -> Result<TextureHandle, String> {
+        let img = image::open(path).map_err(|e| format!("Image loading error: {e}"))?;
+        let thumbnail = img.thumbnail(max_size, max_size).to_rgba8();
+        let size = [thumbnail.width() as usize, thumbnail.height() as usize];
+        let color_image = ColorImage::from_rgba_unmultiplied(size, thumbnail.as_raw());
+        Ok(ctx.load_texture(
+            format!("queue_thumbnail_{path}"),
+            color_image,
+            egui::TextureOptions::LINEAR,
+        ))
+    }
 }
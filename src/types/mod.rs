@@ -1,17 +1,29 @@
 pub mod app_state;
 pub mod color_correction;
 pub mod color_space;
+pub mod color_space_trial;
+pub mod contact_sheet;
+pub mod display_filter;
 pub mod dither;
 pub mod export;
 pub mod image;
+pub mod palette_cycle;
+pub mod palette_diff;
 pub mod preferences;
 pub mod qualetize;
+pub mod snap_palette;
 
 // Re-export all public types for convenience
 pub use app_state::AppState;
 pub use color_correction::ColorCorrection;
 pub use color_space::ColorSpace;
+pub use display_filter::DisplayFilter;
 pub use dither::DitherMode;
-pub use export::ExportFormat;
+pub use export::{
+    ChannelOrder, ExportAlphaMode, ExportColorProfile, ExportFormat, ExportOptions, ExportPadding,
+    RowOrder,
+};
 pub use image::ImageData;
-pub use qualetize::{BGRA8, ClearColor, QualetizePreset, QualetizeSettings};
+pub use palette_cycle::PaletteCycle;
+pub use qualetize::{BGRA8, ClearColor, DepthRounding, QualetizePreset, QualetizeSettings};
+pub use snap_palette::SnapPalette;
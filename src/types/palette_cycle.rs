@@ -0,0 +1,62 @@
+use std::ops::RangeInclusive;
+
+/// Preview-only palette animation: entries `first_index..=last_index` of the
+/// combined palette rotate by one slot per step, like water/fire tiles in retro games.
+/// Exported images keep the original order; only the GIF export animates.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PaletteCycle {
+    pub enabled: bool,
+    pub first_index: u8,
+    pub last_index: u8,
+    pub steps_per_second: f32,
+}
+
+pub const PALETTE_CYCLE_SPEED_RANGE: RangeInclusive<f32> = 0.5..=60.0;
+
+impl Default for PaletteCycle {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            first_index: 1,
+            last_index: 15,
+            steps_per_second: 8.0,
+        }
+    }
+}
+
+impl PaletteCycle {
+    /// Cycled indices, or `None` if the range is empty or past the end of the palette
+    pub fn range(&self, palette_len: usize) -> Option<RangeInclusive<usize>> {
+        let (first, last) = (self.first_index as usize, self.last_index as usize);
+        (first < last && last < palette_len).then_some(first..=last)
+    }
+
+    /// Steps until the colors are back in their original slots
+    pub fn frame_count(&self, palette_len: usize) -> usize {
+        self.range(palette_len)
+            .map_or(1, |range| range.end() - range.start() + 1)
+    }
+
+    /// Animation step shown `seconds` after the preview started
+    pub fn step_at(&self, seconds: f64) -> usize {
+        (seconds * self.steps_per_second.max(f32::EPSILON) as f64) as usize
+    }
+
+    pub fn frame_delay_ms(&self) -> u32 {
+        (1000.0 / self.steps_per_second.max(f32::EPSILON)).round() as u32
+    }
+
+    /// Palette with the cycled range rotated `step` slots toward higher indices
+    pub fn cycled_palette<T: Copy>(&self, palette: &[T], step: usize) -> Vec<T> {
+        let mut cycled = palette.to_vec();
+        if let Some(range) = self.range(palette.len()) {
+            let first = *range.start();
+            let len = range.end() - first + 1;
+            let shift = len - step % len;
+            for index in range {
+                cycled[index] = palette[first + (index - first + shift) % len];
+            }
+        }
+        cycled
+    }
+}
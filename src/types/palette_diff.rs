@@ -0,0 +1,105 @@
+use super::color_space::delta_e;
+
+/// Palette loaded from a file for comparison
+#[derive(Clone, Debug)]
+pub struct LoadedPalette {
+    pub name: String,
+    pub colors: Vec<[u8; 4]>,
+}
+
+/// Which side of the comparison a loaded palette goes to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PaletteDiffSide {
+    Left,
+    Right,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum PaletteDiffSort {
+    #[default]
+    Index,
+    DeltaE,
+}
+
+/// State of the palette comparison window. The left side is the current output
+/// until a file is loaded into it.
+#[derive(Default)]
+pub struct PaletteDiffState {
+    pub show: bool,
+    pub left: Option<LoadedPalette>,
+    pub right: Option<LoadedPalette>,
+    pub sort: PaletteDiffSort,
+    pub descending: bool,
+}
+
+impl PaletteDiffState {
+    pub fn set(&mut self, side: PaletteDiffSide, palette: LoadedPalette) {
+        match side {
+            PaletteDiffSide::Left => self.left = Some(palette),
+            PaletteDiffSide::Right => self.right = Some(palette),
+        }
+    }
+
+    /// Click on a column header: sort by it, or flip the order if it already is
+    pub fn toggle_sort(&mut self, sort: PaletteDiffSort) {
+        if self.sort == sort {
+            self.descending = !self.descending;
+        } else {
+            self.sort = sort;
+            // Largest differences first is the useful default for ΔE
+            self.descending = sort == PaletteDiffSort::DeltaE;
+        }
+    }
+}
+
+/// One palette slot of both sides; a side is `None` past the end of its palette
+pub struct PaletteDiffRow {
+    pub index: usize,
+    pub left: Option<[u8; 4]>,
+    pub right: Option<[u8; 4]>,
+    /// CIE76 ΔE of the RGB colors; `None` unless both sides have the slot
+    pub delta_e: Option<f32>,
+}
+
+/// Compare two palettes slot by slot
+pub fn diff_palettes(left: &[[u8; 4]], right: &[[u8; 4]]) -> Vec<PaletteDiffRow> {
+    (0..left.len().max(right.len()))
+        .map(|index| {
+            let (left, right) = (left.get(index).copied(), right.get(index).copied());
+            let delta_e = left
+                .zip(right)
+                .map(|([lr, lg, lb, _], [rr, rg, rb, _])| delta_e([lr, lg, lb], [rr, rg, rb]));
+            PaletteDiffRow {
+                index,
+                left,
+                right,
+                delta_e,
+            }
+        })
+        .collect()
+}
+
+/// Average and largest ΔE over the slots both palettes have
+pub fn delta_e_summary(rows: &[PaletteDiffRow]) -> Option<(f32, f32)> {
+    let deltas: Vec<f32> = rows.iter().filter_map(|row| row.delta_e).collect();
+    if deltas.is_empty() {
+        return None;
+    }
+    let average = deltas.iter().sum::<f32>() / deltas.len() as f32;
+    let max = deltas.iter().copied().fold(0.0, f32::max);
+    Some((average, max))
+}
+
+pub fn sort_rows(rows: &mut [PaletteDiffRow], sort: PaletteDiffSort, descending: bool) {
+    match sort {
+        PaletteDiffSort::Index => rows.sort_by_key(|row| row.index),
+        // Unpaired slots sort as the largest difference
+        PaletteDiffSort::DeltaE => rows.sort_by(|a, b| {
+            let key = |row: &PaletteDiffRow| row.delta_e.unwrap_or(f32::INFINITY);
+            key(a).total_cmp(&key(b))
+        }),
+    }
+    if descending {
+        rows.reverse();
+    }
+}
@@ -1,7 +1,11 @@
-use super::export::ExportFormat;
+use super::export::{
+    ChannelOrder, ExportAlphaMode, ExportColorProfile, ExportFormat, ExportOptions, RowOrder,
+};
 use crate::types::app_state::AppearanceMode;
+use crate::types::display_filter::DisplayFilter;
 use egui::Color32;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 mod color32_def {
@@ -43,13 +47,201 @@ pub struct UserPreferences {
     pub show_debug_info: bool,
     #[serde(default)]
     pub show_appearance: bool,
+    #[serde(default)]
+    pub show_help: bool,
     pub selected_export_format: ExportFormat,
+    #[serde(default)]
+    pub export_color_profile: ExportColorProfile,
+    /// Resolution written to PNG `pHYs` and BMP headers; `None` leaves it unspecified
+    #[serde(default)]
+    pub export_dpi: Option<u32>,
+    #[serde(default)]
+    pub export_alpha_mode: ExportAlphaMode,
+    /// Row order of BMP and TGA exports
+    #[serde(default, alias = "bmp_row_order")]
+    pub row_order: RowOrder,
+    /// Mirror exported images horizontally; the preview is unchanged
+    #[serde(default)]
+    pub export_flip_x: bool,
+    /// Mirror exported images vertically; the preview is unchanged
+    #[serde(default)]
+    pub export_flip_y: bool,
+    /// Show a summary of each export before writing it
+    #[serde(default = "default_confirm_exports")]
+    pub confirm_exports: bool,
+    /// Write a `<file>.json` with the settings and stats next to each exported image
+    #[serde(default)]
+    pub export_metadata_sidecar: bool,
+    /// Folder offered by export dialogs; `None` uses the input image's folder
+    #[serde(default)]
+    pub export_directory: Option<String>,
+    /// Default export file name, see `export_file_name`; empty keeps `<name>_<suffix>`
+    #[serde(default)]
+    pub export_filename_template: String,
+    /// Write index CSV cells as `palette:color` instead of the raw index
+    #[serde(default)]
+    pub index_csv_split_palette: bool,
+    #[serde(default = "default_palette_swatch_size")]
+    pub palette_swatch_size: u32,
+    /// Byte order of colors in raw binary exports
+    #[serde(default)]
+    pub raw_channel_order: ChannelOrder,
+    /// Pixels of clear color around each cell in the "PNG with Gutters" export
+    #[serde(default = "default_export_cell_gutter")]
+    pub export_cell_gutter: u32,
 
     #[serde(default)]
     pub appearance_mode: AppearanceMode,
 
     #[serde(with = "color32_def")]
     pub background_color: Option<Color32>,
+    /// Shown in palette slots the quantizer left unfilled; `None` leaves them transparent
+    #[serde(with = "color32_def", default = "default_palette_padding_color")]
+    pub palette_padding_color: Option<Color32>,
+
+    #[serde(default)]
+    pub display_filter: DisplayFilter,
+    /// Highlight output pixels that differ from the last export
+    #[serde(default)]
+    pub show_export_diff: bool,
+    /// Show which palette the hovered output tile uses
+    #[serde(default)]
+    pub show_tile_inspector: bool,
+    /// Show every palette in its own window instead of only the corner overlay
+    #[serde(default)]
+    pub show_palette_inspector: bool,
+
+    /// Preview pixel width:height; only affects display scaling
+    #[serde(default = "default_pixel_aspect")]
+    pub pixel_aspect: [u16; 2],
+    /// Filter preview textures linearly when shown smaller than 1:1
+    #[serde(default)]
+    pub smooth_when_zoomed_out: bool,
+    /// Magnified inset of the pixels under the cursor
+    #[serde(default)]
+    pub show_loupe: bool,
+    /// Loupe scale relative to the current zoom
+    #[serde(default = "default_loupe_magnification")]
+    pub loupe_magnification: f32,
+    /// Alternate the output panel between the original and the output
+    #[serde(default)]
+    pub blink_compare: bool,
+    /// Time each side of the blink comparison stays up
+    #[serde(default = "default_blink_interval_ms")]
+    pub blink_interval_ms: u64,
+
+    /// Switch to a luma color space when a grayscale source is loaded
+    #[serde(default)]
+    pub grayscale_source_defaults: bool,
+    /// Recommend a preset from the size and color count of each loaded image
+    #[serde(default)]
+    pub suggest_presets: bool,
+    /// Suggest the JPEG artifact filter when a JPEG is loaded
+    #[serde(default = "default_warn_on_jpeg_sources")]
+    pub warn_on_jpeg_sources: bool,
+
+    /// Worker threads for sprite sheet cells; 0 uses every available core
+    #[serde(default)]
+    pub processing_threads: usize,
+
+    /// Delay after the last settings change before quantization starts
+    #[serde(default = "default_preview_delay_ms")]
+    pub preview_delay_ms: u64,
+
+    /// Hide the settings panel, footer and menus, leaving the images and a small toolbar
+    #[serde(default)]
+    pub compact_mode: bool,
+    /// Keep the window above other applications, e.g. next to a pixel editor
+    #[serde(default)]
+    pub always_on_top: bool,
+
+    #[serde(default)]
+    pub live_export_enabled: bool,
+    #[serde(default)]
+    pub live_export_path: Option<String>,
+
+    /// View preferences saved for individual images, keyed by path
+    #[serde(default)]
+    pub image_view_overrides: HashMap<String, ImageViewOverride>,
+}
+
+/// View preferences remembered for one image; they replace the global ones while
+/// that image is open
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ImageViewOverride {
+    #[serde(with = "color32_def")]
+    pub background_color: Option<Color32>,
+    pub show_original_image: bool,
+    pub show_color_corrected_image: bool,
+    pub show_palettes: bool,
+}
+
+impl ImageViewOverride {
+    pub fn from_preferences(preferences: &UserPreferences) -> Self {
+        Self {
+            background_color: preferences.background_color,
+            show_original_image: preferences.show_original_image,
+            show_color_corrected_image: preferences.show_color_corrected_image,
+            show_palettes: preferences.show_palettes,
+        }
+    }
+
+    pub fn apply_to(&self, preferences: &mut UserPreferences) {
+        preferences.background_color = self.background_color;
+        preferences.show_original_image = self.show_original_image;
+        preferences.show_color_corrected_image = self.show_color_corrected_image;
+        preferences.show_palettes = self.show_palettes;
+    }
+}
+
+fn default_palette_swatch_size() -> u32 {
+    16
+}
+
+fn default_warn_on_jpeg_sources() -> bool {
+    true
+}
+
+fn default_confirm_exports() -> bool {
+    true
+}
+
+fn default_export_cell_gutter() -> u32 {
+    1
+}
+
+fn default_palette_padding_color() -> Option<Color32> {
+    Some(Color32::BLACK)
+}
+
+fn default_pixel_aspect() -> [u16; 2] {
+    [1, 1]
+}
+
+pub const PIXEL_ASPECT_PRESETS: &[(&str, [u16; 2])] = &[
+    ("Square (1:1)", [1, 1]),
+    ("NES / SNES / Genesis H32 (8:7)", [8, 7]),
+    ("Genesis H40 (32:35)", [32, 35]),
+];
+
+pub const LOUPE_MAGNIFICATION_RANGE: std::ops::RangeInclusive<f32> = 2.0..=16.0;
+
+fn default_loupe_magnification() -> f32 {
+    4.0
+}
+
+pub const BLINK_INTERVAL_RANGE_MS: std::ops::RangeInclusive<u64> = 100..=2000;
+
+fn default_blink_interval_ms() -> u64 {
+    500
+}
+
+pub const EXPORT_DPI_RANGE: std::ops::RangeInclusive<u32> = 1..=2400;
+
+pub const PREVIEW_DELAY_RANGE_MS: std::ops::RangeInclusive<u64> = 0..=2000;
+
+fn default_preview_delay_ms() -> u64 {
+    100
 }
 
 impl Default for UserPreferences {
@@ -61,14 +253,85 @@ impl Default for UserPreferences {
             show_palettes: true,
             show_debug_info: false,
             show_appearance: false,
+            show_help: false,
             selected_export_format: ExportFormat::default(),
+            export_color_profile: ExportColorProfile::default(),
+            export_dpi: None,
+            export_alpha_mode: ExportAlphaMode::default(),
+            row_order: RowOrder::default(),
+            export_flip_x: false,
+            export_flip_y: false,
+            confirm_exports: default_confirm_exports(),
+            export_metadata_sidecar: false,
+            export_directory: None,
+            export_filename_template: String::new(),
+            index_csv_split_palette: false,
+            palette_swatch_size: default_palette_swatch_size(),
+            raw_channel_order: ChannelOrder::default(),
+            export_cell_gutter: default_export_cell_gutter(),
             appearance_mode: AppearanceMode::default(),
             background_color: None,
+            palette_padding_color: default_palette_padding_color(),
+            display_filter: DisplayFilter::default(),
+            show_export_diff: false,
+            show_tile_inspector: false,
+            show_palette_inspector: false,
+            pixel_aspect: default_pixel_aspect(),
+            smooth_when_zoomed_out: false,
+            show_loupe: false,
+            loupe_magnification: default_loupe_magnification(),
+            blink_compare: false,
+            blink_interval_ms: default_blink_interval_ms(),
+            grayscale_source_defaults: false,
+            suggest_presets: false,
+            warn_on_jpeg_sources: default_warn_on_jpeg_sources(),
+            processing_threads: 0,
+            preview_delay_ms: default_preview_delay_ms(),
+            compact_mode: false,
+            always_on_top: false,
+            live_export_enabled: false,
+            live_export_path: None,
+            image_view_overrides: HashMap::new(),
         }
     }
 }
 
 impl UserPreferences {
+    /// Horizontal stretch applied to the preview
+    pub fn pixel_aspect_ratio(&self) -> f32 {
+        match self.pixel_aspect {
+            [width, height] if width > 0 && height > 0 => width as f32 / height as f32,
+            _ => 1.0,
+        }
+    }
+
+    pub fn export_options(&self) -> ExportOptions {
+        ExportOptions {
+            color_profile: self.export_color_profile,
+            dpi: self.export_dpi,
+            alpha_mode: self.export_alpha_mode,
+        }
+    }
+
+    pub fn palette_padding(&self) -> Color32 {
+        self.palette_padding_color.unwrap_or(Color32::TRANSPARENT)
+    }
+
+    pub fn processing_thread_count(&self) -> usize {
+        if self.processing_threads > 0 {
+            self.processing_threads
+        } else {
+            std::thread::available_parallelism().map_or(1, |n| n.get())
+        }
+    }
+
+    pub fn preview_delay(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.preview_delay_ms.clamp(
+            *PREVIEW_DELAY_RANGE_MS.start(),
+            *PREVIEW_DELAY_RANGE_MS.end(),
+        ))
+    }
+
     pub fn config_path() -> PathBuf {
         if let Some(config_dir) = dirs::config_dir() {
             config_dir.join("QualetizeGUI").join("preferences.json")
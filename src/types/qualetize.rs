@@ -1,5 +1,7 @@
 use super::color_space::ColorSpace;
 use super::dither::DitherMode;
+use super::export::ExportPadding;
+use super::snap_palette::{SnapPalette, web_safe_colors};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::ptr;
@@ -49,7 +51,7 @@ unsafe extern "C" {
 }
 
 #[repr(C)]
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct BGRA8 {
     pub b: u8,
     pub g: u8,
@@ -57,6 +59,17 @@ pub struct BGRA8 {
     pub a: u8,
 }
 
+/// The library works in BGRA; images and exports mostly in RGBA
+impl BGRA8 {
+    pub fn from_rgba([r, g, b, a]: [u8; 4]) -> Self {
+        Self { b, g, r, a }
+    }
+
+    pub fn to_rgba(self) -> [u8; 4] {
+        [self.r, self.g, self.b, self.a]
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default)]
 pub enum ClearColor {
     #[default]
@@ -64,6 +77,20 @@ pub enum ClearColor {
     Rgb(u8, u8, u8),
 }
 
+/// Parse `#RRGGBB`; the leading `#` is optional
+pub fn parse_hex_rgb(text: &str) -> Result<[u8; 3], String> {
+    let hex = text.trim().trim_start_matches('#');
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err("Expected a hex color like #FF00FF".to_string());
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap_or(0);
+    Ok([channel(0), channel(2), channel(4)])
+}
+
+pub fn format_hex_rgb([r, g, b]: [u8; 3]) -> String {
+    format!("#{r:02X}{g:02X}{b:02X}")
+}
+
 impl ClearColor {
     pub fn to_bgra8(&self) -> BGRA8 {
         match self {
@@ -83,7 +110,7 @@ impl ClearColor {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct QualetizeSettings {
     pub tile_width: u16,
     pub tile_height: u16,
@@ -91,13 +118,49 @@ pub struct QualetizeSettings {
     pub n_colors: u16,
     pub rgba_depth: String,
     pub premul_alpha: bool,
+    /// Snap output palette alpha onto the levels of the A depth, so e.g. 5551 alpha
+    /// is strictly 0 or 255 in the preview and every export
+    #[serde(default = "default_quantize_alpha")]
+    pub quantize_alpha: bool,
     pub color_space: ColorSpace,
     pub dither_mode: DitherMode,
     pub dither_level: f32,
+    /// Extra scale on the threshold pattern of ordered modes. The plan has no separate
+    /// amplitude, so it is folded into the dither level passed to the library.
+    #[serde(default = "default_ordered_dither_amplitude")]
+    pub ordered_dither_amplitude: f32,
+    /// Strength of the Bayer pattern added before diffusion in `DitherMode::Hybrid`
+    #[serde(default = "default_hybrid_pattern_level")]
+    pub hybrid_pattern_level: f32,
     pub tile_passes: u32,
     pub color_passes: u32,
     pub col0_is_clear: bool,
     pub clear_color: ClearColor,
+    /// Reserve color 0 of each palette for pixels the source alpha marks as transparent,
+    /// instead of keying on a clear color
+    #[serde(default)]
+    pub source_alpha_transparency: bool,
+    /// Source pixels with alpha at or below this count as transparent (0 = fully transparent only)
+    #[serde(default)]
+    pub alpha_threshold: u8,
+    /// Hide the RGB of fully transparent pixels from clustering and dithering
+    #[serde(default)]
+    pub ignore_transparent_in_clustering: bool,
+    /// Fixed palette the quantized colors are snapped to afterwards
+    #[serde(default)]
+    pub snap_palette: SnapPalette,
+    /// Colors of a loaded palette, used by `SnapPalette::Custom`
+    #[serde(default)]
+    pub snap_palette_colors: Vec<[u8; 3]>,
+    /// Only build the palettes; the output image is not rendered for preview
+    #[serde(default)]
+    pub palette_only: bool,
+    /// Collapse palette entries closer than `merge_colors_tolerance` after quantization
+    #[serde(default)]
+    pub merge_colors_enabled: bool,
+    /// Distance in the clustering color space, scaled so the main axis spans 0-255
+    #[serde(default = "default_merge_colors_tolerance")]
+    pub merge_colors_tolerance: f32,
     #[serde(default)]
     pub tile_reduce_post_enabled: bool,
     #[serde(default = "default_tile_reduce_post_threshold")]
@@ -110,9 +173,92 @@ pub struct QualetizeSettings {
     pub use_custom_levels: bool,
     #[serde(default = "default_custom_level_strings")]
     pub custom_levels: [String; 4],
+    #[serde(default)]
+    pub use_custom_palette_sizes: bool,
+    #[serde(default)]
+    pub custom_palette_sizes: Vec<u16>,
+    #[serde(default)]
+    pub sprite_sheet_enabled: bool,
+    #[serde(default = "default_sprite_cell_size")]
+    pub sprite_cell_width: u16,
+    #[serde(default = "default_sprite_cell_size")]
+    pub sprite_cell_height: u16,
+    /// Canvas padding applied to indexed exports
+    #[serde(default)]
+    pub export_padding: ExportPadding,
+    /// Canvas size used by `ExportPadding::Specific`
+    #[serde(default = "default_export_padding_size")]
+    pub export_padding_size: [u32; 2],
+    /// Relative weight of R, G and B differences during clustering; 1.0 is neutral
+    #[serde(default = "default_channel_weights")]
+    pub channel_weights: [f32; 3],
+    /// Snap hues to this many dominant hue families before clustering
+    #[serde(default)]
+    pub max_hues: Option<u8>,
+    /// Merge rare input colors into a much more common one within this distance per
+    /// channel before clustering; 0 disables
+    #[serde(default)]
+    pub input_merge_tolerance: u8,
+    /// How source values between two levels of the RGB depth are brought onto one
+    #[serde(default)]
+    pub depth_rounding: DepthRounding,
+    /// Opt-in to more than 256 colors in total: the image is quantized in bands of
+    /// tile rows with up to 256 colors each, exported as one indexed file per band
+    #[serde(default)]
+    pub split_over_256: bool,
+}
+
+/// Total color budget with `split_over_256`, i.e. at most 16 split parts
+pub const MAX_SPLIT_TOTAL_COLORS: u32 = 4096;
+
+/// Rounding of channel values to the reduced depth. `Round` leaves it to the library;
+/// the others snap the input onto the levels in a pre-pass before clustering.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DepthRounding {
+    Truncate,
+    #[default]
+    Round,
+    Dither,
+}
+
+impl DepthRounding {
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            DepthRounding::Truncate => "Truncate",
+            DepthRounding::Round => "Round",
+            DepthRounding::Dither => "Dither to Nearest",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            DepthRounding::Truncate => {
+                "Drop the low bits like hardware conversion does; output comes out darker"
+            }
+            DepthRounding::Round => "Snap to the nearest level (keeps average brightness)",
+            DepthRounding::Dither => {
+                "Pick the level below or above with a 4x4 Bayer pattern, in proportion to the distance"
+            }
+        }
+    }
+
+    pub fn all() -> &'static [DepthRounding] {
+        &[
+            DepthRounding::Truncate,
+            DepthRounding::Round,
+            DepthRounding::Dither,
+        ]
+    }
+}
+
+/// Preset recommended for a newly loaded image, and why
+#[derive(Clone, Debug)]
+pub struct PresetSuggestion {
+    pub preset: QualetizePreset,
+    pub reason: String,
 }
 
-#[derive(Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub enum QualetizePreset {
     #[default]
     Genesis,
@@ -140,6 +286,30 @@ impl QualetizePreset {
         ]
     }
 
+    /// Starting preset for an image of this size and color count: sprite-sized images
+    /// suit GBA/NDS, larger ones Genesis, with extra palettes if one can't hold the colors
+    pub fn suggest(width: u32, height: u32, unique_colors: usize) -> PresetSuggestion {
+        const SPRITE_MAX_SIZE: u32 = 64;
+        const SINGLE_PALETTE_COLORS: usize = 16;
+
+        if width <= SPRITE_MAX_SIZE && height <= SPRITE_MAX_SIZE {
+            PresetSuggestion {
+                preset: QualetizePreset::GbaNds,
+                reason: format!("{width}×{height} is sprite-sized"),
+            }
+        } else if unique_colors <= SINGLE_PALETTE_COLORS {
+            PresetSuggestion {
+                preset: QualetizePreset::Genesis,
+                reason: format!("{unique_colors} colors fit in one palette"),
+            }
+        } else {
+            PresetSuggestion {
+                preset: QualetizePreset::GenesisFullPals,
+                reason: format!("{unique_colors} colors need more than one palette"),
+            }
+        }
+    }
+
     pub fn qualetize_settings(&self) -> QualetizeSettings {
         match self {
             QualetizePreset::Genesis => QualetizeSettings::genesis(),
@@ -160,19 +330,42 @@ impl QualetizeSettings {
             n_colors: 16,
             rgba_depth: rgba_depth.clone(),
             premul_alpha: false,
+            quantize_alpha: default_quantize_alpha(),
             color_space: ColorSpace::YcbcrPsy,
             dither_mode: DitherMode::Floyd,
             dither_level: 0.5,
+            ordered_dither_amplitude: default_ordered_dither_amplitude(),
+            hybrid_pattern_level: default_hybrid_pattern_level(),
             tile_passes: 1000,
             color_passes: 100,
             col0_is_clear: false,
             clear_color: ClearColor::default(),
+            source_alpha_transparency: false,
+            alpha_threshold: 0,
             tile_reduce_post_enabled: false,
             tile_reduce_post_threshold: default_tile_reduce_post_threshold(),
             tile_reduce_allow_flip_x: default_tile_reduce_allow_flip(),
             tile_reduce_allow_flip_y: default_tile_reduce_allow_flip(),
             use_custom_levels: false,
             custom_levels: default_level_strings_from_depth(&rgba_depth),
+            use_custom_palette_sizes: false,
+            custom_palette_sizes: Vec::new(),
+            ignore_transparent_in_clustering: false,
+            snap_palette: SnapPalette::None,
+            snap_palette_colors: Vec::new(),
+            palette_only: false,
+            merge_colors_enabled: false,
+            merge_colors_tolerance: default_merge_colors_tolerance(),
+            sprite_sheet_enabled: false,
+            sprite_cell_width: default_sprite_cell_size(),
+            sprite_cell_height: default_sprite_cell_size(),
+            export_padding: ExportPadding::None,
+            export_padding_size: default_export_padding_size(),
+            channel_weights: default_channel_weights(),
+            max_hues: None,
+            input_merge_tolerance: 0,
+            depth_rounding: DepthRounding::Round,
+            split_over_256: false,
         }
     }
     pub fn gba_nds_full_palettes() -> Self {
@@ -191,19 +384,42 @@ impl QualetizeSettings {
             n_colors: 16,
             rgba_depth: rgba_depth.clone(),
             premul_alpha: false,
+            quantize_alpha: default_quantize_alpha(),
             color_space: ColorSpace::default(),
             dither_mode: DitherMode::default(),
             dither_level: 0.5,
+            ordered_dither_amplitude: default_ordered_dither_amplitude(),
+            hybrid_pattern_level: default_hybrid_pattern_level(),
             tile_passes: 1000,
             color_passes: 100,
             col0_is_clear: false,
             clear_color: ClearColor::default(),
+            source_alpha_transparency: false,
+            alpha_threshold: 0,
             tile_reduce_post_enabled: false,
             tile_reduce_post_threshold: default_tile_reduce_post_threshold(),
             tile_reduce_allow_flip_x: default_tile_reduce_allow_flip(),
             tile_reduce_allow_flip_y: default_tile_reduce_allow_flip(),
             use_custom_levels: true,
             custom_levels: genesis_custom_level_strings(),
+            use_custom_palette_sizes: false,
+            custom_palette_sizes: Vec::new(),
+            ignore_transparent_in_clustering: false,
+            snap_palette: SnapPalette::None,
+            snap_palette_colors: Vec::new(),
+            palette_only: false,
+            merge_colors_enabled: false,
+            merge_colors_tolerance: default_merge_colors_tolerance(),
+            sprite_sheet_enabled: false,
+            sprite_cell_width: default_sprite_cell_size(),
+            sprite_cell_height: default_sprite_cell_size(),
+            export_padding: ExportPadding::None,
+            export_padding_size: default_export_padding_size(),
+            channel_weights: default_channel_weights(),
+            max_hues: None,
+            input_merge_tolerance: 0,
+            depth_rounding: DepthRounding::Round,
+            split_over_256: false,
         }
     }
     pub fn genesis_full_palettes() -> Self {
@@ -213,6 +429,244 @@ impl QualetizeSettings {
             ..Self::genesis()
         }
     }
+
+    /// Whether color 0 of every palette is reserved for transparency
+    /// Key color that owns slot 0 of every palette, when "First Color is Transparent"
+    /// is keyed on a clear color rather than on source alpha
+    pub fn reserved_clear_color(&self) -> Option<BGRA8> {
+        match self.clear_color {
+            ClearColor::Rgb(r, g, b) if self.col0_is_clear && !self.source_alpha_transparency => {
+                Some(BGRA8 { b, g, r, a: 0 })
+            }
+            _ => None,
+        }
+    }
+
+    pub fn uses_transparent_index(&self) -> bool {
+        self.col0_is_clear || self.source_alpha_transparency
+    }
+
+    /// Smallest size a single palette may be shrunk to.
+    pub fn min_palette_size(&self) -> u16 {
+        let min_size = if self.uses_transparent_index() { 2 } else { 1 };
+        min_size.min(self.n_colors.max(1))
+    }
+
+    /// Reason an image of this size cannot be split into tiles
+    pub fn tile_size_error(&self, width: u32, height: u32) -> Option<String> {
+        let (tile_width, tile_height) = (self.tile_width as u32, self.tile_height as u32);
+        if width == 0 || height == 0 {
+            return Some(format!(
+                "Image has no pixels ({width}×{height}). The file may be corrupt."
+            ));
+        }
+        if tile_width == 0 || tile_height == 0 {
+            return Some("Tile size must be at least 1×1.".to_string());
+        }
+        if width < tile_width || height < tile_height {
+            return Some(format!(
+                "Image size ({width}×{height}) is smaller than one tile ({tile_width}×{tile_height}). Qualetize processing cannot proceed."
+            ));
+        }
+        if !width.is_multiple_of(tile_width) || !height.is_multiple_of(tile_height) {
+            return Some(format!(
+                "Image size ({width}×{height}) is not divisible by tile size ({tile_width}×{tile_height}). Qualetize processing cannot proceed."
+            ));
+        }
+        None
+    }
+
+    /// Nearest tile-divisible size reachable with at most `MAX_SEAM_ADJUSTMENT` seams
+    /// per axis; `None` if the size is already divisible or too far off
+    pub fn seam_fit_size(&self, width: u32, height: u32) -> Option<(u32, u32)> {
+        let fit = |size: u32, tile: u32| {
+            if tile == 0 {
+                return None;
+            }
+            let below = size - size % tile;
+            let above = below + tile;
+            let target = if below > 0 && size - below <= above - size {
+                below
+            } else {
+                above
+            };
+            (target.abs_diff(size) <= MAX_SEAM_ADJUSTMENT).then_some(target)
+        };
+        let target = (
+            fit(width, self.tile_width as u32)?,
+            fit(height, self.tile_height as u32)?,
+        );
+        (target != (width, height)).then_some(target)
+    }
+
+    /// Reason the sprite sheet grid cannot be used for an image of this size
+    pub fn sprite_sheet_error(&self, width: u32, height: u32) -> Option<String> {
+        let cell_w = self.sprite_cell_width as u32;
+        let cell_h = self.sprite_cell_height as u32;
+        if cell_w == 0 || cell_h == 0 {
+            return Some("Cell size must be at least 1×1".to_string());
+        }
+        if !width.is_multiple_of(cell_w) || !height.is_multiple_of(cell_h) {
+            return Some(format!(
+                "Image size ({width}×{height}) is not divisible by cell size ({cell_w}×{cell_h})"
+            ));
+        }
+        if !cell_w.is_multiple_of(self.tile_width as u32)
+            || !cell_h.is_multiple_of(self.tile_height as u32)
+        {
+            return Some(format!(
+                "Cell size ({cell_w}×{cell_h}) is not divisible by tile size ({}×{})",
+                self.tile_width, self.tile_height
+            ));
+        }
+        let cell_count = (width / cell_w) * (height / cell_h);
+        let total_colors = cell_count * self.n_colors as u32;
+        if total_colors > 256 {
+            return Some(format!(
+                "{cell_count} cells × {} colors = {total_colors} colors (max: 256)",
+                self.n_colors
+            ));
+        }
+        None
+    }
+
+    /// Canvas size of an exported `width`×`height` image after padding.
+    /// The padded size must stay divisible by the tile size.
+    pub fn padded_export_size(&self, width: u32, height: u32) -> Result<(u32, u32), String> {
+        let (padded_width, padded_height) = match self.export_padding {
+            ExportPadding::None => return Ok((width, height)),
+            ExportPadding::NextPowerOfTwo => {
+                (width.next_power_of_two(), height.next_power_of_two())
+            }
+            ExportPadding::Specific => (self.export_padding_size[0], self.export_padding_size[1]),
+        };
+        if padded_width < width || padded_height < height {
+            return Err(format!(
+                "Padding size {padded_width}×{padded_height} is smaller than the image ({width}×{height})"
+            ));
+        }
+        let (tile_width, tile_height) = (self.tile_width as u32, self.tile_height as u32);
+        if !padded_width.is_multiple_of(tile_width.max(1))
+            || !padded_height.is_multiple_of(tile_height.max(1))
+        {
+            return Err(format!(
+                "Padded size {padded_width}×{padded_height} is not divisible by tile size ({tile_width}×{tile_height})"
+            ));
+        }
+        Ok((padded_width, padded_height))
+    }
+
+    /// Dither level handed to the library, with the ordered pattern amplitude applied
+    pub fn effective_dither_level(&self) -> f32 {
+        if self.dither_mode.is_ordered() {
+            self.dither_level * self.ordered_dither_amplitude
+        } else {
+            self.dither_level
+        }
+    }
+
+    /// Colors to snap the output palette to, if snapping is enabled and has colors
+    pub fn snap_palette_targets(&self) -> Option<Vec<[u8; 3]>> {
+        match self.snap_palette {
+            SnapPalette::None => None,
+            SnapPalette::WebSafe => Some(web_safe_colors()),
+            SnapPalette::Custom if self.snap_palette_colors.is_empty() => None,
+            SnapPalette::Custom => Some(self.snap_palette_colors.clone()),
+        }
+    }
+
+    /// Bits per RGBA channel when using a linear bit depth; `None` for custom levels
+    pub fn channel_bits(&self) -> Option<[u32; 4]> {
+        if self.use_custom_levels {
+            return None;
+        }
+        let digits: Vec<u32> = self
+            .rgba_depth
+            .chars()
+            .map(|c| c.to_digit(10))
+            .collect::<Option<_>>()?;
+        let bits: [u32; 4] = digits.try_into().ok()?;
+        bits.iter().all(|b| (1..=8).contains(b)).then_some(bits)
+    }
+
+    /// Alpha levels the output palette is snapped to, from the A depth or custom
+    /// A levels; `None` when alpha quantization is off or alpha has full 8-bit depth
+    pub fn alpha_levels(&self) -> Option<Vec<u8>> {
+        if !self.quantize_alpha {
+            return None;
+        }
+        self.channel_levels(3)
+    }
+
+    /// Values channel `channel` (R, G, B, A) can take at the RGBA depth or custom
+    /// levels; `None` when the channel has full 8-bit depth
+    pub fn channel_levels(&self, channel: usize) -> Option<Vec<u8>> {
+        let levels: Vec<u8> = if self.use_custom_levels {
+            parse_custom_levels(&self.custom_levels[channel])?
+                .into_iter()
+                .map(|level| (level * 255.0).round() as u8)
+                .collect()
+        } else {
+            depth_to_levels(parse_rgba_depth(&self.rgba_depth)[channel])
+        };
+        (!levels.is_empty() && levels.len() < 256).then_some(levels)
+    }
+
+    /// Clamped channel weights, or `None` when they leave clustering unchanged
+    pub fn active_channel_weights(&self) -> Option<[f32; 3]> {
+        let weights = self
+            .channel_weights
+            .map(|weight| weight.clamp(*CHANNEL_WEIGHT_RANGE.start(), *CHANNEL_WEIGHT_RANGE.end()));
+        (weights != [1.0; 3]).then_some(weights)
+    }
+
+    /// Largest `n_palettes * n_colors`: 256, unless splitting over 256 colors was opted
+    /// into. Sprite sheets always stay within one 256-color image.
+    pub fn max_total_colors(&self) -> u32 {
+        if self.split_over_256 && !self.sprite_sheet_enabled {
+            MAX_SPLIT_TOTAL_COLORS
+        } else {
+            256
+        }
+    }
+
+    /// Whether the output is split into several 256-color parts
+    pub fn is_split(&self) -> bool {
+        self.n_palettes as u32 * self.n_colors as u32 > 256 && self.max_total_colors() > 256
+    }
+
+    /// Enforce `n_palettes * n_colors <= max_total_colors()` (and nonzero counts),
+    /// reducing the palette count if needed. Returns whether anything was changed.
+    pub fn clamp_palette_budget(&mut self) -> bool {
+        let n_colors = self.n_colors.clamp(1, 256);
+        let n_palettes = self
+            .n_palettes
+            .clamp(1, (self.max_total_colors() / n_colors as u32) as u16);
+        let clamped = n_colors != self.n_colors || n_palettes != self.n_palettes;
+        self.n_colors = n_colors;
+        self.n_palettes = n_palettes;
+        clamped
+    }
+
+    /// Number of colors in each palette, in palette order.
+    /// Without custom sizes every palette has `n_colors` entries.
+    pub fn palette_sizes(&self) -> Vec<u16> {
+        let n_palettes = self.n_palettes as usize;
+        let max_size = self.n_colors.max(1);
+        if !self.use_custom_palette_sizes {
+            return vec![max_size; n_palettes];
+        }
+        let min_size = self.min_palette_size();
+        (0..n_palettes)
+            .map(|idx| {
+                self.custom_palette_sizes
+                    .get(idx)
+                    .copied()
+                    .unwrap_or(max_size)
+                    .clamp(min_size, max_size)
+            })
+            .collect()
+    }
 }
 
 impl Default for QualetizeSettings {
@@ -221,6 +675,46 @@ impl Default for QualetizeSettings {
     }
 }
 
+/// A color as stored at a reduced per-channel depth
+pub struct PackedColor {
+    /// Channel levels packed with red in the low bits, then green, blue and alpha,
+    /// so a 5551 depth gives the GBA/NDS BGR555 halfword with alpha on top
+    pub value: u32,
+    pub total_bits: u32,
+    /// The stored levels expanded back to 8 bits per channel (RGBA)
+    pub rounded: [u8; 4],
+}
+
+pub fn pack_color(rgba: [u8; 4], bits: [u32; 4]) -> PackedColor {
+    let mut value = 0;
+    let mut shift = 0;
+    let mut rounded = [0u8; 4];
+    for channel in 0..4 {
+        let max = (1u32 << bits[channel]) - 1;
+        let level = (rgba[channel] as u32 * max + 127) / 255;
+        rounded[channel] = ((level * 255 + max / 2) / max) as u8;
+        value |= level << shift;
+        shift += bits[channel];
+    }
+    PackedColor {
+        value,
+        total_bits: shift,
+        rounded,
+    }
+}
+
+/// Move every palette alpha to the nearest of `levels`
+pub fn quantize_palette_alpha(palette: &mut [BGRA8], levels: &[u8]) {
+    for color in palette.iter_mut() {
+        if let Some(&level) = levels
+            .iter()
+            .min_by_key(|&&level| (level as i16 - color.a as i16).abs())
+        {
+            color.a = level;
+        }
+    }
+}
+
 fn char_to_depth(c: char) -> f32 {
     match c {
         '1' => 1.0,
@@ -235,6 +729,39 @@ fn char_to_depth(c: char) -> f32 {
     }
 }
 
+fn default_quantize_alpha() -> bool {
+    true
+}
+
+fn default_ordered_dither_amplitude() -> f32 {
+    1.0
+}
+
+fn default_hybrid_pattern_level() -> f32 {
+    0.25
+}
+
+pub const CHANNEL_WEIGHT_RANGE: std::ops::RangeInclusive<f32> = 0.1..=1.0;
+
+pub const MAX_HUES_RANGE: std::ops::RangeInclusive<u8> = 1..=12;
+
+pub const INPUT_MERGE_TOLERANCE_RANGE: std::ops::RangeInclusive<u8> = 0..=16;
+
+/// Most seams "Fit with Seams" adds or removes per axis
+pub const MAX_SEAM_ADJUSTMENT: u32 = 8;
+
+fn default_channel_weights() -> [f32; 3] {
+    [1.0; 3]
+}
+
+fn default_export_padding_size() -> [u32; 2] {
+    [256, 256]
+}
+
+fn default_merge_colors_tolerance() -> f32 {
+    4.0
+}
+
 fn default_tile_reduce_post_threshold() -> f32 {
     25.0
 }
@@ -243,6 +770,10 @@ fn default_tile_reduce_allow_flip() -> bool {
     true
 }
 
+fn default_sprite_cell_size() -> u16 {
+    32
+}
+
 fn parse_rgba_depth(rgba_depth: &str) -> [f32; 4] {
     if rgba_depth.len() == 4 {
         let chars: Vec<char> = rgba_depth.chars().collect();
@@ -275,6 +806,81 @@ fn levels_to_string(levels: Vec<u8>) -> String {
         .join(",")
 }
 
+/// `count` levels spaced by `gamma`: level i is 255 * (i / (count - 1))^(1 / gamma).
+/// Gamma 1 is linear; above 1 spreads the dark steps apart, the way evenly spaced
+/// linear-light DAC outputs land in sRGB.
+pub fn gamma_level_string(count: usize, gamma: f32) -> String {
+    let count = count.clamp(2, 255);
+    let exponent = 1.0 / gamma.max(0.01);
+    let mut levels: Vec<u8> = (0..count)
+        .map(|i| ((i as f32 / (count - 1) as f32).powf(exponent) * 255.0).round() as u8)
+        .collect();
+    // Steep curves can round neighboring steps onto the same value
+    levels.dedup();
+    levels_to_string(levels)
+}
+
+pub const LEVEL_COUNT_RANGE: std::ops::RangeInclusive<usize> = 2..=255;
+pub const LEVEL_GAMMA_RANGE: std::ops::RangeInclusive<f32> = 0.2..=4.0;
+
+/// Starting points for generated custom levels
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LevelRampPreset {
+    Linear,
+    Genesis,
+    Srgb,
+}
+
+impl LevelRampPreset {
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            LevelRampPreset::Linear => "Linear",
+            LevelRampPreset::Genesis => "Genesis",
+            LevelRampPreset::Srgb => "sRGB-ish",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            LevelRampPreset::Linear => "Evenly spaced steps (gamma 1.0)",
+            LevelRampPreset::Genesis => {
+                "The measured Genesis/Mega Drive VDP brightness steps (8 levels, 1-bit alpha)"
+            }
+            LevelRampPreset::Srgb => {
+                "Evenly spaced linear-light steps expressed in sRGB (gamma 2.2)"
+            }
+        }
+    }
+
+    pub fn all() -> &'static [LevelRampPreset] {
+        &[
+            LevelRampPreset::Linear,
+            LevelRampPreset::Genesis,
+            LevelRampPreset::Srgb,
+        ]
+    }
+
+    /// Level count and gamma to show in the generator after picking this preset
+    pub fn count_and_gamma(&self, count: usize) -> (usize, f32) {
+        match self {
+            LevelRampPreset::Linear => (count, 1.0),
+            LevelRampPreset::Genesis => (8, 1.0),
+            LevelRampPreset::Srgb => (count, 2.2),
+        }
+    }
+
+    /// Level strings for all four channels
+    pub fn level_strings(&self, count: usize) -> [String; 4] {
+        match self {
+            LevelRampPreset::Genesis => genesis_custom_level_strings(),
+            _ => {
+                let (count, gamma) = self.count_and_gamma(count);
+                std::array::from_fn(|_| gamma_level_string(count, gamma))
+            }
+        }
+    }
+}
+
 pub fn default_level_strings_from_depth(rgba_depth: &str) -> [String; 4] {
     let depth = parse_rgba_depth(rgba_depth);
     [
@@ -317,7 +923,8 @@ pub fn validate_0_255_array(array_str: &str) -> bool {
     true
 }
 
-fn parse_custom_levels(array_str: &str) -> Option<Vec<f32>> {
+/// Parse a level string into sorted 0.0-1.0 levels
+pub fn parse_custom_levels(array_str: &str) -> Option<Vec<f32>> {
     if !validate_0_255_array(array_str) {
         return None;
     }
@@ -350,17 +957,31 @@ impl From<QualetizeSettings> for QualetizePlanOwned {
             tile_height: settings.tile_height,
             n_palette_colors: settings.n_colors,
             n_tile_palettes: settings.n_palettes,
+            // The library converts pixels into this space once, and both clustering and
+            // DitherImage.c work on the converted values, so dither error is diffused in
+            // the same space the palettes were built in. No Rust-side pre-dither is needed.
             colorspace: settings.color_space.to_id(),
-            first_color_is_transparent: if settings.col0_is_clear { 1 } else { 0 },
+            // Slot 0 of every palette is left out of clustering and keyed pixels map to
+            // it; `ImageProcessor::reserve_clear_color_slot` then pins its exact value
+            first_color_is_transparent: if settings.uses_transparent_index() {
+                1
+            } else {
+                0
+            },
             premultiplied_alpha: if settings.premul_alpha { 1 } else { 0 },
             dither_type: settings.dither_mode.to_id(),
-            dither_level: settings.dither_level,
+            dither_level: settings.effective_dither_level(),
             n_tile_cluster_passes: settings.tile_passes,
             n_color_cluster_passes: settings.color_passes,
             color_depth: Vec4f {
                 f32: [rgba_depth[0], rgba_depth[1], rgba_depth[2], rgba_depth[3]],
             },
-            transparent_color: settings.clear_color.to_bgra8(),
+            // With source alpha transparency the alpha channel decides, not a key color
+            transparent_color: if settings.source_alpha_transparency {
+                ClearColor::None.to_bgra8()
+            } else {
+                settings.clear_color.to_bgra8()
+            },
             custom_levels: [ptr::null(); 4],
             custom_level_count: [0; 4],
         };
@@ -385,3 +1006,39 @@ impl From<QualetizeSettings> for QualetizePlanOwned {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hex_rgb_accepts_an_optional_hash() {
+        assert_eq!(parse_hex_rgb("#FF8000"), Ok([255, 128, 0]));
+        assert_eq!(parse_hex_rgb("ff8000"), Ok([255, 128, 0]));
+        assert_eq!(parse_hex_rgb("  #0a0B0c "), Ok([10, 11, 12]));
+    }
+
+    #[test]
+    fn test_parse_hex_rgb_rejects_malformed_input() {
+        for text in [
+            "", "#", "#FFF", "#FF80001", "FF80", "#GG8000", "#FF 800", "#ÿÿÿ",
+        ] {
+            assert!(parse_hex_rgb(text).is_err(), "{text:?}");
+        }
+    }
+
+    #[test]
+    fn test_format_hex_rgb_round_trips() {
+        for rgb in [
+            [0, 0, 0],
+            [255, 255, 255],
+            [1, 128, 254],
+            [0x12, 0xAB, 0xEF],
+        ] {
+            let text = format_hex_rgb(rgb);
+            assert_eq!(text.len(), 7);
+            assert_eq!(parse_hex_rgb(&text), Ok(rgb), "{text}");
+        }
+        assert_eq!(format_hex_rgb([255, 0, 171]), "#FF00AB");
+    }
+}
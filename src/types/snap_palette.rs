@@ -0,0 +1,110 @@
+use super::BGRA8;
+
+/// Fixed palette that output colors are snapped to after quantization
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize, Default)]
+pub enum SnapPalette {
+    #[default]
+    None,
+    WebSafe,
+    Custom,
+}
+
+impl SnapPalette {
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            SnapPalette::None => "Off",
+            SnapPalette::WebSafe => "Web-Safe (216)",
+            SnapPalette::Custom => "Custom",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            SnapPalette::None => "Keep the quantized colors as they are",
+            SnapPalette::WebSafe => "Snap to the 216-color web-safe cube",
+            SnapPalette::Custom => "Snap to the colors of a loaded palette image",
+        }
+    }
+
+    pub fn all() -> &'static [SnapPalette] {
+        &[SnapPalette::None, SnapPalette::WebSafe, SnapPalette::Custom]
+    }
+}
+
+/// Loaded palettes with more colors than this are rejected
+pub const MAX_SNAP_PALETTE_COLORS: usize = 4096;
+
+/// The 216 colors of the web-safe cube
+pub fn web_safe_colors() -> Vec<[u8; 3]> {
+    const LEVELS: [u8; 6] = [0, 51, 102, 153, 204, 255];
+    LEVELS
+        .iter()
+        .flat_map(|&r| {
+            LEVELS
+                .iter()
+                .flat_map(move |&g| LEVELS.iter().map(move |&b| [r, g, b]))
+        })
+        .collect()
+}
+
+/// Unique opaque colors of a palette image, in reading order
+pub fn load_snap_palette(path: &str) -> Result<Vec<[u8; 3]>, String> {
+    let img = image::open(path).map_err(|e| format!("Palette loading error: {e}"))?;
+    let mut colors: Vec<[u8; 3]> = Vec::new();
+    for pixel in img.to_rgba8().pixels() {
+        let [r, g, b, a] = pixel.0;
+        if a == 0 || colors.contains(&[r, g, b]) {
+            continue;
+        }
+        if colors.len() == MAX_SNAP_PALETTE_COLORS {
+            return Err(format!(
+                "Palette has more than {MAX_SNAP_PALETTE_COLORS} colors"
+            ));
+        }
+        colors.push([r, g, b]);
+    }
+    if colors.is_empty() {
+        return Err("Palette image has no opaque colors".to_string());
+    }
+    Ok(colors)
+}
+
+/// Move every visible palette color to its nearest target color, keeping alpha and
+/// pixel indices. Returns the RMSE between the output before and after snapping.
+pub fn snap_palette_colors(
+    palette: &mut [BGRA8],
+    targets: &[[u8; 3]],
+    indexed_pixels: &[u8],
+) -> f32 {
+    let distance_sq = |a: [u8; 3], b: [u8; 3]| -> u32 {
+        a.iter()
+            .zip(b)
+            .map(|(&a, b)| (a as i32 - b as i32).pow(2) as u32)
+            .sum()
+    };
+
+    let mut moved = vec![0u32; palette.len()];
+    for (color, moved) in palette.iter_mut().zip(moved.iter_mut()) {
+        if color.a == 0 {
+            continue;
+        }
+        let current = [color.r, color.g, color.b];
+        let Some(&[r, g, b]) = targets
+            .iter()
+            .min_by_key(|&&target| distance_sq(current, target))
+        else {
+            continue;
+        };
+        *moved = distance_sq(current, [r, g, b]);
+        (color.r, color.g, color.b) = (r, g, b);
+    }
+
+    if indexed_pixels.is_empty() {
+        return 0.0;
+    }
+    let total: u64 = indexed_pixels
+        .iter()
+        .map(|&index| moved.get(index as usize).copied().unwrap_or(0) as u64)
+        .sum();
+    (total as f32 / (indexed_pixels.len() * 3) as f32).sqrt()
+}
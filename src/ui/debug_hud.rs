@@ -0,0 +1,39 @@
+use crate::types::AppState;
+use egui::Align2;
+
+/// Small overlay in the top-right corner with frame and quantization timings, to tell
+/// whether stutter comes from rendering or from processing
+pub fn draw_debug_hud(ctx: &egui::Context, state: &AppState) {
+    let stats = &state.debug_stats;
+    let ms = |duration: std::time::Duration| duration.as_secs_f32() * 1000.0;
+
+    egui::Area::new(egui::Id::new("debug_hud"))
+        .anchor(Align2::RIGHT_TOP, egui::vec2(-12.0, 44.0))
+        .order(egui::Order::Foreground)
+        .interactable(false)
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.style_mut().override_text_style = Some(egui::TextStyle::Monospace);
+                ui.label(format!(
+                    "Frame:    {:6.1} ms (update {:.1} ms)",
+                    ms(stats.frame_time),
+                    ms(stats.update_time)
+                ));
+                ui.label(format!(
+                    "Quantize: {}",
+                    if stats.quantization_in_flight {
+                        "running"
+                    } else {
+                        "idle"
+                    }
+                ));
+                ui.label(format!(
+                    "Last run: {}",
+                    stats
+                        .last_quantization_time
+                        .map_or("-".to_string(), |time| format!("{:.1} ms", ms(time)))
+                ));
+                ui.label(format!("Requests: {} queued", stats.pending_requests));
+            });
+        });
+}
@@ -1,5 +1,11 @@
+use super::image_viewer::pan_offset_centering;
 use super::styles;
-use crate::types::{AppState, ExportFormat, app_state::AppStateRequest, image::ImageData};
+use crate::exporter::format_file_size;
+use crate::types::{
+    AppState, ExportFormat,
+    app_state::{AppStateRequest, AppearanceMode, QUEUE_THUMBNAIL_SIZE, ResizeDialog},
+    image::{ImageData, ResizeFilter},
+};
 use egui::{Color32, Vec2};
 
 pub fn draw_footer(ui: &mut egui::Ui, state: &mut AppState) -> bool {
@@ -9,18 +15,409 @@ pub fn draw_footer(ui: &mut egui::Ui, state: &mut AppState) -> bool {
     ui.with_layout(egui::Layout::left_to_right(egui::Align::Center), |ui| {
         draw_view_controls(ui, state);
 
+        if width > 820.0
+            && let Some(info) = state
+                .input_image
+                .as_ref()
+                .and_then(|image| image.source_info.as_ref())
+        {
+            ui.separator();
+            ui.label(format!("🖼 {}", info.summary()))
+                .on_hover_text(format!(
+                    "Format: {}\nColor type: {} ({} bits per pixel)\nICC profile: {}",
+                    info.format,
+                    info.color_type,
+                    info.bits_per_pixel,
+                    if info.has_icc_profile {
+                        "embedded (ignored, pixels are treated as sRGB)"
+                    } else {
+                        "none"
+                    }
+                ));
+        }
+
         if width > 660.0 {
             ui.separator();
-            ui.label("🖱 Drag to pan, scroll to zoom");
+            ui.label("🖱 Drag to pan, scroll to zoom").on_hover_text(
+                "Shift-drag to adjust brightness (horizontal) and contrast (vertical)",
+            );
         }
 
         ui.separator();
         draw_export_controls(ui, state);
     });
 
+    draw_resize_window(ui.ctx(), state);
+    draw_seam_fit_window(ui.ctx(), state);
+
     export_clicked
 }
 
+/// Resize the input before processing, e.g. to reach a tile-divisible size
+fn draw_resize_window(ctx: &egui::Context, state: &mut AppState) {
+    let Some(dialog) = &mut state.resize_dialog else {
+        return;
+    };
+    // The aspect lock follows the image as loaded, not the current resized copy
+    let Some(reference) = state
+        .original_input_image
+        .as_ref()
+        .or(state.input_image.as_ref())
+    else {
+        state.resize_dialog = None;
+        return;
+    };
+    let aspect = reference.height as f64 / reference.width as f64;
+
+    let mut open = true;
+    let mut apply = false;
+    let mut reset = false;
+    egui::Window::new("Resize Input")
+        .open(&mut open)
+        .resizable(false)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            ui.label(format!(
+                "Original: {}×{}",
+                reference.width, reference.height
+            ));
+            egui::Grid::new("resize_input_grid")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("Width:");
+                    if ui
+                        .add(egui::DragValue::new(&mut dialog.width).range(1..=8192))
+                        .changed()
+                        && dialog.lock_aspect
+                    {
+                        dialog.height = ((dialog.width as f64 * aspect).round() as u32).max(1);
+                    }
+                    ui.end_row();
+
+                    ui.label("Height:");
+                    if ui
+                        .add(egui::DragValue::new(&mut dialog.height).range(1..=8192))
+                        .changed()
+                        && dialog.lock_aspect
+                    {
+                        dialog.width = ((dialog.height as f64 / aspect).round() as u32).max(1);
+                    }
+                    ui.end_row();
+
+                    ui.label("Sampling:");
+                    egui::ComboBox::from_id_salt("resize_filter")
+                        .selected_text(dialog.filter.display_name())
+                        .show_ui(ui, |ui| {
+                            for filter in ResizeFilter::all() {
+                                ui.selectable_value(
+                                    &mut dialog.filter,
+                                    *filter,
+                                    filter.display_name(),
+                                )
+                                .on_hover_text(filter.description());
+                            }
+                        });
+                    ui.end_row();
+                });
+            ui.checkbox(&mut dialog.lock_aspect, "Lock Aspect Ratio");
+
+            if let Some(error) = state.settings.tile_size_error(dialog.width, dialog.height) {
+                ui.label(
+                    egui::RichText::new(format!("⚠ {error}")).color(ui.visuals().warn_fg_color),
+                );
+            }
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button("Apply").clicked() {
+                    apply = true;
+                }
+                if ui
+                    .add_enabled(
+                        state.original_input_image.is_some(),
+                        egui::Button::new("Reset to Original"),
+                    )
+                    .clicked()
+                {
+                    reset = true;
+                }
+            });
+        });
+
+    if apply {
+        _ = state
+            .app_state_request_sender
+            .send(AppStateRequest::ResizeInput {
+                width: dialog.width,
+                height: dialog.height,
+                filter: dialog.filter,
+            });
+    }
+    if reset {
+        _ = state
+            .app_state_request_sender
+            .send(AppStateRequest::ResetInputSize);
+    }
+    if !open || apply || reset {
+        state.resize_dialog = None;
+    }
+}
+
+/// Before/after of the seam-carved input, applied only when confirmed
+fn draw_seam_fit_window(ctx: &egui::Context, state: &mut AppState) {
+    const PREVIEW_HEIGHT: f32 = 256.0;
+    let (Some(before), Some(after)) = (&state.input_image, &state.seam_fit_preview) else {
+        return;
+    };
+
+    let mut open = true;
+    let mut apply = false;
+    let mut cancel = false;
+    egui::Window::new("Fit with Seams")
+        .open(&mut open)
+        .resizable(false)
+        .collapsible(false)
+        .show(ctx, |ui| {
+            ui.label(
+                "Low-detail rows and columns were added or removed to reach a tile-divisible size.",
+            );
+            ui.horizontal(|ui| {
+                for (title, image) in [("Before", before), ("After", after)] {
+                    ui.vertical(|ui| {
+                        ui.label(format!("{title}: {}×{}", image.width, image.height));
+                        let scale = PREVIEW_HEIGHT / before.height.max(after.height) as f32;
+                        ui.image(egui::load::SizedTexture::new(
+                            image.texture.id(),
+                            Vec2::new(image.width as f32, image.height as f32) * scale,
+                        ));
+                    });
+                }
+            });
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button("Apply").clicked() {
+                    apply = true;
+                }
+                if ui.button("Cancel").clicked() {
+                    cancel = true;
+                }
+            });
+        });
+
+    if apply {
+        _ = state
+            .app_state_request_sender
+            .send(AppStateRequest::ApplySeamFit);
+    } else if !open || cancel {
+        state.seam_fit_preview = None;
+    }
+}
+
+/// Thumbnails of the queued files; clicking one switches the active image
+pub fn draw_queue_filmstrip(ui: &mut egui::Ui, state: &mut AppState) {
+    let active_index = state
+        .image_queue
+        .iter()
+        .position(|entry| state.input_path.as_deref() == Some(entry.path.as_str()));
+    let mut selected: Option<usize> = None;
+    let thumbnail_size = Vec2::splat(QUEUE_THUMBNAIL_SIZE as f32);
+
+    ui.horizontal(|ui| {
+        let last = state.image_queue.len() - 1;
+        if ui
+            .add_enabled(
+                active_index.is_some_and(|index| index > 0),
+                egui::Button::new("◀"),
+            )
+            .on_hover_text("Previous image")
+            .clicked()
+        {
+            selected = active_index.map(|index| index - 1);
+        }
+
+        egui::ScrollArea::horizontal()
+            .max_width(ui.available_width() - 180.0)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    for (index, entry) in state.image_queue.iter().enumerate() {
+                        let (rect, response) =
+                            ui.allocate_exact_size(thumbnail_size, egui::Sense::click());
+                        let painter = ui.painter();
+                        painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+                        match &entry.thumbnail {
+                            Some(texture) => {
+                                let image_rect = egui::Rect::from_center_size(
+                                    rect.center(),
+                                    texture.size_vec2(),
+                                );
+                                painter.image(
+                                    texture.id(),
+                                    image_rect,
+                                    egui::Rect::from_min_max(
+                                        egui::pos2(0.0, 0.0),
+                                        egui::pos2(1.0, 1.0),
+                                    ),
+                                    Color32::WHITE,
+                                );
+                            }
+                            None => {
+                                painter.text(
+                                    rect.center(),
+                                    egui::Align2::CENTER_CENTER,
+                                    if entry.error.is_some() { "⚠" } else { "…" },
+                                    egui::FontId::proportional(16.0),
+                                    ui.visuals().weak_text_color(),
+                                );
+                            }
+                        }
+
+                        if active_index == Some(index) {
+                            painter.rect_stroke(
+                                rect,
+                                2.0,
+                                egui::Stroke::new(2.0, styles::COLOR_TINT_ACTIVE),
+                                egui::StrokeKind::Inside,
+                            );
+                        } else if response.hovered() {
+                            painter.rect_stroke(
+                                rect,
+                                2.0,
+                                ui.visuals().widgets.hovered.bg_stroke,
+                                egui::StrokeKind::Inside,
+                            );
+                        }
+
+                        let file_name = std::path::Path::new(&entry.path)
+                            .file_name()
+                            .map_or(entry.path.clone(), |name| {
+                                name.to_string_lossy().to_string()
+                            });
+                        let response = match &entry.error {
+                            Some(error) => {
+                                response.on_hover_text(format!("{file_name}\n⚠ {error}"))
+                            }
+                            None => response.on_hover_text(file_name),
+                        };
+                        if response.clicked() && active_index != Some(index) {
+                            selected = Some(index);
+                        }
+                    }
+                });
+            });
+
+        if ui
+            .add_enabled(
+                active_index.is_some_and(|index| index < last),
+                egui::Button::new("▶"),
+            )
+            .on_hover_text("Next image")
+            .clicked()
+        {
+            selected = active_index.map(|index| index + 1);
+        }
+
+        if ui
+            .add_enabled(
+                state.batch_export.is_none(),
+                egui::Button::new("⏩ Process & Export All"),
+            )
+            .on_hover_text(format!(
+                "Apply the current settings to every queued image and export each as {} into a folder",
+                state.preferences.selected_export_format.display_name()
+            ))
+            .clicked()
+        {
+            _ = state
+                .app_state_request_sender
+                .send(AppStateRequest::BatchExportDialog);
+        }
+    });
+
+    if let Some(index) = selected
+        && let Some(entry) = state.image_queue.get(index)
+    {
+        _ = state
+            .app_state_request_sender
+            .send(AppStateRequest::LoadImage {
+                path: entry.path.clone(),
+            });
+    }
+}
+
+/// Progress of "Process & Export All", then the list of skipped files
+pub fn draw_batch_export(ctx: &egui::Context, state: &mut AppState) {
+    let Some(batch) = &mut state.batch_export else {
+        return;
+    };
+    let mut close = false;
+    egui::Modal::new(egui::Id::new("batch_export")).show(ctx, |ui| {
+        ui.set_width(360.0);
+        ui.heading("Process & Export All");
+        let total = batch.paths.len();
+        if batch.is_finished() {
+            ui.label(format!(
+                "Exported {} of {total} files as {} to {}",
+                batch.exported,
+                batch.format.display_name(),
+                batch.directory
+            ));
+            if batch.cancelled {
+                ui.label(format!(
+                    "Cancelled; {} files were not processed",
+                    total - batch.current.min(total)
+                ));
+            }
+            if !batch.skipped.is_empty() {
+                ui.separator();
+                ui.label(
+                    egui::RichText::new(format!("⚠ {} skipped", batch.skipped.len()))
+                        .color(ui.visuals().warn_fg_color),
+                );
+                egui::ScrollArea::vertical()
+                    .max_height(200.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("batch_export_skipped")
+                            .num_columns(2)
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for (file_name, reason) in &batch.skipped {
+                                    ui.label(file_name);
+                                    ui.label(reason);
+                                    ui.end_row();
+                                }
+                            });
+                    });
+            }
+            ui.separator();
+            close = ui.button("Close").clicked();
+        } else {
+            let file_name = std::path::Path::new(&batch.paths[batch.current])
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            ui.label(format!("{} of {total}: {file_name}", batch.current + 1));
+            ui.add(
+                egui::ProgressBar::new(batch.current as f32 / total as f32)
+                    .show_percentage()
+                    .animate(true),
+            );
+            if !batch.skipped.is_empty() {
+                ui.label(
+                    egui::RichText::new(format!("⚠ {} skipped so far", batch.skipped.len()))
+                        .color(ui.visuals().warn_fg_color),
+                );
+            }
+            ui.separator();
+            if ui.button("Cancel").clicked() {
+                batch.cancelled = true;
+            }
+        }
+    });
+    if close {
+        state.batch_export = None;
+    }
+}
+
 fn draw_view_controls(ui: &mut egui::Ui, state: &mut AppState) {
     let width = ui.available_width();
     if ui
@@ -37,12 +434,93 @@ fn draw_view_controls(ui: &mut egui::Ui, state: &mut AppState) {
     if width > 460.0 {
         ui.label(format!("🔍 Zoom: {:.1}x", state.zoom));
     }
+    draw_go_to_pixel(ui, state);
+}
+
+/// Center the view on a pixel typed in by coordinates
+fn draw_go_to_pixel(ui: &mut egui::Ui, state: &mut AppState) {
+    let Some((width, height)) = state
+        .input_image
+        .as_ref()
+        .map(|image| (image.width, image.height))
+    else {
+        return;
+    };
+    ui.menu_button("📍 Go to", |ui| {
+        let [x, y] = &mut state.go_to_pixel;
+        ui.horizontal(|ui| {
+            ui.label("X:");
+            ui.add(egui::DragValue::new(x).range(0..=width.saturating_sub(1)));
+            ui.label("Y:");
+            ui.add(egui::DragValue::new(y).range(0..=height.saturating_sub(1)));
+        });
+        if ui
+            .button("Center on Pixel")
+            .on_hover_text("Pan so the pixel is in the middle of the view at the current zoom")
+            .clicked()
+        {
+            let [x, y] = state.go_to_pixel.map(|v| v as f32);
+            state.pan_offset = pan_offset_centering(
+                egui::vec2(width as f32, height as f32),
+                egui::vec2(
+                    x.min(width.saturating_sub(1) as f32) + 0.5,
+                    y.min(height.saturating_sub(1) as f32) + 0.5,
+                ),
+                state.zoom,
+                state.preferences.pixel_aspect_ratio(),
+            );
+            ui.close();
+        }
+    });
+}
+
+/// Export controls of "Extract Palette" mode: palette formats only
+fn draw_palette_export_controls(ui: &mut egui::Ui, state: &mut AppState) {
+    let high_contrast = state.preferences.appearance_mode == AppearanceMode::HighContrast;
+    let Some(extraction) = &mut state.palette_extraction else {
+        return;
+    };
+    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+        ui.scope(|ui| {
+            apply_export_button_style(ui, high_contrast);
+            if ui
+                .add_enabled(
+                    state.output_image.is_some(),
+                    egui::Button::new("💾 Export Palette"),
+                )
+                .clicked()
+            {
+                _ = state
+                    .app_state_request_sender
+                    .send(AppStateRequest::ExportImageDialog {
+                        format: extraction.format.clone(),
+                        suffix: Some("palette".to_string()),
+                    });
+            }
+        });
+        egui::ComboBox::from_id_salt("export_format_palette")
+            .selected_text(extraction.format.display_name())
+            .show_ui(ui, |ui| {
+                for format in ExportFormat::palette_list() {
+                    ui.selectable_value(
+                        &mut extraction.format,
+                        format.clone(),
+                        format.display_name(),
+                    );
+                }
+            });
+    });
 }
 
 fn draw_export_controls(ui: &mut egui::Ui, state: &mut AppState) {
+    if state.palette_extraction.is_some() {
+        draw_palette_export_controls(ui, state);
+        return;
+    }
+    let high_contrast = state.preferences.appearance_mode == AppearanceMode::HighContrast;
     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
         ui.scope(|ui| {
-            apply_export_button_style(ui);
+            apply_export_button_style(ui, high_contrast);
             let response = ui.add_enabled(
                 state.output_image.is_some(),
                 egui::Button::new("💾 Export Image"),
@@ -57,6 +535,28 @@ fn draw_export_controls(ui: &mut egui::Ui, state: &mut AppState) {
             }
         });
 
+        if state.export_is_stale() {
+            ui.label(egui::RichText::new("● Changed").color(ui.visuals().warn_fg_color))
+                .on_hover_text("Settings changed since the last export");
+        }
+
+        if let Some(size) = state.export_size_estimate() {
+            let exact = state.preferences.selected_export_format != ExportFormat::PngIndexed;
+            ui.label(
+                egui::RichText::new(format!(
+                    "{}{}",
+                    if exact { "" } else { "≈" },
+                    format_file_size(size)
+                ))
+                .weak(),
+            )
+            .on_hover_text(if exact {
+                "File size of the export, including padding"
+            } else {
+                "File size of the export, from encoding it in memory.\nOther PNG encoders or optimizers will differ."
+            });
+        }
+
         // Format selection ComboBox
         egui::ComboBox::from_id_salt("export_format_footer")
             .selected_text(state.preferences.selected_export_format.display_name())
@@ -114,6 +614,33 @@ fn draw_export_controls(ui: &mut egui::Ui, state: &mut AppState) {
                 let _ = compute_tile_count(state);
             }
         });
+
+        if let Some(input_image) = &state.input_image
+            && ui
+                .button("📐 Resize")
+                .on_hover_text("Scale the input before processing")
+                .clicked()
+        {
+            state.resize_dialog = Some(ResizeDialog {
+                width: input_image.width,
+                height: input_image.height,
+                lock_aspect: true,
+                filter: ResizeFilter::default(),
+            });
+        }
+
+        if ui
+            .add_enabled(
+                state.input_image.is_some() && !state.tile_size_warning,
+                egui::Button::new("🔁 Reprocess"),
+            )
+            .on_hover_text("Run quantization again with the current settings")
+            .clicked()
+        {
+            _ = state
+                .app_state_request_sender
+                .send(AppStateRequest::Reprocess);
+        }
     });
 }
 
@@ -140,7 +667,12 @@ fn compute_tile_count(state: &mut AppState) -> Option<usize> {
     state.tile_count.last_count
 }
 
-fn apply_export_button_style(ui: &mut egui::Ui) {
+fn apply_export_button_style(ui: &mut egui::Ui, high_contrast: bool) {
+    let (tint, tint_active) = if high_contrast {
+        (styles::COLOR_TINT_HIGH_CONTRAST, Color32::WHITE)
+    } else {
+        (styles::COLOR_TINT, styles::COLOR_TINT_ACTIVE)
+    };
     ui.style_mut().spacing.button_padding = egui::vec2(10.0, 4.0);
     let style = &mut ui.style_mut();
 
@@ -148,15 +680,15 @@ fn apply_export_button_style(ui: &mut egui::Ui) {
 
     // Inactive state
     style.visuals.widgets.inactive.fg_stroke = egui::Stroke::new(1.0, Color32::WHITE);
-    style.visuals.widgets.inactive.weak_bg_fill = styles::COLOR_TINT;
+    style.visuals.widgets.inactive.weak_bg_fill = tint;
 
     // Hovered state
-    style.visuals.widgets.hovered.bg_stroke = egui::Stroke::new(1.0, styles::COLOR_TINT_ACTIVE);
+    style.visuals.widgets.hovered.bg_stroke = egui::Stroke::new(1.0, tint_active);
     style.visuals.widgets.hovered.fg_stroke = egui::Stroke::new(1.0, Color32::WHITE);
-    style.visuals.widgets.hovered.weak_bg_fill = styles::COLOR_TINT;
+    style.visuals.widgets.hovered.weak_bg_fill = tint;
 
     // Active state
-    style.visuals.widgets.active.bg_stroke = egui::Stroke::new(1.0, styles::COLOR_TINT_ACTIVE);
+    style.visuals.widgets.active.bg_stroke = egui::Stroke::new(1.0, tint_active);
     style.visuals.widgets.active.fg_stroke = egui::Stroke::new(1.0, Color32::WHITE);
-    style.visuals.widgets.active.weak_bg_fill = styles::COLOR_TINT;
+    style.visuals.widgets.active.weak_bg_fill = tint;
 }
@@ -1,14 +1,27 @@
-use crate::types::app_state::AppStateRequest;
+use crate::settings_manager::SettingsBundle;
+use crate::types::app_state::{AppStateRequest, Toast};
+use crate::types::contact_sheet::{CONTACT_SHEET_COLOR_COUNTS, CONTACT_SHEET_MAX_VARIATIONS};
+use crate::types::palette_cycle::PALETTE_CYCLE_SPEED_RANGE;
+use crate::types::preferences::{
+    BLINK_INTERVAL_RANGE_MS, EXPORT_DPI_RANGE, LOUPE_MAGNIFICATION_RANGE, PIXEL_ASPECT_PRESETS,
+    PREVIEW_DELAY_RANGE_MS,
+};
 use crate::types::{
-    AppState, ExportFormat, QualetizePreset, app_state::AppearanceMode,
-    color_correction::ColorCorrectionPreset,
+    AppState, ChannelOrder, ColorSpace, DisplayFilter, DitherMode, ExportAlphaMode,
+    ExportColorProfile, ExportFormat, ExportPadding, QualetizePreset, RowOrder,
+    app_state::AppearanceMode, color_correction::ColorCorrectionPreset,
 };
-use crate::ui::styles::UiMarginExt;
+use crate::ui::styles::{UiMarginExt, hex_color_field};
 
 pub fn draw_header(ui: &mut egui::Ui, state: &mut AppState) -> bool {
     let mut settings_changed = false;
 
     egui::MenuBar::new().ui(ui, |ui| {
+        if state.preferences.compact_mode {
+            draw_compact_toolbar(ui, state);
+            return;
+        }
+
         // --- File menu ---
         ui.menu_button("File", |ui| {
             if ui.button("Open Image...").clicked() {
@@ -17,42 +30,363 @@ pub fn draw_header(ui: &mut egui::Ui, state: &mut AppState) -> bool {
                     .send(AppStateRequest::OpenImageDialog);
                 ui.close();
             }
+            let mut extracting = state.palette_extraction.is_some();
+            if ui
+                .checkbox(&mut extracting, "Extract Palette Mode")
+                .on_hover_text("Build one palette from the image and export only the palette,\nfor sharing color schemes. Unchecking returns to the full pipeline.")
+                .changed()
+            {
+                if extracting {
+                    state.enter_palette_extraction();
+                } else {
+                    state.leave_palette_extraction();
+                }
+                settings_changed = true;
+                ui.close();
+            }
             ui.separator();
 
-            ui.menu_button("Export Image", |ui| {
-                ui.add_enabled_ui(state.color_corrected_image.is_some(), |ui| {
-                    if ui.button("Color Corrected PNG").clicked() {
-                        _ = state.app_state_request_sender.send(
-                            AppStateRequest::ExportImageDialog {
-                                format: ExportFormat::Png,
-                                suffix: Some("color_corrected".to_string()),
-                            },
+            // Extract Palette mode hides the image exports
+            if state.palette_extraction.is_none() {
+                egui::containers::menu::SubMenuButton::new("Export Image")
+                    .config(
+                        egui::containers::menu::MenuConfig::new()
+                            .close_behavior(egui::PopupCloseBehavior::CloseOnClickOutside),
+                    )
+                    .ui(ui, |ui| {
+                    ui.add_enabled_ui(state.color_corrected_image.is_some(), |ui| {
+                        if ui.button("Color Corrected PNG").clicked() {
+                            _ = state.app_state_request_sender.send(
+                                AppStateRequest::ExportImageDialog {
+                                    format: ExportFormat::Png,
+                                    suffix: Some("color_corrected".to_string()),
+                                },
+                            );
+                            ui.close();
+                        }
+                        // Only worth it when the source had precision to keep
+                        if state
+                            .input_image
+                            .as_ref()
+                            .is_some_and(|image| image.is_high_bit_depth())
+                            && ui
+                                .button("Color Corrected 16-bit PNG")
+                                .on_hover_text("The graded source at full precision, for quantizing in other tools")
+                                .clicked()
+                        {
+                            _ = state.app_state_request_sender.send(
+                                AppStateRequest::ExportImageDialog {
+                                    format: ExportFormat::Png16,
+                                    suffix: Some("color_corrected".to_string()),
+                                },
+                            );
+                            ui.close();
+                        }
+                    });
+                    ui.add_enabled_ui(state.output_image.is_some(), |ui| {
+                        if state.edit_mask.is_some()
+                            && ui
+                                .add_enabled(
+                                    !state
+                                        .output_image
+                                        .as_ref()
+                                        .is_some_and(|o| o.is_palette_only()),
+                                    egui::Button::new("Masked PNG32"),
+                                )
+                                .on_hover_text("The output inside the edit mask over the original image, as 32-bit RGBA")
+                                .clicked()
+                        {
+                            _ = state.app_state_request_sender.send(
+                                AppStateRequest::ExportImageDialog {
+                                    format: ExportFormat::MaskedPng,
+                                    suffix: Some("masked".to_string()),
+                                },
+                            );
+                            ui.close();
+                        }
+                        if ui.button("Qualetized Indexed PNG").clicked() {
+                            _ = state.app_state_request_sender.send(
+                                AppStateRequest::ExportImageDialog {
+                                    format: ExportFormat::PngIndexed,
+                                    suffix: Some("qualetized".to_string()),
+                                },
+                            );
+                            ui.close();
+                        }
+                        if ui.button("Qualetized Indexed BMP").clicked() {
+                            _ = state.app_state_request_sender.send(
+                                AppStateRequest::ExportImageDialog {
+                                    format: ExportFormat::Bmp,
+                                    suffix: Some("qualetized".to_string()),
+                                },
+                            );
+                            ui.close();
+                        }
+                        if ui.button("Qualetized Indexed TGA").clicked() {
+                            _ = state.app_state_request_sender.send(
+                                AppStateRequest::ExportImageDialog {
+                                    format: ExportFormat::Tga,
+                                    suffix: Some("qualetized".to_string()),
+                                },
+                            );
+                            ui.close();
+                        }
+                        if state
+                            .input_image
+                            .as_ref()
+                            .is_some_and(|image| image.animation().is_some())
+                            && ui
+                                .button("Qualetized Indexed APNG")
+                                .on_hover_text("Every frame of the animation as an indexed APNG with one palette")
+                                .clicked()
+                        {
+                            _ = state.app_state_request_sender.send(
+                                AppStateRequest::ExportImageDialog {
+                                    format: ExportFormat::Apng,
+                                    suffix: Some("qualetized".to_string()),
+                                },
+                            );
+                            ui.close();
+                        }
+                        if state
+                            .output_image
+                            .as_ref()
+                            .is_some_and(|image| !image.split_parts.is_empty())
+                            && ui
+                                .button("Split Indexed PNGs")
+                                .on_hover_text("One indexed PNG of at most 256 colors per band, written as\n<name>.part<N>.png, plus a JSON manifest of the tile rows each holds")
+                                .clicked()
+                        {
+                            _ = state.app_state_request_sender.send(
+                                AppStateRequest::ExportImageDialog {
+                                    format: ExportFormat::SplitPng,
+                                    suffix: Some("split".to_string()),
+                                },
+                            );
+                            ui.close();
+                        }
+                        ui.horizontal(|ui| {
+                            if ui
+                                .button("Export with Padding")
+                                .on_hover_text("Indexed PNG with the clear color (index 0) around every tile,\nor every cell in sprite sheet mode, so GPU atlases don't bleed")
+                                .clicked()
+                            {
+                                _ = state.app_state_request_sender.send(
+                                    AppStateRequest::ExportImageDialog {
+                                        format: ExportFormat::GutteredPng,
+                                        suffix: Some("gutters".to_string()),
+                                    },
+                                );
+                                ui.close();
+                            }
+                            ui.add(
+                                egui::DragValue::new(&mut state.preferences.export_cell_gutter)
+                                    .range(0..=64)
+                                    .suffix(" px"),
+                            )
+                            .on_hover_text("Gutter width around each cell");
+                        });
+                        ui.separator();
+                        if ui
+                            .button("Genesis Nametable + Tiles")
+                            .on_hover_text("VDP name table words (big-endian) plus 4bpp tile patterns\nwritten next to it as <name>.tiles.bin. Requires 8×8 tiles,\nat most 4 palettes and 16 colors per palette.")
+                            .clicked()
+                        {
+                            _ = state.app_state_request_sender.send(
+                                AppStateRequest::ExportImageDialog {
+                                    format: ExportFormat::GenesisNametable,
+                                    suffix: Some("nametable".to_string()),
+                                },
+                            );
+                            ui.close();
+                        }
+                        ui.menu_button("Tiled", |ui| {
+                            if ui
+                                .button("Map + Tileset (TMX/TSX)")
+                                .on_hover_text("Tiled map with one tile layer, plus <name>.tsx and <name>.tiles.png\nnext to it. Tiles are deduplicated with flips (run Tile Reduce first\nto merge near-duplicates); the same tile in two palettes is two tiles.")
+                                .clicked()
+                            {
+                                _ = state.app_state_request_sender.send(
+                                    AppStateRequest::ExportImageDialog {
+                                        format: ExportFormat::TiledMap,
+                                        suffix: Some("map".to_string()),
+                                    },
+                                );
+                                ui.close();
+                            }
+                        });
+                        if ui
+                            .button("Index Grid CSV")
+                            .on_hover_text("Palette index of every pixel as comma-separated rows,\nafter a header line with the dimensions.")
+                            .clicked()
+                        {
+                            _ = state.app_state_request_sender.send(
+                                AppStateRequest::ExportImageDialog {
+                                    format: ExportFormat::IndexCsv,
+                                    suffix: Some("indices".to_string()),
+                                },
+                            );
+                            ui.close();
+                        }
+                        ui.checkbox(
+                            &mut state.preferences.index_csv_split_palette,
+                            "CSV as Palette:Color",
                         );
-                        ui.close();
-                    }
+                        ui.separator();
+                        if ui
+                            .button("Palette Cycle GIF")
+                            .on_hover_text("One full cycle of View > Palette Cycling as a looping GIF")
+                            .clicked()
+                        {
+                            _ = state.app_state_request_sender.send(
+                                AppStateRequest::ExportImageDialog {
+                                    format: ExportFormat::PaletteCycleGif,
+                                    suffix: Some("cycle".to_string()),
+                                },
+                            );
+                            ui.close();
+                        }
+                    });
                 });
+            }
+
+            egui::containers::menu::SubMenuButton::new("Export Palette")
+                .config(
+                    egui::containers::menu::MenuConfig::new()
+                        .close_behavior(egui::PopupCloseBehavior::CloseOnClickOutside),
+                )
+                .ui(ui, |ui| {
                 ui.add_enabled_ui(state.output_image.is_some(), |ui| {
-                    if ui.button("Qualetized Indexed PNG").clicked() {
+                    if ui
+                        .button("PNG Swatch Strip")
+                        .on_hover_text("One row of square swatches per palette")
+                        .clicked()
+                    {
                         _ = state.app_state_request_sender.send(
                             AppStateRequest::ExportImageDialog {
-                                format: ExportFormat::PngIndexed,
-                                suffix: Some("qualetized".to_string()),
+                                format: ExportFormat::PaletteStrip,
+                                suffix: Some("palette".to_string()),
                             },
                         );
                         ui.close();
                     }
-                    if ui.button("Qualetized Indexed BMP").clicked() {
+                    if ui
+                        .button("Combined 256-Color Palette")
+                        .on_hover_text("Every palette concatenated into one JASC-PAL color table,\npadded to 256 entries. Palette p color c is at index\n(sizes of palettes before p) + c, as in the exported images.")
+                        .clicked()
+                    {
                         _ = state.app_state_request_sender.send(
                             AppStateRequest::ExportImageDialog {
-                                format: ExportFormat::Bmp,
-                                suffix: Some("qualetized".to_string()),
+                                format: ExportFormat::CombinedPalette,
+                                suffix: Some("palette".to_string()),
                             },
                         );
                         ui.close();
                     }
+                    ui.horizontal(|ui| {
+                        if ui
+                            .button("Raw Palette (.bin)")
+                            .on_hover_text("Every palette color as 4 bytes with no header,\nready to copy or DMA into palette memory")
+                            .clicked()
+                        {
+                            _ = state.app_state_request_sender.send(
+                                AppStateRequest::ExportImageDialog {
+                                    format: ExportFormat::RawPalette,
+                                    suffix: Some("palette".to_string()),
+                                },
+                            );
+                            ui.close();
+                        }
+                        egui::ComboBox::from_id_salt("raw_channel_order")
+                            .selected_text(state.preferences.raw_channel_order.display_name())
+                            .width(56.0)
+                            .show_ui(ui, |ui| {
+                                for order in ChannelOrder::all() {
+                                    ui.selectable_value(
+                                        &mut state.preferences.raw_channel_order,
+                                        *order,
+                                        order.display_name(),
+                                    );
+                                }
+                            })
+                            .response
+                            .on_hover_text("Byte order of each color");
+                    });
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Swatch Size:");
+                    ui.add(
+                        egui::DragValue::new(&mut state.preferences.palette_swatch_size)
+                            .range(1..=64)
+                            .suffix(" px"),
+                    );
                 });
             });
 
+            if ui
+                .add_enabled(
+                    state.output_image.is_some(),
+                    egui::Button::new("Export All Formats..."),
+                )
+                .on_hover_text("Write the indexed PNG, indexed BMP and 256-color palette\ninto one folder, named with the file name template.")
+                .clicked()
+            {
+                _ = state
+                    .app_state_request_sender
+                    .send(AppStateRequest::ExportAllDialog);
+                ui.close();
+            }
+            if ui
+                .add_enabled(
+                    state.input_image.is_some(),
+                    egui::Button::new("Export Contact Sheet..."),
+                )
+                .on_hover_text("Quantize several setting variations and tile the results\ninto one captioned image for side-by-side review.")
+                .clicked()
+            {
+                state.show_contact_sheet = true;
+                ui.close();
+            }
+
+            egui::containers::menu::SubMenuButton::new("Live Export")
+                .config(
+                    egui::containers::menu::MenuConfig::new()
+                        .close_behavior(egui::PopupCloseBehavior::CloseOnClickOutside),
+                )
+                .ui(ui, |ui| {
+                if ui
+                    .checkbox(
+                        &mut state.preferences.live_export_enabled,
+                        "Export on Every Update",
+                    )
+                    .on_hover_text("Write the qualetized output to the live export path\nwhenever a new result is ready, using the selected export format.")
+                    .changed()
+                    && state.preferences.live_export_enabled
+                    && state.preferences.live_export_path.is_none()
+                {
+                    _ = state
+                        .app_state_request_sender
+                        .send(AppStateRequest::LiveExportPathDialog);
+                    ui.close();
+                }
+                if ui.button("Choose Path...").clicked() {
+                    _ = state
+                        .app_state_request_sender
+                        .send(AppStateRequest::LiveExportPathDialog);
+                    ui.close();
+                }
+                ui.label(
+                    egui::RichText::new(
+                        state
+                            .preferences
+                            .live_export_path
+                            .as_deref()
+                            .unwrap_or("No path selected"),
+                    )
+                    .small(),
+                );
+            });
+
             ui.separator();
 
             ui.menu_button("Settings", |ui| {
@@ -91,8 +425,71 @@ pub fn draw_header(ui: &mut egui::Ui, state: &mut AppState) -> bool {
                     }
                 }
             });
+            if ui
+                .button("Reset Everything to Defaults")
+                .on_hover_text("Reset the Qualetize settings, color correction, palette sorting,\nzoom and pan in one go")
+                .clicked()
+            {
+                state.reset_everything();
+                state.push_toast(Toast::info(
+                    "Everything reset to defaults (Edit > Undo Reset to restore)",
+                ));
+                settings_changed = true;
+                ui.close();
+            }
+            if state.reset_undo.is_some() && ui.button("Undo Reset").clicked() {
+                settings_changed |= state.undo_reset_everything();
+                ui.close();
+            }
+            ui.menu_button("Presets", |ui| {
+                ui.menu_button("User", |ui| {
+                    if state.user_presets.is_empty() {
+                        ui.label(egui::RichText::new("No saved presets").small());
+                    }
+                    for (index, preset) in state.user_presets.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            if ui.button(&preset.name).clicked() {
+                                _ = state
+                                    .app_state_request_sender
+                                    .send(AppStateRequest::LoadUserPreset { index });
+                                ui.close();
+                            }
+                            if ui
+                                .small_button("🗑")
+                                .on_hover_text("Delete this preset")
+                                .clicked()
+                            {
+                                _ = state
+                                    .app_state_request_sender
+                                    .send(AppStateRequest::DeleteUserPreset { index });
+                            }
+                        });
+                    }
+                });
+                if ui.button("Save Current as Preset...").clicked() {
+                    state.user_preset_name = Some(String::new());
+                    ui.close();
+                }
+            });
+            ui.checkbox(
+                &mut state.preferences.grayscale_source_defaults,
+                "Grayscale Source Defaults",
+            )
+            .on_hover_text("When a grayscale image is loaded, switch to a luma color space.\nOff by default; a suggestion is shown instead.");
+            ui.checkbox(&mut state.preferences.suggest_presets, "Suggest Preset on Load")
+                .on_hover_text("Recommend a preset from the size and color count of each loaded image.\nNothing changes until the suggestion is applied.");
+            ui.checkbox(
+                &mut state.preferences.warn_on_jpeg_sources,
+                "Warn About JPEG Sources",
+            )
+            .on_hover_text("Suggest the JPEG artifact filter when a JPEG image is loaded");
             ui.separator();
-            ui.menu_button("Export Format", |ui| {
+            egui::containers::menu::SubMenuButton::new("Export Format")
+                .config(
+                    egui::containers::menu::MenuConfig::new()
+                        .close_behavior(egui::PopupCloseBehavior::CloseOnClickOutside),
+                )
+                .ui(ui, |ui| {
                 for format in ExportFormat::indexed_list() {
                     if ui
                         .selectable_value(
@@ -105,6 +502,139 @@ pub fn draw_header(ui: &mut egui::Ui, state: &mut AppState) -> bool {
                         ui.close();
                     }
                 }
+                ui.separator();
+                ui.label(egui::widget_text::RichText::new("Color Profile").small());
+                for profile in ExportColorProfile::all() {
+                    ui.selectable_value(
+                        &mut state.preferences.export_color_profile,
+                        *profile,
+                        profile.display_name(),
+                    )
+                    .on_hover_text(profile.description());
+                }
+                ui.separator();
+                ui.label(egui::widget_text::RichText::new("Resolution").small());
+                ui.horizontal(|ui| {
+                    let mut set_dpi = state.preferences.export_dpi.is_some();
+                    if ui
+                        .checkbox(&mut set_dpi, "Set DPI")
+                        .on_hover_text("Write a resolution into PNG and indexed BMP files\nfor print and layout tools. Pixels are unchanged.\nUnset, tools fall back to their own default.")
+                        .changed()
+                    {
+                        state.preferences.export_dpi = set_dpi.then_some(72);
+                    }
+                    if let Some(dpi) = &mut state.preferences.export_dpi {
+                        ui.add(
+                            egui::DragValue::new(dpi)
+                                .range(EXPORT_DPI_RANGE)
+                                .suffix(" DPI"),
+                        );
+                    }
+                });
+                ui.separator();
+                ui.label(egui::widget_text::RichText::new("Alpha").small());
+                for alpha_mode in ExportAlphaMode::all() {
+                    ui.selectable_value(
+                        &mut state.preferences.export_alpha_mode,
+                        *alpha_mode,
+                        alpha_mode.display_name(),
+                    )
+                    .on_hover_text(alpha_mode.description());
+                }
+                ui.separator();
+                ui.label(egui::widget_text::RichText::new("BMP / TGA Row Order").small());
+                for row_order in RowOrder::all() {
+                    ui.selectable_value(
+                        &mut state.preferences.row_order,
+                        *row_order,
+                        row_order.display_name(),
+                    )
+                    .on_hover_text(row_order.description());
+                }
+                ui.separator();
+                ui.label(egui::widget_text::RichText::new("Flip").small());
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut state.preferences.export_flip_x, "Flip X")
+                        .on_hover_text("Mirror exported images left to right");
+                    ui.checkbox(&mut state.preferences.export_flip_y, "Flip Y")
+                        .on_hover_text("Mirror exported images top to bottom.\nApplied before the BMP / TGA row order.");
+                });
+                ui.separator();
+                ui.label(egui::widget_text::RichText::new("Padding").small());
+                for padding in ExportPadding::all() {
+                    ui.selectable_value(
+                        &mut state.settings.export_padding,
+                        *padding,
+                        padding.display_name(),
+                    )
+                    .on_hover_text(padding.description());
+                }
+                if state.settings.export_padding == ExportPadding::Specific {
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::DragValue::new(&mut state.settings.export_padding_size[0])
+                                .range(1..=8192),
+                        );
+                        ui.label("×");
+                        ui.add(
+                            egui::DragValue::new(&mut state.settings.export_padding_size[1])
+                                .range(1..=8192),
+                        );
+                    });
+                }
+                if let Some(output_image) = &state.output_image
+                    && let Err(error) = state
+                        .settings
+                        .padded_export_size(output_image.width, output_image.height)
+                {
+                    ui.label(
+                        egui::RichText::new(format!("⚠ {error}"))
+                            .color(ui.visuals().warn_fg_color),
+                    );
+                }
+                ui.separator();
+                ui.label(egui::widget_text::RichText::new("Default Folder").small());
+                ui.label(
+                    state
+                        .preferences
+                        .export_directory
+                        .as_deref()
+                        .unwrap_or("Same as input image"),
+                );
+                ui.horizontal(|ui| {
+                    if ui.button("Choose...").clicked() {
+                        _ = state
+                            .app_state_request_sender
+                            .send(AppStateRequest::ExportDirectoryDialog);
+                        ui.close();
+                    }
+                    if ui
+                        .add_enabled(
+                            state.preferences.export_directory.is_some(),
+                            egui::Button::new("Use Input Folder"),
+                        )
+                        .clicked()
+                    {
+                        _ = state
+                            .app_state_request_sender
+                            .send(AppStateRequest::SetExportDirectory { path: None });
+                    }
+                });
+                ui.label(egui::widget_text::RichText::new("File Name Template").small());
+                ui.add(
+                    egui::TextEdit::singleline(&mut state.preferences.export_filename_template)
+                        .hint_text("{name}_{suffix}"),
+                )
+                .on_hover_text("Tokens: {name}, {suffix}, {palettes}, {colors}, {space}\nLeave empty for <name>_<suffix>.");
+
+                ui.separator();
+                ui.checkbox(&mut state.preferences.confirm_exports, "Confirm Before Exporting")
+                    .on_hover_text("Show format, size, palette and destination before writing");
+                ui.checkbox(
+                    &mut state.preferences.export_metadata_sidecar,
+                    "Write Metadata Sidecar (.json)",
+                )
+                .on_hover_text("Save the settings, source, size, palette layout and RMSE next to each exported image.\nThe file can be loaded back with Load Settings.");
             });
         });
 
@@ -117,7 +647,8 @@ pub fn draw_header(ui: &mut egui::Ui, state: &mut AppState) -> bool {
             .ui(ui, |ui| {
                 ui.label(egui::widget_text::RichText::new("Settings").small());
                 ui.checkbox(&mut state.preferences.show_advanced, "Advanced Settings");
-                ui.checkbox(&mut state.preferences.show_debug_info, "Debug Info");
+                ui.checkbox(&mut state.preferences.show_debug_info, "Debug Info")
+                    .on_hover_text("Debug section in the settings panel and a timing HUD in the corner");
 
                 ui.separator();
                 ui.label(egui::widget_text::RichText::new("Canvas").small());
@@ -126,10 +657,147 @@ pub fn draw_header(ui: &mut egui::Ui, state: &mut AppState) -> bool {
                     &mut state.preferences.show_color_corrected_image,
                     "Color Corrected Image",
                 );
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut state.preferences.blink_compare, "Blink Compare")
+                        .on_hover_text("Alternate the output panel between the original and the output,\nso small differences stand out. Space pauses it.");
+                    ui.add_enabled(
+                        state.preferences.blink_compare,
+                        egui::DragValue::new(&mut state.preferences.blink_interval_ms)
+                            .range(BLINK_INTERVAL_RANGE_MS)
+                            .speed(5.0)
+                            .suffix(" ms"),
+                    )
+                    .on_hover_text("How long each side stays up");
+                    let pause_label = if state.blink_paused { "▶" } else { "⏸" };
+                    if ui
+                        .add_enabled(
+                            state.preferences.blink_compare,
+                            egui::Button::new(pause_label),
+                        )
+                        .on_hover_text("Pause or resume blinking on the current side")
+                        .clicked()
+                    {
+                        state.blink_paused = !state.blink_paused;
+                    }
+                });
 
                 ui.separator();
 
                 ui.checkbox(&mut state.preferences.show_palettes, "Palettes");
+                ui.checkbox(
+                    &mut state.preferences.show_export_diff,
+                    "Changes Since Last Export",
+                )
+                .on_hover_text("Highlight output pixels whose index differs from the last export");
+                ui.checkbox(
+                    &mut state.preferences.show_tile_inspector,
+                    "Tile Palette Inspector",
+                )
+                .on_hover_text("Hover a tile of the output to see which palette it uses");
+                ui.checkbox(
+                    &mut state.preferences.show_palette_inspector,
+                    "Palette Inspector",
+                )
+                .on_hover_text("List every palette with indices and hex values in a separate window");
+                if state
+                    .input_image
+                    .as_ref()
+                    .is_some_and(|image| image.animation().is_some())
+                {
+                    ui.checkbox(&mut state.animation_preview.show, "Animation Frames")
+                        .on_hover_text("Step through or play the frames of the animated source and output");
+                }
+                ui.checkbox(&mut state.palette_diff.show, "Palette Comparison")
+                    .on_hover_text("Compare two palette files, or the output and a file, with per-color ΔE");
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut state.preferences.show_loupe, "Loupe")
+                        .on_hover_text("Magnify the pixels under the cursor without changing the zoom");
+                    ui.add_enabled(
+                        state.preferences.show_loupe,
+                        egui::DragValue::new(&mut state.preferences.loupe_magnification)
+                            .range(LOUPE_MAGNIFICATION_RANGE)
+                            .speed(0.1)
+                            .suffix("×"),
+                    )
+                    .on_hover_text("Loupe magnification relative to the current zoom");
+                });
+                ui.checkbox(
+                    &mut state.preferences.smooth_when_zoomed_out,
+                    "Smooth When Zoomed Out",
+                )
+                .on_hover_text(
+                    "Filter images linearly when they are drawn smaller than 1:1.\nZoomed in pixels always stay sharp.",
+                );
+                ui.menu_button("Display Filter", |ui| {
+                    for filter in DisplayFilter::all() {
+                        if ui
+                            .selectable_value(
+                                &mut state.preferences.display_filter,
+                                *filter,
+                                filter.display_name(),
+                            )
+                            .on_hover_text(filter.description())
+                            .clicked()
+                        {
+                            ui.close();
+                        }
+                    }
+                });
+
+                egui::containers::menu::SubMenuButton::new("Pixel Aspect")
+                    .config(
+                        egui::containers::menu::MenuConfig::new()
+                            .close_behavior(egui::PopupCloseBehavior::CloseOnClickOutside),
+                    )
+                    .ui(ui, |ui| {
+                        for (name, aspect) in PIXEL_ASPECT_PRESETS {
+                            ui.selectable_value(
+                                &mut state.preferences.pixel_aspect,
+                                *aspect,
+                                *name,
+                            );
+                        }
+                        ui.separator();
+                        ui.horizontal(|ui| {
+                            ui.label("Custom:");
+                            ui.add(
+                                egui::DragValue::new(&mut state.preferences.pixel_aspect[0])
+                                    .range(1..=64),
+                            );
+                            ui.label(":");
+                            ui.add(
+                                egui::DragValue::new(&mut state.preferences.pixel_aspect[1])
+                                    .range(1..=64),
+                            );
+                        });
+                    });
+
+                egui::containers::menu::SubMenuButton::new("Palette Cycling")
+                    .config(
+                        egui::containers::menu::MenuConfig::new()
+                            .close_behavior(egui::PopupCloseBehavior::CloseOnClickOutside),
+                    )
+                    .ui(ui, |ui| {
+                        ui.checkbox(&mut state.palette_cycle.enabled, "Animate Preview")
+                            .on_hover_text("Rotate a range of palette indices in the Qualetized preview.\nExports keep the original palette order.");
+                        ui.horizontal(|ui| {
+                            ui.label("Indices:");
+                            ui.add(egui::DragValue::new(&mut state.palette_cycle.first_index));
+                            ui.label("to");
+                            ui.add(egui::DragValue::new(&mut state.palette_cycle.last_index));
+                        })
+                        .response
+                        .on_hover_text("Combined palette indices, as in the exported images");
+                        ui.horizontal(|ui| {
+                            ui.label("Speed:");
+                            ui.add(
+                                egui::DragValue::new(&mut state.palette_cycle.steps_per_second)
+                                    .range(PALETTE_CYCLE_SPEED_RANGE)
+                                    .speed(0.1)
+                                    .suffix(" steps/s"),
+                            );
+                        });
+                    });
 
                 ui.separator();
 
@@ -158,6 +826,31 @@ pub fn draw_header(ui: &mut egui::Ui, state: &mut AppState) -> bool {
 
                 ui.separator();
 
+                if ui
+                    .add_enabled(
+                        state.input_path.is_some(),
+                        egui::Button::new("Save View for This Image"),
+                    )
+                    .on_hover_text("Remember the background color and shown panels for this image.\nOther images keep using the global view.")
+                    .clicked()
+                {
+                    state.save_image_view();
+                    ui.close();
+                }
+                if state.has_image_view() && ui.button("Forget View for This Image").clicked() {
+                    state.forget_image_view();
+                    ui.close();
+                }
+
+                ui.separator();
+
+                ui.checkbox(&mut state.preferences.compact_mode, "Compact Mode")
+                    .on_hover_text("Only the images and a small toolbar, for keeping Qualetize\nbeside a pixel editor while iterating");
+                ui.checkbox(&mut state.preferences.always_on_top, "Always on Top")
+                    .on_hover_text("Keep this window above other applications");
+
+                ui.separator();
+
                 if ui
                     .checkbox(&mut state.preferences.show_appearance, "Appearance")
                     .clicked()
@@ -165,8 +858,27 @@ pub fn draw_header(ui: &mut egui::Ui, state: &mut AppState) -> bool {
                     ui.close();
                 }
             });
+
+        // --- Help menu ---
+        ui.menu_button("Help", |ui| {
+            if ui
+                .checkbox(
+                    &mut state.preferences.show_help,
+                    "Color Spaces & Dithering",
+                )
+                .clicked()
+            {
+                ui.close();
+            }
+        });
     });
 
+    draw_help_window(ui.ctx(), state);
+    draw_recovery_window(ui.ctx(), state);
+    draw_export_confirmation(ui.ctx(), state);
+    draw_contact_sheet_window(ui.ctx(), state);
+    draw_user_preset_prompt(ui.ctx(), state);
+
     let mut show_dialog = state.preferences.show_appearance;
     if egui::Window::new("Appearance")
         .open(&mut show_dialog)
@@ -190,6 +902,12 @@ pub fn draw_header(ui: &mut egui::Ui, state: &mut AppState) -> bool {
                     AppearanceMode::Dark,
                     "Dark",
                 );
+                ui.selectable_value(
+                    &mut state.preferences.appearance_mode,
+                    AppearanceMode::HighContrast,
+                    "High Contrast",
+                )
+                .on_hover_text("Black background, white text and outlined controls");
             });
             ui.separator();
 
@@ -218,19 +936,81 @@ pub fn draw_header(ui: &mut egui::Ui, state: &mut AppState) -> bool {
                 // Show color picker only when using custom
                 if !use_default && let Some(ref mut color) = state.preferences.background_color {
                     let mut color_array = [color.r(), color.g(), color.b()];
-                    if ui.color_edit_button_srgb(&mut color_array).changed() {
+                    let mut changed = ui.color_edit_button_srgb(&mut color_array).changed();
+                    changed |= hex_color_field(ui, "background_color_hex", &mut color_array);
+                    if changed {
                         *color =
                             egui::Color32::from_rgb(color_array[0], color_array[1], color_array[2]);
                     }
+                }
+            });
 
-                    // Show current color as text
-                    ui.label(format!(
-                        "#{:02X}{:02X}{:02X}",
-                        color.r(),
-                        color.g(),
-                        color.b()
-                    ));
+            ui.separator();
+
+            ui.subheading_with_margin("Palette Padding Color");
+            let padding_before = state.preferences.palette_padding_color;
+            ui.horizontal(|ui| {
+                let mut transparent = state.preferences.palette_padding_color.is_none();
+                if ui
+                    .selectable_value(&mut transparent, true, "Transparent")
+                    .changed()
+                    && transparent
+                {
+                    state.preferences.palette_padding_color = None;
+                }
+                if ui
+                    .selectable_value(&mut transparent, false, "Color")
+                    .changed()
+                    && !transparent
+                {
+                    state.preferences.palette_padding_color = Some(egui::Color32::BLACK);
+                }
+                if let Some(ref mut color) = state.preferences.palette_padding_color {
+                    let mut color_array = [color.r(), color.g(), color.b()];
+                    let mut changed = ui.color_edit_button_srgb(&mut color_array).changed();
+                    changed |= hex_color_field(ui, "palette_padding_color_hex", &mut color_array);
+                    if changed {
+                        *color =
+                            egui::Color32::from_rgb(color_array[0], color_array[1], color_array[2]);
+                    }
                 }
+            })
+            .response
+            .on_hover_text("Fill for palette slots the quantizer left unused, in the palette strip and inspector");
+            if state.preferences.palette_padding_color != padding_before {
+                state.repad_output_palettes();
+            }
+
+            ui.separator();
+
+            ui.subheading_with_margin("Preview");
+            ui.horizontal(|ui| {
+                ui.label("Preview delay (ms):");
+                ui.add(
+                    egui::DragValue::new(&mut state.preferences.preview_delay_ms)
+                        .range(PREVIEW_DELAY_RANGE_MS)
+                        .speed(10.0),
+                )
+                .on_hover_text(
+                    "How long to wait after the last change before re-quantizing.\nRaise it on slow machines or for heavy CIELAB runs.",
+                );
+            });
+            ui.horizontal(|ui| {
+                ui.label("Processing threads:");
+                ui.add(
+                    egui::DragValue::new(&mut state.preferences.processing_threads)
+                        .range(0..=64)
+                        .custom_formatter(|n, _| {
+                            if n == 0.0 {
+                                "Auto".to_string()
+                            } else {
+                                format!("{n}")
+                            }
+                        }),
+                )
+                .on_hover_text(
+                    "Sprite sheet cells are quantized in parallel on this many threads.\nAuto uses every available core. Results are identical for any count.",
+                );
             });
 
             ui.separator();
@@ -248,3 +1028,266 @@ pub fn draw_header(ui: &mut egui::Ui, state: &mut AppState) -> bool {
 
     settings_changed
 }
+
+/// Stand-in for the menu bar in compact mode
+fn draw_compact_toolbar(ui: &mut egui::Ui, state: &mut AppState) {
+    if ui.button("☰").on_hover_text("Leave compact mode").clicked() {
+        state.preferences.compact_mode = false;
+    }
+    ui.toggle_value(&mut state.preferences.always_on_top, "📌")
+        .on_hover_text("Always on top");
+    ui.separator();
+    if ui.button("Open...").clicked() {
+        _ = state
+            .app_state_request_sender
+            .send(AppStateRequest::OpenImageDialog);
+    }
+    if ui
+        .add_enabled(state.output_image.is_some(), egui::Button::new("💾 Export"))
+        .on_hover_text(format!(
+            "Export as {}",
+            state.preferences.selected_export_format.display_name()
+        ))
+        .clicked()
+    {
+        _ = state
+            .app_state_request_sender
+            .send(AppStateRequest::ExportImageDialog {
+                format: state.preferences.selected_export_format.clone(),
+                suffix: Some("qualetized".to_string()),
+            });
+    }
+    if state.preferences.live_export_path.is_some() {
+        ui.checkbox(&mut state.preferences.live_export_enabled, "Live Export")
+            .on_hover_text("Write the output to the live export path on every update");
+    }
+}
+
+/// Offer the settings autosaved by a session that didn't exit cleanly
+fn draw_recovery_window(ctx: &egui::Context, state: &mut AppState) {
+    if !state.recovery_available {
+        return;
+    }
+    egui::Window::new("Recover Settings")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, |ui| {
+            ui.label("The last session did not exit cleanly.\nRestore the settings it was using?");
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button("Restore").clicked() {
+                    match SettingsBundle::recovery_file_path() {
+                        Ok(path) => {
+                            _ = state.app_state_request_sender.send(
+                                AppStateRequest::LoadSettings {
+                                    path: path.display().to_string(),
+                                },
+                            );
+                        }
+                        Err(e) => log::error!("Failed to locate recovery file: {e}"),
+                    }
+                    state.recovery_available = false;
+                }
+                if ui.button("Discard").clicked() {
+                    SettingsBundle::remove_recovery_file();
+                    state.recovery_available = false;
+                }
+            });
+        });
+}
+
+/// Name prompt for "Save Current as Preset..."
+fn draw_user_preset_prompt(ctx: &egui::Context, state: &mut AppState) {
+    let Some(name) = &mut state.user_preset_name else {
+        return;
+    };
+    let mut open = true;
+    let mut close = false;
+    egui::Window::new("Save Preset")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, |ui| {
+            let response = ui.text_edit_singleline(name);
+            response.request_focus();
+            let trimmed = name.trim();
+            let exists = state
+                .user_presets
+                .iter()
+                .any(|preset| preset.name == trimmed);
+            if exists {
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    "⚠ Replaces the preset with this name",
+                );
+            }
+            ui.horizontal(|ui| {
+                let submitted =
+                    response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter));
+                if (ui
+                    .add_enabled(!trimmed.is_empty(), egui::Button::new("Save"))
+                    .clicked()
+                    || submitted)
+                    && !trimmed.is_empty()
+                {
+                    _ = state
+                        .app_state_request_sender
+                        .send(AppStateRequest::SaveUserPreset {
+                            name: trimmed.to_string(),
+                        });
+                    close = true;
+                }
+                if ui.button("Cancel").clicked() {
+                    close = true;
+                }
+            });
+        });
+    if !open || close {
+        state.user_preset_name = None;
+    }
+}
+
+/// Variation picker for the contact sheet export
+fn draw_contact_sheet_window(ctx: &egui::Context, state: &mut AppState) {
+    let mut show_dialog = state.show_contact_sheet;
+    egui::Window::new("Contact Sheet")
+        .open(&mut show_dialog)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label("Every checked color space is combined with every checked palette size.\nLeave a group empty to keep the current value.");
+            ui.separator();
+            ui.label(egui::RichText::new("Color Spaces").small());
+            for color_space in ColorSpace::all() {
+                let mut checked = state.contact_sheet.color_spaces.contains(color_space);
+                if ui
+                    .checkbox(&mut checked, color_space.display_name())
+                    .changed()
+                {
+                    state.contact_sheet.toggle_color_space(color_space);
+                }
+            }
+            ui.separator();
+            ui.label(egui::RichText::new("Colors per Palette").small());
+            ui.horizontal_wrapped(|ui| {
+                for &n_colors in CONTACT_SHEET_COLOR_COUNTS {
+                    let selected = state.contact_sheet.colors_per_palette.contains(&n_colors);
+                    if ui
+                        .selectable_label(selected, n_colors.to_string())
+                        .clicked()
+                    {
+                        state.contact_sheet.toggle_colors_per_palette(n_colors);
+                    }
+                }
+            });
+            ui.separator();
+            ui.label(format!(
+                "{} variation(s), at most {CONTACT_SHEET_MAX_VARIATIONS}",
+                state.contact_sheet.variation_count()
+            ));
+            if ui
+                .add_enabled(state.input_image.is_some(), egui::Button::new("Export..."))
+                .clicked()
+            {
+                _ = state
+                    .app_state_request_sender
+                    .send(AppStateRequest::ContactSheetDialog);
+            }
+        });
+    state.show_contact_sheet = show_dialog;
+}
+
+/// Summary of a pending export; nothing is written until it is confirmed
+fn draw_export_confirmation(ctx: &egui::Context, state: &mut AppState) {
+    let Some(pending) = state.pending_export.as_ref() else {
+        return;
+    };
+    let mut confirmed = false;
+    let mut cancelled = false;
+    egui::Window::new("Export")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, |ui| {
+            egui::Grid::new("export_summary")
+                .num_columns(2)
+                .striped(true)
+                .show(ui, |ui| {
+                    for (label, value) in &pending.summary {
+                        ui.strong(*label);
+                        ui.label(value);
+                        ui.end_row();
+                    }
+                });
+            ui.separator();
+            let mut dont_show_again = !state.preferences.confirm_exports;
+            ui.checkbox(&mut dont_show_again, "Don't show this again")
+                .on_hover_text("Re-enable under Edit → Export Format");
+            state.preferences.confirm_exports = !dont_show_again;
+            ui.horizontal(|ui| {
+                confirmed = ui.button("Export").clicked();
+                cancelled = ui.button("Cancel").clicked();
+            });
+        });
+    if confirmed {
+        if let Some(pending) = state.pending_export.take() {
+            _ = state.app_state_request_sender.send(pending.request);
+        }
+    } else if cancelled {
+        state.pending_export = None;
+    }
+}
+
+/// Reference of every color space and dither mode, built from their descriptions
+fn draw_help_window(ctx: &egui::Context, state: &mut AppState) {
+    let mut show_dialog = state.preferences.show_help;
+    egui::Window::new("Color Spaces & Dithering")
+        .open(&mut show_dialog)
+        .collapsible(false)
+        .default_width(520.0)
+        .show(ctx, |ui| {
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                ui.subheading_with_margin("Palette Budget");
+                ui.label(
+                    "Indexed output holds at most 256 colors, so palettes × colors per palette \
+                     must be 256 or less (e.g. 16 × 16 or 8 × 32). Color 0 of each palette can \
+                     be reserved as transparent.",
+                );
+                ui.separator();
+
+                ui.subheading_with_margin("Color Spaces");
+                egui::Grid::new("help_color_spaces")
+                    .num_columns(2)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for color_space in ColorSpace::all() {
+                            ui.strong(color_space.display_name());
+                            ui.vertical(|ui| {
+                                ui.label(color_space.description());
+                                ui.label(egui::RichText::new(color_space.recommended_use()).weak());
+                            });
+                            ui.end_row();
+                        }
+                    });
+                ui.separator();
+
+                ui.subheading_with_margin("Dither Modes");
+                egui::Grid::new("help_dither_modes")
+                    .num_columns(2)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for dither_mode in DitherMode::all() {
+                            ui.strong(dither_mode.display_name());
+                            ui.vertical(|ui| {
+                                ui.label(dither_mode.description());
+                                ui.label(egui::RichText::new(dither_mode.recommended_use()).weak());
+                            });
+                            ui.end_row();
+                        }
+                    });
+            });
+        });
+    state.preferences.show_help = show_dialog;
+}
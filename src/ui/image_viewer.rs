@@ -1,5 +1,14 @@
 use super::styles::UiMarginExt;
-use crate::types::AppState;
+use crate::image_processor::RegionOfInterest;
+use crate::types::app_state::AppStateRequest;
+use crate::types::color_correction::{BRIGHTNESS_RANGE, CONTRAST_RANGE};
+use crate::types::image::{ImageDataIndexed, preview_texture_options};
+use crate::types::palette_diff::{
+    PaletteDiffSide, PaletteDiffSort, delta_e_summary, diff_palettes, sort_rows,
+};
+use crate::types::preferences::LOUPE_MAGNIFICATION_RANGE;
+use crate::types::qualetize::pack_color;
+use crate::types::{AppState, DisplayFilter};
 use egui::{Align2, Color32, FontId, Id, Pos2, Rect, Vec2};
 
 pub fn draw_image_view(ui: &mut egui::Ui, state: &mut AppState, image_processing: bool) {
@@ -7,9 +16,21 @@ pub fn draw_image_view(ui: &mut egui::Ui, state: &mut AppState, image_processing
     let mut available_size = ui.available_size();
     available_size.y -= 34.0; // footer size
 
+    handle_palette_keyboard(ui.ctx(), state);
+    let display_filter_texture = update_display_filter_texture(ui.ctx(), state);
+    let palette_cycle_texture = update_palette_cycle_texture(ui.ctx(), state);
+    let masked_output_texture = update_masked_output_texture(ui.ctx(), state);
+    let filtered_output_texture = update_isolated_color_texture(ui.ctx(), state)
+        .or(palette_cycle_texture)
+        .or(display_filter_texture)
+        .or(masked_output_texture);
+    let export_diff_texture = update_export_diff_texture(ui.ctx(), state);
+    let blink_original = update_blink(ui.ctx(), state);
+    let marked_tile = worst_tile_rect(state);
+    let mut interaction = PanelInteraction::default();
+
     let zoom = state.zoom;
     let pan_offset = state.pan_offset;
-    let mut pan_changed = egui::Vec2::ZERO;
 
     let split_x =
         if state.preferences.show_original_image || state.preferences.show_color_corrected_image {
@@ -36,8 +57,12 @@ pub fn draw_image_view(ui: &mut egui::Ui, state: &mut AppState, image_processing
                     zoom,
                     pan_offset,
                     title: "Original".into(),
-                    has_spinner: false,
+                    has_spinner: state.image_loading,
                     overlay_text: None,
+                    texture_override: None,
+                    overlay_texture: None,
+                    tile_inspector: false,
+                    marked_tile: None,
                 };
                 draw_image_panel(
                     ui,
@@ -45,7 +70,7 @@ pub fn draw_image_view(ui: &mut egui::Ui, state: &mut AppState, image_processing
                     settings,
                     &state.input_image,
                     None,
-                    &mut pan_changed,
+                    &mut interaction,
                 );
             }
             if state.preferences.show_color_corrected_image {
@@ -57,6 +82,10 @@ pub fn draw_image_view(ui: &mut egui::Ui, state: &mut AppState, image_processing
                     title: "Color Corrected".into(),
                     has_spinner: state.color_corrected_image.is_none(),
                     overlay_text: None,
+                    texture_override: None,
+                    overlay_texture: None,
+                    tile_inspector: false,
+                    marked_tile: None,
                 };
                 draw_image_panel(
                     ui,
@@ -64,24 +93,13 @@ pub fn draw_image_view(ui: &mut egui::Ui, state: &mut AppState, image_processing
                     settings,
                     &state.color_corrected_image,
                     None,
-                    &mut pan_changed,
+                    &mut interaction,
                 );
             }
         });
 
         // Right panel
         if !state.tile_size_warning {
-            let palettes_for_ui =
-                if let Some(indexed_image) = &state.output_palette_sorted_indexed_image {
-                    Some(&indexed_image.palettes_for_ui)
-                } else if let Some(image) = &state.output_image {
-                    image
-                        .indexed
-                        .as_ref()
-                        .map(|indexed_image| &indexed_image.palettes_for_ui)
-                } else {
-                    None
-                };
             let tile_reduced = state.settings.tile_reduce_post_enabled
                 && (state.tile_reduce_processing || state.reduced_tile_count.is_some());
             let toast = if let Some(toast) = &state.tile_reduce_toast {
@@ -102,27 +120,68 @@ pub fn draw_image_view(ui: &mut egui::Ui, state: &mut AppState, image_processing
             {
                 let _ = state.tile_reduce_toast.take();
             }
+            let palettes_for_ui = displayed_palettes(state);
 
-            let settings = ImagePanelSettings {
-                width: split_x,
-                height: available_size.y,
-                zoom,
-                pan_offset,
-                title: if tile_reduced {
+            let settings = if state.settings.palette_only {
+                ImagePanelSettings {
+                    width: split_x,
+                    height: available_size.y,
+                    zoom,
+                    pan_offset,
+                    title: "Palette Only".into(),
+                    has_spinner: image_processing,
+                    overlay_text: toast,
+                    texture_override: None,
+                    overlay_texture: None,
+                    tile_inspector: false,
+                    marked_tile: None,
+                }
+            } else {
+                let mut title = if blink_original {
+                    "Original".to_string()
+                } else if tile_reduced {
                     "Qualetized + Tile Reduced".into()
                 } else {
                     "Qualetized".into()
-                },
-                has_spinner: image_processing,
-                overlay_text: toast,
+                };
+                if state.preferences.blink_compare {
+                    let paused = if state.blink_paused { ", Paused" } else { "" };
+                    title = format!("{title} (Blink{paused})");
+                }
+                // The original is stretched over the output, so both line up while blinking
+                let blink_texture = state
+                    .input_image
+                    .as_ref()
+                    .filter(|_| blink_original)
+                    .map(|image| image.texture.id());
+                ImagePanelSettings {
+                    width: split_x,
+                    height: available_size.y,
+                    zoom,
+                    pan_offset,
+                    title,
+                    has_spinner: image_processing,
+                    overlay_text: toast,
+                    texture_override: blink_texture.or(filtered_output_texture),
+                    overlay_texture: export_diff_texture.filter(|_| !blink_original),
+                    tile_inspector: state.preferences.show_tile_inspector,
+                    marked_tile,
+                }
+            };
+            // Palette-only mode shows just the palette strip over an empty canvas
+            let no_image = None;
+            let output_image = if state.settings.palette_only {
+                &no_image
+            } else {
+                &state.output_image
             };
             draw_image_panel(
                 ui,
                 state,
                 settings,
-                &state.output_image,
+                output_image,
                 palettes_for_ui,
-                &mut pan_changed,
+                &mut interaction,
             );
         } else {
             // Status/ Warning message
@@ -131,8 +190,30 @@ pub fn draw_image_view(ui: &mut egui::Ui, state: &mut AppState, image_processing
     });
 
     // Apply changes back to state (this block is common to both views)
+    let PanelInteraction {
+        pan_changed,
+        clicked_chip,
+        drawn_mask,
+    } = interaction;
     if pan_changed != egui::Vec2::ZERO {
-        state.pan_offset += pan_changed;
+        if ui.input(|i| i.modifiers.shift) {
+            apply_grading_drag(state, pan_changed);
+        } else {
+            state.pan_offset += pan_changed;
+        }
+    }
+    if state.grading_drag {
+        if ui.input(|i| i.pointer.any_down()) {
+            draw_grading_drag_overlay(ui.ctx(), state);
+        } else {
+            state.grading_drag = false;
+        }
+    }
+    if clicked_chip.is_some() {
+        state.palette_focus = clicked_chip;
+    }
+    if drawn_mask.is_some() {
+        state.edit_mask = drawn_mask;
     }
 
     // Handle mouse interaction (this block is also common)
@@ -146,9 +227,474 @@ pub fn draw_image_view(ui: &mut egui::Ui, state: &mut AppState, image_processing
     }
 }
 
-pub fn draw_main_content(ui: &mut egui::Ui) {
+/// Shift-drag grading: horizontal movement is brightness, upward movement adds contrast.
+/// The re-quantization this triggers goes through the usual preview debounce.
+fn apply_grading_drag(state: &mut AppState, delta: Vec2) {
+    const POINTS_PER_UNIT: f32 = 400.0;
+    let correction = &mut state.color_correction;
+    correction.brightness = (correction.brightness + delta.x / POINTS_PER_UNIT)
+        .clamp(*BRIGHTNESS_RANGE.start(), *BRIGHTNESS_RANGE.end());
+    correction.contrast = (correction.contrast - delta.y / POINTS_PER_UNIT)
+        .clamp(*CONTRAST_RANGE.start(), *CONTRAST_RANGE.end());
+    state.grading_drag = true;
+}
+
+/// Current brightness/contrast next to the cursor while grading by dragging
+fn draw_grading_drag_overlay(ctx: &egui::Context, state: &AppState) {
+    let Some(pointer) = ctx.pointer_latest_pos() else {
+        return;
+    };
+    let painter = ctx.layer_painter(egui::LayerId::new(
+        egui::Order::Tooltip,
+        Id::new("grading_drag_overlay"),
+    ));
+    let text = format!(
+        "Brightness {:+.0}%  Contrast {:.2}",
+        state.color_correction.brightness * 100.0,
+        state.color_correction.contrast
+    );
+    let anchor = Rect::from_center_size(pointer + egui::vec2(0.0, -28.0), Vec2::ZERO);
+    draw_overlay_text(&painter, anchor, ctx, &text);
+}
+
+/// Indexed output as shown and exported (sorted, if sorting is enabled)
+fn displayed_indexed(state: &AppState) -> Option<&ImageDataIndexed> {
+    state.output_palette_sorted_indexed_image.as_ref().or(state
+        .output_image
+        .as_ref()?
+        .indexed
+        .as_ref())
+}
+
+fn displayed_palettes(state: &AppState) -> Option<&Vec<Vec<egui::Color32>>> {
+    displayed_indexed(state).map(|indexed_image| &indexed_image.palettes_for_ui)
+}
+
+/// Resizable window listing every displayed palette with larger swatches,
+/// indices and hex values. Clicking a swatch focuses it like a corner chip.
+pub fn draw_palette_inspector(ctx: &egui::Context, state: &mut AppState) {
+    if !state.preferences.show_palette_inspector {
+        return;
+    }
+    let mut open = true;
+    let mut clicked_chip = None;
+    let swatch_size = state.preferences.palette_swatch_size.max(8) as f32;
+    let focused = state.palette_focus;
+    let isolated = state.isolated_color;
+    let palettes = displayed_palettes(state);
+
+    egui::Window::new("Palette Inspector")
+        .open(&mut open)
+        .resizable(true)
+        .default_width(360.0)
+        .show(ctx, |ui| {
+            let Some(palettes) = palettes.filter(|palettes| !palettes.is_empty()) else {
+                ui.label("No output palettes yet.");
+                return;
+            };
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                let mut global_index = 0;
+                for (palette_idx, palette) in palettes.iter().enumerate() {
+                    egui::CollapsingHeader::new(format!(
+                        "Palette {palette_idx} ({} colors)",
+                        palette.len()
+                    ))
+                    .id_salt(("palette_inspector", palette_idx))
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        egui::Grid::new(("palette_inspector_grid", palette_idx))
+                            .num_columns(3)
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for (color_idx, &color) in palette.iter().enumerate() {
+                                    let chip = (palette_idx, color_idx);
+                                    let (rect, response) = ui.allocate_exact_size(
+                                        Vec2::splat(swatch_size),
+                                        egui::Sense::click(),
+                                    );
+                                    let painter = ui.painter();
+                                    painter.rect_filled(rect, 0.0, color);
+                                    if focused == Some(chip) || isolated == Some(chip) {
+                                        painter.rect_stroke(
+                                            rect,
+                                            0.0,
+                                            egui::Stroke::new(
+                                                2.0,
+                                                ui.visuals().strong_text_color(),
+                                            ),
+                                            egui::StrokeKind::Inside,
+                                        );
+                                    }
+                                    if response.clicked() {
+                                        clicked_chip = Some(chip);
+                                    }
+                                    ui.monospace(format!("{color_idx:>3} ({global_index:>3})"));
+                                    let [r, g, b, a] = color.to_srgba_unmultiplied();
+                                    ui.monospace(if a == 255 {
+                                        format!("#{r:02X}{g:02X}{b:02X}")
+                                    } else {
+                                        format!("#{a:02X}{r:02X}{g:02X}{b:02X}")
+                                    });
+                                    ui.end_row();
+                                    global_index += 1;
+                                }
+                            });
+                    });
+                }
+            });
+        });
+
+    if clicked_chip.is_some() {
+        state.palette_focus = clicked_chip;
+    }
+    state.preferences.show_palette_inspector = open;
+}
+
+/// ΔE at or above which a pair of colors is flagged as clearly different
+const PALETTE_DIFF_WARNING_DELTA_E: f32 = 5.0;
+
+/// One frame of an animated source and of its output at a time, with playback
+pub fn draw_animation_preview(ctx: &egui::Context, state: &mut AppState) {
+    const FRAME_PREVIEW_HEIGHT: f32 = 160.0;
+
+    let Some(input) = &state.input_image else {
+        return;
+    };
+    let Some(animation) = input.animation() else {
+        return;
+    };
+    if !state.animation_preview.show {
+        return;
+    }
+    let frame_count = animation.frame_count();
+    let delays_ms = animation.delays_ms.clone();
+    let frame_height = animation.frame_height(input.height).unwrap_or(input.height);
+    let size = Vec2::new(input.width as f32, frame_height as f32);
+    // Whole-number zoom for small frames so pixels stay square
+    let scale = if size.y > FRAME_PREVIEW_HEIGHT {
+        FRAME_PREVIEW_HEIGHT / size.y
+    } else {
+        (FRAME_PREVIEW_HEIGHT / size.y.max(1.0)).floor()
+    };
+    let textures = [
+        Some(input.texture.id()),
+        state
+            .output_image
+            .as_ref()
+            .filter(|output| !output.is_palette_only())
+            .map(|output| output.texture.id()),
+    ];
+
+    let preview = &mut state.animation_preview;
+    preview.frame = preview.frame.min(frame_count - 1);
+    let now = ctx.input(|input| input.time);
+    if preview.playing {
+        let delay = delays_ms[preview.frame].max(10) as f64 / 1000.0;
+        if now - preview.frame_started >= delay {
+            preview.frame = (preview.frame + 1) % frame_count;
+            preview.frame_started = now;
+        }
+        ctx.request_repaint_after(std::time::Duration::from_millis(
+            delays_ms[preview.frame].max(10) as u64,
+        ));
+    }
+
+    let mut open = true;
+    egui::Window::new("Animation")
+        .open(&mut open)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                for (texture, label) in textures.into_iter().zip(["Original", "Qualetized"]) {
+                    ui.vertical(|ui| {
+                        ui.label(label);
+                        let Some(texture) = texture else {
+                            ui.label("No output yet");
+                            return;
+                        };
+                        let uv = Rect::from_min_max(
+                            Pos2::new(0.0, preview.frame as f32 / frame_count as f32),
+                            Pos2::new(1.0, (preview.frame + 1) as f32 / frame_count as f32),
+                        );
+                        ui.add(
+                            egui::Image::new(egui::load::SizedTexture::new(texture, size * scale))
+                                .uv(uv),
+                        );
+                    });
+                }
+            });
+            ui.horizontal(|ui| {
+                let label = if preview.playing { "⏸" } else { "▶" };
+                if ui.button(label).clicked() {
+                    preview.playing = !preview.playing;
+                    preview.frame_started = now;
+                }
+                let mut frame = preview.frame + 1;
+                if ui
+                    .add(egui::Slider::new(&mut frame, 1..=frame_count).text("Frame"))
+                    .changed()
+                {
+                    preview.frame = frame - 1;
+                    preview.playing = false;
+                }
+                ui.label(format!("{} ms", delays_ms[preview.frame]));
+            });
+            ui.label(
+                egui::RichText::new(format!(
+                    "{frame_count} frames stacked into one image, so they share every palette"
+                ))
+                .small()
+                .weak(),
+            );
+        });
+    if !open {
+        preview.show = false;
+    }
+}
+
+/// Slot-by-slot ΔE comparison of two palette files, or of the current output and a file
+pub fn draw_palette_diff(ctx: &egui::Context, state: &mut AppState) {
+    if !state.palette_diff.show {
+        return;
+    }
+    let mut open = true;
+    let mut load_side = None;
+    let mut use_output = false;
+    let mut clicked_sort = None;
+
+    let output_colors: Vec<[u8; 4]> = displayed_indexed(state)
+        .map(|indexed| {
+            indexed
+                .palettes
+                .iter()
+                .map(|color| color.to_rgba())
+                .collect()
+        })
+        .unwrap_or_default();
+    let diff = &state.palette_diff;
+    let left_name = diff
+        .left
+        .as_ref()
+        .map_or("Current output".to_string(), |palette| palette.name.clone());
+    let right_name = diff.right.as_ref().map(|palette| palette.name.clone());
+    let rows = diff.right.as_ref().map(|right| {
+        let left = diff
+            .left
+            .as_ref()
+            .map_or(&output_colors, |palette| &palette.colors);
+        let mut rows = diff_palettes(left, &right.colors);
+        sort_rows(&mut rows, diff.sort, diff.descending);
+        rows
+    });
+    let (sort, descending, left_loaded) = (diff.sort, diff.descending, diff.left.is_some());
+
+    egui::Window::new("Palette Comparison")
+        .open(&mut open)
+        .resizable(true)
+        .default_width(420.0)
+        .show(ctx, |ui| {
+            egui::Grid::new("palette_diff_sources")
+                .num_columns(3)
+                .show(ui, |ui| {
+                    ui.label("Left:");
+                    ui.label(&left_name);
+                    ui.horizontal(|ui| {
+                        if ui.button("Load...").clicked() {
+                            load_side = Some(PaletteDiffSide::Left);
+                        }
+                        if left_loaded && ui.button("Use Output").clicked() {
+                            use_output = true;
+                        }
+                    });
+                    ui.end_row();
+                    ui.label("Right:");
+                    ui.label(right_name.as_deref().unwrap_or("None"));
+                    if ui.button("Load...").clicked() {
+                        load_side = Some(PaletteDiffSide::Right);
+                    }
+                    ui.end_row();
+                });
+            ui.separator();
+
+            let Some(rows) = &rows else {
+                ui.label("Load a palette to compare against.");
+                return;
+            };
+            match delta_e_summary(rows) {
+                Some((average, max)) => {
+                    ui.strong(format!("Average ΔE {average:.2} · Max ΔE {max:.2}"));
+                }
+                None => {
+                    ui.label("The palettes have no slots in common.");
+                }
+            }
+
+            let arrow = |column| match (sort == column, descending) {
+                (false, _) => "",
+                (true, false) => " ⬆",
+                (true, true) => " ⬇",
+            };
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                egui::Grid::new("palette_diff_rows")
+                    .num_columns(4)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        if ui
+                            .button(format!("Index{}", arrow(PaletteDiffSort::Index)))
+                            .clicked()
+                        {
+                            clicked_sort = Some(PaletteDiffSort::Index);
+                        }
+                        ui.strong("Left");
+                        ui.strong("Right");
+                        if ui
+                            .button(format!("ΔE{}", arrow(PaletteDiffSort::DeltaE)))
+                            .clicked()
+                        {
+                            clicked_sort = Some(PaletteDiffSort::DeltaE);
+                        }
+                        ui.end_row();
+
+                        for row in rows {
+                            ui.monospace(format!("{:>3}", row.index));
+                            palette_diff_swatch(ui, row.left);
+                            palette_diff_swatch(ui, row.right);
+                            match row.delta_e {
+                                Some(delta_e) if delta_e >= PALETTE_DIFF_WARNING_DELTA_E => {
+                                    ui.colored_label(
+                                        ui.visuals().warn_fg_color,
+                                        format!("⚠ {delta_e:.2}"),
+                                    );
+                                }
+                                Some(delta_e) => {
+                                    ui.monospace(format!("{delta_e:.2}"));
+                                }
+                                None => {
+                                    ui.weak("—");
+                                }
+                            }
+                            ui.end_row();
+                        }
+                    });
+            });
+        });
+
+    if let Some(side) = load_side {
+        _ = state
+            .app_state_request_sender
+            .send(AppStateRequest::PaletteDiffDialog { side });
+    }
+    if use_output {
+        state.palette_diff.left = None;
+    }
+    if let Some(column) = clicked_sort {
+        state.palette_diff.toggle_sort(column);
+    }
+    state.palette_diff.show = open;
+}
+
+/// Color chip and hex value of one side of a comparison row
+fn palette_diff_swatch(ui: &mut egui::Ui, color: Option<[u8; 4]>) {
+    let Some([r, g, b, a]) = color else {
+        ui.weak("—");
+        return;
+    };
+    ui.horizontal(|ui| {
+        let (rect, _) = ui.allocate_exact_size(Vec2::splat(14.0), egui::Sense::hover());
+        ui.painter()
+            .rect_filled(rect, 0.0, Color32::from_rgba_unmultiplied(r, g, b, a));
+        ui.monospace(if a == 255 {
+            format!("#{r:02X}{g:02X}{b:02X}")
+        } else {
+            format!("#{a:02X}{r:02X}{g:02X}{b:02X}")
+        });
+    });
+}
+
+/// Advance the blink comparison from the frame time and return whether the output
+/// panel shows the original this frame. Space pauses and resumes it.
+fn update_blink(ctx: &egui::Context, state: &mut AppState) -> bool {
+    if !state.preferences.blink_compare || state.settings.palette_only {
+        return false;
+    }
+    if ctx.memory(|m| m.focused().is_none())
+        && ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Space))
+    {
+        state.blink_paused = !state.blink_paused;
+    }
+    if !state.blink_paused {
+        let interval = state.preferences.blink_interval_ms.max(1) as f64 / 1000.0;
+        let phase = ctx.input(|i| i.time) / interval;
+        state.blink_showing_original = phase as u64 % 2 == 1;
+        // Wake up for the next switch rather than repainting every frame
+        let until_switch = (phase.floor() + 1.0 - phase) * interval;
+        ctx.request_repaint_after(std::time::Duration::from_secs_f64(until_switch));
+    }
+    state.blink_showing_original
+}
+
+/// Arrow keys move the focused palette chip, Enter isolates its color
+/// and Escape clears both. Clicking a chip focuses it.
+fn handle_palette_keyboard(ctx: &egui::Context, state: &mut AppState) {
+    let Some(palettes) = displayed_palettes(state) else {
+        return;
+    };
+    let palette_lens: Vec<usize> = palettes.iter().map(Vec::len).collect();
+    let is_valid = |(palette, index): (usize, usize)| {
+        palette_lens.get(palette).is_some_and(|&len| index < len)
+    };
+    state.palette_focus = state.palette_focus.filter(|&chip| is_valid(chip));
+    state.isolated_color = state.isolated_color.filter(|&chip| is_valid(chip));
+
+    let Some((mut palette_idx, mut color_idx)) = state.palette_focus else {
+        return;
+    };
+    // Leave the keys to text fields and other focused widgets
+    if !state.preferences.show_palettes || ctx.memory(|m| m.focused().is_some()) {
+        return;
+    }
+
+    ctx.input_mut(|i| {
+        if i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowLeft) {
+            color_idx = color_idx.saturating_sub(1);
+        }
+        if i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowRight) {
+            color_idx += 1;
+        }
+        if i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp) {
+            palette_idx = palette_idx.saturating_sub(1);
+        }
+        if i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown) {
+            palette_idx = (palette_idx + 1).min(palette_lens.len() - 1);
+        }
+        color_idx = color_idx.min(palette_lens[palette_idx].saturating_sub(1));
+        let focus = (palette_idx, color_idx);
+        state.palette_focus = Some(focus);
+
+        if i.consume_key(egui::Modifiers::NONE, egui::Key::Enter) {
+            state.isolated_color = if state.isolated_color == Some(focus) {
+                None
+            } else {
+                Some(focus)
+            };
+        }
+        if i.consume_key(egui::Modifiers::NONE, egui::Key::Escape) {
+            state.palette_focus = None;
+            state.isolated_color = None;
+        }
+    });
+}
+
+pub fn draw_main_content(ui: &mut egui::Ui, image_loading: bool) {
     ui.centered_and_justified(|ui| {
-        ui.heading_with_margin("📁 Drop an image file here or use 'File > Open Image...'");
+        if image_loading {
+            ui.horizontal_centered(|ui| {
+                ui.spinner();
+                ui.heading_with_margin("Loading image...");
+            });
+        } else {
+            ui.heading_with_margin("📁 Drop an image file here or use 'File > Open Image...'");
+        }
     });
 }
 #[derive(Clone)]
@@ -160,6 +706,23 @@ struct ImagePanelSettings {
     pub title: String,
     pub has_spinner: bool,
     pub overlay_text: Option<String>,
+    /// Drawn instead of the image's own texture (e.g. a display-filtered preview)
+    pub texture_override: Option<egui::TextureId>,
+    /// Drawn on top of the image at the same size (e.g. the changes-since-export mask)
+    pub overlay_texture: Option<egui::TextureId>,
+    /// Show the palette of the hovered tile
+    pub tile_inspector: bool,
+    /// Pixel rect (x, y, width, height) of a tile to outline, e.g. the worst-matching one
+    pub marked_tile: Option<[u32; 4]>,
+}
+
+/// What the user did on any of the image panels this frame
+#[derive(Default)]
+struct PanelInteraction {
+    pan_changed: Vec2,
+    clicked_chip: Option<(usize, usize)>,
+    /// Edit mask rectangle finished this frame
+    drawn_mask: Option<RegionOfInterest>,
 }
 
 fn draw_background_and_pixels(painter: &egui::Painter, canvas: Rect, base_color: Color32) {
@@ -202,19 +765,354 @@ fn draw_main_image(
     painter: &egui::Painter,
     canvas: Rect,
     image_data: &Option<crate::types::ImageData>,
+    texture_override: Option<egui::TextureId>,
     zoom: f32,
     pan_offset: Vec2,
-) {
-    if let Some(image_data) = image_data {
-        let original_size = egui::vec2(image_data.width as f32, image_data.height as f32);
-        let image_rect = calculate_image_rect(&canvas, original_size, zoom, pan_offset);
-
-        painter.image(
-            image_data.texture.id(),
-            image_rect,
-            Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0)),
-            Color32::WHITE,
-        );
+    pixel_aspect: f32,
+) -> Option<Rect> {
+    let image_data = image_data.as_ref()?;
+    let original_size = egui::vec2(image_data.width as f32, image_data.height as f32);
+    let image_rect = calculate_image_rect(&canvas, original_size, zoom, pan_offset, pixel_aspect);
+
+    painter.image(
+        texture_override.unwrap_or(image_data.texture.id()),
+        image_rect,
+        Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0)),
+        Color32::WHITE,
+    );
+    Some(image_rect)
+}
+
+/// Pixel rect of the worst-matching tile, when its outline is enabled
+fn worst_tile_rect(state: &mut AppState) -> Option<[u32; 4]> {
+    if !state.show_worst_tile {
+        return None;
+    }
+    let (tile_width, tile_height) = (
+        state.settings.tile_width as u32,
+        state.settings.tile_height as u32,
+    );
+    let worst = state.palette_error_stats()?.worst_tile?;
+    Some([
+        worst.tile_x * tile_width,
+        worst.tile_y * tile_height,
+        tile_width,
+        tile_height,
+    ])
+}
+
+/// Pixels not using the isolated color keep at most this alpha
+/// Rebuild the output texture with the cycled palette range rotated for the current step
+fn update_palette_cycle_texture(
+    ctx: &egui::Context,
+    state: &mut AppState,
+) -> Option<egui::TextureId> {
+    if !state.palette_cycle.enabled {
+        state.palette_cycle_cache = None;
+        return None;
+    }
+    let output = state
+        .output_image
+        .as_ref()
+        .filter(|output| !output.is_palette_only())?;
+    let indexed = displayed_indexed(state)?;
+    let cycle = &state.palette_cycle;
+    cycle.range(indexed.palettes.len())?;
+
+    let step = cycle.step_at(ctx.input(|i| i.time)) % cycle.frame_count(indexed.palettes.len());
+    ctx.request_repaint_after(std::time::Duration::from_millis(
+        cycle.frame_delay_ms() as u64
+    ));
+
+    let source_id = output.texture.id();
+    if let Some((cached_id, cached_cycle, cached_step, texture)) = &state.palette_cycle_cache
+        && *cached_id == source_id
+        && cached_cycle == cycle
+        && *cached_step == step
+    {
+        return Some(texture.id());
+    }
+
+    let palette = cycle.cycled_palette(&indexed.palettes, step);
+    let rgba: Vec<u8> = indexed
+        .indexed_pixels
+        .iter()
+        .flat_map(|&index| {
+            palette
+                .get(index as usize)
+                .map_or([0, 0, 0, 255], |color| color.to_rgba())
+        })
+        .collect();
+    let color_image = egui::ColorImage::from_rgba_unmultiplied(
+        [output.width as usize, output.height as usize],
+        &rgba,
+    );
+    let texture = ctx.load_texture(
+        "output_palette_cycle",
+        color_image,
+        preview_texture_options(ctx),
+    );
+    let texture_id = texture.id();
+    state.palette_cycle_cache = Some((source_id, cycle.clone(), step, texture));
+    Some(texture_id)
+}
+
+const ISOLATED_DIM_ALPHA: u8 = 40;
+
+/// Rebuild the output texture with every pixel outside the isolated color dimmed
+fn update_isolated_color_texture(
+    ctx: &egui::Context,
+    state: &mut AppState,
+) -> Option<egui::TextureId> {
+    let Some(chip) = state.isolated_color else {
+        state.isolated_color_cache = None;
+        return None;
+    };
+    let output = state
+        .output_image
+        .as_ref()
+        .filter(|output| !output.is_palette_only())?;
+    let indexed = displayed_indexed(state)?;
+
+    let source_id = output.texture.id();
+    if let Some((cached_id, cached_chip, texture)) = &state.isolated_color_cache
+        && *cached_id == source_id
+        && *cached_chip == chip
+    {
+        return Some(texture.id());
+    }
+
+    let (palette_idx, color_idx) = chip;
+    let palette_start: usize = indexed
+        .palettes_for_ui
+        .get(..palette_idx)?
+        .iter()
+        .map(Vec::len)
+        .sum();
+    let isolated_index = palette_start + color_idx;
+
+    let mut rgba = output.rgba_data.clone();
+    for (pixel, &index) in rgba.chunks_exact_mut(4).zip(&indexed.indexed_pixels) {
+        if index as usize != isolated_index {
+            pixel[3] = pixel[3].min(ISOLATED_DIM_ALPHA);
+        }
+    }
+    let color_image = egui::ColorImage::from_rgba_unmultiplied(
+        [output.width as usize, output.height as usize],
+        &rgba,
+    );
+    let texture = ctx.load_texture(
+        "output_isolated_color",
+        color_image,
+        preview_texture_options(ctx),
+    );
+    let texture_id = texture.id();
+    state.isolated_color_cache = Some((source_id, chip, texture));
+    Some(texture_id)
+}
+
+/// Colors of the diagonal stripes laid over pixels that changed since the last export;
+/// the stripes keep changes visible where the magenta alone blends into the image
+const EXPORT_DIFF_COLORS: [[u8; 4]; 2] = [[255, 0, 160, 190], [0, 0, 0, 190]];
+
+/// Rebuild the mask of output pixels whose index differs from the last export
+fn update_export_diff_texture(
+    ctx: &egui::Context,
+    state: &mut AppState,
+) -> Option<egui::TextureId> {
+    if !state.preferences.show_export_diff {
+        state.export_diff_cache = None;
+        return None;
+    }
+    let snapshot = state.export_snapshot.as_ref()?;
+    let output = state.output_image.as_ref()?;
+    let indexed = displayed_indexed(state)?;
+    if (snapshot.width, snapshot.height) != (output.width, output.height)
+        || snapshot.indexed_pixels.len() != indexed.indexed_pixels.len()
+    {
+        return None;
+    }
+
+    let source_id = output.texture.id();
+    if let Some((cached_id, texture)) = &state.export_diff_cache
+        && *cached_id == source_id
+    {
+        return Some(texture.id());
+    }
+
+    let width = output.width as usize;
+    let mask: Vec<u8> = indexed
+        .indexed_pixels
+        .iter()
+        .zip(&snapshot.indexed_pixels)
+        .enumerate()
+        .flat_map(|(i, (current, exported))| {
+            if current != exported {
+                EXPORT_DIFF_COLORS[(i % width + i / width) / 2 % 2]
+            } else {
+                [0; 4]
+            }
+        })
+        .collect();
+    let color_image = egui::ColorImage::from_rgba_unmultiplied(
+        [output.width as usize, output.height as usize],
+        &mask,
+    );
+    let texture = ctx.load_texture(
+        "output_export_diff",
+        color_image,
+        egui::TextureOptions::NEAREST,
+    );
+    let texture_id = texture.id();
+    state.export_diff_cache = Some((source_id, texture));
+    Some(texture_id)
+}
+
+/// Rebuild the output shown with the input outside the edit mask, as it is exported
+fn update_masked_output_texture(
+    ctx: &egui::Context,
+    state: &mut AppState,
+) -> Option<egui::TextureId> {
+    let (Some(mask), Some(output)) = (state.edit_mask, state.output_image.as_ref()) else {
+        state.masked_output_cache = None;
+        return None;
+    };
+    let source_id = output.texture.id();
+    if let Some((cached_id, cached_mask, texture)) = &state.masked_output_cache
+        && *cached_id == source_id
+        && *cached_mask == mask
+    {
+        return Some(texture.id());
+    }
+
+    let rgba = state.masked_output_rgba(output)?;
+    let color_image = egui::ColorImage::from_rgba_unmultiplied(
+        [output.width as usize, output.height as usize],
+        &rgba,
+    );
+    let texture = ctx.load_texture("output_masked", color_image, preview_texture_options(ctx));
+    let texture_id = texture.id();
+    state.masked_output_cache = Some((source_id, mask, texture));
+    Some(texture_id)
+}
+
+/// Each output pixel becomes a block of this size so filters can draw sub-pixel detail
+const DISPLAY_FILTER_SCALE: usize = 3;
+/// Larger outputs are previewed unfiltered to keep the upscaled buffer reasonable
+const DISPLAY_FILTER_MAX_PIXELS: usize = 1024 * 1024;
+
+/// Rebuild the filtered output texture when the output or filter changes.
+/// Only the preview uses it; exports always read the unfiltered output.
+fn update_display_filter_texture(
+    ctx: &egui::Context,
+    state: &mut AppState,
+) -> Option<egui::TextureId> {
+    let filter = state.preferences.display_filter;
+    let Some(output) = state
+        .output_image
+        .as_ref()
+        .filter(|output| filter != DisplayFilter::None && !output.is_palette_only())
+    else {
+        state.display_filter_cache = None;
+        return None;
+    };
+    let (width, height) = (output.width as usize, output.height as usize);
+    if width * height > DISPLAY_FILTER_MAX_PIXELS {
+        return None;
+    }
+
+    let source_id = output.texture.id();
+    if let Some((cached_id, cached_filter, texture)) = &state.display_filter_cache
+        && *cached_id == source_id
+        && *cached_filter == filter
+    {
+        return Some(texture.id());
+    }
+
+    let rgba = apply_display_filter(filter, &output.rgba_data, width, height);
+    let color_image = egui::ColorImage::from_rgba_unmultiplied(
+        [width * DISPLAY_FILTER_SCALE, height * DISPLAY_FILTER_SCALE],
+        &rgba,
+    );
+    let texture = ctx.load_texture(
+        "output_display_filter",
+        color_image,
+        preview_texture_options(ctx),
+    );
+    let texture_id = texture.id();
+    state.display_filter_cache = Some((source_id, filter, texture));
+    Some(texture_id)
+}
+
+fn apply_display_filter(
+    filter: DisplayFilter,
+    rgba: &[u8],
+    width: usize,
+    height: usize,
+) -> Vec<u8> {
+    const SCALE: usize = DISPLAY_FILTER_SCALE;
+    let out_width = width * SCALE;
+    let out_height = height * SCALE;
+    let mut out = vec![0u8; out_width * out_height * 4];
+
+    for y in 0..out_height {
+        for x in 0..out_width {
+            let src = ((y / SCALE) * width + x / SCALE) * 4;
+            let dst = (y * out_width + x) * 4;
+            let last_row = y % SCALE == SCALE - 1;
+            let last_column = x % SCALE == SCALE - 1;
+            let brightness = match filter {
+                DisplayFilter::CrtScanlines if last_row => 0.45,
+                DisplayFilter::LcdGrid if last_row || last_column => 0.7,
+                _ => 1.0,
+            };
+            if filter == DisplayFilter::AlphaChannel {
+                out[dst..dst + 3].fill(rgba[src + 3]);
+                out[dst + 3] = 255;
+                continue;
+            }
+            for c in 0..3 {
+                out[dst + c] = (rgba[src + c] as f32 * brightness) as u8;
+            }
+            out[dst + 3] = rgba[src + 3];
+        }
+    }
+
+    if filter == DisplayFilter::NtscBlur {
+        ntsc_blur(&mut out, out_width, out_height);
+    }
+    out
+}
+
+/// Horizontal blur in a luma/chroma space, with chroma smeared wider than luma
+fn ntsc_blur(rgba: &mut [u8], width: usize, height: usize) {
+    const LUMA_RADIUS: usize = 1;
+    const CHROMA_RADIUS: usize = 4;
+
+    fn box_average(row: &[[f32; 3]], center: usize, radius: usize, channel: usize) -> f32 {
+        let start = center.saturating_sub(radius);
+        let end = (center + radius + 1).min(row.len());
+        row[start..end].iter().map(|v| v[channel]).sum::<f32>() / (end - start) as f32
+    }
+
+    let mut row = vec![[0.0f32; 3]; width];
+    for y in 0..height {
+        for (x, value) in row.iter_mut().enumerate() {
+            let i = (y * width + x) * 4;
+            let (r, g, b) = (rgba[i] as f32, rgba[i + 1] as f32, rgba[i + 2] as f32);
+            let luma = 0.299 * r + 0.587 * g + 0.114 * b;
+            *value = [luma, r - luma, b - luma];
+        }
+        for x in 0..width {
+            let luma = box_average(&row, x, LUMA_RADIUS, 0);
+            let r = luma + box_average(&row, x, CHROMA_RADIUS, 1);
+            let b = luma + box_average(&row, x, CHROMA_RADIUS, 2);
+            let g = (luma - 0.299 * r - 0.114 * b) / 0.587;
+            let i = (y * width + x) * 4;
+            rgba[i] = r.round().clamp(0.0, 255.0) as u8;
+            rgba[i + 1] = g.round().clamp(0.0, 255.0) as u8;
+            rgba[i + 2] = b.round().clamp(0.0, 255.0) as u8;
+        }
     }
 }
 
@@ -281,7 +1179,7 @@ fn draw_image_panel(
     settings: ImagePanelSettings,
     image_data: &Option<crate::types::ImageData>,
     palettes_for_ui: Option<&Vec<Vec<egui::Color32>>>,
-    pan_changed: &mut Vec2,
+    interaction: &mut PanelInteraction,
 ) {
     ui.allocate_ui_with_layout(
         Vec2::new(settings.width, settings.height),
@@ -301,19 +1199,139 @@ fn draw_image_panel(
 
             // 各要素を描画
             draw_background_and_pixels(&painter, canvas, base_color);
-            draw_main_image(
+            let image_rect = draw_main_image(
                 &painter,
                 canvas,
                 image_data,
+                settings.texture_override,
                 settings.zoom,
                 settings.pan_offset,
+                state.preferences.pixel_aspect_ratio(),
             );
+            if let Some(overlay) = settings.overlay_texture
+                && let Some(image_rect) = image_rect
+            {
+                painter.image(
+                    overlay,
+                    image_rect,
+                    Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0)),
+                    Color32::WHITE,
+                );
+            }
+            if let Some([x, y, w, h]) = settings.marked_tile
+                && let Some(image_rect) = image_rect
+                && let Some(image_data) = image_data
+            {
+                let pixel_size = image_rect.size()
+                    / egui::vec2(image_data.width as f32, image_data.height as f32);
+                painter.rect_stroke(
+                    Rect::from_min_size(
+                        image_rect.min + egui::vec2(x as f32, y as f32) * pixel_size,
+                        egui::vec2(w as f32, h as f32) * pixel_size,
+                    ),
+                    0.0,
+                    egui::Stroke::new(2.0, Color32::from_rgb(255, 60, 60)),
+                    egui::StrokeKind::Outside,
+                );
+            }
+            // Ctrl-drag (Cmd on macOS) draws the edit mask instead of panning. The mask
+            // is only applied on release, since every change re-runs color correction.
+            let drawing_mask = ui.input(|i| i.modifiers.command);
+            let dragged_mask_id = response.id.with("dragged_mask");
+            let mut dragged_mask = None;
+            if let Some(image_rect) = image_rect
+                && let Some(image_data) = image_data
+            {
+                let image_size = egui::vec2(image_data.width as f32, image_data.height as f32);
+                let pixel_size = image_rect.size() / image_size;
+                if drawing_mask
+                    && response.dragged()
+                    && let Some(origin) = ui.input(|i| i.pointer.press_origin())
+                    && let Some(pointer) = response.interact_pointer_pos()
+                {
+                    let to_pixel = |pos: Pos2| {
+                        ((pos - image_rect.min) / pixel_size).clamp(Vec2::ZERO, image_size)
+                    };
+                    let (a, b) = (to_pixel(origin), to_pixel(pointer));
+                    let (min, max) = (a.min(b).floor(), a.max(b).ceil());
+                    let mask = RegionOfInterest {
+                        x: min.x as u32,
+                        y: min.y as u32,
+                        width: ((max.x - min.x) as u32).max(1),
+                        height: ((max.y - min.y) as u32).max(1),
+                    };
+                    ui.data_mut(|data| data.insert_temp(dragged_mask_id, mask));
+                    dragged_mask = Some(mask);
+                }
+                if response.drag_stopped() {
+                    interaction.drawn_mask = ui.data_mut(|data| {
+                        let mask = data.get_temp::<RegionOfInterest>(dragged_mask_id);
+                        data.remove::<RegionOfInterest>(dragged_mask_id);
+                        mask
+                    });
+                }
+                if let Some(mask) = dragged_mask.as_ref().or(state.edit_mask.as_ref()) {
+                    dashed_rect_stroke(
+                        &painter,
+                        Rect::from_min_size(
+                            image_rect.min + egui::vec2(mask.x as f32, mask.y as f32) * pixel_size,
+                            egui::vec2(mask.width as f32, mask.height as f32) * pixel_size,
+                        ),
+                        ui.visuals().selection.stroke.color,
+                    );
+                }
+            }
             draw_title(&painter, canvas, &settings.title, ui.ctx());
 
-            if state.preferences.show_palettes
+            let mut hovered_chip = None;
+            if (state.preferences.show_palettes || state.settings.palette_only)
                 && let Some(palettes_for_ui) = palettes_for_ui
             {
-                draw_palettes_overlay(&painter, canvas, palettes_for_ui);
+                hovered_chip = draw_palettes_overlay(
+                    &painter,
+                    canvas,
+                    palettes_for_ui,
+                    state.settings.channel_bits(),
+                    state.palette_focus,
+                    state.isolated_color,
+                );
+                if response.clicked() && hovered_chip.is_some() {
+                    interaction.clicked_chip = hovered_chip;
+                }
+            }
+
+            // Palette chip tooltips take precedence over the tile under them
+            if settings.tile_inspector
+                && hovered_chip.is_none()
+                && let Some(image_rect) = image_rect
+                && let Some(image_data) = image_data
+                && let Some(indexed) = displayed_indexed(state)
+            {
+                draw_tile_inspector(
+                    &painter,
+                    canvas,
+                    image_rect,
+                    indexed,
+                    (image_data.width, image_data.height),
+                    (
+                        state.settings.tile_width as u32,
+                        state.settings.tile_height as u32,
+                    ),
+                );
+            }
+
+            if state.preferences.show_loupe
+                && hovered_chip.is_none()
+                && let Some(image_rect) = image_rect
+                && let Some(image_data) = image_data
+            {
+                draw_loupe(
+                    &painter,
+                    canvas,
+                    image_rect,
+                    settings.texture_override.unwrap_or(image_data.texture.id()),
+                    state.preferences.loupe_magnification,
+                );
             }
 
             if settings.has_spinner {
@@ -324,13 +1342,187 @@ fn draw_image_panel(
             }
 
             // パン操作の処理
-            if response.dragged() {
-                *pan_changed += response.drag_delta();
+            if response.dragged() && !drawing_mask {
+                interaction.pan_changed += response.drag_delta();
             }
         },
     );
 }
 
+/// Circular inset next to the cursor showing the image under it magnified
+/// `magnification` times on top of the current zoom
+fn draw_loupe(
+    painter: &egui::Painter,
+    canvas: Rect,
+    image_rect: Rect,
+    texture_id: egui::TextureId,
+    magnification: f32,
+) {
+    const LOUPE_DIAMETER: f32 = 128.0;
+    const CURSOR_GAP: f32 = 24.0;
+
+    let Some(pointer_pos) = painter.ctx().pointer_hover_pos() else {
+        return;
+    };
+    if !canvas.contains(pointer_pos) || !image_rect.contains(pointer_pos) {
+        return;
+    }
+
+    // The screen area that ends up filling the loupe, in texture coordinates
+    let magnification = magnification.clamp(
+        *LOUPE_MAGNIFICATION_RANGE.start(),
+        *LOUPE_MAGNIFICATION_RANGE.end(),
+    );
+    let half_extent = Vec2::splat(LOUPE_DIAMETER / magnification / 2.0) / image_rect.size();
+    let center_uv = ((pointer_pos - image_rect.min) / image_rect.size()).to_pos2();
+    let uv = Rect::from_min_max(center_uv - half_extent, center_uv + half_extent);
+
+    // Below-right of the cursor, flipped to the other side near the canvas edges
+    let size = Vec2::splat(LOUPE_DIAMETER);
+    let mut min = pointer_pos + Vec2::splat(CURSOR_GAP);
+    if min.x + size.x > canvas.max.x {
+        min.x = pointer_pos.x - CURSOR_GAP - size.x;
+    }
+    if min.y + size.y > canvas.max.y {
+        min.y = pointer_pos.y - CURSOR_GAP - size.y;
+    }
+    let rect = Rect::from_min_size(min, size);
+
+    let radius = LOUPE_DIAMETER / 2.0;
+    painter.circle_filled(rect.center(), radius, Color32::from_gray(32));
+    painter.add(
+        egui::epaint::RectShape::filled(rect, radius, Color32::WHITE).with_texture(texture_id, uv),
+    );
+    painter.circle_stroke(
+        rect.center(),
+        radius,
+        egui::Stroke::new(2.0, Color32::WHITE),
+    );
+    // Mark the spot under the cursor
+    let center = rect.center();
+    let stroke = egui::Stroke::new(1.0, Color32::from_white_alpha(160));
+    for direction in [egui::vec2(1.0, 0.0), egui::vec2(0.0, 1.0)] {
+        painter.line_segment([center - direction * 6.0, center + direction * 6.0], stroke);
+    }
+}
+
+/// Outline the hovered output tile and show which palette its pixels come from.
+/// More than one palette in a tile breaks the hardware palette-per-tile rule.
+fn draw_tile_inspector(
+    painter: &egui::Painter,
+    canvas: Rect,
+    image_rect: Rect,
+    indexed: &ImageDataIndexed,
+    (width, height): (u32, u32),
+    (tile_width, tile_height): (u32, u32),
+) {
+    let ctx = painter.ctx();
+    let Some(pointer_pos) = ctx.pointer_hover_pos() else {
+        return;
+    };
+    if tile_width == 0
+        || tile_height == 0
+        || !canvas.contains(pointer_pos)
+        || !image_rect.contains(pointer_pos)
+    {
+        return;
+    }
+
+    // Same pixel mapping as the image rect, so it follows zoom, pan and pixel aspect
+    let relative = (pointer_pos - image_rect.min) / image_rect.size();
+    let x = ((relative.x * width as f32) as u32).min(width - 1);
+    let y = ((relative.y * height as f32) as u32).min(height - 1);
+    let (tile_x, tile_y) = (x / tile_width, y / tile_height);
+
+    let palette_starts = indexed.palette_starts();
+
+    let mut used_palettes: Vec<usize> = Vec::new();
+    for py in tile_y * tile_height..((tile_y + 1) * tile_height).min(height) {
+        for px in tile_x * tile_width..((tile_x + 1) * tile_width).min(width) {
+            if let Some(&index) = indexed.indexed_pixels.get((py * width + px) as usize) {
+                let palette = ImageDataIndexed::palette_of_index(&palette_starts, index);
+                if !used_palettes.contains(&palette) {
+                    used_palettes.push(palette);
+                }
+            }
+        }
+    }
+    used_palettes.sort_unstable();
+
+    let pixel_size = image_rect.size() / egui::vec2(width as f32, height as f32);
+    let tile_rect = Rect::from_min_size(
+        image_rect.min
+            + egui::vec2(
+                (tile_x * tile_width) as f32 * pixel_size.x,
+                (tile_y * tile_height) as f32 * pixel_size.y,
+            ),
+        egui::vec2(
+            tile_width as f32 * pixel_size.x,
+            tile_height as f32 * pixel_size.y,
+        ),
+    );
+    let violation = used_palettes.len() > 1;
+    if violation {
+        dashed_rect_stroke(
+            painter,
+            tile_rect.expand(1.0),
+            painter.ctx().style().visuals.warn_fg_color,
+        );
+    } else {
+        painter.rect_stroke(
+            tile_rect,
+            0.0,
+            egui::Stroke::new(2.0, painter.ctx().style().visuals.selection.stroke.color),
+            egui::StrokeKind::Outside,
+        );
+    }
+
+    egui::Tooltip::always_open(
+        ctx.clone(),
+        painter.layer_id(),
+        Id::new("tile_inspector_tooltip"),
+        pointer_pos,
+    )
+    .show(|ui| {
+        ui.label(format!("Tile ({tile_x}, {tile_y}) · Pixel ({x}, {y})"));
+        if violation {
+            ui.label(
+                egui::RichText::new(format!(
+                    "⚠ Uses {} palettes, hardware allows one per tile",
+                    used_palettes.len()
+                ))
+                .color(ui.visuals().warn_fg_color),
+            );
+        }
+        for &palette_idx in &used_palettes {
+            let Some(palette) = indexed.palettes_for_ui.get(palette_idx) else {
+                continue;
+            };
+            ui.horizontal(|ui| {
+                ui.label(format!("Palette {palette_idx}"));
+                let chip = 10.0;
+                let (rect, _) = ui.allocate_exact_size(
+                    Vec2::new(chip * palette.len() as f32, chip),
+                    egui::Sense::hover(),
+                );
+                for (color_idx, &color) in palette.iter().enumerate() {
+                    let color_rect = Rect::from_min_size(
+                        rect.min + Vec2::new(color_idx as f32 * chip, 0.0),
+                        Vec2::splat(chip),
+                    );
+                    ui.painter().rect_filled(color_rect, 0.0, color);
+                }
+                ui.painter().rect_stroke(
+                    rect,
+                    0.0,
+                    egui::Stroke::new(1.0, Color32::from_gray(48)),
+                    egui::StrokeKind::Outside,
+                );
+            });
+        }
+    });
+}
+
 fn draw_status_panel(ui: &mut egui::Ui, state: &AppState, width: f32, height: f32) {
     ui.allocate_ui_with_layout(
         Vec2::new(width, height),
@@ -344,7 +1536,7 @@ fn draw_status_panel(ui: &mut egui::Ui, state: &AppState, width: f32, height: f3
             ui.scope_builder(
                 egui::UiBuilder::new().max_rect(Rect::from_center_size(
                     painter.clip_rect().center(),
-                    Vec2::new(300.0, 150.0),
+                    Vec2::new(300.0, 180.0),
                 )),
                 |ui| {
                     ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
@@ -377,6 +1569,22 @@ fn draw_warning_message(ui: &mut egui::Ui, state: &AppState) {
             .size(11.0)
             .color(Color32::LIGHT_GRAY),
     );
+    if let Some(input_image) = &state.input_image
+        && let Some((width, height)) = state
+            .settings
+            .seam_fit_size(input_image.width, input_image.height)
+    {
+        ui.add_space(6.0);
+        if ui
+            .button(format!("Fit to {width}×{height} with Seams..."))
+            .on_hover_text("Add or remove a few low-detail rows/columns instead of cropping or padding.\nShows a before/after preview first.")
+            .clicked()
+        {
+            _ = state
+                .app_state_request_sender
+                .send(AppStateRequest::PreviewSeamFit);
+        }
+    }
 }
 
 fn calculate_image_rect(
@@ -384,15 +1592,35 @@ fn calculate_image_rect(
     original_size: Vec2,
     zoom: f32,
     pan_offset: Vec2,
+    pixel_aspect: f32,
 ) -> Rect {
-    let display_size = original_size * zoom;
+    // Non-square pixels stretch the preview horizontally; the pixel data is untouched
+    let display_size = original_size * zoom * egui::vec2(pixel_aspect, 1.0);
     let view_center = available_rect.center() + pan_offset;
     Rect::from_center_size(view_center, display_size)
 }
 
-fn draw_palettes_overlay(painter: &egui::Painter, rect: Rect, palettes: &[Vec<egui::Color32>]) {
+/// Inverse of `calculate_image_rect`: the pan offset that puts `point` (in image
+/// pixels) at the center of the view
+pub(super) fn pan_offset_centering(
+    original_size: Vec2,
+    point: Vec2,
+    zoom: f32,
+    pixel_aspect: f32,
+) -> Vec2 {
+    (original_size / 2.0 - point) * zoom * egui::vec2(pixel_aspect, 1.0)
+}
+
+fn draw_palettes_overlay(
+    painter: &egui::Painter,
+    rect: Rect,
+    palettes: &[Vec<egui::Color32>],
+    channel_bits: Option<[u32; 4]>,
+    focused: Option<(usize, usize)>,
+    isolated: Option<(usize, usize)>,
+) -> Option<(usize, usize)> {
     if palettes.is_empty() {
-        return;
+        return None;
     }
 
     let ctx = painter.ctx();
@@ -438,7 +1666,11 @@ fn draw_palettes_overlay(painter: &egui::Painter, rect: Rect, palettes: &[Vec<eg
             Pos2::new(start_x, current_y),
             palette_size,
             palette_spacing,
-            hovered,
+            ChipHighlights {
+                hovered,
+                focused,
+                isolated,
+            },
         );
 
         current_y += palette_size + palette_spacing;
@@ -482,9 +1714,54 @@ fn draw_palettes_overlay(painter: &egui::Painter, rect: Rect, palettes: &[Vec<eg
                 ui.label(format!("Palette {} / Index {}", palette_idx, color_idx));
                 ui.label(hex);
                 ui.label(rgba);
+
+                // Show how the color is stored at the target depth
+                if let Some(bits) = channel_bits.filter(|bits| bits.iter().any(|&b| b < 8)) {
+                    let packed = pack_color(color.to_srgba_unmultiplied(), bits);
+                    let [r, g, b, a] = packed.rounded;
+                    ui.separator();
+                    ui.label(format!(
+                        "ABGR{}{}{}{}: 0x{:0width$X}",
+                        bits[3],
+                        bits[2],
+                        bits[1],
+                        bits[0],
+                        packed.value,
+                        width = packed.total_bits.div_ceil(4) as usize
+                    ));
+                    ui.label(if bits[3] < 8 {
+                        format!("Stored as RGBA({r},{g},{b},{a})")
+                    } else {
+                        format!("Stored as RGB({r},{g},{b})")
+                    });
+                }
             });
         }
     }
+
+    hovered
+}
+
+/// Dashed outline over a black one, so the marker reads on any color and is told
+/// apart from the solid selection outline by its pattern alone
+fn dashed_rect_stroke(painter: &egui::Painter, rect: Rect, color: Color32) {
+    let corners = [
+        rect.left_top(),
+        rect.right_top(),
+        rect.right_bottom(),
+        rect.left_bottom(),
+        rect.left_top(),
+    ];
+    painter.add(egui::Shape::line(
+        corners.to_vec(),
+        egui::Stroke::new(2.0, Color32::BLACK),
+    ));
+    painter.extend(egui::Shape::dashed_line(
+        &corners,
+        egui::Stroke::new(2.0, color),
+        3.0,
+        3.0,
+    ));
 }
 
 fn calculate_palette_size(
@@ -493,18 +1770,27 @@ fn calculate_palette_size(
     palette_margin: f32,
     palette_spacing: f32,
 ) -> f32 {
-    if let Some(first_palette) = palettes.first() {
+    // Palettes can differ in size; fit the widest one
+    if let Some(widest) = palettes.iter().map(|palette| palette.len()).max()
+        && widest > 0
+    {
         4.0_f32.max(16.0_f32.min(
-            (rect.width()
-                - palette_margin * 2.0
-                - ((first_palette.len() as f32) - 1.0) * palette_spacing)
-                / (first_palette.len() as f32),
+            (rect.width() - palette_margin * 2.0 - ((widest as f32) - 1.0) * palette_spacing)
+                / (widest as f32),
         ))
     } else {
         8.0
     }
 }
 
+/// Palette chips, as (palette, color), drawn with a highlight
+#[derive(Clone, Copy)]
+struct ChipHighlights {
+    hovered: Option<(usize, usize)>,
+    focused: Option<(usize, usize)>,
+    isolated: Option<(usize, usize)>,
+}
+
 fn draw_single_palette(
     painter: &egui::Painter,
     palette_idx: usize,
@@ -512,8 +1798,13 @@ fn draw_single_palette(
     origin: Pos2,
     palette_size: f32,
     palette_spacing: f32,
-    hovered: Option<(usize, usize)>,
+    highlights: ChipHighlights,
 ) {
+    let ChipHighlights {
+        hovered,
+        focused,
+        isolated,
+    } = highlights;
     let palette_width = (palette.len() as f32) * (palette_size + palette_spacing) - palette_spacing;
     let highlight_color = painter.ctx().style().visuals.selection.stroke.color;
 
@@ -541,5 +1832,22 @@ fn draw_single_palette(
             ),
             egui::StrokeKind::Middle,
         );
+
+        let chip = Some((palette_idx, color_idx));
+        if isolated == chip {
+            dashed_rect_stroke(
+                painter,
+                color_rect.shrink(1.0),
+                painter.ctx().style().visuals.warn_fg_color,
+            );
+        }
+        if focused == chip {
+            painter.rect_stroke(
+                color_rect.expand(1.0),
+                0.0,
+                egui::Stroke::new(2.0, highlight_color),
+                egui::StrokeKind::Outside,
+            );
+        }
     }
 }
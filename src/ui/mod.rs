@@ -1,8 +1,10 @@
+mod debug_hud;
 mod footer;
 mod header;
 mod image_viewer;
 mod settings_panel;
 pub mod styles;
+mod toasts;
 
 use crate::types::AppState;
 
@@ -17,8 +19,8 @@ impl UI {
         image_viewer::draw_image_view(ui, state, image_processing)
     }
 
-    pub fn draw_main_content(ui: &mut egui::Ui) {
-        image_viewer::draw_main_content(ui)
+    pub fn draw_main_content(ui: &mut egui::Ui, image_loading: bool) {
+        image_viewer::draw_main_content(ui, image_loading)
     }
 
     pub fn draw_header(ui: &mut egui::Ui, state: &mut AppState) -> bool {
@@ -28,4 +30,32 @@ impl UI {
     pub fn draw_footer(ui: &mut egui::Ui, state: &mut AppState) -> bool {
         footer::draw_footer(ui, state)
     }
+
+    pub fn draw_queue_filmstrip(ui: &mut egui::Ui, state: &mut AppState) {
+        footer::draw_queue_filmstrip(ui, state)
+    }
+
+    pub fn draw_batch_export(ctx: &egui::Context, state: &mut AppState) {
+        footer::draw_batch_export(ctx, state)
+    }
+
+    pub fn draw_palette_inspector(ctx: &egui::Context, state: &mut AppState) {
+        image_viewer::draw_palette_inspector(ctx, state)
+    }
+
+    pub fn draw_palette_diff(ctx: &egui::Context, state: &mut AppState) {
+        image_viewer::draw_palette_diff(ctx, state)
+    }
+
+    pub fn draw_animation_preview(ctx: &egui::Context, state: &mut AppState) {
+        image_viewer::draw_animation_preview(ctx, state)
+    }
+
+    pub fn draw_toasts(ctx: &egui::Context, state: &AppState) {
+        toasts::draw_toasts(ctx, state)
+    }
+
+    pub fn draw_debug_hud(ctx: &egui::Context, state: &AppState) {
+        debug_hud::draw_debug_hud(ctx, state)
+    }
 }
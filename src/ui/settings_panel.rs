@@ -1,20 +1,41 @@
-use super::styles::UiMarginExt;
+use super::image_viewer::pan_offset_centering;
+use super::styles::{UiMarginExt, hex_color_field};
 use crate::color_processor::{
     display_value_to_gamma, format_gamma, format_percentage, gamma_to_display_value,
 };
-use crate::types::qualetize::validate_0_255_array;
+use crate::image_processor::{ImageProcessor, RegionOfInterest};
+use crate::types::qualetize::{
+    CHANNEL_WEIGHT_RANGE, INPUT_MERGE_TOLERANCE_RANGE, LEVEL_COUNT_RANGE, LEVEL_GAMMA_RANGE,
+    LevelRampPreset, MAX_HUES_RANGE, MAX_SPLIT_TOTAL_COLORS, gamma_level_string,
+    parse_custom_levels, validate_0_255_array,
+};
 use crate::types::{
-    AppState, ClearColor, ColorSpace, DitherMode,
-    color_correction::ColorCorrection,
+    AppState, ClearColor, ColorSpace, DepthRounding, DitherMode, SnapPalette,
+    app_state::AppStateRequest,
+    color_correction::{
+        BRIGHTNESS_RANGE, CONTRAST_RANGE, ColorCorrection, DENOISE_RADIUS_RANGE,
+        DENOISE_STRENGTH_RANGE,
+    },
     image::{SortMode, SortOrder},
 };
 use egui::Color32;
 use regex::Regex;
 
+// Arrow-key steps for focused sliders and drag values (DragValue nudges by its speed)
+const INTEGER_NUDGE: f64 = 1.0;
+const FINE_NUDGE: f64 = 0.01;
+const COARSE_NUDGE: f64 = 1.0;
+const DITHER_LEVEL_RANGE: std::ops::RangeInclusive<f32> = 0.0..=2.0;
+const ORDERED_DITHER_AMPLITUDE_RANGE: std::ops::RangeInclusive<f32> = 0.0..=2.0;
+const HYBRID_PATTERN_LEVEL_RANGE: std::ops::RangeInclusive<f32> = 0.0..=1.0;
+
 pub fn draw_settings_panel(ui: &mut egui::Ui, state: &mut AppState) -> (bool, bool) {
     let mut settings_changed = false;
     let mut tile_reduce_changed = false;
 
+    settings_changed |= draw_preset_suggestion(ui, state);
+    settings_changed |= draw_palette_extraction_banner(ui, state);
+
     // Basic settings
     settings_changed |= draw_basic_settings(ui, state);
 
@@ -32,6 +53,14 @@ pub fn draw_settings_panel(ui: &mut egui::Ui, state: &mut AppState) -> (bool, bo
 
     ui.separator();
 
+    settings_changed |= draw_snap_palette_settings(ui, state);
+
+    ui.separator();
+
+    settings_changed |= draw_merge_colors_settings(ui, state);
+
+    ui.separator();
+
     // Advanced clustering settings (if enabled)
     if state.preferences.show_advanced {
         settings_changed |= draw_advanced_settings(ui, state);
@@ -62,6 +91,12 @@ fn draw_advanced_settings(ui: &mut egui::Ui, state: &mut AppState) -> bool {
 
     settings_changed |= draw_tile_settings(ui, state);
 
+    ui.separator();
+    settings_changed |= draw_palette_size_settings(ui, state);
+
+    ui.separator();
+    settings_changed |= draw_sprite_sheet_settings(ui, state);
+
     ui.separator();
     settings_changed |= draw_depth_settings(ui, state);
 
@@ -88,10 +123,10 @@ fn draw_advanced_settings(ui: &mut egui::Ui, state: &mut AppState) -> bool {
             ui.add_space(16.0); // Indent the color picker
 
             let mut color_array = [*r, *g, *b];
-            if ui.color_edit_button_srgb(&mut color_array).changed() {
-                *r = color_array[0];
-                *g = color_array[1];
-                *b = color_array[2];
+            let mut changed = ui.color_edit_button_srgb(&mut color_array).changed();
+            changed |= hex_color_field(ui, "clear_color_hex", &mut color_array);
+            if changed {
+                [*r, *g, *b] = color_array;
                 settings_changed = true;
             }
             if ui.button("Use Top-Left Pixel Color").clicked()
@@ -103,7 +138,6 @@ fn draw_advanced_settings(ui: &mut egui::Ui, state: &mut AppState) -> bool {
                 *b = color.b();
                 settings_changed = true;
             }
-            ui.label(format!("#{:02X}{:02X}{:02X}", *r, *g, *b));
         });
     }
 
@@ -114,15 +148,111 @@ fn draw_advanced_settings(ui: &mut egui::Ui, state: &mut AppState) -> bool {
     ui.separator();
     if ui
         .checkbox(&mut state.settings.premul_alpha, "Premultiplied Alpha")
-        .on_hover_text("Alpha is pre-multiplied (y/n)\nWhile most formats generally pre-multiply the colors by the alpha value,\n32-bit BMP files generally do not.\nNote that if this option is set, then output colors in the palette will also be pre-multiplied.")
+        .on_hover_text("Alpha is pre-multiplied (y/n)\nWhile most formats generally pre-multiply the colors by the alpha value,\n32-bit BMP files generally do not.\nThe output palette is converted back to straight alpha for the preview and exports.")
         .changed()
     {
         settings_changed = true;
     }
+    if ui
+        .checkbox(
+            &mut state.settings.quantize_alpha,
+            "Quantize Alpha to Depth",
+        )
+        .on_hover_text(
+            "Snap output palette alpha onto the levels of the A depth,\nso e.g. 1-bit alpha is always 0 or 255 in the preview and exports.\nUse View > Display Filter > Alpha Channel to inspect the result.",
+        )
+        .changed()
+    {
+        settings_changed = true;
+    }
+
+    ui.separator();
+    draw_region_settings(ui, state);
+    draw_edit_mask_settings(ui, state);
 
     settings_changed
 }
 
+/// Region of interest only changes how the next run is done, not its settings
+fn draw_region_settings(ui: &mut egui::Ui, state: &mut AppState) {
+    let (width, height) = state
+        .color_corrected_image
+        .as_ref()
+        .map_or((0, 0), |image| (image.width, image.height));
+
+    let mut enabled = state.region_of_interest.is_some();
+    let sprite_sheet = state.settings.sprite_sheet_enabled;
+    if ui
+        .add_enabled(
+            width > 0 && height > 0 && !sprite_sheet,
+            egui::Checkbox::new(&mut enabled, "Re-quantize Region Only"),
+        )
+        .on_hover_text("Only re-quantize the tiles overlapping this rectangle when the input changes,\nmapping them onto the current palettes and keeping every other tile as it is.\nChanging any Qualetize setting still re-quantizes the whole image.")
+        .on_disabled_hover_text("Not available in sprite sheet mode: each cell has its own palette,\nso tiles can't be re-mapped independently.")
+        .changed()
+    {
+        state.region_of_interest = enabled.then_some(RegionOfInterest {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        });
+    }
+
+    if let Some(region) = &mut state.region_of_interest {
+        ui.horizontal(|ui| {
+            ui.add_space(16.0);
+            ui.label("X:");
+            ui.add(egui::DragValue::new(&mut region.x).range(0..=width.saturating_sub(1)));
+            ui.label("Y:");
+            ui.add(egui::DragValue::new(&mut region.y).range(0..=height.saturating_sub(1)));
+            ui.label("W:");
+            ui.add(egui::DragValue::new(&mut region.width).range(1..=width.max(1)));
+            ui.label("H:");
+            ui.add(egui::DragValue::new(&mut region.height).range(1..=height.max(1)));
+        });
+    }
+}
+
+/// The edit mask is tracked like the color correction, so changing it needs no flag
+fn draw_edit_mask_settings(ui: &mut egui::Ui, state: &mut AppState) {
+    let (width, height) = state
+        .input_image
+        .as_ref()
+        .map_or((0, 0), |image| (image.width, image.height));
+
+    let mut enabled = state.edit_mask.is_some();
+    if ui
+        .add_enabled(
+            width > 0 && height > 0,
+            egui::Checkbox::new(&mut enabled, "Edit Inside Mask Only"),
+        )
+        .on_hover_text("Apply color correction and quantization only inside this rectangle,\nshowing and exporting the original image everywhere else.\nCtrl-drag (Cmd-drag on macOS) on the image to draw it.\nIndexed exports can't hold the original pixels; use Masked PNG32.")
+        .changed()
+    {
+        state.edit_mask = enabled.then_some(RegionOfInterest {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        });
+    }
+
+    if let Some(mask) = &mut state.edit_mask {
+        ui.horizontal(|ui| {
+            ui.add_space(16.0);
+            ui.label("X:");
+            ui.add(egui::DragValue::new(&mut mask.x).range(0..=width.saturating_sub(1)));
+            ui.label("Y:");
+            ui.add(egui::DragValue::new(&mut mask.y).range(0..=height.saturating_sub(1)));
+            ui.label("W:");
+            ui.add(egui::DragValue::new(&mut mask.width).range(1..=width.max(1)));
+            ui.label("H:");
+            ui.add(egui::DragValue::new(&mut mask.height).range(1..=height.max(1)));
+        });
+    }
+}
+
 fn draw_basic_settings(ui: &mut egui::Ui, state: &mut AppState) -> bool {
     let mut settings_changed = false;
 
@@ -132,13 +262,20 @@ fn draw_basic_settings(ui: &mut egui::Ui, state: &mut AppState) -> bool {
         ui.label("Palettes:")
             .on_hover_text("Set number of palettes available");
 
+        let max_total = state.settings.max_total_colors() as u16;
         // Limit max palettes based on color count
-        let max_palettes = 256 / state.settings.n_colors.max(1);
-        // Limit max colors based on palette count
-        let max_colors = 256 / state.settings.n_palettes.max(1);
+        let max_palettes = max_total / state.settings.n_colors.max(1);
+        // Limit max colors based on palette count; a palette never exceeds 256
+        let max_colors = (max_total / state.settings.n_palettes.max(1)).min(256);
 
+        // In sprite sheet mode the palette count follows the number of cells
         if ui
-            .add(egui::DragValue::new(&mut state.settings.n_palettes).range(1..=max_palettes))
+            .add_enabled(
+                !state.settings.sprite_sheet_enabled,
+                egui::DragValue::new(&mut state.settings.n_palettes)
+                    .range(1..=max_palettes)
+                    .speed(INTEGER_NUDGE),
+            )
             .on_hover_text("Number of palettes available")
             .changed()
         {
@@ -151,7 +288,11 @@ fn draw_basic_settings(ui: &mut egui::Ui, state: &mut AppState) -> bool {
             .on_hover_text("Set number of colors per palette\nNote that this value times the number of palettes must be less than or equal to 256.");
 
         if ui
-            .add(egui::DragValue::new(&mut state.settings.n_colors).range(1..=max_colors))
+            .add(
+                egui::DragValue::new(&mut state.settings.n_colors)
+                    .range(1..=max_colors)
+                    .speed(INTEGER_NUDGE),
+            )
             .on_hover_text("Number of colors per palette")
             .changed()
         {
@@ -160,10 +301,152 @@ fn draw_basic_settings(ui: &mut egui::Ui, state: &mut AppState) -> bool {
 
         ui.label("=");
         ui.label(egui::RichText::new(format!("{}", state.settings.n_colors * state.settings.n_palettes))
-          .strong()).on_hover_text("Palettes * Colors per palette must be <= 256");
-        ui.label("(max: 256)");
+          .strong()).on_hover_text(format!("Palettes * Colors per palette must be <= {max_total}"));
+        ui.label(format!("(max: {max_total})"));
     });
 
+    if ui
+        .add_enabled(
+            !state.settings.sprite_sheet_enabled,
+            egui::Checkbox::new(&mut state.settings.split_over_256, "Split Over 256 Colors"),
+        )
+        .on_hover_text(format!(
+            "Allow up to {MAX_SPLIT_TOTAL_COLORS} colors in total. The image is cut into bands of whole tile rows,\n\
+             each quantized with at most 256 colors, and exported with \"Split Indexed PNGs\"\n\
+             as one indexed file per band plus a JSON manifest of which tile rows each file holds."
+        ))
+        .changed()
+    {
+        if !state.settings.split_over_256 {
+            state.settings.clamp_palette_budget();
+        }
+        settings_changed = true;
+    }
+
+    if ui
+        .checkbox(&mut state.settings.palette_only, "Palette Only")
+        .on_hover_text("Skip rendering the quantized image and show only the palettes.\nFaster for extracting palettes from large images.\nIndexed and palette exports still work; Masked PNG32 needs the rendered image.")
+        .changed()
+    {
+        settings_changed = true;
+    }
+
+    settings_changed
+}
+
+fn draw_palette_size_settings(ui: &mut egui::Ui, state: &mut AppState) -> bool {
+    let mut settings_changed = false;
+
+    if ui
+        .checkbox(
+            &mut state.settings.use_custom_palette_sizes,
+            "Per-palette Color Counts",
+        )
+        .on_hover_text("Give each palette its own number of colors (up to Colors).\nPalettes are shrunk after quantization and packed back-to-back in the output.")
+        .changed()
+    {
+        settings_changed = true;
+    }
+
+    if state.settings.use_custom_palette_sizes {
+        let n_palettes = state.settings.n_palettes as usize;
+        let min_size = state.settings.min_palette_size();
+        let max_size = state.settings.n_colors.max(1);
+        state
+            .settings
+            .custom_palette_sizes
+            .resize(n_palettes, max_size);
+
+        ui.horizontal_wrapped(|ui| {
+            for (idx, size) in state.settings.custom_palette_sizes.iter_mut().enumerate() {
+                ui.label(format!("{idx}:"));
+                if ui
+                    .add(
+                        egui::DragValue::new(size)
+                            .range(min_size..=max_size)
+                            .speed(INTEGER_NUDGE),
+                    )
+                    .on_hover_text(format!("Number of colors in palette {idx}"))
+                    .changed()
+                {
+                    settings_changed = true;
+                }
+            }
+        });
+
+        let total: u32 = state
+            .settings
+            .palette_sizes()
+            .iter()
+            .map(|&size| size as u32)
+            .sum();
+        ui.label(format!("Total colors: {total}"));
+    }
+
+    settings_changed
+}
+
+fn draw_sprite_sheet_settings(ui: &mut egui::Ui, state: &mut AppState) -> bool {
+    let mut settings_changed = false;
+
+    if ui
+        .checkbox(
+            &mut state.settings.sprite_sheet_enabled,
+            "Sprite Sheet (Palette per Cell)",
+        )
+        .on_hover_text("Quantize each cell of the grid independently with its own palette.\nThe palette count becomes the number of cells.")
+        .changed()
+    {
+        settings_changed = true;
+    }
+
+    if state.settings.sprite_sheet_enabled {
+        ui.horizontal(|ui| {
+            ui.label("Cell Width:");
+            if ui
+                .add(
+                    egui::DragValue::new(&mut state.settings.sprite_cell_width)
+                        .range(1..=1024)
+                        .speed(INTEGER_NUDGE),
+                )
+                .on_hover_text("Width of one sprite cell in pixels")
+                .changed()
+            {
+                settings_changed = true;
+            }
+            ui.label("Height:");
+            if ui
+                .add(
+                    egui::DragValue::new(&mut state.settings.sprite_cell_height)
+                        .range(1..=1024)
+                        .speed(INTEGER_NUDGE),
+                )
+                .on_hover_text("Height of one sprite cell in pixels")
+                .changed()
+            {
+                settings_changed = true;
+            }
+        });
+
+        if let Some(input_image) = &state.input_image {
+            match state
+                .settings
+                .sprite_sheet_error(input_image.width, input_image.height)
+            {
+                Some(error) => {
+                    ui.label(
+                        egui::RichText::new(format!("⚠ {error}")).color(ui.visuals().warn_fg_color),
+                    );
+                }
+                None => {
+                    let cells = (input_image.width / state.settings.sprite_cell_width as u32)
+                        * (input_image.height / state.settings.sprite_cell_height as u32);
+                    ui.label(format!("Cells: {cells}"));
+                }
+            }
+        }
+    }
+
     settings_changed
 }
 
@@ -197,17 +480,126 @@ fn draw_custom_level_inputs(ui: &mut egui::Ui, state: &mut AppState) -> bool {
             settings_changed |= response.changed();
 
             if !is_valid {
-                ui.label(egui::RichText::new("⚠").color(Color32::from_rgb(255, 180, 0)))
+                ui.label(egui::RichText::new("⚠").color(ui.visuals().warn_fg_color))
                     .on_hover_text(
                         "Enter comma-separated integers between 0 and 255 (max 255 entries)",
                     );
             }
         });
+        if let Some(levels) = parse_custom_levels(&state.settings.custom_levels[idx]) {
+            draw_level_bar(ui, &levels, idx);
+        }
     }
 
+    settings_changed |= draw_level_generator(ui, state);
+
     settings_changed
 }
 
+/// Fill all four channels with a generated ramp instead of typing levels by hand
+fn draw_level_generator(ui: &mut egui::Ui, state: &mut AppState) -> bool {
+    let mut settings_changed = false;
+    ui.horizontal(|ui| {
+        ui.label("Generate:");
+        ui.add(
+            egui::DragValue::new(&mut state.level_ramp_count)
+                .range(LEVEL_COUNT_RANGE)
+                .speed(INTEGER_NUDGE)
+                .suffix(" levels"),
+        );
+        ui.add(
+            egui::DragValue::new(&mut state.level_ramp_gamma)
+                .range(LEVEL_GAMMA_RANGE)
+                .speed(0.01)
+                .prefix("γ "),
+        )
+        .on_hover_text("1.0 = evenly spaced, higher values spread the dark steps apart");
+        if ui
+            .button("Fill All Channels")
+            .on_hover_text("Replace the R, G, B and A levels with this ramp")
+            .clicked()
+        {
+            let levels = gamma_level_string(state.level_ramp_count, state.level_ramp_gamma);
+            state.settings.custom_levels = std::array::from_fn(|_| levels.clone());
+            settings_changed = true;
+        }
+    });
+    ui.horizontal(|ui| {
+        ui.label("Presets:");
+        for preset in LevelRampPreset::all() {
+            if ui
+                .button(preset.display_name())
+                .on_hover_text(preset.description())
+                .clicked()
+            {
+                (state.level_ramp_count, state.level_ramp_gamma) =
+                    preset.count_and_gamma(state.level_ramp_count);
+                state.settings.custom_levels = preset.level_strings(state.level_ramp_count);
+                settings_changed = true;
+            }
+        }
+    });
+    settings_changed
+}
+
+/// Ramp of one channel quantized to `levels` (0.0-1.0), with a tick at every level
+fn draw_level_bar(ui: &mut egui::Ui, levels: &[f32], channel: usize) {
+    const BAR_SIZE: egui::Vec2 = egui::vec2(260.0, 10.0);
+    const SEGMENTS: usize = 128;
+
+    let channel_color = |value: f32| {
+        let v = (value * 255.0).round() as u8;
+        match channel {
+            0 => Color32::from_rgb(v, 0, 0),
+            1 => Color32::from_rgb(0, v, 0),
+            2 => Color32::from_rgb(0, 0, v),
+            _ => Color32::from_gray(v),
+        }
+    };
+    let nearest_level = |value: f32| {
+        levels
+            .iter()
+            .copied()
+            .min_by(|a, b| (a - value).abs().total_cmp(&(b - value).abs()))
+            .unwrap_or(value)
+    };
+
+    ui.horizontal(|ui| {
+        // Line up with the text fields after the channel label
+        ui.add_space(ui.spacing().interact_size.y);
+        let (rect, response) = ui.allocate_exact_size(BAR_SIZE, egui::Sense::hover());
+        let painter = ui.painter();
+        let segment_width = rect.width() / SEGMENTS as f32;
+        for i in 0..SEGMENTS {
+            let value = (i as f32 + 0.5) / SEGMENTS as f32;
+            let x = rect.min.x + i as f32 * segment_width;
+            painter.rect_filled(
+                egui::Rect::from_min_max(
+                    egui::pos2(x, rect.min.y),
+                    egui::pos2(x + segment_width, rect.max.y),
+                ),
+                0.0,
+                channel_color(nearest_level(value)),
+            );
+        }
+        let tick_color = ui.visuals().strong_text_color();
+        for &level in levels {
+            let x = rect.min.x + level * rect.width();
+            painter.line_segment(
+                [
+                    egui::pos2(x, rect.min.y - 2.0),
+                    egui::pos2(x, rect.max.y + 2.0),
+                ],
+                egui::Stroke::new(1.0, tick_color),
+            );
+        }
+        response.on_hover_text(format!(
+            "{} levels: each band shows the level values snap to",
+            levels.len()
+        ));
+    });
+}
+
 fn draw_depth_settings(ui: &mut egui::Ui, state: &mut AppState) -> bool {
     let mut settings_changed = false;
     ui.horizontal(|ui| {
@@ -267,11 +659,35 @@ fn draw_depth_settings(ui: &mut egui::Ui, state: &mut AppState) -> bool {
         }
 
         if let Some(error) = get_rgba_depth_error(&state.settings.rgba_depth) {
-            ui.label(egui::RichText::new("⚠").color(Color32::from_rgb(255, 180, 0)))
+            ui.label(egui::RichText::new("⚠").color(ui.visuals().warn_fg_color))
                 .on_hover_text(format!("{error}\nExamples: 8888, 5551, 3331"));
         }
     }
 
+    ui.horizontal(|ui| {
+        ui.label("Rounding:")
+            .on_hover_text("How values between two depth levels are rounded");
+        egui::ComboBox::from_id_salt("depth_rounding")
+            .selected_text(state.settings.depth_rounding.display_name())
+            .show_ui(ui, |ui| {
+                for &rounding in DepthRounding::all() {
+                    if ui
+                        .selectable_value(
+                            &mut state.settings.depth_rounding,
+                            rounding,
+                            rounding.display_name(),
+                        )
+                        .on_hover_text(rounding.description())
+                        .clicked()
+                    {
+                        settings_changed = true;
+                    }
+                }
+            })
+            .response
+            .on_hover_text("Truncating to 5 bits darkens the image by up to half a level;\nTruncate and Dither snap the input before clustering, so error-diffusion\ndithering has little left to spread");
+    });
+
     settings_changed
 }
 
@@ -282,7 +698,11 @@ fn draw_tile_settings(ui: &mut egui::Ui, state: &mut AppState) -> bool {
         ui.label("Tile Width:")
             .on_hover_text("Set tile width for processing");
         if ui
-            .add(egui::DragValue::new(&mut state.settings.tile_width).range(1..=64))
+            .add(
+                egui::DragValue::new(&mut state.settings.tile_width)
+                    .range(1..=64)
+                    .speed(INTEGER_NUDGE),
+            )
             .on_hover_text("Width of processing tiles")
             .changed()
         {
@@ -291,7 +711,11 @@ fn draw_tile_settings(ui: &mut egui::Ui, state: &mut AppState) -> bool {
         ui.label("Height:")
             .on_hover_text("Set tile height for processing");
         if ui
-            .add(egui::DragValue::new(&mut state.settings.tile_height).range(1..=64))
+            .add(
+                egui::DragValue::new(&mut state.settings.tile_height)
+                    .range(1..=64)
+                    .speed(INTEGER_NUDGE),
+            )
             .on_hover_text("Height of processing tiles")
             .changed()
         {
@@ -318,6 +742,102 @@ fn draw_tile_settings(ui: &mut egui::Ui, state: &mut AppState) -> bool {
         }
     });
 
+    ui.horizontal_wrapped(|ui| {
+        if ui
+            .add_enabled(state.input_image.is_some(), egui::Button::new("Detect Grid").small())
+            .on_hover_text("Look for a repeating grid of edges in the input (e.g. sprite sheet cells)\nand suggest matching tile sizes")
+            .clicked()
+            && let Some(image) = &state.input_image
+        {
+            state.tile_grid_suggestions = Some(ImageProcessor::detect_tile_grid(
+                &image.rgba_data,
+                image.width,
+                image.height,
+            ));
+        }
+        match &state.tile_grid_suggestions {
+            Some(suggestions) if suggestions.is_empty() => {
+                ui.weak("No clear grid found");
+            }
+            Some(suggestions) => {
+                for suggestion in suggestions {
+                    let label = format!("{}x{}", suggestion.tile_width, suggestion.tile_height);
+                    if ui
+                        .small_button(label)
+                        .on_hover_text(format!(
+                            "Grid lines carry {:.1}x the average edge strength",
+                            suggestion.strength
+                        ))
+                        .clicked()
+                    {
+                        state.settings.tile_width = suggestion.tile_width;
+                        state.settings.tile_height = suggestion.tile_height;
+                        settings_changed = true;
+                    }
+                }
+            }
+            None => {}
+        }
+    });
+
+    settings_changed
+}
+
+/// Results of "Find Best"; picking a row overrides the automatic choice
+fn draw_color_space_trials(ctx: &egui::Context, state: &mut AppState) -> bool {
+    let mut settings_changed = false;
+    let mut open = state.color_space_trials.show;
+    egui::Window::new("Color Space Comparison")
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            if state.color_space_trials.is_running() {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(format!(
+                        "Quantizing with {} color spaces...",
+                        ColorSpace::all().len()
+                    ));
+                });
+                return;
+            }
+            egui::Grid::new("color_space_trials")
+                .num_columns(3)
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.strong("Color Space");
+                    ui.strong("RGB RMSE");
+                    ui.label("");
+                    ui.end_row();
+                    for trial in &state.color_space_trials.results {
+                        let current = trial.color_space == state.settings.color_space;
+                        let name = egui::RichText::new(trial.color_space.display_name());
+                        ui.label(if current { name.strong() } else { name })
+                            .on_hover_text(trial.color_space.description());
+                        match &trial.rmse {
+                            Ok(rmse) => ui.label(format!("{rmse:.2}")),
+                            Err(e) => ui
+                                .label(
+                                    egui::RichText::new("failed")
+                                        .color(ui.visuals().error_fg_color),
+                                )
+                                .on_hover_text(e),
+                        };
+                        if current {
+                            ui.label("Current");
+                        } else if ui
+                            .add_enabled(trial.rmse.is_ok(), egui::Button::new("Use"))
+                            .clicked()
+                        {
+                            state.settings.color_space = trial.color_space.clone();
+                            settings_changed = true;
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+    state.color_space_trials.show = open;
     settings_changed
 }
 
@@ -325,21 +845,60 @@ fn draw_color_space_settings(ui: &mut egui::Ui, state: &mut AppState) -> bool {
     let mut settings_changed = false;
 
     ui.subheading_with_margin("Color Space");
-    egui::ComboBox::from_id_salt("color_space")
-        .selected_text(state.settings.color_space.display_name())
-        .show_ui(ui, |ui| {
-            for color_space in ColorSpace::all() {
-                if ui
-                    .selectable_value(&mut state.settings.color_space, color_space.clone(), color_space.display_name())
-                    .on_hover_text(color_space.description())
-                    .clicked()
-                {
-                    settings_changed = true;
+    ui.horizontal(|ui| {
+        egui::ComboBox::from_id_salt("color_space")
+            .selected_text(state.settings.color_space.display_name())
+            .show_ui(ui, |ui| {
+                for color_space in ColorSpace::all() {
+                    if ui
+                        .selectable_value(&mut state.settings.color_space, color_space.clone(), color_space.display_name())
+                        .on_hover_text(color_space.description())
+                        .clicked()
+                    {
+                        settings_changed = true;
+                    }
                 }
-            }
-        })
-        .response
-        .on_hover_text("Set colorspace\nDifferent colorspaces may give better/worse results depending on the input image,\nand it may be necessary to experiment to find the optimal one.");
+            })
+            .response
+            .on_hover_text("Set colorspace\nDifferent colorspaces may give better/worse results depending on the input image,\nand it may be necessary to experiment to find the optimal one.");
+
+        let can_search =
+            state.color_corrected_image.is_some() && !state.color_space_trials.is_running();
+        if ui
+            .add_enabled(can_search, egui::Button::new("Find Best"))
+            .on_hover_text("Quantize with every color space and switch to the one\nwith the lowest RGB error against the input")
+            .clicked()
+        {
+            _ = state
+                .app_state_request_sender
+                .send(AppStateRequest::FindBestColorSpace);
+        }
+    });
+    settings_changed |= draw_color_space_trials(ui.ctx(), state);
+
+    ui.horizontal(|ui| {
+        let mut limit_hues = state.settings.max_hues.is_some();
+        if ui
+            .checkbox(&mut limit_hues, "Limit Hues")
+            .on_hover_text("Snap the image to a few dominant hue families before clustering,\nkeeping saturation and brightness, for a cohesive limited-hue look")
+            .changed()
+        {
+            state.settings.max_hues = limit_hues.then_some(4);
+            settings_changed = true;
+        }
+        if let Some(max_hues) = &mut state.settings.max_hues
+            && ui
+                .add(
+                    egui::DragValue::new(max_hues)
+                        .range(MAX_HUES_RANGE)
+                        .speed(0.05)
+                        .suffix(" hues"),
+                )
+                .changed()
+        {
+            settings_changed = true;
+        }
+    });
 
     settings_changed
 }
@@ -362,21 +921,208 @@ fn draw_dithering_settings(ui: &mut egui::Ui, state: &mut AppState) -> bool {
             }
         })
         .response
-        .on_hover_text("Set dither mode and level for output\nThis can reduce some of the banding artifacts caused when the colors per palette is very small,\nat the expense of added \"noise\".");
+        .on_hover_text("Set dither mode and level for output\nThis can reduce some of the banding artifacts caused when the colors per palette is very small,\nat the expense of added \"noise\".\nError diffusion runs in the selected color space, the same one used for clustering.");
 
     ui.horizontal(|ui| {
         ui.label("Dither Level:")
             .on_hover_text("Dithering intensity level");
         if ui
-            .add(egui::Slider::new(
-                &mut state.settings.dither_level,
-                0.0..=2.0,
-            ))
+            .add(
+                egui::Slider::new(&mut state.settings.dither_level, DITHER_LEVEL_RANGE)
+                    .step_by(FINE_NUDGE)
+                    .show_value(false),
+            )
             .on_hover_text("Adjust dithering intensity (0.0 = no dithering)")
             .changed()
         {
             settings_changed = true;
         }
+        if ui
+            .add(
+                egui::DragValue::new(&mut state.settings.dither_level)
+                    .range(DITHER_LEVEL_RANGE)
+                    .speed(FINE_NUDGE)
+                    .fixed_decimals(2),
+            )
+            .changed()
+        {
+            settings_changed = true;
+        }
+    });
+
+    if state.settings.dither_mode == DitherMode::Hybrid {
+        ui.horizontal(|ui| {
+            ui.label("Pattern Level:").on_hover_text(
+                "Strength of the Bayer pattern added before error diffusion.\nThe Dither Level above controls the diffusion.",
+            );
+            if ui
+                .add(
+                    egui::Slider::new(
+                        &mut state.settings.hybrid_pattern_level,
+                        HYBRID_PATTERN_LEVEL_RANGE,
+                    )
+                    .step_by(FINE_NUDGE)
+                    .show_value(false),
+                )
+                .on_hover_text("0.0 leaves plain Floyd-Steinberg; higher values give a stronger crosshatch")
+                .changed()
+            {
+                settings_changed = true;
+            }
+            if ui
+                .add(
+                    egui::DragValue::new(&mut state.settings.hybrid_pattern_level)
+                        .range(HYBRID_PATTERN_LEVEL_RANGE)
+                        .speed(FINE_NUDGE)
+                        .fixed_decimals(2),
+                )
+                .changed()
+            {
+                settings_changed = true;
+            }
+        });
+    }
+
+    if state.settings.dither_mode.is_ordered() {
+        ui.horizontal(|ui| {
+            ui.label("Pattern Amplitude:").on_hover_text(
+                "Strength of the ordered threshold pattern, applied on top of the dither level",
+            );
+            if ui
+                .add(
+                    egui::Slider::new(
+                        &mut state.settings.ordered_dither_amplitude,
+                        ORDERED_DITHER_AMPLITUDE_RANGE,
+                    )
+                    .step_by(FINE_NUDGE)
+                    .show_value(false),
+                )
+                .on_hover_text(
+                    "1.0 keeps the pattern as generated; lower values give a subtler pattern",
+                )
+                .changed()
+            {
+                settings_changed = true;
+            }
+            if ui
+                .add(
+                    egui::DragValue::new(&mut state.settings.ordered_dither_amplitude)
+                        .range(ORDERED_DITHER_AMPLITUDE_RANGE)
+                        .speed(FINE_NUDGE)
+                        .fixed_decimals(2),
+                )
+                .changed()
+            {
+                settings_changed = true;
+            }
+        });
+    }
+
+    settings_changed
+}
+
+fn draw_snap_palette_settings(ui: &mut egui::Ui, state: &mut AppState) -> bool {
+    let mut settings_changed = false;
+
+    ui.subheading_with_margin("Snap to Palette");
+    egui::ComboBox::from_id_salt("snap_palette")
+        .selected_text(state.settings.snap_palette.display_name())
+        .show_ui(ui, |ui| {
+            for snap_palette in SnapPalette::all() {
+                if ui
+                    .selectable_value(
+                        &mut state.settings.snap_palette,
+                        *snap_palette,
+                        snap_palette.display_name(),
+                    )
+                    .on_hover_text(snap_palette.description())
+                    .clicked()
+                {
+                    settings_changed = true;
+                }
+            }
+        })
+        .response
+        .on_hover_text(
+            "After quantization, move each palette color to the nearest color\nof a fixed palette. Pixel indices are kept, so clustering still runs freely.",
+        );
+
+    if state.settings.snap_palette == SnapPalette::Custom {
+        ui.horizontal(|ui| {
+            if ui.button("Load Palette...").clicked() {
+                _ = state
+                    .app_state_request_sender
+                    .send(AppStateRequest::SnapPaletteDialog);
+            }
+            if state.settings.snap_palette_colors.is_empty() {
+                ui.label(
+                    egui::RichText::new("⚠ No palette loaded").color(ui.visuals().warn_fg_color),
+                );
+            } else {
+                ui.label(format!(
+                    "{} colors",
+                    state.settings.snap_palette_colors.len()
+                ));
+            }
+        });
+    }
+
+    if let Some(snap_rmse) = state
+        .base_output_image
+        .as_ref()
+        .and_then(|image| image.snap_rmse)
+    {
+        ui.label(format!("Snapping added RMSE: {snap_rmse:.2}"))
+            .on_hover_text(
+                "Root-mean-square change (0-255) of the output pixels caused by snapping",
+            );
+    }
+
+    settings_changed
+}
+
+fn draw_merge_colors_settings(ui: &mut egui::Ui, state: &mut AppState) -> bool {
+    let mut settings_changed = false;
+
+    ui.subheading_with_margin("Merge Similar Colors");
+    if ui
+        .checkbox(
+            &mut state.settings.merge_colors_enabled,
+            "Merge colors within tolerance",
+        )
+        .on_hover_text(
+            "After quantization, collapse entries of a palette that are closer than the\ntolerance in the selected color space. Freed entries move to the end of the palette.",
+        )
+        .changed()
+    {
+        settings_changed = true;
+    }
+
+    ui.add_enabled_ui(state.settings.merge_colors_enabled, |ui| {
+        ui.horizontal(|ui| {
+            ui.label("Tolerance:").on_hover_text(
+                "Distance in the clustering color space, scaled so the main axis spans 0-255",
+            );
+            if ui
+                .add(
+                    egui::Slider::new(&mut state.settings.merge_colors_tolerance, 0.0..=64.0)
+                        .fixed_decimals(1),
+                )
+                .changed()
+            {
+                settings_changed = true;
+            }
+        });
+
+        let merged_text = match state
+            .base_output_image
+            .as_ref()
+            .and_then(|image| image.merged_colors)
+        {
+            Some(merged) => format!("Merged {merged} colors"),
+            None => "Merged -- colors".to_string(),
+        };
+        ui.label(egui::RichText::new(merged_text).strong());
     });
 
     settings_changed
@@ -425,6 +1171,7 @@ fn draw_tile_reduce_settings(ui: &mut egui::Ui, state: &mut AppState) -> bool {
             let slider =
                 egui::Slider::new(&mut state.settings.tile_reduce_post_threshold, 1.0..=500.0)
                     .logarithmic(false)
+                    .step_by(COARSE_NUDGE)
                     .show_value(false);
             if ui.add(slider).changed() {
                 settings_changed = true;
@@ -466,6 +1213,41 @@ fn draw_transparency_settings(ui: &mut egui::Ui, state: &mut AppState) -> bool {
     {
         settings_changed = true;
     }
+    ui.horizontal(|ui| {
+        if ui
+            .checkbox(
+                &mut state.settings.source_alpha_transparency,
+                "Use Source Alpha",
+            )
+            .on_hover_text("Pixels whose source alpha is at or below the threshold map to color 0 of their tile's palette,\nwithout needing a clear color key.")
+            .changed()
+        {
+            settings_changed = true;
+        }
+        ui.add_enabled_ui(state.settings.source_alpha_transparency, |ui| {
+            ui.label("Threshold:");
+            if ui
+                .add(
+                    egui::DragValue::new(&mut state.settings.alpha_threshold)
+                        .speed(INTEGER_NUDGE),
+                )
+                .on_hover_text("Highest source alpha (0-255) still treated as transparent")
+                .changed()
+            {
+                settings_changed = true;
+            }
+        });
+    });
+    if ui
+        .checkbox(
+            &mut state.settings.ignore_transparent_in_clustering,
+            "Ignore Transparent Pixels",
+        )
+        .on_hover_text("Fully transparent pixels take the color of the opaque pixels around them,\nso large transparent margins don't steal palette colors or get dithered.")
+        .changed()
+    {
+        settings_changed = true;
+    }
     settings_changed
 }
 
@@ -477,7 +1259,11 @@ fn draw_clustering_settings(ui: &mut egui::Ui, state: &mut AppState) -> bool {
             ui.label("Tile Passes:")
                 .on_hover_text("Set tile cluster passes (0 = default)");
             if ui
-                .add(egui::DragValue::new(&mut state.settings.tile_passes).range(0..=1000))
+                .add(
+                    egui::DragValue::new(&mut state.settings.tile_passes)
+                        .range(0..=1000)
+                        .speed(INTEGER_NUDGE),
+                )
                 .on_hover_text("Number of tile clustering passes (0 to 1000)")
                 .changed()
             {
@@ -488,7 +1274,11 @@ fn draw_clustering_settings(ui: &mut egui::Ui, state: &mut AppState) -> bool {
             ui.label("Color Passes:")
                 .on_hover_text("Set color cluster passes (0 = default)\nMost of the processing time will be spent in the loop that clusters the colors together.\nIf processing is taking excessive amounts of time, this option may be adjusted\n(e.g., for 256-color palettes, set to ~4; for 16-color palettes, set to 32-64)");
             if ui
-                .add(egui::DragValue::new(&mut state.settings.color_passes).range(0..=100))
+                .add(
+                    egui::DragValue::new(&mut state.settings.color_passes)
+                        .range(0..=100)
+                        .speed(INTEGER_NUDGE),
+                )
                 .on_hover_text("Number of color passes (0 to 100)")
                 .changed()
             {
@@ -497,6 +1287,50 @@ fn draw_clustering_settings(ui: &mut egui::Ui, state: &mut AppState) -> bool {
         });
     });
 
+    ui.horizontal(|ui| {
+        ui.label("Channel Weights:").on_hover_text(
+            "How much differences in R, G and B count when clustering (1.0 = full weight).\nThe channels are scaled before the color space transform, so with the -psy\ncolor spaces these stack on top of their built-in luma emphasis.\nLowering a channel also coarsens how finely it is resolved.",
+        );
+        for (weight, name) in state.settings.channel_weights.iter_mut().zip(["R", "G", "B"]) {
+            ui.label(name);
+            if ui
+                .add(
+                    egui::DragValue::new(weight)
+                        .range(CHANNEL_WEIGHT_RANGE)
+                        .speed(0.01)
+                        .fixed_decimals(2),
+                )
+                .changed()
+            {
+                settings_changed = true;
+            }
+        }
+        if ui
+            .small_button("Reset")
+            .on_hover_text("Weigh every channel equally")
+            .clicked()
+        {
+            state.settings.channel_weights = [1.0; 3];
+            settings_changed = true;
+        }
+    });
+
+    ui.horizontal(|ui| {
+        ui.label("Merge Input Colors Within:").on_hover_text(
+            "Before clustering, replace rare colors with a much more common color\nthat is at most this far away on every channel (0 = off).\nSpeeds up clustering and ignores noise; gradients are kept because\ntheir steps are about equally common.",
+        );
+        if ui
+            .add(
+                egui::DragValue::new(&mut state.settings.input_merge_tolerance)
+                    .range(INPUT_MERGE_TOLERANCE_RANGE)
+                    .speed(INTEGER_NUDGE),
+            )
+            .changed()
+        {
+            settings_changed = true;
+        }
+    });
+
     settings_changed
 }
 
@@ -505,9 +1339,70 @@ fn draw_color_correction_settings(ui: &mut egui::Ui, state: &mut AppState) -> bo
 
     ui.heading_with_margin("Color Correction");
 
+    ui.checkbox(
+        &mut state.palette_only_color_correction,
+        "Grade Palette Only",
+    )
+    .on_hover_text("Apply color correction to the output palettes instead of the source image.\nAdjustments are instant because the existing clustering is kept;\nturn this off to re-quantize from the corrected source.");
+
+    ui.horizontal(|ui| {
+        ui.checkbox(
+            &mut state.color_correction.reduce_jpeg_artifacts,
+            "Reduce JPEG Artifacts",
+        )
+        .on_hover_text("Smooth compression noise in the source before quantizing.\nEdges are kept; applies to the source even when grading the palette only.");
+        if !state.color_correction.reduce_jpeg_artifacts
+            && state.input_image.as_ref().is_some_and(|image| image.is_jpeg())
+        {
+            ui.label(
+                egui::RichText::new("⚠ JPEG source")
+                    .color(ui.visuals().warn_fg_color)
+                    .small(),
+            )
+            .on_hover_text("Quantization tends to turn JPEG block noise into extra palette colors");
+        }
+    });
+
+    ui.collapsing("Denoise", |ui| {
+        settings_changed |= ui
+            .checkbox(
+                &mut state.color_correction.denoise_enabled,
+                "Edge-Preserving Denoise",
+            )
+            .on_hover_text("Smooth noise in the source before quantizing while keeping sprite edges crisp.\nHelps photographic or noisy sources end up with cleaner palettes.\nApplies to the source even when grading the palette only.")
+            .changed();
+        ui.add_enabled_ui(state.color_correction.denoise_enabled, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Strength:").on_hover_text(
+                    "How different a neighbor's color may be and still be averaged in",
+                );
+                settings_changed |= ui
+                    .add(
+                        egui::Slider::new(
+                            &mut state.color_correction.denoise_strength,
+                            DENOISE_STRENGTH_RANGE,
+                        )
+                        .step_by(FINE_NUDGE),
+                    )
+                    .changed();
+            });
+            ui.horizontal(|ui| {
+                ui.label("Radius:")
+                    .on_hover_text("Pixels around each pixel that are considered");
+                settings_changed |= ui
+                    .add(
+                        egui::Slider::new(
+                            &mut state.color_correction.denoise_radius,
+                            DENOISE_RADIUS_RANGE,
+                        )
+                        .suffix(" px"),
+                    )
+                    .changed();
+            });
+        });
+    });
+
     // Define ranges to avoid duplication
-    const BRIGHTNESS_RANGE: std::ops::RangeInclusive<f32> = -1.0..=1.0;
-    const CONTRAST_RANGE: std::ops::RangeInclusive<f32> = 0.0..=2.0;
     const SATURATION_RANGE: std::ops::RangeInclusive<f32> = 0.0..=2.0;
     const HUE_SHIFT_RANGE: std::ops::RangeInclusive<f32> = -180.0..=180.0;
     const SHADOWS_RANGE: std::ops::RangeInclusive<f32> = -1.0..=1.0;
@@ -532,6 +1427,7 @@ fn draw_color_correction_settings(ui: &mut egui::Ui, state: &mut AppState) -> bo
                 .add_sized(
                     [slider_width, 24.0],
                     egui::Slider::new(&mut state.color_correction.brightness, BRIGHTNESS_RANGE)
+                        .step_by(FINE_NUDGE)
                         .show_value(false),
                 )
                 .changed()
@@ -542,7 +1438,7 @@ fn draw_color_correction_settings(ui: &mut egui::Ui, state: &mut AppState) -> bo
                 .add(
                     egui::DragValue::new(&mut state.color_correction.brightness)
                         .range(BRIGHTNESS_RANGE)
-                        .speed(0.01)
+                        .speed(FINE_NUDGE)
                         .custom_formatter(|n, _| format_percentage(n as f32))
                         .custom_parser(|s| {
                             // Try to parse as percentage first
@@ -567,6 +1463,7 @@ fn draw_color_correction_settings(ui: &mut egui::Ui, state: &mut AppState) -> bo
                 .add_sized(
                     [slider_width, 24.0],
                     egui::Slider::new(&mut state.color_correction.contrast, CONTRAST_RANGE)
+                        .step_by(FINE_NUDGE)
                         .show_value(false),
                 )
                 .changed()
@@ -577,7 +1474,7 @@ fn draw_color_correction_settings(ui: &mut egui::Ui, state: &mut AppState) -> bo
                 .add(
                     egui::DragValue::new(&mut state.color_correction.contrast)
                         .range(CONTRAST_RANGE)
-                        .speed(0.01)
+                        .speed(FINE_NUDGE)
                         .fixed_decimals(2),
                 )
                 .changed()
@@ -594,6 +1491,7 @@ fn draw_color_correction_settings(ui: &mut egui::Ui, state: &mut AppState) -> bo
                 .add_sized(
                     [slider_width, 24.0],
                     egui::Slider::new(&mut state.color_correction.saturation, SATURATION_RANGE)
+                        .step_by(FINE_NUDGE)
                         .show_value(false),
                 )
                 .changed()
@@ -604,7 +1502,7 @@ fn draw_color_correction_settings(ui: &mut egui::Ui, state: &mut AppState) -> bo
                 .add(
                     egui::DragValue::new(&mut state.color_correction.saturation)
                         .range(SATURATION_RANGE)
-                        .speed(0.01)
+                        .speed(FINE_NUDGE)
                         .fixed_decimals(2),
                 )
                 .changed()
@@ -621,6 +1519,7 @@ fn draw_color_correction_settings(ui: &mut egui::Ui, state: &mut AppState) -> bo
                 .add_sized(
                     [slider_width, 24.0],
                     egui::Slider::new(&mut state.color_correction.hue_shift, HUE_SHIFT_RANGE)
+                        .step_by(COARSE_NUDGE)
                         .show_value(false),
                 )
                 .changed()
@@ -631,7 +1530,7 @@ fn draw_color_correction_settings(ui: &mut egui::Ui, state: &mut AppState) -> bo
                 .add(
                     egui::DragValue::new(&mut state.color_correction.hue_shift)
                         .range(HUE_SHIFT_RANGE)
-                        .speed(1.0)
+                        .speed(COARSE_NUDGE)
                         .suffix("°")
                         .fixed_decimals(0),
                 )
@@ -649,6 +1548,7 @@ fn draw_color_correction_settings(ui: &mut egui::Ui, state: &mut AppState) -> bo
                 .add_sized(
                     [slider_width, 24.0],
                     egui::Slider::new(&mut state.color_correction.shadows, SHADOWS_RANGE)
+                        .step_by(FINE_NUDGE)
                         .show_value(false),
                 )
                 .changed()
@@ -659,7 +1559,7 @@ fn draw_color_correction_settings(ui: &mut egui::Ui, state: &mut AppState) -> bo
                 .add(
                     egui::DragValue::new(&mut state.color_correction.shadows)
                         .range(SHADOWS_RANGE)
-                        .speed(0.01)
+                        .speed(FINE_NUDGE)
                         .custom_formatter(|n, _| format_percentage(n as f32))
                         .custom_parser(|s| {
                             // Try to parse as percentage first
@@ -684,6 +1584,7 @@ fn draw_color_correction_settings(ui: &mut egui::Ui, state: &mut AppState) -> bo
                 .add_sized(
                     [slider_width, 24.0],
                     egui::Slider::new(&mut state.color_correction.highlights, HIGHLIGHTS_RANGE)
+                        .step_by(FINE_NUDGE)
                         .show_value(false),
                 )
                 .changed()
@@ -694,7 +1595,7 @@ fn draw_color_correction_settings(ui: &mut egui::Ui, state: &mut AppState) -> bo
                 .add(
                     egui::DragValue::new(&mut state.color_correction.highlights)
                         .range(HIGHLIGHTS_RANGE)
-                        .speed(0.01)
+                        .speed(FINE_NUDGE)
                         .custom_formatter(|n, _| format_percentage(n as f32))
                         .custom_parser(|s| {
                             // Try to parse as percentage first
@@ -719,7 +1620,9 @@ fn draw_color_correction_settings(ui: &mut egui::Ui, state: &mut AppState) -> bo
             if ui
                 .add_sized(
                     [slider_width, 24.0],
-                    egui::Slider::new(&mut gamma_display, GAMMA_DISPLAY_RANGE).show_value(false),
+                    egui::Slider::new(&mut gamma_display, GAMMA_DISPLAY_RANGE)
+                        .step_by(COARSE_NUDGE)
+                        .show_value(false),
                 )
                 .changed()
             {
@@ -730,7 +1633,7 @@ fn draw_color_correction_settings(ui: &mut egui::Ui, state: &mut AppState) -> bo
                 .add(
                     egui::DragValue::new(&mut state.color_correction.gamma)
                         .range(GAMMA_RANGE)
-                        .speed(0.01)
+                        .speed(FINE_NUDGE)
                         .custom_formatter(|n, _| format_gamma(n as f32))
                         .custom_parser(|s| s.parse::<f64>().ok()),
                 )
@@ -765,10 +1668,70 @@ fn draw_color_correction_settings(ui: &mut egui::Ui, state: &mut AppState) -> bo
         }
     });
 
+    // Palette-only grading is applied without re-quantizing
+    settings_changed && !state.palette_only_color_correction
+}
+
+/// Dismissible preset recommendation for the loaded image
+fn draw_palette_extraction_banner(ui: &mut egui::Ui, state: &mut AppState) -> bool {
+    if state.palette_extraction.is_none() {
+        return false;
+    }
+    let mut settings_changed = false;
+    ui.group(|ui| {
+        ui.label(egui::RichText::new("Extract Palette").strong());
+        ui.label(
+            egui::RichText::new(
+                "Only the palette is built and exported. Set the color count below.",
+            )
+            .small()
+            .weak(),
+        );
+        if ui
+            .button("Back to Full Pipeline")
+            .on_hover_text("Render and export the quantized image again")
+            .clicked()
+        {
+            state.leave_palette_extraction();
+            settings_changed = true;
+        }
+    });
     settings_changed
 }
 
-fn draw_status_section(ui: &mut egui::Ui, state: &AppState) {
+fn draw_preset_suggestion(ui: &mut egui::Ui, state: &mut AppState) -> bool {
+    let Some(suggestion) = &state.preset_suggestion else {
+        return false;
+    };
+    let preset = suggestion.preset;
+    let reason = suggestion.reason.clone();
+    let mut settings_changed = false;
+    let mut dismissed = false;
+    ui.group(|ui| {
+        ui.label(format!("Suggested preset: {}", preset.display_name()));
+        ui.label(egui::RichText::new(reason).small().weak());
+        ui.horizontal(|ui| {
+            if ui
+                .button("Apply")
+                .on_hover_text("Replace the quantization settings with this preset")
+                .clicked()
+            {
+                state.settings = preset.qualetize_settings();
+                settings_changed = true;
+                dismissed = true;
+            }
+            if ui.button("Dismiss").clicked() {
+                dismissed = true;
+            }
+        });
+    });
+    if dismissed {
+        state.preset_suggestion = None;
+    }
+    settings_changed
+}
+
+fn draw_status_section(ui: &mut egui::Ui, state: &mut AppState) {
     ui.heading_with_margin("Debug Info");
     if let Some(request_qualetize) = &state.request_update_qualetized_image {
         let elapsed = request_qualetize.time.elapsed();
@@ -792,6 +1755,68 @@ fn draw_status_section(ui: &mut egui::Ui, state: &AppState) {
         "Settings changed: {:?}",
         state.request_update_qualetized_image.is_some(),
     ));
+
+    if let Some(rmse) = state.output_rmse {
+        ui.label(format!(
+            "RMSE: {:.3} / {:.3} / {:.3} / {:.3}",
+            rmse[0], rmse[1], rmse[2], rmse[3]
+        ));
+    }
+    if let Some(difference) = state.output_difference() {
+        ui.label(format!(
+            "Changed pixels: {:.1}% ({} / {})",
+            difference.differing_percentage(),
+            difference.differing_pixels,
+            difference.total_pixels,
+        ))
+        .on_hover_text("Pixels that differ between the color corrected input and the output");
+        ui.label(format!("Max pixel error: {}", difference.max_error))
+            .on_hover_text("Largest per-channel difference (0-255) of any pixel");
+    }
+
+    let Some(stats) = state.palette_error_stats().cloned() else {
+        return;
+    };
+    ui.label("Palette RMSE:")
+        .on_hover_text("Error (0-255 over RGBA) of the pixels drawn from each palette");
+    egui::Grid::new("palette_rmse_grid")
+        .num_columns(2)
+        .show(ui, |ui| {
+            for (palette, rmse) in stats.palette_rmse.iter().enumerate() {
+                ui.label(format!("  Palette {palette}"));
+                ui.label(rmse.map_or("unused".to_string(), |rmse| format!("{rmse:.2}")));
+                ui.end_row();
+            }
+        });
+    if let Some(worst) = stats.worst_tile {
+        ui.label(format!(
+            "Worst tile: ({}, {}) · palette {} · RMSE {:.2}",
+            worst.tile_x, worst.tile_y, worst.palette, worst.rmse
+        ));
+        ui.horizontal(|ui| {
+            if ui
+                .button("Jump to Worst Tile")
+                .on_hover_text("Center the view on the tile and outline it")
+                .clicked()
+                && let Some(output) = &state.output_image
+            {
+                let tile_size = egui::vec2(
+                    state.settings.tile_width as f32,
+                    state.settings.tile_height as f32,
+                );
+                let tile_center = egui::vec2(worst.tile_x as f32, worst.tile_y as f32) * tile_size
+                    + tile_size / 2.0;
+                state.pan_offset = pan_offset_centering(
+                    egui::vec2(output.width as f32, output.height as f32),
+                    tile_center,
+                    state.zoom,
+                    state.preferences.pixel_aspect_ratio(),
+                );
+                state.show_worst_tile = true;
+            }
+            ui.checkbox(&mut state.show_worst_tile, "Outline");
+        });
+    }
 }
 
 fn validate_rgba_depth(rgba_str: &str) -> bool {
@@ -856,7 +1881,11 @@ fn draw_palette_sort_settings(ui: &mut egui::Ui, state: &mut AppState) {
                     );
                 }
             });
-        ui.add_enabled_ui(state.palette_sort_settings.mode != SortMode::None, |ui| {
+        let ordered = !matches!(
+            state.palette_sort_settings.mode,
+            SortMode::None | SortMode::Reference
+        );
+        ui.add_enabled_ui(ordered, |ui| {
             egui::ComboBox::from_id_salt("sort_order")
                 .selected_text(state.palette_sort_settings.order.display_name())
                 .show_ui(ui, |ui| {
@@ -870,4 +1899,96 @@ fn draw_palette_sort_settings(ui: &mut egui::Ui, state: &mut AppState) {
                 });
         });
     });
+
+    if state.palette_sort_settings.mode == SortMode::Reference {
+        ui.horizontal(|ui| {
+            if ui
+                .button("Load Reference...")
+                .on_hover_text("Indexed PNGs keep their palette order;\nother images use the order colors first appear in")
+                .clicked()
+            {
+                _ = state
+                    .app_state_request_sender
+                    .send(AppStateRequest::ReferencePaletteDialog);
+            }
+            let reference = &state.palette_sort_settings.reference_palette;
+            if reference.is_empty() {
+                ui.label(
+                    egui::RichText::new("⚠ No reference loaded")
+                        .color(ui.visuals().warn_fg_color),
+                );
+            } else {
+                ui.label(format!("{} colors", reference.len()));
+            }
+        })
+        .response
+        .on_hover_text("Move each output color to the index of the closest reference color,\nso related sprites share index meanings");
+    }
+
+    draw_palette_order_settings(ui, state);
+}
+
+fn draw_palette_order_settings(ui: &mut egui::Ui, state: &mut AppState) {
+    // Show the palettes as currently arranged
+    let Some(palettes) = state
+        .output_palette_sorted_indexed_image
+        .as_ref()
+        .or_else(|| {
+            state
+                .output_image
+                .as_ref()
+                .and_then(|image| image.indexed.as_ref())
+        })
+        .map(|indexed| indexed.palettes_for_ui.clone())
+    else {
+        return;
+    };
+    if palettes.len() < 2 {
+        return;
+    }
+
+    ui.collapsing("Palette Order", |ui| {
+        const SWATCH_SIZE: f32 = 10.0;
+        let mut swap_with_previous = None;
+        for (position, palette) in palettes.iter().enumerate() {
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(position > 0, egui::Button::new("⬆").small())
+                    .on_hover_text("Swap with the palette above\nPixels are remapped, so the image stays the same.")
+                    .clicked()
+                {
+                    swap_with_previous = Some(position);
+                }
+                ui.label(format!("{position}"));
+                let (rect, _) = ui.allocate_exact_size(
+                    egui::vec2(SWATCH_SIZE * palette.len() as f32, SWATCH_SIZE),
+                    egui::Sense::hover(),
+                );
+                for (i, color) in palette.iter().enumerate() {
+                    let min = rect.min + egui::vec2(SWATCH_SIZE * i as f32, 0.0);
+                    ui.painter().rect_filled(
+                        egui::Rect::from_min_size(min, egui::Vec2::splat(SWATCH_SIZE)),
+                        0.0,
+                        *color,
+                    );
+                }
+            });
+        }
+        if let Some(position) = swap_with_previous {
+            state
+                .palette_sort_settings
+                .swap_palettes(palettes.len(), position - 1, position);
+        }
+
+        let reordered = state
+            .palette_sort_settings
+            .palette_order_for(palettes.len())
+            .is_some();
+        if ui
+            .add_enabled(reordered, egui::Button::new("Reset Order"))
+            .clicked()
+        {
+            state.palette_sort_settings.palette_order.clear();
+        }
+    });
 }
@@ -1,6 +1,47 @@
-use egui::Color32;
+use crate::types::qualetize::{format_hex_rgb, parse_hex_rgb};
+use egui::{Color32, Stroke};
 pub const COLOR_TINT: Color32 = Color32::from_rgb(240, 100, 156);
 pub const COLOR_TINT_ACTIVE: Color32 = Color32::from_rgb(131, 100, 144);
+/// Darker tint for the high-contrast theme; white text on it stays above 4.5:1
+pub const COLOR_TINT_HIGH_CONTRAST: Color32 = Color32::from_rgb(170, 0, 95);
+
+/// Warning color of the dark theme, read back through `Visuals::warn_fg_color`
+pub const COLOR_WARNING: Color32 = Color32::from_rgb(255, 180, 0);
+/// Amber is unreadable on white, so the light theme darkens it
+pub const COLOR_WARNING_LIGHT: Color32 = Color32::from_rgb(165, 90, 0);
+
+/// Black panels, white text and outlines on every widget. Selection is cyan and
+/// warnings are pure yellow so the two never rely on telling red from green.
+pub fn high_contrast_visuals() -> egui::Visuals {
+    let mut visuals = egui::Visuals::dark();
+    visuals.override_text_color = Some(Color32::WHITE);
+    visuals.panel_fill = Color32::BLACK;
+    visuals.window_fill = Color32::BLACK;
+    visuals.extreme_bg_color = Color32::BLACK;
+    visuals.faint_bg_color = Color32::from_gray(28);
+    visuals.window_stroke = Stroke::new(1.0, Color32::WHITE);
+    visuals.warn_fg_color = Color32::YELLOW;
+    visuals.error_fg_color = Color32::from_rgb(255, 120, 120);
+    visuals.hyperlink_color = Color32::from_rgb(110, 200, 255);
+    visuals.selection.bg_fill = Color32::from_rgb(0, 80, 190);
+    visuals.selection.stroke = Stroke::new(2.0, Color32::from_rgb(0, 230, 255));
+
+    let widgets = &mut visuals.widgets;
+    widgets.noninteractive.bg_stroke = Stroke::new(1.0, Color32::from_gray(170));
+    widgets.noninteractive.fg_stroke = Stroke::new(1.0, Color32::WHITE);
+    for (widget, fill) in [
+        (&mut widgets.inactive, Color32::from_gray(16)),
+        (&mut widgets.hovered, Color32::from_gray(64)),
+        (&mut widgets.active, Color32::from_gray(96)),
+        (&mut widgets.open, Color32::from_gray(48)),
+    ] {
+        widget.bg_fill = fill;
+        widget.weak_bg_fill = fill;
+        widget.bg_stroke = Stroke::new(1.0, Color32::WHITE);
+        widget.fg_stroke = Stroke::new(1.5, Color32::WHITE);
+    }
+    visuals
+}
 
 pub fn init_styles(ctx: &egui::Context) {
     let mut fonts = egui::FontDefinitions::default();
@@ -93,3 +134,45 @@ impl UiMarginExt for egui::Ui {
         });
     }
 }
+
+/// `#RRGGBB` field next to a color picker, for pasting exact values. The text follows
+/// `color` unless the field has focus; malformed text is flagged and leaves `color`
+/// unchanged. Returns whether `color` changed.
+pub fn hex_color_field(ui: &mut egui::Ui, id_salt: &str, color: &mut [u8; 3]) -> bool {
+    let id = ui.make_persistent_id(id_salt);
+    let text_id = id.with("text");
+    let mut text = if ui.memory(|memory| memory.has_focus(id)) {
+        ui.data(|data| data.get_temp::<String>(text_id))
+    } else {
+        None
+    }
+    .unwrap_or_else(|| format_hex_rgb(*color));
+
+    let response = ui
+        .add_sized(
+            [72.0, ui.spacing().interact_size.y],
+            egui::TextEdit::singleline(&mut text).id(id),
+        )
+        .on_hover_text("Color as #RRGGBB");
+    let mut changed = false;
+    match parse_hex_rgb(&text) {
+        Ok(parsed) => {
+            if response.changed() && parsed != *color {
+                *color = parsed;
+                changed = true;
+            }
+        }
+        Err(error) => {
+            ui.painter().rect_stroke(
+                response.rect,
+                2.0,
+                Stroke::new(1.0, ui.visuals().error_fg_color),
+                egui::StrokeKind::Outside,
+            );
+            ui.label(egui::RichText::new("⚠").color(ui.visuals().warn_fg_color))
+                .on_hover_text(error);
+        }
+    }
+    ui.data_mut(|data| data.insert_temp(text_id, text));
+    changed
+}
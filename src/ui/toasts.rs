@@ -0,0 +1,30 @@
+use crate::types::{AppState, app_state::ToastKind};
+use egui::{Align2, Color32};
+
+const MAX_VISIBLE_TOASTS: usize = 4;
+
+pub fn draw_toasts(ctx: &egui::Context, state: &AppState) {
+    if state.toasts.is_empty() {
+        return;
+    }
+
+    egui::Area::new(egui::Id::new("toasts"))
+        .anchor(Align2::RIGHT_BOTTOM, egui::vec2(-12.0, -48.0))
+        .order(egui::Order::Foreground)
+        .interactable(false)
+        .show(ctx, |ui| {
+            // Newest toasts are shown at the bottom
+            let start = state.toasts.len().saturating_sub(MAX_VISIBLE_TOASTS);
+            for toast in &state.toasts[start..] {
+                let (icon, color) = match toast.kind {
+                    ToastKind::Info => ("✔", ui.visuals().strong_text_color()),
+                    ToastKind::Warning => ("⚠", Color32::from_rgb(255, 200, 90)),
+                    ToastKind::Error => ("⚠", Color32::from_rgb(255, 120, 120)),
+                };
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.label(egui::RichText::new(format!("{icon} {}", toast.message)).color(color));
+                });
+                ui.add_space(4.0);
+            }
+        });
+}